@@ -0,0 +1,19 @@
+#!/usr/bin/env python3
+"""Tiny example bot for the external bot protocol.
+
+Answers every turn message with the first legal command, which has each unit
+wait, builds whatever is affordable and then ends the turn.
+"""
+import json
+import sys
+
+for line in sys.stdin:
+    message = json.loads(line)
+    if message["type"] == "hello":
+        print(f"echo bot: playing {message['faction']} (protocol {message['protocol']})", file=sys.stderr)
+    elif message["type"] == "turn":
+        for rejected in message["rejected"]:
+            print(f"echo bot: rejected: {rejected['reason']}", file=sys.stderr)
+        print(json.dumps(message["legal"][0]), flush=True)
+    elif message["type"] == "quit":
+        break
@@ -1,22 +1,42 @@
 use bevy::prelude::*;
 use bevy::ecs::system::SystemParam;
-use std::collections::{HashMap, HashSet};
+use bevy::platform::time::Instant;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
 use super::{
-    Faction, FactionMember, Unit, UnitType, GridPosition, GameMap, Tile, Terrain,
+    Faction, FactionMember, Unit, UnitType, UnitClass, GridPosition, GameMap, Tile, Terrain,
     TurnState, TurnPhase, FactionFunds, AttackEvent, CaptureEvent, GameResult,
-    calculate_movement_range, calculate_damage, spawn_unit, CoBonuses,
+    JoinEvent, ResupplyEvent, LoadEvent, UnloadEvent, AttackResultEvent,
+    calculate_movement_range, calculate_movement_range_with_costs, route_from_costs, trap_stop, TrapEvent,
+    calculate_damage, spawn_unit, CoBonuses,
     Commanders, PowerActivatedEvent, Weather, WeatherType, SpriteAssetsParam,
-    UnitAnimation, effective_movement, GameData,
+    UnitAnimation, effective_movement, estimate_damage, GameData, FogOfWar, TurnStartEvent, announce_turn_start,
+    AiControllers, ControllerCatalog, AiController, BattleSnapshot, BotCommand, AttackReport,
+    SnapshotSource, validate_command, ObjectId, MapRules,
 };
 
 /// Bundled AI-related resources to stay under Bevy's system parameter limit
 #[derive(SystemParam)]
 struct AiResources<'w> {
     ai_state: ResMut<'w, AiState>,
-    turn_plan: ResMut<'w, AiTurnPlan>,
-    memory: ResMut<'w, AiMemory>,
+    controllers: ResMut<'w, AiControllers>,
     game_data: Res<'w, GameData>,
+    fog: Res<'w, FogOfWar>,
+    weather: Res<'w, Weather>,
+    rules: Res<'w, MapRules>,
+}
+
+/// Message writers for every action the AI can take
+#[derive(SystemParam)]
+struct AiActionWriters<'w> {
+    attack: MessageWriter<'w, AttackEvent>,
+    capture: MessageWriter<'w, CaptureEvent>,
+    join: MessageWriter<'w, JoinEvent>,
+    resupply: MessageWriter<'w, ResupplyEvent>,
+    load: MessageWriter<'w, LoadEvent>,
+    unload: MessageWriter<'w, UnloadEvent>,
+    trap: MessageWriter<'w, TrapEvent>,
 }
 
 pub struct AiPlugin;
@@ -24,8 +44,8 @@ pub struct AiPlugin;
 impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<AiState>()
-            .init_resource::<AiTurnPlan>()
-            .init_resource::<AiMemory>()
+            .init_resource::<AiControllers>()
+            .init_resource::<ControllerCatalog>()
             .add_systems(Update, ai_turn_system);
     }
 }
@@ -57,6 +77,120 @@ pub enum AiStrategy {
     Fortress,      // Defend key positions, counter-attack only
 }
 
+/// AI difficulty levels - affects how WELL the AI plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum AiDifficulty {
+    Cub,     // Short-sighted and sloppy, for learning the game
+    #[default]
+    Hunter,  // Solid play with the odd mistake
+    Alpha,   // Full strength, plays fair under fog
+    Apex,    // Full strength and sees through fog
+}
+
+impl AiDifficulty {
+    pub fn all() -> [AiDifficulty; 4] {
+        [AiDifficulty::Cub, AiDifficulty::Hunter, AiDifficulty::Alpha, AiDifficulty::Apex]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AiDifficulty::Cub => "Cub",
+            AiDifficulty::Hunter => "Hunter",
+            AiDifficulty::Alpha => "Alpha",
+            AiDifficulty::Apex => "Apex",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AiDifficulty::Cub => "Plays loosely and wastes funds",
+            AiDifficulty::Hunter => "A fair fight with the odd mistake",
+            AiDifficulty::Alpha => "Plans carefully and spends well",
+            AiDifficulty::Apex => "Ruthless, and sees through the fog",
+        }
+    }
+
+    /// How many ranked options each unit tries before giving up for the turn
+    pub fn planning_depth(&self) -> usize {
+        match self {
+            AiDifficulty::Cub => 1,
+            AiDifficulty::Hunter => 3,
+            AiDifficulty::Alpha => 10,
+            AiDifficulty::Apex => usize::MAX,
+        }
+    }
+
+    /// Fraction of predicted enemy actions the AI plans around
+    pub fn prediction_use(&self) -> f32 {
+        match self {
+            AiDifficulty::Cub => 0.0,
+            AiDifficulty::Hunter => 0.5,
+            AiDifficulty::Alpha | AiDifficulty::Apex => 1.0,
+        }
+    }
+
+    /// Largest random adjustment to an action's score
+    pub fn score_noise(&self) -> f32 {
+        match self {
+            AiDifficulty::Cub => 40.0,
+            AiDifficulty::Hunter => 15.0,
+            AiDifficulty::Alpha => 5.0,
+            AiDifficulty::Apex => 0.0,
+        }
+    }
+
+    /// Fraction of its funds the AI will spend on production each turn
+    pub fn production_efficiency(&self) -> f32 {
+        match self {
+            AiDifficulty::Cub => 0.5,
+            AiDifficulty::Hunter => 0.75,
+            AiDifficulty::Alpha | AiDifficulty::Apex => 1.0,
+        }
+    }
+
+    pub fn sees_through_fog(&self) -> bool {
+        *self == AiDifficulty::Apex
+    }
+
+    /// Turn plans the lookahead search evaluates before it stops; zero plays one-ply greedy.
+    /// The count normally ends the search, so a difficulty plays the same on any machine
+    pub fn search_evaluations(&self) -> usize {
+        match self {
+            AiDifficulty::Cub | AiDifficulty::Hunter => 0,
+            AiDifficulty::Alpha => 400,
+            AiDifficulty::Apex => 1500,
+        }
+    }
+
+    /// Wall-clock cap on one search, so a slow machine still gets a move in time;
+    /// when it runs out the search plays the best plan found so far
+    pub fn search_time(&self) -> Duration {
+        match self {
+            AiDifficulty::Cub | AiDifficulty::Hunter => Duration::ZERO,
+            AiDifficulty::Alpha => Duration::from_millis(500),
+            AiDifficulty::Apex => Duration::from_millis(1500),
+        }
+    }
+
+    /// Partial plans kept at each step of the search
+    pub fn beam_width(&self) -> usize {
+        match self {
+            AiDifficulty::Alpha => 4,
+            AiDifficulty::Apex => 8,
+            _ => 1,
+        }
+    }
+
+    /// Options considered per unit during the search
+    pub fn search_branching(&self) -> usize {
+        match self {
+            AiDifficulty::Alpha => 3,
+            AiDifficulty::Apex => 4,
+            _ => 1,
+        }
+    }
+}
+
 /// AI configuration combining personality and strategy
 #[derive(Resource, Clone)]
 pub struct AiConfig {
@@ -115,6 +249,13 @@ pub struct AiState {
     pub action_delay: Timer,
     pub phase: AiTurnPhase,
     pub config: AiConfig,
+    /// Only plan with what the AI's faction can see through fog of war
+    pub respect_fog: bool,
+    /// Commands from the controller still to be carried out
+    pending: VecDeque<BotCommand>,
+    commands_this_turn: usize,
+    /// Attacks each controlled faction hasn't been told about yet
+    attack_reports: HashMap<Faction, Vec<AttackReport>>,
 }
 
 impl Default for AiState {
@@ -124,6 +265,11 @@ impl Default for AiState {
             action_delay: Timer::from_seconds(0.2, TimerMode::Once),
             phase: AiTurnPhase::Waiting,
             config: AiConfig::default(),
+            // Same as battle setup's default
+            respect_fog: !AiDifficulty::default().sees_through_fog(),
+            pending: VecDeque::new(),
+            commands_this_turn: 0,
+            attack_reports: HashMap::new(),
         }
     }
 }
@@ -135,35 +281,131 @@ impl AiState {
             ..Default::default()
         }
     }
+
+    /// Apply a difficulty's fog rule for a new battle; the controllers get the difficulty and seed
+    pub fn set_difficulty(&mut self, difficulty: AiDifficulty, seed: u64) {
+        self.respect_fog = !difficulty.sees_through_fog();
+        info!("AI difficulty: {} (seed {})", difficulty.name(), seed);
+    }
+
+    fn take_attack_reports(&mut self, faction: Faction) -> Vec<AttackReport> {
+        self.attack_reports.remove(&faction).unwrap_or_default()
+    }
+}
+
+/// Deterministic score noise, so lower difficulties make reproducible mistakes
+struct ScoreNoise {
+    amplitude: f32,
+    seed: u64,
+}
+
+impl ScoreNoise {
+    fn new(difficulty: AiDifficulty, seed: u64, turn: u32) -> Self {
+        Self {
+            amplitude: difficulty.score_noise(),
+            seed: seed ^ (turn as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15),
+        }
+    }
+
+    /// Noise in [-amplitude, amplitude] for a unit taking an action
+    fn sample(&self, unit: &UnitInfo, action: &AiAction) -> f32 {
+        if self.amplitude == 0.0 {
+            return 0.0;
+        }
+        let (kind, (x, y)) = match action {
+            AiAction::Attack { move_to, .. } => (1, *move_to),
+            AiAction::Capture { move_to, .. } => (2, *move_to),
+            AiAction::Move { move_to } => (3, *move_to),
+            AiAction::Wait => (4, (unit.pos.x, unit.pos.y)),
+            AiAction::Load { move_to, .. } => (5, *move_to),
+            AiAction::Unload { drop_at, .. } => (6, *drop_at),
+            AiAction::Join { move_to, .. } => (7, *move_to),
+            AiAction::Resupply { move_to } => (8, *move_to),
+        };
+        let mut h = self.seed;
+        for v in [unit.pos.x as u64, unit.pos.y as u64, kind, x as u64, y as u64] {
+            h = splitmix64(h ^ v);
+        }
+        let unit_interval = (h >> 11) as f32 / (1u64 << 53) as f32;
+        (unit_interval * 2.0 - 1.0) * self.amplitude
+    }
+}
+
+fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum AiTurnPhase {
     #[default]
     Waiting,
-    Planning,
     ExecutingActions,
-    Production,
     EndingTurn,
 }
 
+/// Commands a controller may issue in one turn before it's made to end it
+pub const MAX_COMMANDS_PER_TURN: usize = 500;
+
+/// How much weight the AI gives an enemy it can't currently see
+const BELIEF_CONFIDENCE: f32 = 0.5;
+
+/// What the AI believes about an enemy unit it has seen
+#[derive(Debug, Clone)]
+pub struct EnemySighting {
+    /// Where the unit was last seen
+    pub position: (i32, i32),
+    /// The unit as it was when last seen
+    pub unit: Unit,
+    /// AI turn on which the unit was last seen
+    pub last_seen: u32,
+    /// Tiles the unit could have reached since it was last seen
+    pub reachable: HashSet<(i32, i32)>,
+}
+
+impl EnemySighting {
+    /// Most likely current position: the reachable tile closest to any of the given points
+    fn probable_position(&self, toward: &[(i32, i32)]) -> (i32, i32) {
+        self.reachable.iter()
+            .copied()
+            .min_by_key(|&(x, y)| {
+                let dist = toward.iter()
+                    .map(|&(tx, ty)| (x - tx).abs() + (y - ty).abs())
+                    .min()
+                    .unwrap_or(0);
+                (dist, x, y)
+            })
+            .unwrap_or(self.position)
+    }
+}
+
 /// Memory of past turns for opponent modeling
-#[derive(Resource, Default)]
+#[derive(Default)]
 pub struct AiMemory {
-    /// Track where player units were last turn
-    player_last_positions: HashMap<Entity, (i32, i32)>,
+    /// Where each enemy unit was last seen, and where it might be now
+    player_last_positions: HashMap<Entity, EnemySighting>,
     /// Track player's aggressive tendency (how often they attack vs defend)
     player_aggression: f32,
     /// Track which of our units the player tends to target
     targeted_unit_types: HashMap<UnitType, u32>,
+    /// Player attacks seen since the last AI turn
+    player_attacks_seen: u32,
     /// Turns since game start
     turn_count: u32,
+    /// Where each loaded transport is ferrying its cargo
+    transport_plans: HashMap<Entity, (i32, i32)>,
 }
 
-#[derive(Resource, Default)]
+#[derive(Default)]
 struct AiTurnPlan {
+    /// Remaining actions in execution order, rebuilt after every action
     actions: Vec<PlannedAction>,
-    current_index: usize,
+    /// Actions executed so far this turn
+    actions_taken: usize,
+    /// Plan chosen by the lookahead search, followed while it stays valid
+    searched: Vec<PlannedAction>,
 }
 
 #[derive(Debug, Clone)]
@@ -178,9 +420,38 @@ enum AiAction {
     Attack { move_to: (i32, i32), target: Entity },
     Capture { move_to: (i32, i32), tile: Entity },
     Move { move_to: (i32, i32) },
+    Load { move_to: (i32, i32), transport: Entity },
+    Unload { move_to: (i32, i32), drop_at: (i32, i32) },
+    Join { move_to: (i32, i32), target: Entity },
+    Resupply { move_to: (i32, i32) },
     Wait,
 }
 
+impl AiAction {
+    /// Tile the unit moves to before acting, if any
+    fn destination(&self) -> Option<(i32, i32)> {
+        match self {
+            AiAction::Attack { move_to, .. }
+            | AiAction::Capture { move_to, .. }
+            | AiAction::Move { move_to }
+            | AiAction::Load { move_to, .. }
+            | AiAction::Unload { move_to, .. }
+            | AiAction::Join { move_to, .. }
+            | AiAction::Resupply { move_to } => Some(*move_to),
+            AiAction::Wait => None,
+        }
+    }
+
+    /// Friendly unit whose tile this action moves onto
+    fn partner(&self) -> Option<Entity> {
+        match self {
+            AiAction::Load { transport, .. } => Some(*transport),
+            AiAction::Join { target, .. } => Some(*target),
+            _ => None,
+        }
+    }
+}
+
 // ============================================================================
 // INFLUENCE MAPS - Core of spatial reasoning
 // ============================================================================
@@ -257,14 +528,14 @@ fn build_influence_maps(
         let strength = get_unit_strength(unit) * unit.hp_percent;
         project_influence(&mut maps.territory, unit.pos.x, unit.pos.y, strength, 5, map);
     }
-    for unit in &analysis.enemy_units {
-        let strength = get_unit_strength(unit) * unit.hp_percent;
+    for (unit, certainty) in analysis.enemies_with_certainty() {
+        let strength = get_unit_strength(unit) * unit.hp_percent * certainty;
         project_influence(&mut maps.territory, unit.pos.x, unit.pos.y, -strength, 5, map);
     }
 
     // === ENEMY THREAT MAP ===
     // Project threat based on attack range and damage potential
-    for enemy in &analysis.enemy_units {
+    for (enemy, certainty) in analysis.enemies_with_certainty() {
         let stats = enemy.unit.unit_type.stats();
         if stats.attack == 0 {
             continue;
@@ -277,14 +548,14 @@ fn build_influence_maps(
 
         // Calculate all positions this enemy could attack from (limited by stamina)
         let actual_movement = effective_movement(stats.movement, enemy.unit.stamina);
-        let moves = calculate_movement_range(
+        let moves = sorted_moves(calculate_movement_range(
             &enemy.pos,
             actual_movement,
             map,
             &analysis.unit_positions,
             stats.class,
             game_data,
-        );
+        ));
 
         for (mx, my) in moves {
             let (min_r, max_r) = stats.attack_range;
@@ -295,7 +566,7 @@ fn build_influence_maps(
                         let tx = mx + dx;
                         let ty = my + dy;
                         if tx >= 0 && tx < map.width as i32 && ty >= 0 && ty < map.height as i32 {
-                            let threat = stats.attack as f32 * enemy.hp_percent;
+                            let threat = stats.attack as f32 * enemy.hp_percent * certainty;
                             *maps.enemy_threat.entry((tx, ty)).or_insert(0.0) += threat;
                         }
                     }
@@ -324,22 +595,28 @@ fn build_influence_maps(
         if tile.terrain.is_capturable() {
             let base_value = tile.terrain.income_value() as f32 * 2.0;
             let ownership_bonus = match tile.owner {
-                Some(Faction::Northern) => 5.0,  // Defend our stuff
+                Some(owner) if owner == analysis.faction => 5.0,  // Defend our stuff
                 Some(_) => 15.0,                  // Enemy property = high value target
                 None => 10.0,                     // Neutral = capture opportunity
             };
             *maps.strategic_value.entry((x, y)).or_insert(0.0) += base_value + ownership_bonus;
 
-            // Bases are extra valuable (production!)
-            if tile.terrain == Terrain::Base {
-                *maps.strategic_value.entry((x, y)).or_insert(0.0) += 30.0;
+            // Bases are extra valuable (production!), HQs can decide the battle
+            match tile.terrain {
+                Terrain::Base => *maps.strategic_value.entry((x, y)).or_insert(0.0) += 30.0,
+                Terrain::HQ => *maps.strategic_value.entry((x, y)).or_insert(0.0) += 40.0,
+                _ => {}
             }
         }
+    }
 
-        // Defensive terrain is strategically valuable
-        let defense = map.get(x, y).map(|t| t.defense_bonus()).unwrap_or(0);
-        if defense >= 2 {
-            *maps.strategic_value.entry((x, y)).or_insert(0.0) += defense as f32 * 3.0;
+    // Defensive terrain is strategically valuable
+    for y in 0..map.height as i32 {
+        for x in 0..map.width as i32 {
+            let defense = map.get(x, y).map(|t| t.defense_bonus()).unwrap_or(0);
+            if defense >= 2 {
+                *maps.strategic_value.entry((x, y)).or_insert(0.0) += defense as f32 * 3.0;
+            }
         }
     }
 
@@ -359,7 +636,7 @@ fn build_influence_maps(
     // === RETREAT VALUE ===
     // Paths back to our bases
     for (_entity, tile) in tiles {
-        if tile.terrain == Terrain::Base && tile.owner == Some(Faction::Northern) {
+        if tile.terrain == Terrain::Base && tile.owner == Some(analysis.faction) {
             project_influence(
                 &mut maps.retreat_value,
                 tile.position.x,
@@ -399,6 +676,13 @@ fn project_influence(
     }
 }
 
+/// Movement ranges in a fixed order, so planning doesn't depend on hash order
+fn sorted_moves(moves: HashSet<(i32, i32)>) -> Vec<(i32, i32)> {
+    let mut moves: Vec<_> = moves.into_iter().collect();
+    moves.sort();
+    moves
+}
+
 fn get_unit_strength(unit: &UnitInfo) -> f32 {
     let stats = unit.unit.unit_type.stats();
     (stats.attack as f32 + stats.defense as f32 * 0.5) * (unit.value as f32 / 50.0)
@@ -457,7 +741,8 @@ fn determine_strategic_goals(
 
     // Check for enemy HQ
     let enemy_hq = analysis.enemy_properties.iter()
-        .find(|t| t.terrain == Terrain::Base);
+        .find(|t| t.terrain == Terrain::HQ)
+        .or_else(|| analysis.enemy_properties.iter().find(|t| t.terrain == Terrain::Base));
 
     // =========================================================================
     // STRATEGY-BASED GOAL PRIORITIES
@@ -720,19 +1005,19 @@ fn predict_enemy_actions(
 ) -> Vec<PredictedAction> {
     let mut predictions = Vec::new();
 
-    for enemy in &analysis.enemy_units {
+    for (enemy, certainty) in analysis.enemies_with_certainty() {
         let stats = enemy.unit.unit_type.stats();
 
         // Calculate possible moves (limited by stamina)
         let actual_movement = effective_movement(stats.movement, enemy.unit.stamina);
-        let moves = calculate_movement_range(
+        let moves = sorted_moves(calculate_movement_range(
             &enemy.pos,
             actual_movement,
             map,
             &analysis.unit_positions,
             stats.class,
             game_data,
-        );
+        ));
 
         // Find best predicted action for this enemy
         let mut best_score = f32::NEG_INFINITY;
@@ -828,6 +1113,8 @@ fn predict_enemy_actions(
             }
         }
 
+        // Units we can't see are only guesses
+        best_action.confidence *= certainty;
         predictions.push(best_action);
     }
 
@@ -847,8 +1134,18 @@ struct PredictedAction {
 // ============================================================================
 
 struct GameAnalysis {
+    /// Faction the AI is playing
+    faction: Faction,
     ai_units: Vec<UnitInfo>,
     enemy_units: Vec<UnitInfo>,
+    /// Hidden enemies placed at their probable positions
+    believed_enemies: Vec<UnitInfo>,
+    /// Places worth sending Recon units to look at
+    scout_targets: Vec<(i32, i32)>,
+    /// Foot units that can't walk to any property they could capture
+    stranded: HashSet<Entity>,
+    /// Where each loaded transport is heading
+    transport_plans: HashMap<Entity, (i32, i32)>,
     capturable_tiles: Vec<TileInfo>,
     our_properties: Vec<TileInfo>,
     enemy_properties: Vec<TileInfo>,
@@ -888,16 +1185,7 @@ fn analyze_game_state(
     for (entity, pos, faction, unit) in units {
         unit_positions.insert((pos.x, pos.y), *entity);
 
-        let stats = unit.unit_type.stats();
-        let info = UnitInfo {
-            entity: *entity,
-            pos: pos.clone(),
-            unit: unit.clone(),
-            unit_type: unit.unit_type,
-            hp_percent: unit.hp_percentage(),
-            value: get_unit_value_by_type(unit.unit_type),
-            is_indirect: stats.attack_range.0 > 1,
-        };
+        let info = unit_info(*entity, pos.clone(), unit);
 
         if faction.faction == ai_faction {
             ai_units.push(info);
@@ -933,8 +1221,13 @@ fn analyze_game_state(
     }
 
     GameAnalysis {
+        faction: ai_faction,
         ai_units,
         enemy_units,
+        believed_enemies: Vec::new(),
+        scout_targets: Vec::new(),
+        stranded: HashSet::new(),
+        transport_plans: HashMap::new(),
         capturable_tiles,
         our_properties,
         enemy_properties,
@@ -942,6 +1235,94 @@ fn analyze_game_state(
     }
 }
 
+fn unit_info(entity: Entity, pos: GridPosition, unit: &Unit) -> UnitInfo {
+    UnitInfo {
+        entity,
+        pos,
+        unit: unit.clone(),
+        unit_type: unit.unit_type,
+        hp_percent: unit.hp_percentage(),
+        value: get_unit_value_by_type(unit.unit_type),
+        is_indirect: unit.unit_type.stats().attack_range.0 > 1,
+    }
+}
+
+impl GameAnalysis {
+    /// Visible enemies at full certainty, followed by believed ones
+    fn enemies_with_certainty(&self) -> impl Iterator<Item = (&UnitInfo, f32)> {
+        self.enemy_units.iter().map(|e| (e, 1.0))
+            .chain(self.believed_enemies.iter().map(|e| (e, BELIEF_CONFIDENCE)))
+    }
+
+    /// Add remembered enemies that are out of sight, and pick places to scout
+    fn add_beliefs(&mut self, memory: &AiMemory) {
+        // Enemies are assumed to advance on our units and properties
+        let toward: Vec<(i32, i32)> = self.ai_units.iter().map(|u| (u.pos.x, u.pos.y))
+            .chain(self.our_properties.iter().map(|t| (t.pos.x, t.pos.y)))
+            .collect();
+
+        for (entity, sighting) in &memory.player_last_positions {
+            if self.enemy_units.iter().any(|e| e.entity == *entity) {
+                continue;
+            }
+            let (x, y) = sighting.probable_position(&toward);
+            self.believed_enemies.push(unit_info(*entity, GridPosition::new(x, y), &sighting.unit));
+        }
+        self.believed_enemies.sort_by_key(|e| e.entity);
+
+        // Look where hidden enemies probably are, or at enemy property if nothing is known
+        self.scout_targets = self.believed_enemies.iter().map(|e| (e.pos.x, e.pos.y)).collect();
+        if self.scout_targets.is_empty() && self.enemy_units.is_empty() {
+            self.scout_targets = self.enemy_properties.iter().map(|t| (t.pos.x, t.pos.y)).collect();
+        }
+    }
+
+    /// Find foot units cut off from every property they could capture (e.g. on another island)
+    fn find_stranded(&mut self, map: &GameMap, game_data: &GameData) {
+        if self.capturable_tiles.is_empty() {
+            return;
+        }
+        let max_range = map.width + map.height;
+        for unit in &self.ai_units {
+            if !unit.unit.can_be_transported() {
+                continue;
+            }
+            let stats = unit.unit.unit_type.stats();
+            let walkable = calculate_movement_range(&unit.pos, max_range * 2, map, &HashMap::new(), stats.class, game_data);
+            if !self.capturable_tiles.iter().any(|t| walkable.contains(&(t.pos.x, t.pos.y))) {
+                self.stranded.insert(unit.entity);
+            }
+        }
+    }
+
+    /// Keep each loaded transport heading for a property, carrying plans over between turns
+    fn plan_transports(&mut self, memory: &mut AiMemory) {
+        memory.transport_plans.retain(|transport, target| {
+            self.ai_units.iter().any(|u| u.entity == *transport && u.unit.has_cargo())
+                && self.capturable_tiles.iter().any(|t| (t.pos.x, t.pos.y) == *target)
+        });
+
+        for unit in &self.ai_units {
+            if !unit.unit.has_cargo() || memory.transport_plans.contains_key(&unit.entity) {
+                continue;
+            }
+            // Prefer a property no other transport is already heading for
+            let taken: HashSet<(i32, i32)> = memory.transport_plans.values().copied().collect();
+            let target = self.capturable_tiles.iter()
+                .map(|t| (t.pos.x, t.pos.y))
+                .min_by_key(|&(x, y)| {
+                    (taken.contains(&(x, y)), (x - unit.pos.x).abs() + (y - unit.pos.y).abs(), x, y)
+                });
+            if let Some(target) = target {
+                info!("AI: {:?} ferrying cargo toward ({},{})", unit.unit_type, target.0, target.1);
+                memory.transport_plans.insert(unit.entity, target);
+            }
+        }
+
+        self.transport_plans = memory.transport_plans.clone();
+    }
+}
+
 fn get_unit_value_by_type(unit_type: UnitType) -> u32 {
     match unit_type {
         UnitType::Scout => 10,
@@ -974,22 +1355,75 @@ fn score_action(
     map: &GameMap,
     config: &AiConfig,
     game_data: &GameData,
+    noise: &ScoreNoise,
+) -> f32 {
+    score_action_terms(unit, action, analysis, influence, goals, predictions, map, config, game_data, noise, &mut ScoreTerms::default())
+}
+
+/// `score_action`, noting each part of the score in `terms`
+fn score_action_terms(
+    unit: &UnitInfo,
+    action: &AiAction,
+    analysis: &GameAnalysis,
+    influence: &InfluenceMaps,
+    goals: &[StrategicGoal],
+    predictions: &[PredictedAction],
+    map: &GameMap,
+    config: &AiConfig,
+    game_data: &GameData,
+    noise: &ScoreNoise,
+    terms: &mut ScoreTerms,
 ) -> f32 {
     let primary_goal = goals.first().cloned().unwrap_or(StrategicGoal::Attack { priority: 50.0 });
 
-    match action {
+    let score = match action {
         AiAction::Attack { move_to, target } => {
-            score_attack_action(unit, *move_to, *target, analysis, influence, &primary_goal, predictions, map, config, game_data)
+            score_attack_action(unit, *move_to, *target, analysis, influence, &primary_goal, predictions, map, config, game_data, terms)
         }
         AiAction::Capture { move_to, tile } => {
-            score_capture_action(unit, *move_to, *tile, analysis, influence, &primary_goal, predictions, map, config)
+            score_capture_action(unit, *move_to, *tile, analysis, influence, &primary_goal, predictions, map, config, terms)
         }
         AiAction::Move { move_to } => {
-            score_move_action(unit, *move_to, analysis, influence, &primary_goal, predictions, map, config)
+            score_move_action(unit, *move_to, analysis, influence, &primary_goal, predictions, map, config, terms)
+        }
+        AiAction::Load { move_to, .. } => {
+            terms.add("Boarding", score_load_action(unit, *move_to, analysis, influence, config))
+        }
+        AiAction::Unload { move_to, drop_at } => {
+            terms.add("Unloading", score_unload_action(unit, *move_to, *drop_at, analysis, influence, config))
+        }
+        AiAction::Join { target, .. } => {
+            terms.add("Joining", score_join_action(unit, *target, analysis, influence))
+        }
+        AiAction::Resupply { move_to } => {
+            terms.add("Resupplying", score_resupply_action(unit, *move_to, analysis, influence, config))
         }
         AiAction::Wait => {
-            score_wait_action(unit, influence, config)
+            terms.add("Waiting", score_wait_action(unit, influence, config))
+        }
+    };
+
+    score + terms.add("Noise", noise.sample(unit, action))
+}
+
+/// Named parts of an action's score, recorded only for the debug overlay
+#[derive(Default)]
+struct ScoreTerms {
+    recording: bool,
+    terms: Vec<(&'static str, f32)>,
+}
+
+impl ScoreTerms {
+    fn recording() -> Self {
+        Self { recording: true, terms: Vec::new() }
+    }
+
+    /// Pass a part of a score through, noting it down when recording
+    fn add(&mut self, label: &'static str, value: f32) -> f32 {
+        if self.recording && value != 0.0 {
+            self.terms.push((label, value));
         }
+        value
     }
 }
 
@@ -1004,6 +1438,7 @@ fn score_attack_action(
     map: &GameMap,
     config: &AiConfig,
     game_data: &GameData,
+    terms: &mut ScoreTerms,
 ) -> f32 {
     let target_unit = match analysis.enemy_units.iter().find(|u| u.entity == target) {
         Some(u) => u,
@@ -1025,11 +1460,11 @@ fn score_attack_action(
 
     // === BASE DAMAGE UTILITY ===
     // Apply personality attack preference
-    let mut score = UtilityCurves::damage_utility(damage, target_unit.unit.hp, target_unit.value)
-        * config.attack_preference();
+    let mut score = terms.add("Damage", UtilityCurves::damage_utility(damage, target_unit.unit.hp, target_unit.value)
+        * config.attack_preference());
 
     // Base attack bonus - attacking is generally good in a tactics game
-    score += 20.0 * config.attack_preference();
+    score += terms.add("Attack bonus", 20.0 * config.attack_preference());
 
     // === COUNTER-ATTACK RISK ===
     // Apply personality risk tolerance (lower = ignores risk more)
@@ -1039,21 +1474,21 @@ fn score_attack_action(
         let mut temp_target = target_unit.unit.clone();
         temp_target.hp -= damage;
         let counter = calculate_damage(&temp_target, &attacker.unit, attacker_terrain, &no_bonus, &no_bonus, &clear_weather, game_data);
-        score += UtilityCurves::risk_utility(counter, attacker.unit.hp, attacker.value)
-            * config.risk_tolerance();
+        score += terms.add("Counter risk", UtilityCurves::risk_utility(counter, attacker.unit.hp, attacker.value)
+            * config.risk_tolerance());
     }
 
     // === FOCUS FIRE BONUS ===
     // If another unit is also attacking this target, big bonus (coordinated attack)
     if target_unit.hp_percent < 0.6 {
-        score += 35.0; // Target is already damaged, focus fire!
+        score += terms.add("Focus fire", 35.0); // Target is already damaged, focus fire!
     }
 
     // === PREEMPTIVE STRIKE ===
     // If this enemy is predicted to attack us, bonus for hitting them first
     for pred in predictions {
         if pred.unit == target && pred.likely_target.is_some() && pred.confidence > 0.5 {
-            score += 25.0; // Preemptive strike!
+            score += terms.add("Preemptive strike", 25.0);
         }
     }
 
@@ -1061,9 +1496,9 @@ fn score_attack_action(
     match config.strategy {
         AiStrategy::Annihilation => {
             // Massive bonus for any attack, extra for kills
-            score += 30.0;
+            score += terms.add("Annihilation strategy", 30.0);
             if damage >= target_unit.unit.hp {
-                score += 50.0; // Kill bonus
+                score += terms.add("Kill bonus", 50.0);
             }
         }
         AiStrategy::Attrition => {
@@ -1072,18 +1507,18 @@ fn score_attack_action(
                 (target_stats.attack as f32 * 0.3) as i32
             } else { 0 };
             if damage > our_loss_estimate * 2 {
-                score += 25.0; // Good trade
+                score += terms.add("Good trade", 25.0);
             }
         }
         AiStrategy::Blitz => {
             // Bonus for attacking units blocking path to HQ
             let dist_to_enemy_hq = analysis.enemy_properties.iter()
-                .filter(|t| t.terrain == Terrain::Base)
+                .filter(|t| matches!(t.terrain, Terrain::Base | Terrain::HQ))
                 .map(|t| ((target_unit.pos.x - t.pos.x).abs() + (target_unit.pos.y - t.pos.y).abs()) as f32)
                 .min_by(|a, b| a.partial_cmp(b).unwrap())
                 .unwrap_or(100.0);
             if dist_to_enemy_hq < 5.0 {
-                score += 30.0; // Clearing path to HQ
+                score += terms.add("Clearing path to HQ", 30.0);
             }
         }
         _ => {}
@@ -1092,12 +1527,12 @@ fn score_attack_action(
     // === STRATEGIC GOAL ALIGNMENT ===
     match goal {
         StrategicGoal::Attack { priority } => {
-            score += priority * 0.5;
+            score += terms.add("Attack goal", priority * 0.5);
         }
         StrategicGoal::Defend { .. } => {
             // Defensive attacks on threats to our territory
             if influence.get_territory(target_unit.pos.x, target_unit.pos.y) > 0.0 {
-                score += 20.0; // Attacking invader
+                score += terms.add("Attacking invader", 20.0);
             }
         }
         _ => {}
@@ -1111,16 +1546,16 @@ fn score_attack_action(
             (target_stats.attack as f32 * 0.3) as i32
         } else { 0 }
     } else { 0 };
-    score += UtilityCurves::position_safety(threat_after, estimated_hp_after)
-        * config.risk_tolerance();
+    score += terms.add("Position safety", UtilityCurves::position_safety(threat_after, estimated_hp_after)
+        * config.risk_tolerance());
 
     // === INFLUENCE MAP BONUSES ===
     // Attacking in contested territory is good
     let frontline = influence.get_frontline(target_unit.pos.x, target_unit.pos.y);
-    score += frontline * 15.0;
+    score += terms.add("Frontline", frontline * 15.0);
 
     // Attacking high-strategic-value positions
-    score += influence.get_strategic(target_unit.pos.x, target_unit.pos.y) * 0.3;
+    score += terms.add("Strategic value", influence.get_strategic(target_unit.pos.x, target_unit.pos.y) * 0.3);
 
     score
 }
@@ -1135,6 +1570,7 @@ fn score_capture_action(
     predictions: &[PredictedAction],
     _map: &GameMap,
     config: &AiConfig,
+    terms: &mut ScoreTerms,
 ) -> f32 {
     let tile = match analysis.capturable_tiles.iter().find(|t| t.entity == tile_entity) {
         Some(t) => t,
@@ -1142,7 +1578,7 @@ fn score_capture_action(
     };
 
     // Progress toward capture (unit HP = capture power)
-    let progress = if tile.capturing_faction == Some(Faction::Northern) {
+    let progress = if tile.capturing_faction == Some(analysis.faction) {
         tile.capture_progress
     } else {
         0
@@ -1150,65 +1586,65 @@ fn score_capture_action(
     let total_progress = progress + unit.unit.hp;
     let required = tile.terrain.capture_points();
 
-    let is_base = tile.terrain == Terrain::Base;
+    let is_base = matches!(tile.terrain, Terrain::Base | Terrain::HQ);
     let income = tile.terrain.income_value();
 
     // === BASE CAPTURE UTILITY ===
-    let mut score = UtilityCurves::capture_utility(total_progress, required, income, is_base);
+    let mut score = terms.add("Capture progress", UtilityCurves::capture_utility(total_progress, required, income, is_base));
 
     // === STRATEGY-SPECIFIC BONUSES ===
     match config.strategy {
         AiStrategy::Domination => {
             // Huge bonus for capturing
-            score *= 1.5;
+            score += terms.add("Domination strategy", score * 0.5);
             if tile.owner.is_none() {
-                score += 30.0; // Extra for neutral properties
+                score += terms.add("Neutral property", 30.0);
             }
         }
         AiStrategy::Blitz => {
             // Only care about enemy HQ
-            if is_base && tile.owner == Some(Faction::Eastern) {
-                score += 200.0; // This is the win condition!
+            if is_base && tile.owner.is_some_and(|owner| owner != analysis.faction) {
+                score += terms.add("Blitz: enemy HQ", 200.0); // This is the win condition!
             } else {
-                score *= 0.3; // Ignore other captures
+                score += terms.add("Blitz strategy", -score * 0.7); // Ignore other captures
             }
         }
         AiStrategy::Annihilation => {
             // Don't really care about capturing
-            score *= 0.3;
+            score += terms.add("Annihilation strategy", -score * 0.7);
         }
         AiStrategy::Swarm => {
             // Need income for swarm
-            score *= 1.3;
+            score += terms.add("Swarm strategy", score * 0.3);
         }
         _ => {}
     }
 
     // === ENEMY BASE = VICTORY CONDITION ===
-    if is_base && tile.owner.is_some() && tile.owner != Some(Faction::Northern) {
-        score += 150.0; // Capturing enemy base is huge
+    if is_base && tile.owner.is_some() && tile.owner != Some(analysis.faction) {
+        score += terms.add("Enemy base", 150.0); // Capturing enemy base is huge
     }
 
     // === RISK ASSESSMENT ===
     let threat = influence.get_threat(move_to.0, move_to.1);
-    score += UtilityCurves::position_safety(threat, unit.unit.hp) * config.risk_tolerance();
+    score += terms.add("Position safety", UtilityCurves::position_safety(threat, unit.unit.hp) * config.risk_tolerance());
 
     // If enemies are predicted to attack this position, risky
     for pred in predictions {
         if pred.likely_position == move_to {
-            score -= 30.0 * pred.confidence * config.risk_tolerance();
+            score += terms.add("Predicted enemy attack", -30.0 * pred.confidence * config.risk_tolerance());
         }
     }
 
     // === STRATEGIC GOAL ALIGNMENT ===
     match goal {
         StrategicGoal::Expand { priority } => {
-            score += priority * 0.8;
+            score += terms.add("Expand goal", priority * 0.8);
         }
         StrategicGoal::Defend { priority } => {
             // Capturing to deny enemy
-            if tile.capturing_faction.is_some() && tile.capturing_faction != Some(Faction::Northern) {
-                score += priority * 0.5;
+            if tile.capturing_faction.is_some() && tile.capturing_faction != Some(analysis.faction) {
+                score += terms.add("Defend goal", priority * 0.5);
             }
         }
         _ => {}
@@ -1217,12 +1653,12 @@ fn score_capture_action(
     // === INFLUENCE MAP ===
     // Capturing behind enemy lines is risky (but personality affects this)
     if influence.get_territory(move_to.0, move_to.1) < -0.3 {
-        score -= 20.0 * config.risk_tolerance();
+        score += terms.add("Behind enemy lines", -20.0 * config.risk_tolerance());
     }
 
     // Capturing in safe territory is better
     if influence.is_behind_lines(move_to.0, move_to.1) {
-        score += 15.0;
+        score += terms.add("Safe territory", 15.0);
     }
 
     score
@@ -1237,6 +1673,7 @@ fn score_move_action(
     predictions: &[PredictedAction],
     map: &GameMap,
     config: &AiConfig,
+    terms: &mut ScoreTerms,
 ) -> f32 {
     let mut score = 0.0;
 
@@ -1245,11 +1682,11 @@ fn score_move_action(
 
     // === TERRAIN DEFENSE ===
     // Personality affects how much we value defensive terrain
-    score += defense_bonus as f32 * 4.0 * config.position_value();
+    score += terms.add("Terrain defense", defense_bonus as f32 * 4.0 * config.position_value());
 
     // === THREAT AVOIDANCE ===
     let threat = influence.get_threat(move_to.0, move_to.1);
-    score += UtilityCurves::position_safety(threat, unit.unit.hp) * config.risk_tolerance();
+    score += terms.add("Position safety", UtilityCurves::position_safety(threat, unit.unit.hp) * config.risk_tolerance());
 
     // === SUPPORT FROM ALLIES ===
     let nearby_allies = analysis.ai_units.iter()
@@ -1258,18 +1695,18 @@ fn score_move_action(
             dist <= 3 && u.entity != unit.entity
         })
         .count();
-    score += UtilityCurves::support_utility(nearby_allies);
+    score += terms.add("Nearby allies", UtilityCurves::support_utility(nearby_allies));
 
     // === STRATEGY-SPECIFIC MOVEMENT ===
     match config.strategy {
         AiStrategy::Blitz => {
             // Move toward enemy HQ aggressively
             let dist_to_enemy_hq = analysis.enemy_properties.iter()
-                .filter(|t| t.terrain == Terrain::Base)
+                .filter(|t| matches!(t.terrain, Terrain::Base | Terrain::HQ))
                 .map(|t| ((move_to.0 - t.pos.x).abs() + (move_to.1 - t.pos.y).abs()) as f32)
                 .min_by(|a, b| a.partial_cmp(b).unwrap())
                 .unwrap_or(100.0);
-            score += 50.0 - dist_to_enemy_hq * 3.0; // Strong pull toward HQ
+            score += terms.add("Blitz: toward enemy HQ", 50.0 - dist_to_enemy_hq * 3.0); // Strong pull toward HQ
         }
         AiStrategy::Fortress => {
             // Stay near our properties
@@ -1279,7 +1716,7 @@ fn score_move_action(
                 .min_by(|a, b| a.partial_cmp(b).unwrap())
                 .unwrap_or(100.0);
             if dist_to_our_base < 4.0 {
-                score += 20.0; // Bonus for staying close to base
+                score += terms.add("Fortress: near base", 20.0);
             }
         }
         AiStrategy::Annihilation => {
@@ -1288,7 +1725,7 @@ fn score_move_action(
                 .map(|e| ((move_to.0 - e.pos.x).abs() + (move_to.1 - e.pos.y).abs()) as f32)
                 .min_by(|a, b| a.partial_cmp(b).unwrap())
                 .unwrap_or(100.0);
-            score += 30.0 - dist_to_enemy * 2.0;
+            score += terms.add("Annihilation: toward enemies", 30.0 - dist_to_enemy * 2.0);
         }
         _ => {}
     }
@@ -1301,11 +1738,11 @@ fn score_move_action(
                 .map(|e| ((move_to.0 - e.pos.x).abs() + (move_to.1 - e.pos.y).abs()) as f32)
                 .min_by(|a, b| a.partial_cmp(b).unwrap())
                 .unwrap_or(100.0);
-            score += (20.0 - dist_to_enemy * 1.5) * (priority / 100.0);
+            score += terms.add("Attack goal", (20.0 - dist_to_enemy * 1.5) * (priority / 100.0));
 
             // Move toward frontline
             let frontline = influence.get_frontline(move_to.0, move_to.1);
-            score += frontline * priority * 0.3;
+            score += terms.add("Frontline", frontline * priority * 0.3);
         }
         StrategicGoal::Defend { priority } => {
             // Move toward our properties
@@ -1313,7 +1750,7 @@ fn score_move_action(
                 .map(|p| ((move_to.0 - p.pos.x).abs() + (move_to.1 - p.pos.y).abs()) as f32)
                 .min_by(|a, b| a.partial_cmp(b).unwrap())
                 .unwrap_or(100.0);
-            score += (15.0 - dist_to_property) * (priority / 100.0);
+            score += terms.add("Defend goal", (15.0 - dist_to_property) * (priority / 100.0));
 
             // Interpose between enemies and our base
             for pred in predictions {
@@ -1321,7 +1758,7 @@ fn score_move_action(
                     let dist_to_threat = ((move_to.0 - pred.likely_position.0).abs() +
                         (move_to.1 - pred.likely_position.1).abs()) as f32;
                     if dist_to_threat <= 2.0 {
-                        score += 20.0 * pred.confidence;
+                        score += terms.add("Interposing", 20.0 * pred.confidence);
                     }
                 }
             }
@@ -1334,25 +1771,53 @@ fn score_move_action(
                     .map(|t| ((move_to.0 - t.pos.x).abs() + (move_to.1 - t.pos.y).abs()) as f32)
                     .min_by(|a, b| a.partial_cmp(b).unwrap())
                     .unwrap_or(100.0);
-                score += (25.0 - dist_to_capture * 2.0) * (priority / 100.0);
+                score += terms.add("Expand goal", (25.0 - dist_to_capture * 2.0) * (priority / 100.0));
             }
         }
         StrategicGoal::Consolidate { priority } => {
             // Move toward safe positions but don't flee too aggressively
             let retreat = influence.retreat_value.get(&move_to).copied().unwrap_or(0.0);
-            score += retreat * 0.2 * (priority / 100.0);
+            score += terms.add("Consolidate: retreat", retreat * 0.2 * (priority / 100.0));
 
             // Slight preference for distance from enemies, but don't run away
             let dist_to_enemy = analysis.enemy_units.iter()
                 .map(|e| ((move_to.0 - e.pos.x).abs() + (move_to.1 - e.pos.y).abs()) as f32)
                 .min_by(|a, b| a.partial_cmp(b).unwrap())
                 .unwrap_or(100.0);
-            score += dist_to_enemy * 0.2 * (priority / 100.0);
+            score += terms.add("Consolidate: distance", dist_to_enemy * 0.2 * (priority / 100.0));
         }
     }
 
     // === STRATEGIC VALUE OF POSITION ===
-    score += influence.get_strategic(move_to.0, move_to.1) * 0.2 * config.position_value();
+    score += terms.add("Strategic value", influence.get_strategic(move_to.0, move_to.1) * 0.2 * config.position_value());
+
+    // === TRANSPORTS ===
+    // Loaded transports follow their plan; empty ones go to pick up stranded infantry
+    if unit.unit.is_transport() {
+        if let Some(&(tx, ty)) = analysis.transport_plans.get(&unit.entity) {
+            let dist = ((move_to.0 - tx).abs() + (move_to.1 - ty).abs()) as f32;
+            score += terms.add("Transport plan", 40.0 - dist * 3.0);
+        } else if !unit.unit.has_cargo() {
+            let dist_to_passenger = analysis.ai_units.iter()
+                .filter(|u| analysis.stranded.contains(&u.entity))
+                .map(|u| ((move_to.0 - u.pos.x).abs() + (move_to.1 - u.pos.y).abs()) as f32)
+                .min_by(|a, b| a.partial_cmp(b).unwrap());
+            if let Some(dist) = dist_to_passenger {
+                score += terms.add("Fetching passengers", 25.0 - dist * 2.0);
+            }
+        }
+    }
+
+    // === SCOUTING ===
+    // Recon units go looking for enemies hidden by fog
+    if unit.unit_type == UnitType::Recon {
+        let dist_to_unknown = analysis.scout_targets.iter()
+            .map(|&(x, y)| ((move_to.0 - x).abs() + (move_to.1 - y).abs()) as f32)
+            .min_by(|a, b| a.partial_cmp(b).unwrap());
+        if let Some(dist) = dist_to_unknown {
+            score += terms.add("Scouting", 30.0 - dist * 2.0);
+        }
+    }
 
     // === ARTILLERY POSITIONING ===
     if unit.is_indirect {
@@ -1363,10 +1828,10 @@ fn score_move_action(
             dist >= stats.attack_range.0 && dist <= stats.attack_range.1
         });
         if can_hit_enemy {
-            score += 25.0;
+            score += terms.add("Enemies in range", 25.0);
         }
         // Extra safety for artillery
-        score += UtilityCurves::position_safety(threat, unit.unit.hp) * 0.5 * config.risk_tolerance();
+        score += terms.add("Artillery safety", UtilityCurves::position_safety(threat, unit.unit.hp) * 0.5 * config.risk_tolerance());
     }
 
     // === RESUPPLY SEEKING ===
@@ -1378,18 +1843,18 @@ fn score_move_action(
     if low_stamina || low_ammo {
         // Find distance to nearest friendly resupply building (Base or Storehouse)
         let dist_to_supply = analysis.our_properties.iter()
-            .filter(|t| matches!(t.terrain, Terrain::Base | Terrain::Storehouse))
+            .filter(|t| matches!(t.terrain, Terrain::Base | Terrain::HQ | Terrain::Storehouse))
             .map(|t| ((move_to.0 - t.pos.x).abs() + (move_to.1 - t.pos.y).abs()) as f32)
             .min_by(|a, b| a.partial_cmp(b).unwrap());
 
         if let Some(dist) = dist_to_supply {
             // Strong bonus for moving toward supply when resources are low
             let urgency = if low_stamina && low_ammo { 2.0 } else { 1.0 };
-            score += (40.0 - dist * 4.0) * urgency;
+            score += terms.add("Toward supply", (40.0 - dist * 4.0) * urgency);
 
             // Huge bonus for actually being on a supply building
             if dist == 0.0 {
-                score += 50.0 * urgency;
+                score += terms.add("On supply", 50.0 * urgency);
             }
         }
     }
@@ -1397,6 +1862,121 @@ fn score_move_action(
     score
 }
 
+fn score_load_action(
+    unit: &UnitInfo,
+    move_to: (i32, i32),
+    analysis: &GameAnalysis,
+    influence: &InfluenceMaps,
+    config: &AiConfig,
+) -> f32 {
+    let threat = influence.get_threat(move_to.0, move_to.1);
+    let safety = UtilityCurves::position_safety(threat, unit.unit.hp) * config.risk_tolerance() * 0.5;
+
+    // Stranded infantry can only reach a capture by transport
+    if analysis.stranded.contains(&unit.entity) {
+        return 60.0 + safety;
+    }
+
+    // Otherwise only ride when the nearest capture is a long walk away
+    let movement = unit.unit.unit_type.stats().movement as f32;
+    let dist_to_capture = analysis.capturable_tiles.iter()
+        .map(|t| ((unit.pos.x - t.pos.x).abs() + (unit.pos.y - t.pos.y).abs()) as f32)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+        .unwrap_or(0.0);
+    if dist_to_capture > movement * 3.0 {
+        20.0 + safety
+    } else {
+        -20.0
+    }
+}
+
+fn score_unload_action(
+    transport: &UnitInfo,
+    move_to: (i32, i32),
+    drop_at: (i32, i32),
+    analysis: &GameAnalysis,
+    influence: &InfluenceMaps,
+    config: &AiConfig,
+) -> f32 {
+    let target = analysis.transport_plans.get(&transport.entity).copied().or_else(|| {
+        analysis.capturable_tiles.iter()
+            .map(|t| (t.pos.x, t.pos.y))
+            .min_by_key(|&(x, y)| (x - drop_at.0).abs() + (y - drop_at.1).abs())
+    });
+    let Some(target) = target else { return -20.0 };
+
+    // Drop the cargo as close to its target as we can
+    let dist = ((drop_at.0 - target.0).abs() + (drop_at.1 - target.1).abs()) as f32;
+    let mut score = 60.0 - dist * 8.0;
+    if dist == 0.0 {
+        score += 20.0;
+    }
+
+    let threat = influence.get_threat(move_to.0, move_to.1);
+    score += UtilityCurves::position_safety(threat, transport.unit.hp) * config.risk_tolerance() * 0.5;
+    score
+}
+
+fn score_join_action(
+    unit: &UnitInfo,
+    target: Entity,
+    analysis: &GameAnalysis,
+    influence: &InfluenceMaps,
+) -> f32 {
+    let Some(target_info) = analysis.ai_units.iter().find(|u| u.entity == target) else {
+        return f32::NEG_INFINITY;
+    };
+    let max_hp = target_info.unit.unit_type.stats().max_hp;
+
+    // Reward HP recovered, penalize HP lost to the cap
+    let gained = unit.unit.hp.min(max_hp - target_info.unit.hp);
+    let wasted = unit.unit.hp - gained;
+    let mut score = gained as f32 * 0.5 - wasted as f32 * 0.3;
+
+    // Weak units in danger are better folded into a healthy one
+    score += (1.0 - unit.hp_percent) * 20.0;
+    score += influence.get_threat(unit.pos.x, unit.pos.y) * 0.2;
+    score
+}
+
+fn score_resupply_action(
+    supplier: &UnitInfo,
+    move_to: (i32, i32),
+    analysis: &GameAnalysis,
+    influence: &InfluenceMaps,
+    config: &AiConfig,
+) -> f32 {
+    let needy = analysis.ai_units.iter()
+        .filter(|u| u.entity != supplier.entity)
+        .filter(|u| (u.pos.x - move_to.0).abs() + (u.pos.y - move_to.1).abs() == 1)
+        .filter(|u| needs_supply(&u.unit))
+        .count();
+
+    let threat = influence.get_threat(move_to.0, move_to.1);
+    needy as f32 * 25.0 + UtilityCurves::position_safety(threat, supplier.unit.hp) * config.risk_tolerance()
+}
+
+/// Whether a unit is low enough on stamina or ammo to want resupplying
+fn needs_supply(unit: &Unit) -> bool {
+    let stats = unit.unit_type.stats();
+    unit.stamina <= stats.max_stamina / 3 || (stats.max_ammo > 0 && unit.ammo <= stats.max_ammo / 3)
+}
+
+/// Whether a unit can move onto a tile held by a friendly unit (to load or join)
+fn can_reach_partner(
+    unit: &UnitInfo,
+    partner_pos: (i32, i32),
+    analysis: &GameAnalysis,
+    map: &GameMap,
+    game_data: &GameData,
+) -> bool {
+    let stats = unit.unit.unit_type.stats();
+    let mut others = analysis.unit_positions.clone();
+    others.remove(&partner_pos);
+    let movement = effective_movement(stats.movement, unit.unit.stamina);
+    calculate_movement_range(&unit.pos, movement, map, &others, stats.class, game_data).contains(&partner_pos)
+}
+
 fn score_wait_action(unit: &UnitInfo, influence: &InfluenceMaps, config: &AiConfig) -> f32 {
     // Waiting is usually bad, but okay if in a good defensive position
     let threat = influence.get_threat(unit.pos.x, unit.pos.y);
@@ -1422,37 +2002,67 @@ fn score_wait_action(unit: &UnitInfo, influence: &InfluenceMaps, config: &AiConf
 // ============================================================================
 
 fn plan_turn_advanced(
-    analysis: &GameAnalysis,
-    influence: &InfluenceMaps,
-    goals: &[StrategicGoal],
-    predictions: &[PredictedAction],
+    ctx: &PlanningContext,
     map: &GameMap,
     config: &AiConfig,
     game_data: &GameData,
+    difficulty: AiDifficulty,
 ) -> Vec<PlannedAction> {
+    let all_possible = collect_candidates(ctx, map, config, game_data);
+
     let mut actions: Vec<PlannedAction> = Vec::new();
-    let mut planned_positions: HashSet<(i32, i32)> = HashSet::new();
-    let mut assigned_units: HashSet<Entity> = HashSet::new();
+    let mut reservations = Reservations::default();
+
+    // Assign greedily, each unit only trying as many options as the difficulty allows
+    let mut options_tried: HashMap<Entity, usize> = HashMap::new();
+    for (unit_entity, action, priority) in all_possible {
+        if reservations.assigned.contains(&unit_entity) {
+            continue;
+        }
+
+        let tried = options_tried.entry(unit_entity).or_insert(0);
+        if *tried >= difficulty.planning_depth() {
+            continue;
+        }
+        *tried += 1;
+
+        if reservations.try_reserve(unit_entity, &action) {
+            push_planned(&mut actions, unit_entity, action, priority);
+        }
+    }
+
+    order_for_execution(&mut actions, &ctx.analysis.ai_units);
+    actions
+}
+
+/// Every scored action available to units that haven't acted, best first
+fn collect_candidates(
+    ctx: &PlanningContext,
+    map: &GameMap,
+    config: &AiConfig,
+    game_data: &GameData,
+) -> Vec<(Entity, AiAction, f32)> {
+    let PlanningContext { analysis, influence, goals, predictions, noise } = ctx;
 
     // Collect all possible actions with scores
     let mut all_possible: Vec<(Entity, AiAction, f32)> = Vec::new();
 
     for ai_unit in &analysis.ai_units {
-        if ai_unit.unit.moved {
+        if ai_unit.unit.moved || ai_unit.unit.exhausted {
             continue;
         }
 
         let stats = ai_unit.unit.unit_type.stats();
         // Use effective movement (limited by stamina)
         let actual_movement = effective_movement(stats.movement, ai_unit.unit.stamina);
-        let moves = calculate_movement_range(
+        let moves = sorted_moves(calculate_movement_range(
             &ai_unit.pos,
             actual_movement,
             map,
             &analysis.unit_positions,
             stats.class,
             game_data,
-        );
+        ));
 
         for (mx, my) in &moves {
             if analysis.unit_positions.contains_key(&(*mx, *my))
@@ -1473,7 +2083,7 @@ fn plan_turn_advanced(
                             move_to: (*mx, *my),
                             target: enemy.entity,
                         };
-                        let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data);
+                        let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data, noise);
                         all_possible.push((ai_unit.entity, action, score));
                     }
                 }
@@ -1487,222 +2097,735 @@ fn plan_turn_advanced(
                             move_to: (*mx, *my),
                             tile: tile.entity,
                         };
-                        let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data);
+                        let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data, noise);
                         all_possible.push((ai_unit.entity, action, score));
                     }
                 }
             }
 
+            // Evaluate unloading cargo next to this tile
+            if let Some(cargo) = ai_unit.unit.cargo.as_ref().filter(|_| !ai_unit.unit.attacked) {
+                let cargo_class = cargo.unit_type.stats().class;
+                for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
+                    let drop_at = (mx + dx, my + dy);
+                    let passable = map.get(drop_at.0, drop_at.1)
+                        .is_some_and(|t| game_data.movement_cost_or_default(t, cargo_class) < 99);
+                    if !passable || analysis.unit_positions.contains_key(&drop_at) {
+                        continue;
+                    }
+                    let action = AiAction::Unload { move_to: (*mx, *my), drop_at };
+                    let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data, noise);
+                    all_possible.push((ai_unit.entity, action, score));
+                }
+            }
+
+            // Evaluate resupplying from this tile
+            if ai_unit.unit_type == UnitType::Supplier {
+                let action = AiAction::Resupply { move_to: (*mx, *my) };
+                let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data, noise);
+                all_possible.push((ai_unit.entity, action, score));
+            }
+
             // Evaluate moves
             let action = AiAction::Move { move_to: (*mx, *my) };
-            let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data);
+            let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data, noise);
             all_possible.push((ai_unit.entity, action, score));
         }
 
+        // Evaluate boarding an empty transport
+        if ai_unit.unit.can_be_transported() {
+            for transport in analysis.ai_units.iter().filter(|u| u.unit.is_transport() && !u.unit.has_cargo()) {
+                let transport_pos = (transport.pos.x, transport.pos.y);
+                if can_reach_partner(ai_unit, transport_pos, analysis, map, game_data) {
+                    let action = AiAction::Load { move_to: transport_pos, transport: transport.entity };
+                    let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data, noise);
+                    all_possible.push((ai_unit.entity, action, score));
+                }
+            }
+        }
+
+        // Evaluate merging a badly damaged unit into a damaged ally of the same type
+        if ai_unit.hp_percent <= 0.5 {
+            for ally in &analysis.ai_units {
+                if ally.entity == ai_unit.entity || ally.unit_type != ai_unit.unit_type || ally.hp_percent >= 1.0 {
+                    continue;
+                }
+                let ally_pos = (ally.pos.x, ally.pos.y);
+                if can_reach_partner(ai_unit, ally_pos, analysis, map, game_data) {
+                    let action = AiAction::Join { move_to: ally_pos, target: ally.entity };
+                    let score = score_action(ai_unit, &action, analysis, influence, goals, predictions, map, config, game_data, noise);
+                    all_possible.push((ai_unit.entity, action, score));
+                }
+            }
+        }
+
         // Wait action
         let wait = AiAction::Wait;
-        let score = score_action(ai_unit, &wait, analysis, influence, goals, predictions, map, config, game_data);
+        let score = score_action(ai_unit, &wait, analysis, influence, goals, predictions, map, config, game_data, noise);
         all_possible.push((ai_unit.entity, wait, score));
     }
 
     // Sort by score
     all_possible.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+    all_possible
+}
 
-    // Assign greedily
-    for (unit_entity, action, priority) in all_possible {
-        if assigned_units.contains(&unit_entity) {
-            continue;
+/// Units and tiles already claimed by a turn plan
+#[derive(Clone, Default)]
+struct Reservations {
+    assigned: HashSet<Entity>,
+    positions: HashSet<(i32, i32)>,
+}
+
+impl Reservations {
+    /// Claim what an action needs, or return false if it clashes with the plan so far
+    fn try_reserve(&mut self, unit: Entity, action: &AiAction) -> bool {
+        if self.assigned.contains(&unit) {
+            return false;
         }
 
-        let move_pos = match &action {
-            AiAction::Attack { move_to, .. } => Some(*move_to),
-            AiAction::Capture { move_to, .. } => Some(*move_to),
-            AiAction::Move { move_to } => Some(*move_to),
-            AiAction::Wait => None,
-        };
-
-        if let Some(pos) = move_pos {
-            if planned_positions.contains(&pos) {
-                continue;
+        // Loading and joining need the partner to hold still this turn
+        if let Some(partner) = action.partner() {
+            if self.assigned.contains(&partner) {
+                return false;
             }
-            planned_positions.insert(pos);
+            self.assigned.insert(partner);
+        } else if let Some(pos) = action.destination() {
+            if self.positions.contains(&pos) {
+                return false;
+            }
+            if let AiAction::Unload { drop_at, .. } = action {
+                if self.positions.contains(drop_at) {
+                    return false;
+                }
+                self.positions.insert(*drop_at);
+            }
+            self.positions.insert(pos);
         }
 
-        assigned_units.insert(unit_entity);
+        self.assigned.insert(unit);
+        true
+    }
+}
+
+/// Add an action to a plan, holding its load/join partner in place
+fn push_planned(actions: &mut Vec<PlannedAction>, unit: Entity, action: AiAction, priority: f32) {
+    if let Some(partner) = action.partner() {
         actions.push(PlannedAction {
-            unit: unit_entity,
-            action,
+            unit: partner,
+            action: AiAction::Wait,
             priority,
         });
     }
+    actions.push(PlannedAction {
+        unit,
+        action,
+        priority,
+    });
+}
 
-    // Sort by priority for execution
-    actions.sort_by(|a, b| b.priority.partial_cmp(&a.priority).unwrap_or(std::cmp::Ordering::Equal));
+/// Order for focus fire rather than raw priority
+fn order_for_execution(actions: &mut [PlannedAction], units: &[UnitInfo]) {
+    actions.sort_by(|a, b| {
+        execution_stage(a, units).cmp(&execution_stage(b, units))
+            .then(b.priority.partial_cmp(&a.priority).unwrap_or(std::cmp::Ordering::Equal))
+    });
+}
 
-    actions
+/// Focus-fire sequencing: indirects soften targets first, cheap units finish them,
+/// then everyone else repositions and capturers commit last
+fn execution_stage(planned: &PlannedAction, units: &[UnitInfo]) -> (u8, u32) {
+    let unit = units.iter().find(|u| u.entity == planned.unit);
+    match planned.action {
+        AiAction::Attack { .. } => match unit {
+            Some(u) if u.is_indirect => (0, 0),
+            Some(u) => (1, u.value),
+            None => (1, 0),
+        },
+        AiAction::Wait => (3, 0),
+        AiAction::Capture { .. } => (4, 0),
+        _ => (2, 0),
+    }
 }
 
 // ============================================================================
-// SMART PRODUCTION
+// LOOKAHEAD SEARCH - Beam search over turn plans against a simulated reply
 // ============================================================================
 
-fn smart_production(
-    config: &AiConfig,
-    funds: &mut ResMut<FactionFunds>,
-    faction: Faction,
-    analysis: &GameAnalysis,
-    tiles: &[(Entity, Tile)],
-    commands: &mut Commands,
-    map: &GameMap,
-    sprite_param: &mut SpriteAssetsParam,
-    goals: &[StrategicGoal],
-    cost_modifier: f32,
-) {
-    let unit_positions: HashSet<(i32, i32)> = analysis.ai_units.iter()
-        .map(|u| (u.pos.x, u.pos.y))
-        .chain(analysis.enemy_units.iter().map(|u| (u.pos.x, u.pos.y)))
-        .collect();
+/// Time the search may take in a single frame so the UI stays responsive.
+/// This only spreads the work across frames; `search_evaluations` and `search_time` end it
+const SEARCH_FRAME_BUDGET: Duration = Duration::from_millis(4);
 
-    let empty_bases: Vec<_> = tiles.iter()
-        .filter(|(_, t)| {
-            t.terrain == Terrain::Base
-                && t.owner == Some(faction)
-                && !unit_positions.contains(&(t.position.x, t.position.y))
-        })
-        .map(|(_, t)| (t.position.x, t.position.y))
-        .collect();
+/// How much of the planner's own scores carries into a searched plan's evaluation
+const PLAN_SCORE_WEIGHT: f32 = 0.1;
 
-    if empty_bases.is_empty() {
-        return;
-    }
+/// Evaluation bonus for completing a capture
+const CAPTURE_VALUE: f32 = 40.0;
 
-    // Analyze needs
-    let our_scouts = analysis.ai_units.iter().filter(|u| u.unit_type == UnitType::Scout).count();
-    let _our_combat = analysis.ai_units.iter().filter(|u| u.unit_type != UnitType::Scout).count();
-    let enemy_tanks = analysis.enemy_units.iter().filter(|u| u.unit_type == UnitType::Ironclad).count();
-    let enemy_artillery = analysis.enemy_units.iter().filter(|u| u.is_indirect).count();
+/// A unit as seen by the plan simulator
+#[derive(Clone)]
+struct SimUnit {
+    entity: Entity,
+    ours: bool,
+    unit: Unit,
+    pos: (i32, i32),
+    value: u32,
+    /// Loaded into a transport, so out of harm's way this turn
+    carried: bool,
+}
 
-    let primary_goal = goals.first();
+/// A partial turn plan in the beam
+#[derive(Clone, Default)]
+struct SearchNode {
+    actions: Vec<PlannedAction>,
+    reservations: Reservations,
+    eval: f32,
+}
 
-    let mut build_list: Vec<(UnitType, u32, f32)> = Vec::new();
+/// Beam search over turn plans, carried across frames
+#[derive(Default)]
+struct AiSearch {
+    /// Each unit's best few options, in the order units are decided
+    candidates: Vec<(Entity, Vec<(AiAction, f32)>)>,
+    depth: usize,
+    beam: Vec<SearchNode>,
+    /// Children of the current beam still waiting to be evaluated
+    pending: Vec<SearchNode>,
+    scored: Vec<SearchNode>,
+    /// The one-ply greedy plan, which the search has to beat
+    greedy: SearchNode,
+    units: Vec<SimUnit>,
+    ai_units: Vec<UnitInfo>,
+    /// Tiles each enemy could attack from on its reply
+    enemy_reach: HashMap<Entity, HashSet<(i32, i32)>>,
+    /// Capture points still needed on each capturable tile
+    capture_remaining: HashMap<(i32, i32), i32>,
+    ai_co: CoBonuses,
+    evaluations: usize,
+    /// When the search began, for the `search_time` cap
+    started: Option<Instant>,
+}
 
-    // === STRATEGY-BASED PRODUCTION ===
-    match config.strategy {
-        AiStrategy::Annihilation => {
-            // Build heavy combat units
-            build_list.push((UnitType::Ironclad, 70, 90.0));
-            build_list.push((UnitType::Shocktrooper, 30, 80.0));
-            build_list.push((UnitType::Siege, 60, 70.0));
-            // Minimal scouts
-            if our_scouts < 1 {
-                build_list.push((UnitType::Scout, 10, 50.0));
+impl AiSearch {
+    fn start(
+        &mut self,
+        ctx: &PlanningContext,
+        map: &GameMap,
+        config: &AiConfig,
+        game_data: &GameData,
+        weather: &Weather,
+        difficulty: AiDifficulty,
+        ai_co: CoBonuses,
+    ) {
+        let analysis = &ctx.analysis;
+
+        // Group the ranked candidates by unit, best unit first
+        self.candidates.clear();
+        for (unit, action, score) in collect_candidates(ctx, map, config, game_data) {
+            let index = match self.candidates.iter().position(|(e, _)| *e == unit) {
+                Some(i) => i,
+                None => {
+                    self.candidates.push((unit, Vec::new()));
+                    self.candidates.len() - 1
+                }
+            };
+            let options = &mut self.candidates[index].1;
+            if options.len() < difficulty.search_branching() {
+                options.push((action, score));
             }
         }
-        AiStrategy::Domination => {
-            // Lots of scouts for capturing
-            build_list.push((UnitType::Scout, 10, 95.0));
-            build_list.push((UnitType::Scout, 10, 90.0)); // Want multiple scouts
-            build_list.push((UnitType::Recon, 40, 60.0)); // Fast capture support
-            build_list.push((UnitType::Shocktrooper, 30, 50.0));
-        }
-        AiStrategy::Blitz => {
-            // Fast, mobile units
-            build_list.push((UnitType::Recon, 40, 85.0));
-            build_list.push((UnitType::Scout, 10, 80.0)); // For capturing HQ
-            build_list.push((UnitType::Ironclad, 70, 70.0));
-        }
-        AiStrategy::Attrition => {
-            // Balanced, cost-effective units
-            build_list.push((UnitType::Shocktrooper, 30, 80.0));
-            build_list.push((UnitType::Siege, 60, 75.0)); // Artillery for safe damage
-            if our_scouts < 2 {
-                build_list.push((UnitType::Scout, 10, 70.0));
+
+        self.units = analysis.ai_units.iter().map(|u| (u, true))
+            .chain(analysis.enemies_with_certainty().map(|(u, _)| (u, false)))
+            .map(|(u, ours)| SimUnit {
+                entity: u.entity,
+                ours,
+                unit: u.unit.clone(),
+                pos: (u.pos.x, u.pos.y),
+                value: u.value,
+                carried: false,
+            })
+            .collect();
+        self.ai_units = analysis.ai_units.clone();
+
+        // Enemy movement doesn't depend on our plan beyond blocking, so work it out once
+        self.enemy_reach.clear();
+        for (enemy, _) in analysis.enemies_with_certainty() {
+            let stats = enemy.unit.unit_type.stats();
+            let reach = if enemy.is_indirect {
+                // Indirect units can't move and fire in the same turn
+                HashSet::from([(enemy.pos.x, enemy.pos.y)])
+            } else {
+                let movement = effective_movement(stats.movement, enemy.unit.stamina);
+                calculate_movement_range(&enemy.pos, movement, map, &analysis.unit_positions, stats.class, game_data)
+            };
+            self.enemy_reach.insert(enemy.entity, reach);
+        }
+
+        self.capture_remaining = analysis.capturable_tiles.iter()
+            .map(|t| {
+                let progress = if t.capturing_faction == Some(analysis.faction) { t.capture_progress } else { 0 };
+                ((t.pos.x, t.pos.y), t.terrain.capture_points() - progress)
+            })
+            .collect();
+        self.ai_co = ai_co;
+
+        let greedy = plan_turn_advanced(ctx, map, config, game_data, difficulty);
+        self.greedy = SearchNode {
+            eval: self.evaluate(&greedy, map, game_data, weather),
+            actions: greedy,
+            reservations: Reservations::default(),
+        };
+
+        self.depth = 0;
+        self.beam = vec![SearchNode::default()];
+        self.pending.clear();
+        self.scored.clear();
+        self.evaluations = 1;
+        self.started = Some(Instant::now());
+    }
+
+    /// Advance the search within this frame's budget; true once it's done
+    fn step(&mut self, map: &GameMap, game_data: &GameData, weather: &Weather, difficulty: AiDifficulty) -> bool {
+        let frame_start = Instant::now();
+        while frame_start.elapsed() < SEARCH_FRAME_BUDGET {
+            let out_of_time = self.started.is_none_or(|t| t.elapsed() >= difficulty.search_time());
+            if out_of_time || self.evaluations >= difficulty.search_evaluations() {
+                return true;
+            }
+
+            if let Some(mut node) = self.pending.pop() {
+                node.eval = self.evaluate(&node.actions, map, game_data, weather);
+                self.evaluations += 1;
+                self.scored.push(node);
+                continue;
+            }
+
+            if !self.scored.is_empty() {
+                self.beam = best_nodes(std::mem::take(&mut self.scored), difficulty.beam_width());
+                self.depth += 1;
+            }
+            if self.depth >= self.candidates.len() {
+                return true;
+            }
+            self.expand();
+        }
+        false
+    }
+
+    /// Branch every plan in the beam on the next unit's options
+    fn expand(&mut self) {
+        let (unit, options) = &self.candidates[self.depth];
+        for parent in &self.beam {
+            let mut extended = false;
+            for (action, score) in options {
+                let mut child = parent.clone();
+                if child.reservations.try_reserve(*unit, action) {
+                    push_planned(&mut child.actions, *unit, action.clone(), *score);
+                    self.pending.push(child);
+                    extended = true;
+                }
+            }
+            // A unit whose options all clash just sits this one out
+            if !extended {
+                self.pending.push(parent.clone());
             }
         }
-        AiStrategy::Swarm => {
-            // Cheap units, lots of them
-            build_list.push((UnitType::Scout, 10, 95.0));
-            build_list.push((UnitType::Scout, 10, 90.0));
-            build_list.push((UnitType::Shocktrooper, 30, 85.0));
-            build_list.push((UnitType::Shocktrooper, 30, 80.0));
-        }
-        AiStrategy::Fortress => {
-            // Defensive units
-            build_list.push((UnitType::Siege, 60, 85.0)); // Artillery
-            build_list.push((UnitType::Ironclad, 70, 80.0)); // Tanks to hold ground
-            build_list.push((UnitType::Shocktrooper, 30, 70.0));
-            if our_scouts < 1 {
-                build_list.push((UnitType::Scout, 10, 60.0));
+    }
+
+    /// Best plan found, completed greedily for any units the budget didn't reach.
+    /// The log line compares it with the greedy plan under the search's own evaluation;
+    /// that is all the search is known to improve, as win rates haven't been measured
+    fn finish(&mut self, map: &GameMap, game_data: &GameData, weather: &Weather) -> Vec<PlannedAction> {
+        // Children already scored have decided one more unit than the beam they came from
+        let (frontier, decided) = if self.scored.is_empty() {
+            (std::mem::take(&mut self.beam), self.depth)
+        } else {
+            (std::mem::take(&mut self.scored), self.depth + 1)
+        };
+        let mut best = best_nodes(frontier, 1).pop().unwrap_or_default();
+        self.pending.clear();
+
+        for (unit, options) in self.candidates.iter().skip(decided) {
+            for (action, score) in options {
+                if best.reservations.try_reserve(*unit, action) {
+                    push_planned(&mut best.actions, *unit, action.clone(), *score);
+                    break;
+                }
             }
         }
-        AiStrategy::Balanced => {
-            // Standard production based on goals
-            if our_scouts < 2 {
-                build_list.push((UnitType::Scout, 10, 85.0));
+        best.eval = self.evaluate(&best.actions, map, game_data, weather);
+
+        let elapsed = self.started.map(|t| t.elapsed()).unwrap_or_default();
+        info!("AI search: {} plans in {:?}, greedy plan evaluates {:.1}, lookahead best {:.1}",
+            self.evaluations, elapsed, self.greedy.eval, best.eval);
+
+        let mut chosen = if best.eval > self.greedy.eval { best.actions } else { std::mem::take(&mut self.greedy.actions) };
+        order_for_execution(&mut chosen, &self.ai_units);
+        chosen
+    }
+
+    /// Play out a plan and the opponent's best reply, then score the position
+    fn evaluate(&self, actions: &[PlannedAction], map: &GameMap, game_data: &GameData, weather: &Weather) -> f32 {
+        let mut units = self.units.clone();
+        let mut score = 0.0;
+
+        for planned in actions {
+            // Keep the planner's positional judgement as a tie-breaker
+            score += planned.priority * PLAN_SCORE_WEIGHT;
+
+            let Some(i) = units.iter().position(|u| u.entity == planned.unit) else { continue };
+            if let Some(dest) = planned.action.destination() {
+                units[i].pos = dest;
             }
 
-            match primary_goal {
-                Some(StrategicGoal::Attack { .. }) => {
-                    build_list.push((UnitType::Ironclad, 70, 70.0));
-                    build_list.push((UnitType::Shocktrooper, 30, 65.0));
-                }
-                Some(StrategicGoal::Defend { .. }) => {
-                    build_list.push((UnitType::Siege, 60, 70.0));
-                    build_list.push((UnitType::Shocktrooper, 30, 65.0));
+            match &planned.action {
+                AiAction::Attack { target, .. } => {
+                    if let Some(t) = units.iter().position(|u| u.entity == *target && u.unit.hp > 0) {
+                        sim_exchange(&mut units, i, t, &self.ai_co, &CoBonuses::none(), map, game_data, weather);
+                    }
                 }
-                Some(StrategicGoal::Expand { .. }) => {
-                    build_list.push((UnitType::Scout, 10, 90.0));
-                    build_list.push((UnitType::Recon, 40, 60.0));
+                AiAction::Capture { move_to, .. } => {
+                    let remaining = self.capture_remaining.get(move_to).copied().unwrap_or(20).max(1);
+                    let power = units[i].unit.hp;
+                    score += if power >= remaining {
+                        CAPTURE_VALUE
+                    } else {
+                        CAPTURE_VALUE * 0.5 * power as f32 / remaining as f32
+                    };
                 }
-                _ => {
-                    build_list.push((UnitType::Shocktrooper, 30, 60.0));
+                AiAction::Load { .. } => units[i].carried = true,
+                AiAction::Join { target, .. } => {
+                    if let Some(t) = units.iter().position(|u| u.entity == *target) {
+                        let max_hp = units[t].unit.unit_type.stats().max_hp;
+                        units[t].unit.hp = (units[t].unit.hp + units[i].unit.hp).min(max_hp);
+                        units[i].unit.hp = 0;
+                    }
                 }
+                _ => {}
             }
         }
+
+        simulate_reply(&mut units, &self.enemy_reach, &self.ai_co, map, game_data, weather);
+        score + material_balance(&units)
     }
+}
 
-    // Counter enemy composition (applies to all strategies)
-    if enemy_tanks > 1 {
-        build_list.push((UnitType::Shocktrooper, 30, 75.0 + enemy_tanks as f32 * 10.0));
+/// Keep the highest evaluated nodes; the sort is stable so ties stay deterministic
+fn best_nodes(mut nodes: Vec<SearchNode>, count: usize) -> Vec<SearchNode> {
+    nodes.sort_by(|a, b| b.eval.partial_cmp(&a.eval).unwrap_or(std::cmp::Ordering::Equal));
+    nodes.truncate(count);
+    nodes
+}
+
+/// Resolve an attack and its counter using the average of `estimate_damage`
+fn sim_exchange(
+    units: &mut [SimUnit],
+    attacker: usize,
+    defender: usize,
+    attacker_co: &CoBonuses,
+    defender_co: &CoBonuses,
+    map: &GameMap,
+    game_data: &GameData,
+    weather: &Weather,
+) {
+    let terrain = map.get(units[defender].pos.0, units[defender].pos.1).unwrap_or(Terrain::Grass);
+    let (min, max) = estimate_damage(&units[attacker].unit, &units[defender].unit, terrain, attacker_co, defender_co, weather, game_data);
+    units[defender].unit.hp -= (min + max) / 2;
+    if units[defender].unit.hp <= 0 {
+        return;
     }
-    if enemy_artillery > 1 {
-        build_list.push((UnitType::Recon, 40, 70.0 + enemy_artillery as f32 * 15.0));
+
+    let stats = units[defender].unit.unit_type.stats();
+    let (ax, ay) = units[attacker].pos;
+    let (dx, dy) = units[defender].pos;
+    let dist = ((ax - dx).abs() + (ay - dy).abs()) as u32;
+    let has_ammo = stats.max_ammo == 0 || units[defender].unit.ammo > 0;
+    if stats.attack > 0 && has_ammo && dist >= stats.attack_range.0 && dist <= stats.attack_range.1 {
+        let terrain = map.get(ax, ay).unwrap_or(Terrain::Grass);
+        let (min, max) = estimate_damage(&units[defender].unit, &units[attacker].unit, terrain, defender_co, attacker_co, weather, game_data);
+        units[attacker].unit.hp -= (min + max) / 2;
     }
+}
 
-    // Personality adjustments
-    match config.personality {
-        AiPersonality::Aggressive | AiPersonality::Reckless => {
-            // Prefer offensive units
-            for (unit_type, _, priority) in build_list.iter_mut() {
-                if *unit_type == UnitType::Ironclad || *unit_type == UnitType::Shocktrooper {
-                    *priority += 10.0;
-                }
+/// Greedy opponent reply: each enemy, most valuable first, makes the attack worth the most
+fn simulate_reply(
+    units: &mut [SimUnit],
+    enemy_reach: &HashMap<Entity, HashSet<(i32, i32)>>,
+    ai_co: &CoBonuses,
+    map: &GameMap,
+    game_data: &GameData,
+    weather: &Weather,
+) {
+    let mut order: Vec<usize> = (0..units.len())
+        .filter(|&i| !units[i].ours && units[i].unit.hp > 0)
+        .collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(units[i].value));
+
+    for e in order {
+        if units[e].unit.hp <= 0 {
+            continue;
+        }
+        let stats = units[e].unit.unit_type.stats();
+        if stats.attack == 0 || (stats.max_ammo > 0 && units[e].unit.ammo == 0) {
+            continue;
+        }
+        let Some(reach) = enemy_reach.get(&units[e].entity) else { continue };
+        let occupied: HashSet<(i32, i32)> = units.iter().enumerate()
+            .filter(|(j, u)| *j != e && u.unit.hp > 0 && !u.carried)
+            .map(|(_, u)| u.pos)
+            .collect();
+
+        let mut best: Option<(f32, usize, (i32, i32))> = None;
+        for t in 0..units.len() {
+            let target = &units[t];
+            if !target.ours || target.carried || target.unit.hp <= 0 {
+                continue;
+            }
+            let Some(from) = attack_tile(reach, &occupied, target.pos, stats.attack_range) else { continue };
+
+            let terrain = map.get(target.pos.0, target.pos.1).unwrap_or(Terrain::Grass);
+            let (min, max) = estimate_damage(&units[e].unit, &target.unit, terrain, &CoBonuses::none(), ai_co, weather, game_data);
+            let damage = ((min + max) / 2).min(target.unit.hp);
+            let gain = damage as f32 / target.unit.unit_type.stats().max_hp as f32 * target.value as f32;
+            if best.is_none_or(|(g, _, _)| gain > g) {
+                best = Some((gain, t, from));
             }
         }
-        AiPersonality::Cautious | AiPersonality::Methodical => {
-            // Prefer defensive units
-            for (unit_type, _, priority) in build_list.iter_mut() {
-                if *unit_type == UnitType::Siege {
-                    *priority += 10.0;
-                }
+
+        if let Some((_, t, from)) = best {
+            units[e].pos = from;
+            sim_exchange(units, e, t, &CoBonuses::none(), ai_co, map, game_data, weather);
+        }
+    }
+}
+
+/// First free reachable tile (in a fixed scan order) from which `target` is in range
+fn attack_tile(
+    reach: &HashSet<(i32, i32)>,
+    occupied: &HashSet<(i32, i32)>,
+    target: (i32, i32),
+    (min_range, max_range): (u32, u32),
+) -> Option<(i32, i32)> {
+    let max = max_range as i32;
+    for dx in -max..=max {
+        for dy in -max..=max {
+            let dist = (dx.abs() + dy.abs()) as u32;
+            let tile = (target.0 + dx, target.1 + dy);
+            if dist >= min_range && dist <= max_range && reach.contains(&tile) && !occupied.contains(&tile) {
+                return Some(tile);
             }
         }
     }
+    None
+}
+
+/// Surviving value on our side minus the enemy's, scaled by health
+fn material_balance(units: &[SimUnit]) -> f32 {
+    units.iter()
+        .filter(|u| u.unit.hp > 0)
+        .map(|u| {
+            let worth = u.value as f32 * u.unit.hp as f32 / u.unit.unit_type.stats().max_hp as f32;
+            if u.ours { worth } else { -worth }
+        })
+        .sum()
+}
+
+/// Next step of the searched plan, or None once it's used up or events have made it stale
+fn next_searched_action(plan: &mut AiTurnPlan, snapshot: &BattleSnapshot) -> Option<PlannedAction> {
+    if plan.searched.is_empty() {
+        return None;
+    }
+    let next = plan.searched.remove(0);
+
+    let id = next.unit.to_bits();
+    let ready = snapshot.unit(id).is_some_and(|u| !u.unit.moved && !u.unit.exhausted);
+    let partner_alive = match next.action {
+        AiAction::Attack { target, .. } => snapshot.unit(target.to_bits()).is_some(),
+        _ => next.action.partner().is_none_or(|p| snapshot.unit(p.to_bits()).is_some()),
+    };
+    let blocked = next.action.partner().is_none() && next.action.destination().is_some_and(|(x, y)| {
+        snapshot.unit_at(x, y).is_some_and(|u| u.id != id)
+    });
+
+    if ready && partner_alive && !blocked {
+        return Some(next);
+    }
+
+    // The dice went differently than simulated; fall back to re-planning every step
+    info!("AI: searched plan went stale, re-planning");
+    plan.searched.clear();
+    None
+}
 
-    build_list.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+// ============================================================================
+// SMART PRODUCTION
+// ============================================================================
+
+fn smart_production(
+    config: &AiConfig,
+    snapshot: &BattleSnapshot,
+    analysis: &GameAnalysis,
+    goals: &[StrategicGoal],
+    game_data: &GameData,
+    efficiency: f32,
+) -> Vec<BotCommand> {
+    let unit_positions: HashSet<(i32, i32)> = analysis.ai_units.iter()
+        .map(|u| (u.pos.x, u.pos.y))
+        .chain(analysis.enemy_units.iter().map(|u| (u.pos.x, u.pos.y)))
+        .collect();
+
+    let empty_bases: Vec<_> = snapshot.properties.iter()
+        .map(|p| &p.tile)
+        .filter(|t| {
+            t.terrain == Terrain::Base
+                && t.owner == Some(snapshot.faction)
+                && !unit_positions.contains(&(t.position.x, t.position.y))
+        })
+        .map(|t| (t.position.x, t.position.y))
+        .collect();
+
+    if empty_bases.is_empty() {
+        return Vec::new();
+    }
+
+    // Everything bases can produce, ranked against what the enemy actually fields
+    let mut build_list = production_priorities(config, analysis, goals, &snapshot.map, game_data);
+
+    // Stranded infantry needs a ride, if bases can build one
+    let our_transports = analysis.ai_units.iter().filter(|u| u.unit.is_transport()).count();
+    let transport = UnitType::base_buildable().iter().copied().find(|t| Unit::new(*t).is_transport());
+    if let Some(transport) = transport.filter(|_| !analysis.stranded.is_empty() && our_transports == 0) {
+        build_list.push((transport, 90.0));
+    }
+    build_list.retain(|(unit_type, _)| !snapshot.banned_units.contains(unit_type));
+
+    // Less efficient AIs hold back part of their funds
+    let mut budget = (snapshot.funds as f32 * efficiency) as u32;
+    let mut orders = Vec::new();
 
     for (x, y) in empty_bases {
-        for (unit_type, base_cost, _) in &build_list {
-            // Apply CO cost modifier
-            let adjusted_cost = (*base_cost as f32 * cost_modifier).round() as u32;
-            if funds.spend(faction, adjusted_cost) {
-                spawn_unit(commands, map, &mut sprite_param.meshes, &mut sprite_param.materials, &sprite_param.assets, &sprite_param.images, faction, *unit_type, x, y);
-                info!("AI ({:?}/{:?}) built {:?} at ({}, {})",
-                    config.strategy, config.personality, unit_type, x, y);
+        build_list.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        for (unit_type, priority) in build_list.iter_mut() {
+            // Includes the CO cost modifier
+            let cost = snapshot.build_cost(*unit_type, game_data);
+            if cost <= budget {
+                budget -= cost;
+                info!("AI ({:?}/{:?}) builds {:?} at ({}, {}), priority {:.0}",
+                    config.strategy, config.personality, unit_type, x, y, priority);
+                orders.push(BotCommand::Build { x, y, unit_type: *unit_type });
+                // Diminishing returns keep the army mixed
+                *priority -= PRODUCTION_REPEAT_PENALTY;
                 break;
             }
         }
     }
+    orders
+}
+
+/// Priority lost each time a unit type is built in the same turn
+const PRODUCTION_REPEAT_PENALTY: f32 = 15.0;
+
+/// Rank everything a base can produce from the data files: expected damage per cost against
+/// the enemy's army, cover against enemy units nothing of ours can hurt, and how well the
+/// unit's class gets around this map. Strategy and personality then shape the role mix.
+fn production_priorities(
+    config: &AiConfig,
+    analysis: &GameAnalysis,
+    goals: &[StrategicGoal],
+    map: &GameMap,
+    game_data: &GameData,
+) -> Vec<(UnitType, f32)> {
+    let buildable: Vec<_> = UnitType::base_buildable().iter()
+        .filter_map(|t| game_data.unit_stats(*t).map(|s| (*t, s)))
+        .filter(|(_, s)| s.attack > 0 && game_data.is_passable(Terrain::Base, s.class))
+        .collect();
+    if buildable.is_empty() {
+        return Vec::new();
+    }
+
+    // Before the enemy has shown anything, assume it builds from the same list
+    let mut enemy_army: Vec<(UnitType, f32)> = analysis.enemies_with_certainty()
+        .map(|(e, certainty)| (e.unit_type, e.hp_percent * certainty))
+        .collect();
+    if enemy_army.is_empty() {
+        enemy_army = buildable.iter().map(|(t, _)| (*t, 1.0)).collect();
+    }
+    let army_weight = enemy_army.iter().map(|(_, w)| w).sum::<f32>().max(0.01);
+    let data_cost = |t: UnitType| game_data.unit_stats(t).map_or(t.cost(), |s| s.cost).max(1) as f32;
+    let base_damage = |attacker: UnitType, defender: UnitType| {
+        game_data.get_base_damage(attacker, defender).unwrap_or(0) as f32 / 100.0
+    };
+
+    // Enemy units nothing we field can damage (aircraft, usually)
+    let uncovered: Vec<(UnitType, f32)> = enemy_army.iter()
+        .filter(|(e, _)| !analysis.ai_units.iter().any(|u| game_data.can_damage(u.unit_type, *e)))
+        .copied()
+        .collect();
+
+    let mobility: Vec<f32> = buildable.iter().map(|(_, s)| map_mobility(map, s.class, game_data)).collect();
+    let best_mobility = mobility.iter().copied().fold(0.0, f32::max).max(0.01);
+    let max_cost = buildable.iter().map(|(t, _)| data_cost(*t)).fold(1.0, f32::max);
+
+    let capturers = analysis.ai_units.iter()
+        .filter(|u| game_data.unit_stats(u.unit_type).is_some_and(|s| s.can_capture))
+        .count();
+    let wants_capturers = matches!(config.strategy, AiStrategy::Domination | AiStrategy::Swarm)
+        || matches!(goals.first(), Some(StrategicGoal::Expand { .. }))
+        || capturers < 2;
+
+    buildable.iter().zip(&mobility).map(|((unit_type, stats), mobility)| {
+        let cost = data_cost(*unit_type);
+        // Value we expect to destroy per attack, and to lose per enemy attack
+        let offense = enemy_army.iter()
+            .map(|(e, w)| w * base_damage(*unit_type, *e) * data_cost(*e))
+            .sum::<f32>() / army_weight;
+        let threat = enemy_army.iter()
+            .map(|(e, w)| w * base_damage(*e, *unit_type) * cost)
+            .sum::<f32>() / army_weight;
+        let value_for_money = (offense - 0.5 * threat) / cost;
+        let coverage = uncovered.iter()
+            .filter(|(e, _)| game_data.can_damage(*unit_type, *e))
+            .map(|(_, w)| w)
+            .sum::<f32>() / army_weight;
+
+        let mut priority = 50.0 + 40.0 * value_for_money * (mobility / best_mobility) + 40.0 * coverage;
+
+        let indirect = stats.attack_range.0 > 1;
+        match config.strategy {
+            AiStrategy::Annihilation => priority += 20.0 * value_for_money,
+            AiStrategy::Blitz => priority += stats.movement as f32 * 3.0,
+            AiStrategy::Swarm => priority += 20.0 * (1.0 - cost / max_cost),
+            AiStrategy::Attrition | AiStrategy::Fortress if indirect => priority += 15.0,
+            _ => {}
+        }
+        if stats.can_capture && wants_capturers {
+            priority += 25.0;
+        }
+        match config.personality {
+            AiPersonality::Aggressive | AiPersonality::Reckless if !indirect => priority += 10.0,
+            AiPersonality::Cautious | AiPersonality::Methodical if indirect => priority += 10.0,
+            _ => {}
+        }
+
+        (*unit_type, priority)
+    }).collect()
+}
+
+/// How freely a movement class gets around the map: passable share, weighted by move cost
+fn map_mobility(map: &GameMap, class: UnitClass, game_data: &GameData) -> f32 {
+    let mut total = 0.0;
+    let mut tiles = 0;
+    for y in 0..map.height as i32 {
+        for x in 0..map.width as i32 {
+            let Some(terrain) = map.get(x, y) else { continue };
+            tiles += 1;
+            if game_data.is_passable(terrain, class) {
+                total += 1.0 / game_data.movement_cost_or_default(terrain, class).max(1) as f32;
+            }
+        }
+    }
+    if tiles == 0 { 1.0 } else { total / tiles as f32 }
 }
 
 // ============================================================================
@@ -1710,28 +2833,18 @@ fn smart_production(
 // ============================================================================
 
 /// Decide whether the AI should activate its CO power
-fn should_ai_activate_power(
-    commanders: &Commanders,
-    units: &Query<(Entity, &mut GridPosition, &Transform, &FactionMember, &mut Unit)>,
-    tiles: &Query<(Entity, &Tile)>,
-    faction: Faction,
-) -> bool {
+fn should_ai_activate_power(snapshot: &BattleSnapshot) -> bool {
     use super::PowerEffect;
 
-    let Some(co) = commanders.get_commander(faction) else {
+    let Some(co) = snapshot.commander.map(|id| id.get_commander()) else {
         return false;
     };
+    let faction = snapshot.faction;
 
-    let ai_units: Vec<_> = units.iter()
-        .filter(|(_, _, _, f, _)| f.faction == faction)
-        .collect();
-
-    let enemy_units: Vec<_> = units.iter()
-        .filter(|(_, _, _, f, _)| f.faction != faction)
-        .collect();
+    let ai_units: Vec<&Unit> = snapshot.own_units().map(|u| &u.unit).collect();
 
     let ai_unit_count = ai_units.len();
-    let enemy_unit_count = enemy_units.len();
+    let enemy_unit_count = snapshot.units.iter().filter(|u| u.faction != faction).count();
 
     // Don't waste power if we have no units
     if ai_unit_count == 0 {
@@ -1752,8 +2865,8 @@ fn should_ai_activate_power(
             // Use Gold Rush when we have decent funds to multiply
             // Don't use when nearly broke (waste) or when swimming in cash (overkill)
             // Best used mid-game when economy is established
-            let owned_bases = tiles.iter()
-                .filter(|(_, t)| t.terrain == Terrain::Base && t.owner == Some(faction))
+            let owned_bases = snapshot.properties.iter()
+                .filter(|p| p.tile.terrain == Terrain::Base && p.tile.owner == Some(faction))
                 .count();
             owned_bases >= 1 // Use if we have at least one base
         }
@@ -1766,7 +2879,7 @@ fn should_ai_activate_power(
         PowerEffect::DefenseAndHeal { defense: _, heal } => {
             // Iron Wall - use when units are damaged
             let damaged_units = ai_units.iter()
-                .filter(|(_, _, _, _, u)| {
+                .filter(|u| {
                     let max_hp = u.unit_type.stats().max_hp;
                     u.hp < max_hp - *heal // Would benefit from heal
                 })
@@ -1776,11 +2889,9 @@ fn should_ai_activate_power(
 
         PowerEffect::FreeUnits { unit_type: _ } => {
             // Endless Horde - use when we have empty bases
-            let empty_bases = tiles.iter()
-                .filter(|(_, t)| t.terrain == Terrain::Base && t.owner == Some(faction))
-                .filter(|(_, t)| {
-                    !units.iter().any(|(_, p, _, _, _)| p.x == t.position.x && p.y == t.position.y)
-                })
+            let empty_bases = snapshot.properties.iter()
+                .filter(|p| p.tile.terrain == Terrain::Base && p.tile.owner == Some(faction))
+                .filter(|p| snapshot.unit_at(p.tile.position.x, p.tile.position.y).is_none())
                 .count();
             empty_bases >= 1
         }
@@ -1789,7 +2900,7 @@ fn should_ai_activate_power(
             // Charge! - use when we have unmoved units that can attack
             // Best used when enemies are in range
             let can_attack_count = ai_units.iter()
-                .filter(|(_, _, _, _, u)| !u.moved && u.unit_type.stats().attack > 0)
+                .filter(|u| !u.moved && u.unit_type.stats().attack > 0)
                 .count();
             can_attack_count >= 2 && enemy_unit_count > 0
         }
@@ -1809,7 +2920,475 @@ fn should_ai_activate_power(
 }
 
 // ============================================================================
-// AI TURN SYSTEM
+// DEBUG VIEW - What the planner was thinking
+// ============================================================================
+
+/// Influence map layers the debug overlay can draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum InfluenceLayer {
+    #[default]
+    Territory,
+    EnemyThreat,
+    FriendlySupport,
+    StrategicValue,
+    Frontline,
+}
+
+impl InfluenceLayer {
+    pub fn all() -> &'static [InfluenceLayer] {
+        &[
+            InfluenceLayer::Territory,
+            InfluenceLayer::EnemyThreat,
+            InfluenceLayer::FriendlySupport,
+            InfluenceLayer::StrategicValue,
+            InfluenceLayer::Frontline,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InfluenceLayer::Territory => "Territory",
+            InfluenceLayer::EnemyThreat => "Enemy threat",
+            InfluenceLayer::FriendlySupport => "Friendly support",
+            InfluenceLayer::StrategicValue => "Strategic value",
+            InfluenceLayer::Frontline => "Frontline",
+        }
+    }
+
+    /// Territory is positive where the AI is in control and negative where the enemy is
+    pub fn is_signed(&self) -> bool {
+        *self == InfluenceLayer::Territory
+    }
+}
+
+/// A planned action and the parts that make up its score
+#[derive(Debug, Clone)]
+pub struct DebugAction {
+    pub unit: ObjectId,
+    pub unit_type: UnitType,
+    pub from: (i32, i32),
+    pub description: String,
+    pub priority: f32,
+    /// Named score terms; their sum is the priority unless the search picked the action
+    pub terms: Vec<(&'static str, f32)>,
+}
+
+/// The Utility AI's reasoning as of its latest plan
+#[derive(Debug, Clone)]
+pub struct AiDebugView {
+    pub faction: Faction,
+    pub goals: Vec<String>,
+    pub layers: HashMap<InfluenceLayer, HashMap<(i32, i32), f32>>,
+    /// Remaining actions in execution order
+    pub actions: Vec<DebugAction>,
+}
+
+impl AiDebugView {
+    fn new(ctx: &PlanningContext, plan: &[PlannedAction], map: &GameMap, config: &AiConfig, game_data: &GameData) -> Self {
+        let influence = &ctx.influence;
+        let layers = [
+            (InfluenceLayer::Territory, influence.territory.clone()),
+            (InfluenceLayer::EnemyThreat, influence.enemy_threat.clone()),
+            (InfluenceLayer::FriendlySupport, influence.friendly_support.clone()),
+            (InfluenceLayer::StrategicValue, influence.strategic_value.clone()),
+            (InfluenceLayer::Frontline, influence.frontline.clone()),
+        ]
+        .into_iter()
+        .collect();
+
+        let actions = plan.iter()
+            .filter_map(|planned| {
+                let unit = ctx.analysis.ai_units.iter().find(|u| u.entity == planned.unit)?;
+                let mut terms = ScoreTerms::recording();
+                score_action_terms(unit, &planned.action, &ctx.analysis, influence, &ctx.goals, &ctx.predictions, map, config, game_data, &ctx.noise, &mut terms);
+                Some(DebugAction {
+                    unit: planned.unit.to_bits(),
+                    unit_type: unit.unit_type,
+                    from: (unit.pos.x, unit.pos.y),
+                    description: describe_action(&planned.action, &ctx.analysis),
+                    priority: planned.priority,
+                    terms: terms.terms,
+                })
+            })
+            .collect();
+
+        Self {
+            faction: ctx.analysis.faction,
+            goals: ctx.goals.iter().map(|g| format!("{:?}", g)).collect(),
+            layers,
+            actions,
+        }
+    }
+
+    pub fn layer(&self, layer: InfluenceLayer) -> Option<&HashMap<(i32, i32), f32>> {
+        self.layers.get(&layer)
+    }
+}
+
+fn describe_action(action: &AiAction, analysis: &GameAnalysis) -> String {
+    match action {
+        AiAction::Attack { move_to, target } => {
+            let target = analysis.enemy_units.iter()
+                .find(|u| u.entity == *target)
+                .map_or("unit".to_string(), |u| format!("{:?}", u.unit_type));
+            format!("Attack {} from ({}, {})", target, move_to.0, move_to.1)
+        }
+        AiAction::Capture { move_to, .. } => format!("Capture ({}, {})", move_to.0, move_to.1),
+        AiAction::Move { move_to } => format!("Move to ({}, {})", move_to.0, move_to.1),
+        AiAction::Load { move_to, .. } => format!("Board at ({}, {})", move_to.0, move_to.1),
+        AiAction::Unload { move_to, drop_at } => {
+            format!("Unload at ({}, {}) from ({}, {})", drop_at.0, drop_at.1, move_to.0, move_to.1)
+        }
+        AiAction::Join { move_to, .. } => format!("Join at ({}, {})", move_to.0, move_to.1),
+        AiAction::Resupply { move_to } => format!("Resupply from ({}, {})", move_to.0, move_to.1),
+        AiAction::Wait => "Wait".to_string(),
+    }
+}
+
+// ============================================================================
+// UTILITY AI - The built-in controller
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UtilityPhase {
+    Planning,
+    Searching,
+    Acting,
+    Done,
+}
+
+/// The built-in planner: influence maps, strategic goals and opponent modelling, plus a
+/// lookahead search on the hardest difficulties
+pub struct UtilityAi {
+    difficulty: AiDifficulty,
+    seed: u64,
+    strategy: AiStrategy,
+    memory: AiMemory,
+    turn_plan: AiTurnPlan,
+    search: AiSearch,
+    phase: UtilityPhase,
+    /// Whether this turn's CO power decision has been made
+    power_checked: bool,
+    /// Record an `AiDebugView` every time the plan changes
+    debug: bool,
+    debug_view: Option<AiDebugView>,
+}
+
+impl UtilityAi {
+    pub fn new(difficulty: AiDifficulty, seed: u64) -> Self {
+        Self {
+            difficulty,
+            seed,
+            strategy: AiStrategy::default(),
+            memory: AiMemory::default(),
+            turn_plan: AiTurnPlan::default(),
+            search: AiSearch::default(),
+            phase: UtilityPhase::Planning,
+            power_checked: false,
+            debug: false,
+            debug_view: None,
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: AiStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Personality comes from the faction's CO
+    fn config(&self, snapshot: &BattleSnapshot) -> AiConfig {
+        let personality = snapshot.commander
+            .map(|id| id.get_commander().personality)
+            .unwrap_or_default();
+        AiConfig::new(personality, self.strategy)
+    }
+
+    /// Learn from every resolved attack, the opponent's included
+    fn observe(&mut self, snapshot: &BattleSnapshot) {
+        for report in &snapshot.attacks {
+            record_attack_result(&mut self.memory, report, snapshot.faction);
+        }
+    }
+
+    /// Everything the planner derives from the board before scoring actions
+    fn planning_context(&mut self, snapshot: &BattleSnapshot, game_data: &GameData, config: &AiConfig) -> PlanningContext {
+        let units = known_units(snapshot);
+        let tiles = known_tiles(snapshot);
+        let map = &snapshot.map;
+
+        // Full analysis pipeline
+        let mut analysis = analyze_game_state(&units, &tiles, snapshot.faction);
+        if snapshot.visible.is_some() {
+            analysis.add_beliefs(&self.memory);
+        }
+        analysis.find_stranded(map, game_data);
+        analysis.plan_transports(&mut self.memory);
+        let influence = build_influence_maps(&analysis, map, &tiles, game_data);
+        let goals = determine_strategic_goals(&analysis, &influence, &self.memory, config);
+        let mut predictions = predict_enemy_actions(&analysis, &influence, &self.memory, map, game_data);
+
+        // Weaker AIs only anticipate the most obvious enemy moves
+        predictions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+        predictions.truncate((predictions.len() as f32 * self.difficulty.prediction_use()).ceil() as usize);
+        let noise = ScoreNoise::new(self.difficulty, self.seed, self.memory.turn_count);
+
+        PlanningContext { analysis, influence, goals, predictions, noise }
+    }
+
+    /// Plan the rest of the turn from the current board; returns the next action to take
+    fn replan(&mut self, snapshot: &BattleSnapshot, game_data: &GameData, config: &AiConfig) -> Option<PlannedAction> {
+        let ctx = self.planning_context(snapshot, game_data, config);
+
+        if self.turn_plan.actions_taken == 0 {
+            info!("AI Strategic Goals: {:?}", ctx.goals.iter().take(2).collect::<Vec<_>>());
+        }
+
+        let actions = plan_turn_advanced(&ctx, &snapshot.map, config, game_data, self.difficulty);
+        let next = actions.first().cloned();
+        self.turn_plan.actions = actions;
+        self.record_debug(&ctx, snapshot, game_data, config);
+        next
+    }
+
+    fn record_debug(&mut self, ctx: &PlanningContext, snapshot: &BattleSnapshot, game_data: &GameData, config: &AiConfig) {
+        if self.debug {
+            self.debug_view = Some(AiDebugView::new(ctx, &self.turn_plan.actions, &snapshot.map, config, game_data));
+        }
+    }
+
+    /// The next unit order, or production and the end of the turn once every unit is done
+    fn act(&mut self, snapshot: &BattleSnapshot, game_data: &GameData, config: &AiConfig) -> Vec<BotCommand> {
+        // Follow the searched plan while it holds; otherwise re-plan from the live board
+        // so earlier kills, misses and counters are accounted for
+        let next = next_searched_action(&mut self.turn_plan, snapshot)
+            .or_else(|| self.replan(snapshot, game_data, config));
+        if let Some(planned) = next {
+            self.turn_plan.actions_taken += 1;
+            return vec![planned_command(&planned)];
+        }
+
+        self.phase = UtilityPhase::Done;
+        let ctx = self.planning_context(snapshot, game_data, config);
+        let mut commands = smart_production(
+            config,
+            snapshot,
+            &ctx.analysis,
+            &ctx.goals,
+            game_data,
+            self.difficulty.production_efficiency(),
+        );
+        commands.push(BotCommand::EndTurn);
+        commands
+    }
+}
+
+impl AiController for UtilityAi {
+    fn name(&self) -> &str {
+        "Utility AI"
+    }
+
+    fn begin_turn(&mut self, snapshot: &BattleSnapshot, game_data: &GameData) {
+        self.observe(snapshot);
+
+        // Update memory with the opponent's positions from last turn
+        self.memory.turn_count += 1;
+        update_memory(&mut self.memory, snapshot, game_data);
+        self.turn_plan = AiTurnPlan::default();
+        self.phase = UtilityPhase::Planning;
+        self.power_checked = false;
+
+        let config = self.config(snapshot);
+        info!("AI Turn ({:?}, {}) - Strategy: {:?}, Personality: {:?}",
+            snapshot.faction, self.difficulty.name(), config.strategy, config.personality);
+    }
+
+    fn next_commands(&mut self, snapshot: &BattleSnapshot, game_data: &GameData) -> Vec<BotCommand> {
+        self.observe(snapshot);
+        let config = self.config(snapshot);
+
+        match self.phase {
+            UtilityPhase::Planning => {
+                // Power first, so the plan is made with its bonuses in effect
+                if !self.power_checked {
+                    self.power_checked = true;
+                    if snapshot.power_ready && should_ai_activate_power(snapshot) {
+                        return vec![BotCommand::ActivatePower];
+                    }
+                }
+
+                // Stronger AIs look ahead at the opponent's reply before committing
+                if self.difficulty.search_evaluations() > 0 {
+                    let ctx = self.planning_context(snapshot, game_data, &config);
+                    let weather = Weather::new(snapshot.weather);
+                    self.search.start(&ctx, &snapshot.map, &config, game_data, &weather, self.difficulty, snapshot.co_bonuses.clone());
+                    self.phase = UtilityPhase::Searching;
+                    return Vec::new();
+                }
+                self.phase = UtilityPhase::Acting;
+                self.act(snapshot, game_data, &config)
+            }
+
+            UtilityPhase::Searching => {
+                // A few milliseconds per frame until the evaluation budget or the time cap is spent
+                let weather = Weather::new(snapshot.weather);
+                if !self.search.step(&snapshot.map, game_data, &weather, self.difficulty) {
+                    return Vec::new();
+                }
+                let plan = self.search.finish(&snapshot.map, game_data, &weather);
+                self.turn_plan.actions = plan.clone();
+                self.turn_plan.searched = plan;
+                if self.debug {
+                    let ctx = self.planning_context(snapshot, game_data, &config);
+                    self.record_debug(&ctx, snapshot, game_data, &config);
+                }
+                self.phase = UtilityPhase::Acting;
+                self.act(snapshot, game_data, &config)
+            }
+
+            UtilityPhase::Acting => self.act(snapshot, game_data, &config),
+
+            UtilityPhase::Done => vec![BotCommand::EndTurn],
+        }
+    }
+
+    fn set_debug(&mut self, enabled: bool) {
+        self.debug = enabled;
+        if !enabled {
+            self.debug_view = None;
+        }
+    }
+
+    fn debug_view(&self) -> Option<&AiDebugView> {
+        self.debug_view.as_ref()
+    }
+}
+
+/// Units the AI knows about: its own, plus whichever enemies the snapshot shows
+fn known_units(snapshot: &BattleSnapshot) -> Vec<(Entity, GridPosition, FactionMember, Unit)> {
+    snapshot.units.iter()
+        .map(|u| (
+            Entity::from_bits(u.id),
+            GridPosition::new(u.x, u.y),
+            FactionMember { faction: u.faction },
+            u.unit.clone(),
+        ))
+        .collect()
+}
+
+fn known_tiles(snapshot: &BattleSnapshot) -> Vec<(Entity, Tile)> {
+    snapshot.properties.iter()
+        .map(|p| (Entity::from_bits(p.id), p.tile.clone()))
+        .collect()
+}
+
+/// Turn a planned action into a command for the runtime
+fn planned_command(planned: &PlannedAction) -> BotCommand {
+    let unit = planned.unit.to_bits();
+    match planned.action {
+        AiAction::Attack { move_to, target } => BotCommand::Attack { unit, move_to, target: target.to_bits() },
+        AiAction::Capture { move_to, .. } => BotCommand::Capture { unit, move_to },
+        AiAction::Move { move_to } => BotCommand::Move { unit, to: move_to },
+        AiAction::Load { move_to, .. } => BotCommand::Load { unit, move_to },
+        AiAction::Unload { move_to, drop_at } => BotCommand::Unload { unit, move_to, drop_at },
+        AiAction::Join { move_to, .. } => BotCommand::Join { unit, move_to },
+        AiAction::Resupply { move_to } => BotCommand::Resupply { unit, move_to },
+        AiAction::Wait => BotCommand::Wait { unit },
+    }
+}
+
+/// Everything the planner derives from the board before scoring actions
+struct PlanningContext {
+    analysis: GameAnalysis,
+    influence: InfluenceMaps,
+    goals: Vec<StrategicGoal>,
+    predictions: Vec<PredictedAction>,
+    noise: ScoreNoise,
+}
+
+/// Feed a resolved attack back into the AI's memory
+fn record_attack_result(memory: &mut AiMemory, report: &AttackReport, faction: Faction) {
+    if report.attacker_faction == faction {
+        info!("AI: attack dealt {} damage{}, took {}{}",
+            report.damage,
+            if report.defender_destroyed { " (destroyed)" } else { "" },
+            report.counter_damage,
+            if report.attacker_destroyed { " (lost attacker)" } else { "" });
+    } else if report.defender_faction == faction {
+        // The opponent's targeting habits inform predictions
+        *memory.targeted_unit_types.entry(report.defender_type).or_insert(0) += 1;
+        memory.player_attacks_seen += 1;
+    }
+
+    // Destroyed units are forgotten
+    for (id, destroyed) in [(report.defender, report.defender_destroyed), (report.attacker, report.attacker_destroyed)] {
+        if destroyed {
+            memory.player_last_positions.retain(|entity, _| entity.to_bits() != id);
+        }
+    }
+}
+
+fn update_memory(memory: &mut AiMemory, snapshot: &BattleSnapshot, game_data: &GameData) {
+    // Track the opponent's unit movements for aggression calculation
+    let attacks_detected = std::mem::take(&mut memory.player_attacks_seen);
+    let mut total_moves = 0;
+
+    for enemy in snapshot.units.iter().filter(|u| u.faction != snapshot.faction) {
+        let entity = Entity::from_bits(enemy.id);
+        if let Some(sighting) = memory.player_last_positions.get(&entity) {
+            let moved = (enemy.x, enemy.y) != sighting.position;
+            if moved {
+                total_moves += 1;
+                // If they moved closer to our units, that's aggressive
+                // (simplified - just count moves for now)
+            }
+        }
+        memory.player_last_positions.insert(entity, EnemySighting {
+            position: (enemy.x, enemy.y),
+            unit: enemy.unit.clone(),
+            last_seen: memory.turn_count,
+            reachable: HashSet::from([(enemy.x, enemy.y)]),
+        });
+    }
+
+    // With nothing hidden, an enemy missing from the board is gone
+    if snapshot.visible.is_none() {
+        memory.player_last_positions.retain(|entity, _| snapshot.unit(entity.to_bits()).is_some());
+    }
+
+    // Grow the area each unseen enemy could be in, minus what we can see is empty
+    let map = &snapshot.map;
+    let max_range = map.width + map.height;
+    let turn_count = memory.turn_count;
+    memory.player_last_positions.retain(|_, sighting| {
+        if sighting.last_seen == turn_count {
+            return true;
+        }
+        let stats = sighting.unit.unit_type.stats();
+        let turns_unseen = turn_count - sighting.last_seen;
+        let range = (effective_movement(stats.movement, sighting.unit.stamina) * turns_unseen).min(max_range);
+        let (x, y) = sighting.position;
+        sighting.reachable = calculate_movement_range(
+            &GridPosition::new(x, y),
+            range,
+            map,
+            &HashMap::new(),
+            stats.class,
+            game_data,
+        );
+        sighting.reachable.retain(|&(tx, ty)| !snapshot.can_see(tx, ty));
+        // Nowhere left to hide means the belief was wrong
+        !sighting.reachable.is_empty()
+    });
+
+    // Update aggression estimate (exponential moving average)
+    if total_moves > 0 {
+        let new_aggression = (attacks_detected as f32 / total_moves as f32).min(1.0);
+        memory.player_aggression = memory.player_aggression * 0.7 + new_aggression * 0.3;
+    }
+}
+
+// ============================================================================
+// AI TURN SYSTEM - Runs whichever controller plays the current faction
 // ============================================================================
 
 fn ai_turn_system(
@@ -1821,18 +3400,29 @@ fn ai_turn_system(
     map: Res<GameMap>,
     mut units: Query<(Entity, &mut GridPosition, &Transform, &FactionMember, &mut Unit)>,
     tiles: Query<(Entity, &Tile)>,
-    mut attack_events: MessageWriter<AttackEvent>,
-    mut capture_events: MessageWriter<CaptureEvent>,
+    mut writers: AiActionWriters,
+    mut attack_results: MessageReader<AttackResultEvent>,
     game_result: Res<GameResult>,
     mut commanders: ResMut<Commanders>,
     mut power_events: MessageWriter<PowerActivatedEvent>,
+    mut turn_start_events: MessageWriter<TurnStartEvent>,
     mut sprite_param: SpriteAssetsParam,
 ) {
+    // Every controller hears about every resolved attack, the opponent's included
+    let controlled: Vec<Faction> = ai_res.controllers.factions().collect();
+    for result in attack_results.read() {
+        let report = AttackReport::from(result);
+        for faction in &controlled {
+            ai_res.ai_state.attack_reports.entry(*faction).or_default().push(report.clone());
+        }
+    }
+
     if game_result.game_over {
         return;
     }
 
-    if !ai_res.ai_state.enabled || turn_state.current_faction != Faction::Northern {
+    let faction = turn_state.current_faction;
+    if !ai_res.ai_state.enabled || !ai_res.controllers.controls(faction) {
         ai_res.ai_state.phase = AiTurnPhase::Waiting;
         return;
     }
@@ -1846,171 +3436,170 @@ fn ai_turn_system(
         return;
     }
 
-    // Get AI config from CO personality
-    let co = commanders.get_active(Faction::Northern).get_commander();
-    let config = AiConfig {
-        personality: co.personality,
-        strategy: ai_res.ai_state.config.strategy, // Keep strategy from state
-    };
-
     match ai_res.ai_state.phase {
         AiTurnPhase::Waiting => {
-            // Update memory with player positions from last turn
-            update_memory(&mut ai_res.memory, &units);
-            ai_res.memory.turn_count += 1;
-            ai_res.ai_state.phase = AiTurnPhase::Planning;
-
-            // Log AI configuration at start of turn
-            info!("AI Turn ({}) - Strategy: {:?}, Personality: {:?}",
-                co.name, config.strategy, config.personality);
-        }
-
-        AiTurnPhase::Planning => {
-            // Check if AI should activate CO power
-            if commanders.can_activate(Faction::Northern) {
-                let should_activate = should_ai_activate_power(
-                    &commanders,
-                    &units,
-                    &tiles,
-                    Faction::Northern,
-                );
-
-                if should_activate {
-                    if let Some(effect) = commanders.activate_power(Faction::Northern) {
-                        info!("AI activated CO Power: {}!", co.power.name);
-                        power_events.write(PowerActivatedEvent {
-                            faction: Faction::Northern,
-                            effect,
-                        });
-                    }
-                }
+            let attacks = ai_res.ai_state.take_attack_reports(faction);
+            let snapshot = take_snapshot(&ai_res, faction, &turn_state, &funds, &map, &units, &tiles, &commanders, attacks);
+            if let Some(controller) = ai_res.controllers.get_mut(faction) {
+                info!("{:?} turn {} played by {}", faction, turn_state.turn_number, controller.name());
+                controller.begin_turn(&snapshot, &ai_res.game_data);
             }
-
-            let all_units: Vec<_> = units.iter()
-                .map(|(e, p, _, f, u)| (e, p.clone(), f.clone(), u.clone()))
-                .collect();
-
-            let all_tiles: Vec<_> = tiles.iter()
-                .map(|(e, t)| (e, t.clone()))
-                .collect();
-
-            // Full analysis pipeline
-            let analysis = analyze_game_state(&all_units, &all_tiles, Faction::Northern);
-            let influence = build_influence_maps(&analysis, &map, &all_tiles, &ai_res.game_data);
-            let goals = determine_strategic_goals(&analysis, &influence, &ai_res.memory, &config);
-            let predictions = predict_enemy_actions(&analysis, &influence, &ai_res.memory, &map, &ai_res.game_data);
-
-            info!("AI Strategic Goals: {:?}", goals.iter().take(2).collect::<Vec<_>>());
-
-            // Plan with all systems
-            let actions = plan_turn_advanced(&analysis, &influence, &goals, &predictions, &map, &config, &ai_res.game_data);
-
-            ai_res.turn_plan.actions = actions;
-            ai_res.turn_plan.current_index = 0;
-
+            ai_res.ai_state.pending.clear();
+            ai_res.ai_state.commands_this_turn = 0;
             ai_res.ai_state.phase = AiTurnPhase::ExecutingActions;
-            ai_res.ai_state.action_delay.reset();
         }
 
         AiTurnPhase::ExecutingActions => {
-            if ai_res.turn_plan.current_index >= ai_res.turn_plan.actions.len() {
-                ai_res.ai_state.phase = AiTurnPhase::Production;
-                ai_res.ai_state.action_delay.reset();
-                return;
+            if ai_res.ai_state.pending.is_empty() {
+                let attacks = ai_res.ai_state.take_attack_reports(faction);
+                let snapshot = take_snapshot(&ai_res, faction, &turn_state, &funds, &map, &units, &tiles, &commanders, attacks);
+                let Some(controller) = ai_res.controllers.get_mut(faction) else { return };
+                let reply = controller.next_commands(&snapshot, &ai_res.game_data);
+                ai_res.ai_state.pending.extend(reply);
             }
+            // Nothing yet means the controller is still thinking
+            let Some(command) = ai_res.ai_state.pending.pop_front() else { return };
 
-            let planned = &ai_res.turn_plan.actions[ai_res.turn_plan.current_index];
-            execute_action(
-                &mut commands,
-                planned.action.clone(),
-                planned.unit,
-                &mut units,
-                &map,
-                &mut attack_events,
-                &mut capture_events,
-            );
+            ai_res.ai_state.commands_this_turn += 1;
+            if ai_res.ai_state.commands_this_turn > MAX_COMMANDS_PER_TURN {
+                warn!("{:?} controller issued {} commands without ending its turn; ending it", faction, MAX_COMMANDS_PER_TURN);
+                ai_res.ai_state.phase = AiTurnPhase::EndingTurn;
+                return;
+            }
 
-            ai_res.turn_plan.current_index += 1;
-            ai_res.ai_state.action_delay.reset();
-        }
+            // Check against the board as it is now, not as it was when the batch was sent
+            let snapshot = take_snapshot(&ai_res, faction, &turn_state, &funds, &map, &units, &tiles, &commanders, Vec::new());
+            if let Err(reason) = validate_command(&command, &snapshot, &ai_res.game_data) {
+                warn!("{:?} controller: rejected {:?}: {}", faction, command, reason);
+                if let Some(controller) = ai_res.controllers.get_mut(faction) {
+                    controller.command_rejected(&command, &reason);
+                }
+                // Stop a confused controller from ordering the same unit forever
+                let own_unit = command.unit().filter(|id| snapshot.unit(*id).is_some_and(|u| u.faction == faction));
+                if let Some(id) = own_unit {
+                    if let Ok((_, _, _, _, mut unit)) = units.get_mut(Entity::from_bits(id)) {
+                        unit.moved = true;
+                        unit.exhausted = true;
+                    }
+                }
+                return;
+            }
 
-        AiTurnPhase::Production => {
-            let all_units: Vec<_> = units.iter()
-                .map(|(e, p, _, f, u)| (e, p.clone(), f.clone(), u.clone()))
-                .collect();
-
-            let all_tiles: Vec<_> = tiles.iter()
-                .map(|(e, t)| (e, t.clone()))
-                .collect();
-
-            let analysis = analyze_game_state(&all_units, &all_tiles, Faction::Northern);
-            let influence = build_influence_maps(&analysis, &map, &all_tiles, &ai_res.game_data);
-            let goals = determine_strategic_goals(&analysis, &influence, &ai_res.memory, &config);
-
-            let co_bonuses = commanders.get_bonuses(Faction::Northern);
-            smart_production(
-                &config,
-                &mut funds,
-                Faction::Northern,
-                &analysis,
-                &all_tiles,
-                &mut commands,
-                &map,
-                &mut sprite_param,
-                &goals,
-                co_bonuses.cost,
-            );
+            match command {
+                BotCommand::EndTurn => {
+                    ai_res.ai_state.pending.clear();
+                    ai_res.ai_state.phase = AiTurnPhase::EndingTurn;
+                }
+                BotCommand::ActivatePower => {
+                    if let Some(effect) = commanders.activate_power(faction) {
+                        let co = commanders.get_active(faction).get_commander();
+                        info!("AI activated CO Power: {}!", co.power.name);
+                        power_events.write(PowerActivatedEvent { faction, effect });
+                    }
+                }
+                BotCommand::Build { x, y, unit_type } => {
+                    // A unit hidden by fog may be standing on the base
+                    let occupied = units.iter().any(|(_, pos, _, _, _)| (pos.x, pos.y) == (x, y));
+                    let cost = snapshot.build_cost(unit_type, &ai_res.game_data);
+                    if occupied {
+                        info!("AI: base at ({},{}) is occupied by a hidden unit", x, y);
+                    } else if funds.spend(faction, cost) {
+                        spawn_unit(&mut commands, &map, &mut sprite_param.meshes, &mut sprite_param.materials, &sprite_param.assets, &sprite_param.images, faction, unit_type, x, y);
+                        info!("AI: built {:?} at ({}, {})", unit_type, x, y);
+                    }
+                }
+                command => match resolve_command(&command, &units, &tiles) {
+                    Some((entity, action)) => execute_action(&mut commands, action, entity, &mut units, &map, &ai_res.fog, &ai_res.game_data, &mut writers),
+                    None => warn!("{:?} controller: couldn't carry out {:?}", faction, command),
+                },
+            }
 
-            ai_res.ai_state.phase = AiTurnPhase::EndingTurn;
             ai_res.ai_state.action_delay.reset();
         }
 
         AiTurnPhase::EndingTurn => {
-            for (_, _, _, faction, mut unit) in units.iter_mut() {
-                if faction.faction == Faction::Northern {
+            for (_, _, _, member, mut unit) in units.iter_mut() {
+                if member.faction == faction {
                     unit.moved = false;
                     unit.attacked = false;
                     unit.exhausted = false;
                 }
             }
 
-            turn_state.current_faction = Faction::Eastern;
-            turn_state.turn_number += 1;
-            turn_state.phase = TurnPhase::Select;
+            turn_state.next_faction();
             ai_res.ai_state.phase = AiTurnPhase::Waiting;
 
-            info!("AI ended turn {}", turn_state.turn_number - 1);
+            announce_turn_start(&turn_state, tiles.iter().map(|(_, t)| t), &mut turn_start_events);
+
+            info!("AI ({:?}) ended its turn", faction);
         }
     }
 }
 
-fn update_memory(
-    memory: &mut AiMemory,
+/// What a controller sees of the battle right now
+fn take_snapshot(
+    ai_res: &AiResources,
+    faction: Faction,
+    turn_state: &TurnState,
+    funds: &FactionFunds,
+    map: &GameMap,
     units: &Query<(Entity, &mut GridPosition, &Transform, &FactionMember, &mut Unit)>,
-) {
-    // Track player unit movements for aggression calculation
-    let attacks_detected = 0; // TODO: Track actual attacks in future
-    let mut total_moves = 0;
+    tiles: &Query<(Entity, &Tile)>,
+    commanders: &Commanders,
+    attacks: Vec<AttackReport>,
+) -> BattleSnapshot {
+    // Processes outside the game never get to see through fog
+    let fogged = ai_res.ai_state.respect_fog || ai_res.controllers.always_fogged(faction);
+    let visible = (fogged && ai_res.fog.enabled)
+        .then(|| ai_res.fog.visible_tiles(faction));
+    SnapshotSource {
+        faction,
+        turn: turn_state.turn_number,
+        funds: funds.get(faction),
+        weather: ai_res.weather.current,
+        commander: commanders.get_commander(faction).map(|_| commanders.get_active(faction)),
+        commanders: commanders.active.clone(),
+        co_bonuses: commanders.get_bonuses(faction),
+        power_ready: commanders.can_activate(faction),
+        map,
+        visible,
+        banned_units: ai_res.rules.banned_units.clone(),
+    }
+    .build(
+        units.iter().map(|(entity, pos, _, member, unit)| (entity, pos, member.faction, unit)),
+        tiles.iter(),
+        attacks,
+    )
+}
 
-    for (entity, pos, _, faction, _) in units.iter() {
-        if faction.faction == Faction::Eastern {
-            if let Some(&(old_x, old_y)) = memory.player_last_positions.get(&entity) {
-                let moved = (pos.x != old_x) || (pos.y != old_y);
-                if moved {
-                    total_moves += 1;
-                    // If they moved closer to our units, that's aggressive
-                    // (simplified - just count moves for now)
-                }
-            }
-            memory.player_last_positions.insert(entity, (pos.x, pos.y));
-        }
-    }
+/// Find the entities a validated unit command refers to
+fn resolve_command(
+    command: &BotCommand,
+    units: &Query<(Entity, &mut GridPosition, &Transform, &FactionMember, &mut Unit)>,
+    tiles: &Query<(Entity, &Tile)>,
+) -> Option<(Entity, AiAction)> {
+    let entity = Entity::from_bits(command.unit()?);
+    let partner_at = |(x, y): (i32, i32)| {
+        units.iter()
+            .find(|(other, pos, _, _, _)| *other != entity && (pos.x, pos.y) == (x, y))
+            .map(|(other, _, _, _, _)| other)
+    };
 
-    // Update aggression estimate (exponential moving average)
-    if total_moves > 0 {
-        let new_aggression = attacks_detected as f32 / total_moves as f32;
-        memory.player_aggression = memory.player_aggression * 0.7 + new_aggression * 0.3;
-    }
+    let action = match *command {
+        BotCommand::Move { to, .. } => AiAction::Move { move_to: to },
+        BotCommand::Attack { move_to, target, .. } => AiAction::Attack { move_to, target: Entity::from_bits(target) },
+        BotCommand::Capture { move_to, .. } => {
+            let tile = tiles.iter().find(|(_, t)| (t.position.x, t.position.y) == move_to)?.0;
+            AiAction::Capture { move_to, tile }
+        }
+        BotCommand::Load { move_to, .. } => AiAction::Load { move_to, transport: partner_at(move_to)? },
+        BotCommand::Unload { move_to, drop_at, .. } => AiAction::Unload { move_to, drop_at },
+        BotCommand::Join { move_to, .. } => AiAction::Join { move_to, target: partner_at(move_to)? },
+        BotCommand::Resupply { move_to, .. } => AiAction::Resupply { move_to },
+        BotCommand::Wait { .. } => AiAction::Wait,
+        BotCommand::Build { .. } | BotCommand::ActivatePower | BotCommand::EndTurn => return None,
+    };
+    units.contains(entity).then_some((entity, action))
 }
 
 fn execute_action(
@@ -2019,60 +3608,81 @@ fn execute_action(
     entity: Entity,
     units: &mut Query<(Entity, &mut GridPosition, &Transform, &FactionMember, &mut Unit)>,
     map: &GameMap,
-    attack_events: &mut MessageWriter<AttackEvent>,
-    capture_events: &mut MessageWriter<CaptureEvent>,
+    fog: &FogOfWar,
+    game_data: &GameData,
+    writers: &mut AiActionWriters,
 ) {
+    if let Some(dest) = action.destination() {
+        let partner = action.partner();
+
+        // A unit hidden by fog on the way springs a trap, just as it would on a player's unit
+        if let Some((stop, ambusher)) = ambush_stop(entity, dest, partner, units, map, fog, game_data) {
+            if let Some(unit_type) = move_unit(commands, entity, units, map, stop) {
+                info!("AI: {:?} stopped at ({},{}) by a hidden unit at ({},{})", unit_type, stop.0, stop.1, ambusher.0, ambusher.1);
+            }
+            writers.trap.write(TrapEvent { unit: entity, position: stop, ambusher });
+            return;
+        }
+
+        let blocked = units.iter()
+            .any(|(other, pos, _, _, _)| other != entity && Some(other) != partner && (pos.x, pos.y) == dest);
+        if blocked {
+            if let Ok((_, _, _, _, mut unit)) = units.get_mut(entity) {
+                info!("AI: {:?} found ({},{}) occupied", unit.unit_type, dest.0, dest.1);
+                unit.moved = true;
+                unit.exhausted = true;
+            }
+            return;
+        }
+    }
+
     match action {
         AiAction::Attack { move_to, target } => {
-            if let Ok((_, mut pos, transform, _, mut unit)) = units.get_mut(entity) {
-                info!("AI: {:?} ({},{}) -> ({},{}) attacks",
-                    unit.unit_type, pos.x, pos.y, move_to.0, move_to.1);
-                let start_pos = transform.translation;
-                pos.x = move_to.0;
-                pos.y = move_to.1;
-                // Calculate end position (preserve Y height)
-                let new_world_pos = pos.to_world(map);
-                let end_pos = Vec3::new(new_world_pos.x, start_pos.y, new_world_pos.z);
-                // Add animation component for smooth movement
-                commands.entity(entity).insert(UnitAnimation::new(start_pos, end_pos));
-                unit.moved = true;
+            if let Some(unit_type) = move_unit(commands, entity, units, map, move_to) {
+                info!("AI: {:?} -> ({},{}) attacks", unit_type, move_to.0, move_to.1);
+            }
+            if let Ok((_, _, _, _, mut unit)) = units.get_mut(entity) {
                 unit.attacked = true;
-                unit.exhausted = true;
             }
-            attack_events.write(AttackEvent { attacker: entity, defender: target });
+            writers.attack.write(AttackEvent { attacker: entity, defender: target });
         }
         AiAction::Capture { move_to, tile } => {
-            if let Ok((_, mut pos, transform, _, mut unit)) = units.get_mut(entity) {
-                info!("AI: {:?} captures at ({},{})", unit.unit_type, move_to.0, move_to.1);
-                let start_pos = transform.translation;
-                pos.x = move_to.0;
-                pos.y = move_to.1;
-                // Calculate end position (preserve Y height)
-                let new_world_pos = pos.to_world(map);
-                let end_pos = Vec3::new(new_world_pos.x, start_pos.y, new_world_pos.z);
-                // Add animation component for smooth movement
-                commands.entity(entity).insert(UnitAnimation::new(start_pos, end_pos));
-                unit.moved = true;
+            if let Some(unit_type) = move_unit(commands, entity, units, map, move_to) {
+                info!("AI: {:?} captures at ({},{})", unit_type, move_to.0, move_to.1);
+            }
+            if let Ok((_, _, _, _, mut unit)) = units.get_mut(entity) {
                 unit.attacked = true;
-                unit.exhausted = true;
             }
-            capture_events.write(CaptureEvent { unit: entity, tile });
+            writers.capture.write(CaptureEvent { unit: entity, tile });
         }
         AiAction::Move { move_to } => {
-            if let Ok((_, mut pos, transform, _, mut unit)) = units.get_mut(entity) {
-                info!("AI: {:?} ({},{}) -> ({},{})",
-                    unit.unit_type, pos.x, pos.y, move_to.0, move_to.1);
-                let start_pos = transform.translation;
-                pos.x = move_to.0;
-                pos.y = move_to.1;
-                // Calculate end position (preserve Y height)
-                let new_world_pos = pos.to_world(map);
-                let end_pos = Vec3::new(new_world_pos.x, start_pos.y, new_world_pos.z);
-                // Add animation component for smooth movement
-                commands.entity(entity).insert(UnitAnimation::new(start_pos, end_pos));
-                unit.moved = true;
-                unit.exhausted = true;
+            if let Some(unit_type) = move_unit(commands, entity, units, map, move_to) {
+                info!("AI: {:?} -> ({},{})", unit_type, move_to.0, move_to.1);
+            }
+        }
+        AiAction::Load { move_to, .. } => {
+            if let Some(unit_type) = move_unit(commands, entity, units, map, move_to) {
+                info!("AI: {:?} boards transport at ({},{})", unit_type, move_to.0, move_to.1);
+            }
+            writers.load.write(LoadEvent { transport_pos: move_to, passenger: entity });
+        }
+        AiAction::Unload { move_to, drop_at } => {
+            if let Some(unit_type) = move_unit(commands, entity, units, map, move_to) {
+                info!("AI: {:?} -> ({},{}) unloads at ({},{})", unit_type, move_to.0, move_to.1, drop_at.0, drop_at.1);
+            }
+            writers.unload.write(UnloadEvent { transport: entity, position: drop_at });
+        }
+        AiAction::Join { move_to, target } => {
+            if let Some(unit_type) = move_unit(commands, entity, units, map, move_to) {
+                info!("AI: {:?} joins ally at ({},{})", unit_type, move_to.0, move_to.1);
             }
+            writers.join.write(JoinEvent { source: entity, target });
+        }
+        AiAction::Resupply { move_to } => {
+            if let Some(unit_type) = move_unit(commands, entity, units, map, move_to) {
+                info!("AI: {:?} -> ({},{}) resupplies", unit_type, move_to.0, move_to.1);
+            }
+            writers.resupply.write(ResupplyEvent { supplier: entity });
         }
         AiAction::Wait => {
             if let Ok((_, _, _, _, mut unit)) = units.get_mut(entity) {
@@ -2082,3 +3692,65 @@ fn execute_action(
         }
     }
 }
+
+/// Where an AI move really ends when an enemy the AI can't see lies on its route or destination
+/// Returns the stop and the ambusher's position, or None when the way is clear
+fn ambush_stop(
+    entity: Entity,
+    dest: (i32, i32),
+    partner: Option<Entity>,
+    units: &Query<(Entity, &mut GridPosition, &Transform, &FactionMember, &mut Unit)>,
+    map: &GameMap,
+    fog: &FogOfWar,
+    game_data: &GameData,
+) -> Option<((i32, i32), (i32, i32))> {
+    let (_, pos, _, member, unit) = units.get(entity).ok()?;
+    let faction = member.faction;
+    let hidden_enemies: HashSet<(i32, i32)> = units.iter()
+        .filter(|(_, p, _, f, _)| f.faction != faction && fog.is_hidden_from(faction, p.x, p.y))
+        .map(|(_, p, _, _, _)| (p.x, p.y))
+        .collect();
+    if hidden_enemies.is_empty() {
+        return None;
+    }
+
+    // Route the move as the AI planned it, around the units it knows about
+    let known: HashMap<(i32, i32), Entity> = units.iter()
+        .filter(|(other, p, _, _, _)| *other != entity && Some(*other) != partner && !hidden_enemies.contains(&(p.x, p.y)))
+        .map(|(other, p, _, _, _)| ((p.x, p.y), other))
+        .collect();
+    let stats = unit.unit_type.stats();
+    let movement = effective_movement(stats.movement, unit.stamina);
+    let (_, costs) = calculate_movement_range_with_costs(pos, movement, map, &known, stats.class, game_data);
+    let start = IVec2::new(pos.x, pos.y);
+    let route = route_from_costs(start, IVec2::new(dest.0, dest.1), &costs, map, stats.class, game_data);
+
+    let occupied: HashSet<(i32, i32)> = units.iter()
+        .filter(|(other, _, _, _, _)| *other != entity)
+        .map(|(_, p, _, _, _)| (p.x, p.y))
+        .collect();
+    let (index, ambusher) = trap_stop(&route, &hidden_enemies, &occupied)?;
+    Some(((route[index].x, route[index].y), (ambusher.x, ambusher.y)))
+}
+
+/// Move an AI unit with animation and end its turn; returns its type if it exists
+fn move_unit(
+    commands: &mut Commands,
+    entity: Entity,
+    units: &mut Query<(Entity, &mut GridPosition, &Transform, &FactionMember, &mut Unit)>,
+    map: &GameMap,
+    move_to: (i32, i32),
+) -> Option<UnitType> {
+    let Ok((_, mut pos, transform, _, mut unit)) = units.get_mut(entity) else { return None };
+    let start_pos = transform.translation;
+    pos.x = move_to.0;
+    pos.y = move_to.1;
+    // Calculate end position (preserve Y height)
+    let new_world_pos = pos.to_world(map);
+    let end_pos = Vec3::new(new_world_pos.x, start_pos.y, new_world_pos.z);
+    // Add animation component for smooth movement
+    commands.entity(entity).insert(UnitAnimation::new(start_pos, end_pos));
+    unit.moved = true;
+    unit.exhausted = true;
+    Some(unit.unit_type)
+}
@@ -0,0 +1,115 @@
+//! AI debug overlay (F3)
+//!
+//! Shows why an AI controller plays the way it does: a heatmap of one influence layer
+//! drawn over the board, and, in the UI panel, the scored plan it settled on. Controllers
+//! only record this while the overlay is open, so it costs nothing otherwise.
+
+use bevy::prelude::*;
+
+use super::{AiControllers, AiDebugView, Faction, GameMap, InfluenceLayer, TILE_SIZE};
+
+pub struct AiDebugPlugin;
+
+impl Plugin for AiDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AiDebugOverlay>()
+            .add_systems(Update, (
+                toggle_ai_debug,
+                sync_controller_debug,
+                draw_influence_heatmap,
+            ).chain());
+    }
+}
+
+/// Debug view of the AI's reasoning: influence heatmaps and the scored plan (F3)
+#[derive(Resource, Default)]
+pub struct AiDebugOverlay {
+    pub enabled: bool,
+    pub layer: InfluenceLayer,
+    /// AI faction being inspected; the first one found when unset
+    pub faction: Option<Faction>,
+}
+
+impl AiDebugOverlay {
+    /// The reasoning being shown, if the inspected controller has recorded any
+    pub fn view<'a>(&self, controllers: &'a AiControllers) -> Option<&'a AiDebugView> {
+        match self.faction {
+            Some(faction) => controllers.debug_view(faction),
+            None => controllers.factions().find_map(|f| controllers.debug_view(f)),
+        }
+    }
+}
+
+fn toggle_ai_debug(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<AiDebugOverlay>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        overlay.enabled = !overlay.enabled;
+        info!("AI debug overlay: {}", if overlay.enabled { "ON" } else { "OFF" });
+    }
+}
+
+/// Controllers only record their reasoning while the overlay is open,
+/// including ones set up after it was turned on
+fn sync_controller_debug(
+    overlay: Res<AiDebugOverlay>,
+    mut controllers: ResMut<AiControllers>,
+) {
+    if overlay.is_changed() || controllers.is_changed() {
+        controllers.set_debug(overlay.enabled);
+    }
+}
+
+/// Tint each tile by the selected influence layer, scaled to the layer's largest value
+fn draw_influence_heatmap(
+    overlay: Res<AiDebugOverlay>,
+    controllers: Res<AiControllers>,
+    map: Res<GameMap>,
+    mut gizmos: Gizmos,
+) {
+    if !overlay.enabled {
+        return;
+    }
+    let Some(values) = overlay.view(&controllers).and_then(|view| view.layer(overlay.layer)) else {
+        return;
+    };
+
+    let peak = values.values().fold(0.0f32, |peak, v| peak.max(v.abs()));
+    if peak <= f32::EPSILON {
+        return;
+    }
+
+    let offset_x = -(map.width as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
+    let offset_z = -(map.height as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
+    let flat_rotation = Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+
+    for (&(x, y), &value) in values {
+        let strength = (value.abs() / peak).min(1.0);
+        if strength < 0.05 {
+            continue;
+        }
+        // Signed layers: blue for the AI, red for the enemy; others run yellow to red
+        let color = if overlay.layer.is_signed() {
+            if value > 0.0 {
+                Color::srgba(0.2, 0.5, 1.0, 0.25 + 0.6 * strength)
+            } else {
+                Color::srgba(1.0, 0.2, 0.2, 0.25 + 0.6 * strength)
+            }
+        } else {
+            Color::srgba(1.0, 1.0 - 0.8 * strength, 0.1, 0.25 + 0.6 * strength)
+        };
+
+        let world_x = x as f32 * TILE_SIZE + offset_x;
+        let world_z = y as f32 * TILE_SIZE + offset_z;
+        // Nested outlines fill more of the tile the stronger the value
+        let rings = 1 + (strength * 6.0) as i32;
+        for ring in 0..rings {
+            gizmos.rect(
+                Isometry3d::new(Vec3::new(world_x, 0.14, world_z), flat_rotation),
+                Vec2::splat(TILE_SIZE - 4.0 - ring as f32 * 3.0),
+                color,
+            );
+        }
+    }
+}
@@ -176,6 +176,10 @@ fn get_procedural_feature_params(terrain: Terrain) -> (Color, Vec2) {
             Color::srgb(0.50, 0.45, 0.40), // Stone building
             Vec2::new(TILE_SIZE * 0.85, 48.0),
         ),
+        Terrain::HQ => (
+            Color::srgb(0.55, 0.35, 0.32), // Keep with banners
+            Vec2::new(TILE_SIZE * 0.9, 56.0),
+        ),
         Terrain::Outpost => (
             Color::srgb(0.55, 0.50, 0.45), // Wooden outpost
             Vec2::new(TILE_SIZE * 0.75, 40.0),
@@ -184,6 +188,10 @@ fn get_procedural_feature_params(terrain: Terrain) -> (Color, Vec2) {
             Color::srgb(0.48, 0.42, 0.35), // Shed
             Vec2::new(TILE_SIZE * 0.65, 32.0),
         ),
+        Terrain::Barricade => (
+            Color::srgb(0.40, 0.30, 0.20), // Stick wall
+            Vec2::new(TILE_SIZE * 0.9, 24.0),
+        ),
         _ => (Color::WHITE, Vec2::ZERO),
     }
 }
@@ -0,0 +1,449 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    AiDifficulty, CommanderId, DialogueLine, Faction, FactionMember, GameResult, MapData, MapId, MapLibrary,
+    MapObjective, Trigger, TurnState, Unit, get_builtin_map,
+};
+
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CampaignLibrary>()
+            .init_resource::<CampaignProgress>()
+            .init_resource::<CampaignSession>()
+            .add_systems(Startup, load_campaigns)
+            .add_systems(Update, record_mission_result);
+    }
+}
+
+/// The campaign that ships with the game
+const DEFAULT_CAMPAIGN_RON: &str = include_str!("../../assets/campaigns/woodland_war.ron");
+
+/// Directory scanned for extra campaigns on native builds
+pub const CAMPAIGN_DIR: &str = "campaigns";
+
+// ============================================================================
+// CAMPAIGN DEFINITIONS
+// ============================================================================
+
+/// A story campaign: missions unlocked one after another, sometimes with a choice of path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    /// Key for progress; keep it stable once players have started the campaign
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// The first mission is unlocked from the start
+    pub missions: Vec<Mission>,
+}
+
+impl Campaign {
+    pub fn mission(&self, id: &str) -> Option<&Mission> {
+        self.missions.iter().find(|m| m.id == id)
+    }
+}
+
+/// One battle in a campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mission {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub map: MissionMap,
+    /// Sides of the map played by other factions, e.g. `{Northern: Nether}`
+    #[serde(default)]
+    pub recolor: HashMap<Faction, Faction>,
+    pub player: Faction,
+    pub player_co: CommanderId,
+    /// AI sides and their COs; AI sides not listed get their faction's first CO
+    #[serde(default)]
+    pub enemies: Vec<(Faction, CommanderId)>,
+    #[serde(default)]
+    pub difficulty: AiDifficulty,
+    /// Replaces the map's objectives when not empty
+    #[serde(default)]
+    pub objectives: Vec<MapObjective>,
+    /// Replaces the map's turn limit when set
+    #[serde(default)]
+    pub turn_limit: Option<u32>,
+    /// Added to the map's own triggers
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+    /// Shown before the mission starts
+    #[serde(default)]
+    pub briefing: Vec<DialogueLine>,
+    /// Shown after the mission is won
+    #[serde(default)]
+    pub debriefing: Vec<DialogueLine>,
+    /// Missions unlocked by winning this one; more than one lets the player choose a path
+    #[serde(default)]
+    pub unlocks: Vec<String>,
+    /// Turns to win in for the best speed rank
+    #[serde(default = "default_par_turns")]
+    pub par_turns: u32,
+}
+
+fn default_par_turns() -> u32 {
+    15
+}
+
+/// Where a mission's map comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MissionMap {
+    Builtin(MapId),
+    /// A map in the map library, by file name
+    Library(String),
+}
+
+impl Mission {
+    /// The map as this mission plays it: sides recolored, objectives and triggers applied
+    pub fn build_map(&self, library: &MapLibrary) -> Result<MapData, String> {
+        let mut map = match &self.map {
+            MissionMap::Builtin(MapId::Custom(_)) => return Err("built-in map id expected".to_string()),
+            MissionMap::Builtin(id) => get_builtin_map(*id),
+            MissionMap::Library(file_name) => library.maps.iter()
+                .find(|m| &m.file_name == file_name)
+                .map(|m| m.map.clone())
+                .ok_or_else(|| format!("map '{}' is not in the map library", file_name))?,
+        };
+
+        let recolor = |faction: Faction| self.recolor.get(&faction).copied().unwrap_or(faction);
+        for unit in &mut map.units {
+            unit.faction = recolor(unit.faction);
+        }
+        for property in &mut map.properties {
+            property.owner = recolor(property.owner);
+        }
+        map.rules.starting_funds = map.rules.starting_funds.iter().map(|(f, amount)| (recolor(*f), *amount)).collect();
+        for objective in &mut map.rules.objectives {
+            objective.faction = objective.faction.map(recolor);
+        }
+        for trigger in &mut map.triggers {
+            trigger.recolor(&recolor);
+        }
+
+        if !self.objectives.is_empty() {
+            map.rules.objectives = self.objectives.clone();
+        }
+        if self.turn_limit.is_some() {
+            map.rules.turn_limit = self.turn_limit;
+        }
+        map.triggers.extend(self.triggers.iter().cloned());
+
+        // Only sides with units or properties take turns
+        if !map.factions().contains(&self.player) {
+            return Err(format!("{} has no side on the map, so it would never get a turn", self.player.name()));
+        }
+        Ok(map)
+    }
+
+    /// CO for a side of this mission
+    pub fn commander(&self, faction: Faction) -> Option<CommanderId> {
+        if faction == self.player {
+            return Some(self.player_co);
+        }
+        self.enemies.iter()
+            .find(|(f, _)| *f == faction)
+            .map(|(_, co)| *co)
+            .or_else(|| CommanderId::for_faction(faction).first().copied())
+    }
+}
+
+/// Every campaign that can be played
+#[derive(Resource, Default)]
+pub struct CampaignLibrary {
+    pub campaigns: Vec<Campaign>,
+}
+
+/// Load the built-in campaign, then any in the campaigns directory
+fn load_campaigns(mut library: ResMut<CampaignLibrary>, mut progress: ResMut<CampaignProgress>) {
+    match ron::from_str::<Campaign>(DEFAULT_CAMPAIGN_RON) {
+        Ok(campaign) => library.campaigns.push(campaign),
+        Err(e) => error!("Failed to parse built-in campaign: {}", e),
+    }
+    library.campaigns.extend(load_campaign_files());
+    info!("Loaded {} campaign(s)", library.campaigns.len());
+
+    // Library maps load later, so only missions on built-in maps can be checked now
+    for campaign in &library.campaigns {
+        for mission in campaign.missions.iter().filter(|m| matches!(m.map, MissionMap::Builtin(_))) {
+            if let Err(e) = mission.build_map(&MapLibrary::default()) {
+                warn!("Mission '{}' in '{}' can't be played: {}", mission.id, campaign.name, e);
+            }
+        }
+    }
+
+    *progress = CampaignProgress::load();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_campaign_files() -> Vec<Campaign> {
+    use std::fs;
+
+    let Ok(entries) = fs::read_dir(CAMPAIGN_DIR) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<_> = entries.flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+        .collect();
+    paths.sort();
+
+    paths.iter()
+        .filter_map(|path| {
+            let content = fs::read_to_string(path).ok()?;
+            match ron::from_str::<Campaign>(&content) {
+                Ok(campaign) => Some(campaign),
+                Err(e) => {
+                    warn!("Failed to parse {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Only the built-in campaign on WASM
+#[cfg(target_arch = "wasm32")]
+fn load_campaign_files() -> Vec<Campaign> {
+    Vec::new()
+}
+
+// ============================================================================
+// RANKS AND PROGRESS
+// ============================================================================
+
+/// Grade for a won mission, from C (worst) to S (best)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Rank {
+    C,
+    B,
+    A,
+    S,
+}
+
+impl Rank {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rank::C => "C",
+            Rank::B => "B",
+            Rank::A => "A",
+            Rank::S => "S",
+        }
+    }
+
+    /// Grade a win on speed (turns against par) and on how much of the starting army survived
+    pub fn grade(turns: u32, par_turns: u32, survivors: usize, starting_units: usize) -> Rank {
+        let speed = if turns <= par_turns {
+            2
+        } else if turns <= par_turns + par_turns / 2 {
+            1
+        } else {
+            0
+        };
+        let kept = survivors as f32 / starting_units.max(1) as f32;
+        let survival = if kept >= 0.75 {
+            2
+        } else if kept >= 0.4 {
+            1
+        } else {
+            0
+        };
+        match speed + survival {
+            4 => Rank::S,
+            3 => Rank::A,
+            2 => Rank::B,
+            _ => Rank::C,
+        }
+    }
+}
+
+/// A player's progress through one campaign
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CampaignRecord {
+    /// Missions unlocked by wins (the first mission is always open)
+    pub unlocked: HashSet<String>,
+    /// Best rank per won mission
+    pub best_ranks: HashMap<String, Rank>,
+}
+
+/// Progress through every campaign, kept apart from battle saves
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    pub campaigns: HashMap<String, CampaignRecord>,
+}
+
+impl CampaignProgress {
+    pub fn is_unlocked(&self, campaign: &Campaign, mission_id: &str) -> bool {
+        campaign.missions.first().is_some_and(|m| m.id == mission_id)
+            || self.campaigns.get(&campaign.id).is_some_and(|r| r.unlocked.contains(mission_id))
+    }
+
+    pub fn best_rank(&self, campaign: &Campaign, mission_id: &str) -> Option<Rank> {
+        self.campaigns.get(&campaign.id).and_then(|r| r.best_ranks.get(mission_id).copied())
+    }
+
+    /// Record a won mission and unlock what follows. Returns whether the rank is a new best.
+    pub fn record_win(&mut self, campaign: &Campaign, mission: &Mission, rank: Rank) -> bool {
+        let record = self.campaigns.entry(campaign.id.clone()).or_default();
+        record.unlocked.extend(mission.unlocks.iter().cloned());
+        match record.best_ranks.get(&mission.id).copied() {
+            Some(best) if rank <= best => false,
+            _ => {
+                record.best_ranks.insert(mission.id.clone(), rank);
+                true
+            }
+        }
+    }
+
+    /// Forget all progress in a campaign
+    pub fn reset(&mut self, campaign: &Campaign) {
+        self.campaigns.remove(&campaign.id);
+    }
+
+    /// Read saved progress; a missing or unreadable file starts fresh
+    pub fn load() -> Self {
+        let Ok(json) = load_progress_from_storage() else { return Self::default() };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("Failed to parse campaign progress: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize campaign progress: {}", e))?;
+        save_progress_to_storage(&json)
+    }
+}
+
+/// Storage key for campaign progress on the web
+#[cfg(target_arch = "wasm32")]
+const PROGRESS_KEY: &str = "paw_and_claw_campaign";
+
+/// File for campaign progress on native builds, next to (not among) the save slots
+#[cfg(not(target_arch = "wasm32"))]
+const PROGRESS_FILE: &str = "saves/campaign_progress.json";
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    let window = web_sys::window().ok_or("No window object")?;
+    window
+        .local_storage()
+        .map_err(|_| "Failed to access localStorage")?
+        .ok_or_else(|| "localStorage not available".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_progress_to_storage(json: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(PROGRESS_KEY, json)
+        .map_err(|_| "Failed to write to localStorage".to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_progress_to_storage(json: &str) -> Result<(), String> {
+    std::fs::create_dir_all("saves").map_err(|e| e.to_string())?;
+    std::fs::write(PROGRESS_FILE, json).map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_progress_from_storage() -> Result<String, String> {
+    local_storage()?
+        .get_item(PROGRESS_KEY)
+        .map_err(|_| "Failed to read from localStorage")?
+        .ok_or_else(|| "No campaign progress".to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_progress_from_storage() -> Result<String, String> {
+    std::fs::read_to_string(PROGRESS_FILE).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// MISSION SESSION
+// ============================================================================
+
+/// A mission being played
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveMission {
+    /// Index into the `CampaignLibrary`
+    pub campaign: usize,
+    /// Index into the campaign's missions
+    pub mission: usize,
+    pub player: Faction,
+    /// Player units on the map at the start, for ranking
+    pub starting_units: usize,
+    /// The result has been recorded
+    pub finished: bool,
+}
+
+/// How a mission ended, for the debriefing
+#[derive(Debug, Clone)]
+pub struct MissionResult {
+    pub campaign: usize,
+    pub mission: usize,
+    pub won: bool,
+    pub turns: u32,
+    pub rank: Option<Rank>,
+    pub new_best: bool,
+}
+
+/// Links the battle to the campaign screen
+#[derive(Resource, Default)]
+pub struct CampaignSession {
+    /// Mission chosen on the campaign screen, taken when the battle starts
+    pub pending: Option<(usize, usize)>,
+    pub active: Option<ActiveMission>,
+    /// Outcome of the last mission played, until the debriefing is dismissed
+    pub last_result: Option<MissionResult>,
+}
+
+/// Grade and record the mission once the battle ends
+fn record_mission_result(
+    mut session: ResMut<CampaignSession>,
+    mut progress: ResMut<CampaignProgress>,
+    library: Res<CampaignLibrary>,
+    game_result: Res<GameResult>,
+    turn_state: Res<TurnState>,
+    units: Query<&FactionMember, With<Unit>>,
+) {
+    let Some(active) = session.active.as_mut() else { return };
+    if active.finished || !game_result.game_over {
+        return;
+    }
+    active.finished = true;
+    let active = *active;
+
+    let Some(campaign) = library.campaigns.get(active.campaign) else { return };
+    let Some(mission) = campaign.missions.get(active.mission) else { return };
+
+    let won = game_result.winner == Some(active.player);
+    let (rank, new_best) = if won {
+        let survivors = units.iter().filter(|m| m.faction == active.player).count();
+        let rank = Rank::grade(turn_state.turn_number, mission.par_turns, survivors, active.starting_units);
+        let new_best = progress.record_win(campaign, mission, rank);
+        if let Err(e) = progress.save() {
+            error!("Failed to save campaign progress: {}", e);
+        }
+        (Some(rank), new_best)
+    } else {
+        (None, false)
+    };
+    info!("Mission '{}' {} in {} turns", mission.name, if won { "won" } else { "lost" }, turn_state.turn_number);
+
+    session.last_result = Some(MissionResult {
+        campaign: active.campaign,
+        mission: active.mission,
+        won,
+        turns: turn_state.turn_number,
+        rank,
+        new_best,
+    });
+}
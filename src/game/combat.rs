@@ -1,19 +1,20 @@
 use bevy::prelude::*;
 use rand::Rng;
 
-use super::{GridPosition, Unit, FactionMember, Terrain, Tile, GameMap, Commanders, CoBonuses, Weather, UnitType, CargoUnit, spawn_unit, SpriteAssets, GameData};
+use super::{Faction, GridPosition, Unit, FactionMember, Terrain, Tile, GameMap, Commanders, CoBonuses, Weather, UnitType, CargoUnit, spawn_unit, SpriteAssets, GameData, TerrainChanger};
 
 pub struct CombatPlugin;
 
 impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<AttackEvent>()
+            .add_event::<AttackResultEvent>()
             .add_event::<CaptureEvent>()
             .add_event::<JoinEvent>()
             .add_event::<ResupplyEvent>()
             .add_event::<LoadEvent>()
             .add_event::<UnloadEvent>()
-            .add_systems(Update, (process_attacks, process_captures, process_joins, process_resupply, process_load, process_unload));
+            .add_systems(Update, (process_attacks, process_structure_attacks, process_captures, process_joins, process_resupply, process_load, process_unload));
     }
 }
 
@@ -24,6 +25,20 @@ pub struct AttackEvent {
     pub defender: Entity,
 }
 
+/// Event fired once an attack between units has been resolved
+#[derive(Message)]
+pub struct AttackResultEvent {
+    pub attacker: Entity,
+    pub defender: Entity,
+    pub attacker_faction: Faction,
+    pub defender_faction: Faction,
+    pub defender_type: UnitType,
+    pub damage: i32,
+    pub counter_damage: i32,
+    pub defender_destroyed: bool,
+    pub attacker_destroyed: bool,
+}
+
 /// Event fired when a unit attempts to capture a tile
 #[derive(Message)]
 pub struct CaptureEvent {
@@ -79,6 +94,22 @@ pub fn calculate_damage(
     defender_co: &CoBonuses,
     weather: &Weather,
     game_data: &GameData,
+) -> i32 {
+    let luck_roll = rand::thread_rng().gen_range(0..=9);
+    calculate_damage_with_luck(attacker, defender, defender_terrain, attacker_co, defender_co, weather, game_data, luck_roll)
+}
+
+/// Same as `calculate_damage`, with the 0-9 luck roll supplied by the caller
+/// (so headless simulations can use their own seeded RNG)
+pub fn calculate_damage_with_luck(
+    attacker: &Unit,
+    defender: &Unit,
+    defender_terrain: Terrain,
+    attacker_co: &CoBonuses,
+    defender_co: &CoBonuses,
+    weather: &Weather,
+    game_data: &GameData,
+    luck_roll: u32,
 ) -> i32 {
     // Look up base damage from damage tables (B in AW2 formula)
     let base_damage_percent = match game_data.get_base_damage(attacker.unit_type, defender.unit_type) {
@@ -102,8 +133,8 @@ pub fn calculate_damage(
 
     let weather_effects = weather.effects();
 
-    // Convert CO attack bonus to AW2 scale (1.0 = 100, 1.1 = 110)
-    let aco = attacker_co.attack * 100.0 * weather_effects.attack_multiplier;
+    // Convert CO attack bonus to AW2 scale (1.0 = 100, 1.1 = 110); veterancy adds on top
+    let aco = (attacker_co.attack * 100.0 + attacker.veterancy_bonus()) * weather_effects.attack_multiplier;
 
     // Attacker HP on 1-10 scale (AW2 uses display HP)
     let ahp = (attacker.hp as f32 / 10.0).ceil().max(1.0);
@@ -123,7 +154,7 @@ pub fn calculate_damage(
     };
 
     // Convert CO defense bonus to AW2 scale
-    let dco = defender_co.defense * 100.0 * weather_effects.defense_multiplier;
+    let dco = (defender_co.defense * 100.0 + defender.veterancy_bonus()) * weather_effects.defense_multiplier;
 
     // Defender HP on 1-10 scale
     let dhp = (defender.hp as f32 / 10.0).ceil().max(1.0);
@@ -139,9 +170,7 @@ pub fn calculate_damage(
 
     // AW2 Luck: adds 0-9 random damage, scaled by attacker HP percentage
     // (A full HP unit can add up to 9 damage, a 1 HP unit adds ~0-1)
-    let mut rng = rand::thread_rng();
-    let luck_roll = rng.gen_range(0..=9) as f32;
-    let luck_damage = luck_roll * (ahp / 10.0);
+    let luck_damage = luck_roll.min(9) as f32 * (ahp / 10.0);
 
     // Final damage (minimum 0)
     let final_damage = (base_final + luck_damage).max(0.0);
@@ -179,7 +208,7 @@ pub fn estimate_damage(
     }
 
     let weather_effects = weather.effects();
-    let aco = attacker_co.attack * 100.0 * weather_effects.attack_multiplier;
+    let aco = (attacker_co.attack * 100.0 + attacker.veterancy_bonus()) * weather_effects.attack_multiplier;
     let ahp = (attacker.hp as f32 / 10.0).ceil().max(1.0);
     let attack_component = (aco / 100.0) + (aco * (ahp - 1.0) / 1000.0);
 
@@ -191,7 +220,7 @@ pub fn estimate_damage(
         game_data.terrain_defense(defender_terrain) as f32
     };
 
-    let dco = defender_co.defense * 100.0 * weather_effects.defense_multiplier;
+    let dco = (defender_co.defense * 100.0 + defender.veterancy_bonus()) * weather_effects.defense_multiplier;
     let dhp = (defender.hp as f32 / 10.0).ceil().max(1.0);
     let defense_component = (200.0 - (dco + terrain_stars * 10.0 * dhp)) / 100.0;
     let defense_component = defense_component.max(0.1);
@@ -208,6 +237,25 @@ pub fn estimate_damage(
     (min_damage, max_damage)
 }
 
+/// Calculate damage against destructible terrain
+/// Like AW2 pipe seams: the "structure" damage column scaled by attacker HP,
+/// CO attack and weather. Structures have no terrain cover and there is no luck.
+pub fn calculate_structure_damage(
+    attacker: &Unit,
+    attacker_co: &CoBonuses,
+    weather: &Weather,
+    game_data: &GameData,
+) -> i32 {
+    let Some(base_damage_percent) = game_data.get_structure_damage(attacker.unit_type) else {
+        return 0;
+    };
+
+    let aco = attacker_co.attack * weather.effects().attack_multiplier;
+    let ahp = (attacker.hp as f32 / 10.0).ceil().max(1.0);
+
+    (base_damage_percent as f32 * aco * ahp / 10.0).round().max(1.0) as i32
+}
+
 /// Check if attacker can attack defender
 pub fn can_attack(
     attacker: &Unit,
@@ -236,6 +284,7 @@ pub fn can_attack(
 
 fn process_attacks(
     mut events: EventReader<AttackEvent>,
+    mut results: MessageWriter<AttackResultEvent>,
     mut commands: Commands,
     mut units: Query<(&mut Unit, &GridPosition, &FactionMember)>,
     map: Res<GameMap>,
@@ -301,10 +350,13 @@ fn process_attacks(
         // Mark attacker as having attacked
         attacker_unit.attacked = true;
 
+        let mut counter_damage = 0;
+
         // Check if defender is destroyed
         if defender_unit.hp <= 0 {
             info!("{} destroyed!", game_data.unit_name(defender_unit.unit_type));
             commands.entity(event.defender).despawn();
+            attacker_unit.promote();
         } else {
             // Counter-attack (if defender can reach attacker)
             let counter_stats = game_data.unit_stats(defender_unit.unit_type)
@@ -339,7 +391,7 @@ fn process_attacks(
                     .get(attacker_pos.x, attacker_pos.y)
                     .unwrap_or(Terrain::Grass);
 
-                let counter_damage = calculate_damage(&defender_unit, &attacker_unit, attacker_terrain, &defender_co, &attacker_co, &weather, &game_data);
+                counter_damage = calculate_damage(&defender_unit, &attacker_unit, attacker_terrain, &defender_co, &attacker_co, &weather, &game_data);
                 attacker_unit.hp -= counter_damage;
 
                 info!(
@@ -357,9 +409,99 @@ fn process_attacks(
                 if attacker_unit.hp <= 0 {
                     info!("{} destroyed by counter-attack!", game_data.unit_name(attacker_unit.unit_type));
                     commands.entity(event.attacker).despawn();
+                    defender_unit.promote();
                 }
             }
         }
+
+        results.write(AttackResultEvent {
+            attacker: event.attacker,
+            defender: event.defender,
+            attacker_faction: attacker_faction.faction,
+            defender_faction: defender_faction.faction,
+            defender_type: defender_unit.unit_type,
+            damage,
+            counter_damage,
+            defender_destroyed: defender_unit.hp <= 0,
+            attacker_destroyed: attacker_unit.hp <= 0,
+        });
+    }
+}
+
+/// Resolve attacks against destructible terrain (barricades, boulders, sieged outposts)
+/// Structures never counter-attack; destroyed ones turn into their `becomes` terrain
+fn process_structure_attacks(
+    mut events: EventReader<AttackEvent>,
+    mut units: Query<(&mut Unit, &GridPosition, &FactionMember)>,
+    mut map: ResMut<GameMap>,
+    mut commanders: ResMut<Commanders>,
+    weather: Res<Weather>,
+    game_data: Res<GameData>,
+    mut terrain_changer: TerrainChanger,
+) {
+    for event in events.read() {
+        let Ok((_, tile, _, _, _)) = terrain_changer.tiles.get(event.defender) else {
+            continue;
+        };
+        let terrain = tile.terrain;
+        let owner = tile.owner;
+        let (x, y) = (tile.position.x, tile.position.y);
+
+        let Ok((mut attacker_unit, attacker_pos, attacker_faction)) = units.get_mut(event.attacker) else {
+            continue;
+        };
+
+        let max_hp = game_data.terrain_hp(terrain) as i32;
+        let (min_range, max_range) = attacker_unit.unit_type.stats().attack_range;
+        let distance = attacker_pos.distance_to(&GridPosition::new(x, y));
+        if max_hp == 0
+            || owner == Some(attacker_faction.faction)
+            || game_data.get_structure_damage(attacker_unit.unit_type).is_none()
+            || distance < min_range
+            || distance > max_range
+        {
+            warn!("Invalid structure attack!");
+            continue;
+        }
+
+        // Check and deduct ammo
+        let attacker_max_ammo = game_data.unit_stats(attacker_unit.unit_type)
+            .map(|s| s.max_ammo)
+            .unwrap_or_else(|| attacker_unit.unit_type.stats().max_ammo);
+        if attacker_max_ammo > 0 {
+            if attacker_unit.ammo == 0 {
+                warn!("No ammo to attack!");
+                continue;
+            }
+            attacker_unit.ammo = attacker_unit.ammo.saturating_sub(1);
+        }
+
+        let attacker_co = commanders.get_bonuses(attacker_faction.faction);
+        let damage = calculate_structure_damage(&attacker_unit, &attacker_co, &weather, &game_data);
+        attacker_unit.attacked = true;
+        commanders.charge(attacker_faction.faction, (damage as u32) / 10);
+
+        let Ok((_, mut tile, _, _, _)) = terrain_changer.tiles.get_mut(event.defender) else {
+            continue;
+        };
+        let before = (max_hp - tile.damage).max(0);
+        tile.damage += damage;
+        let remaining = (max_hp - tile.damage).max(0);
+
+        info!(
+            "{} hits {} for {} damage! (HP: {} -> {})",
+            game_data.unit_name(attacker_unit.unit_type),
+            game_data.terrain_name(terrain),
+            damage,
+            before,
+            remaining
+        );
+
+        if remaining == 0 {
+            let rubble = game_data.terrain_becomes(terrain).unwrap_or(Terrain::Grass);
+            info!("{} destroyed at ({}, {})!", game_data.terrain_name(terrain), x, y);
+            terrain_changer.change_terrain(&mut map, x, y, rubble);
+        }
     }
 }
 
@@ -454,9 +596,9 @@ fn process_joins(
             continue;
         };
 
-        // Both units must be same type (this should have been checked before sending the event)
-        if source_unit.unit_type != target_unit.unit_type {
-            warn!("Cannot join different unit types!");
+        // Both units must be able to merge (this should have been checked before sending the event)
+        if !target_unit.can_join(&source_unit) {
+            warn!("Cannot join these units!");
             continue;
         }
 
@@ -481,6 +623,7 @@ fn process_joins(
         target_unit.hp = combined_hp;
         target_unit.stamina = combined_stamina;
         target_unit.ammo = combined_ammo;
+        target_unit.vip |= source_unit.vip;  // A VIP lives on in the merged unit
         target_unit.exhausted = true;  // Unit's turn is complete after joining
 
         // Despawn source unit (with children like shadows/borders)
@@ -489,6 +489,8 @@ pub struct Commanders {
     pub power_active: HashMap<Faction, bool>,
     /// Cached power effect when activated (for systems to read)
     pub active_effect: HashMap<Faction, PowerEffect>,
+    /// Extra bonuses drafted during a roguelike run, stacked on the CO's own
+    pub perks: HashMap<Faction, CoBonuses>,
 }
 
 impl Default for Commanders {
@@ -523,6 +525,7 @@ impl Default for Commanders {
             power_meter,
             power_active,
             active_effect: HashMap::new(),
+            perks: HashMap::new(),
         }
     }
 }
@@ -644,12 +647,21 @@ impl Commanders {
             }
         }
 
+        if let Some(perks) = self.perks.get(&faction) {
+            bonuses.attack *= perks.attack;
+            bonuses.defense *= perks.defense;
+            bonuses.movement += perks.movement;
+            bonuses.income *= perks.income;
+            bonuses.vision += perks.vision;
+            bonuses.cost *= perks.cost;
+        }
+
         bonuses
     }
 }
 
 /// Computed bonuses from active CO (passive + power effects)
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CoBonuses {
     pub attack: f32,
     pub defense: f32,
@@ -742,7 +754,7 @@ fn apply_power_effects(
                 // For now, mark all tiles as explored
                 for x in 0..map.width as i32 {
                     for y in 0..map.height as i32 {
-                        fog.mark_explored(x, y);
+                        fog.mark_explored(event.faction, x, y);
                     }
                 }
                 info!("Fog Piercer activated - map revealed and attack boosted!");
@@ -0,0 +1,642 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    Faction, Unit, UnitType, GridPosition, GameMap, Tile, WeatherType, CommanderId, CoBonuses,
+    GameData, AiDifficulty, AttackResultEvent, Terrain, UtilityAi, AiDebugView,
+    calculate_movement_range, effective_movement,
+};
+
+// ============================================================================
+// BATTLE SNAPSHOT - What a controller is allowed to see
+// ============================================================================
+
+/// Stable id of a unit or property tile for the length of a battle
+pub type ObjectId = u64;
+
+/// Read-only view of the battle from one faction's side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BattleSnapshot {
+    /// Faction the controller is playing
+    pub faction: Faction,
+    pub turn: u32,
+    pub funds: u32,
+    pub weather: WeatherType,
+    pub commander: Option<CommanderId>,
+    /// Every faction's CO, opponents included
+    pub commanders: HashMap<Faction, CommanderId>,
+    /// The faction's CO bonuses, including any active power
+    pub co_bonuses: CoBonuses,
+    pub power_ready: bool,
+    pub map: GameMap,
+    /// Own units, plus every enemy unit the faction can see
+    pub units: Vec<UnitSnapshot>,
+    /// Capturable tiles and who owns them
+    pub properties: Vec<PropertySnapshot>,
+    /// Tiles the faction can see; `None` when nothing is hidden from it
+    pub visible: Option<HashSet<(i32, i32)>>,
+    /// Attacks resolved since the faction's previous snapshot
+    pub attacks: Vec<AttackReport>,
+    /// Units the map forbids building
+    #[serde(default)]
+    pub banned_units: Vec<UnitType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnitSnapshot {
+    pub id: ObjectId,
+    pub faction: Faction,
+    pub x: i32,
+    pub y: i32,
+    pub unit: Unit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertySnapshot {
+    pub id: ObjectId,
+    pub tile: Tile,
+}
+
+/// A resolved attack, as reported to controllers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackReport {
+    pub attacker: ObjectId,
+    pub defender: ObjectId,
+    pub attacker_faction: Faction,
+    pub defender_faction: Faction,
+    pub defender_type: UnitType,
+    pub damage: i32,
+    pub counter_damage: i32,
+    pub defender_destroyed: bool,
+    pub attacker_destroyed: bool,
+}
+
+impl From<&AttackResultEvent> for AttackReport {
+    fn from(result: &AttackResultEvent) -> Self {
+        Self {
+            attacker: result.attacker.to_bits(),
+            defender: result.defender.to_bits(),
+            attacker_faction: result.attacker_faction,
+            defender_faction: result.defender_faction,
+            defender_type: result.defender_type,
+            damage: result.damage,
+            counter_damage: result.counter_damage,
+            defender_destroyed: result.defender_destroyed,
+            attacker_destroyed: result.attacker_destroyed,
+        }
+    }
+}
+
+impl BattleSnapshot {
+    pub fn unit(&self, id: ObjectId) -> Option<&UnitSnapshot> {
+        self.units.iter().find(|u| u.id == id)
+    }
+
+    pub fn unit_at(&self, x: i32, y: i32) -> Option<&UnitSnapshot> {
+        self.units.iter().find(|u| u.x == x && u.y == y)
+    }
+
+    pub fn property_at(&self, x: i32, y: i32) -> Option<&PropertySnapshot> {
+        self.properties.iter().find(|p| p.tile.position.x == x && p.tile.position.y == y)
+    }
+
+    /// The controller's own units
+    pub fn own_units(&self) -> impl Iterator<Item = &UnitSnapshot> {
+        self.units.iter().filter(move |u| u.faction == self.faction)
+    }
+
+    /// Whether the faction can currently see a tile
+    pub fn can_see(&self, x: i32, y: i32) -> bool {
+        self.visible.as_ref().is_none_or(|v| v.contains(&(x, y)))
+    }
+
+    /// Cost of building a unit after faction and CO modifiers
+    pub fn build_cost(&self, unit_type: UnitType, game_data: &GameData) -> u32 {
+        game_data.build_cost(unit_type, self.faction, &self.co_bonuses)
+    }
+
+    /// The same snapshot with every unit and property id passed through `map`
+    pub fn map_ids(&self, mut map: impl FnMut(ObjectId) -> ObjectId) -> BattleSnapshot {
+        let mut snapshot = self.clone();
+        for unit in &mut snapshot.units {
+            unit.id = map(unit.id);
+        }
+        for property in &mut snapshot.properties {
+            property.id = map(property.id);
+        }
+        for attack in &mut snapshot.attacks {
+            attack.attacker = map(attack.attacker);
+            attack.defender = map(attack.defender);
+        }
+        snapshot
+    }
+}
+
+/// Everything needed to take a battle snapshot, gathered by the caller
+pub struct SnapshotSource<'a> {
+    pub faction: Faction,
+    pub turn: u32,
+    pub funds: u32,
+    pub weather: WeatherType,
+    pub commander: Option<CommanderId>,
+    pub commanders: HashMap<Faction, CommanderId>,
+    pub co_bonuses: CoBonuses,
+    pub power_ready: bool,
+    pub map: &'a GameMap,
+    /// Tiles the faction can see, or `None` to show it the whole board
+    pub visible: Option<HashSet<(i32, i32)>>,
+    pub banned_units: Vec<UnitType>,
+}
+
+impl SnapshotSource<'_> {
+    pub fn build<'u, 't>(
+        self,
+        units: impl Iterator<Item = (Entity, &'u GridPosition, Faction, &'u Unit)>,
+        tiles: impl Iterator<Item = (Entity, &'t Tile)>,
+        attacks: Vec<AttackReport>,
+    ) -> BattleSnapshot {
+        let faction = self.faction;
+        let visible = self.visible;
+        let can_see = |x: i32, y: i32| visible.as_ref().is_none_or(|v| v.contains(&(x, y)));
+
+        let units = units
+            .filter(|(_, pos, f, _)| *f == faction || can_see(pos.x, pos.y))
+            .map(|(entity, pos, f, unit)| UnitSnapshot {
+                id: entity.to_bits(),
+                faction: f,
+                x: pos.x,
+                y: pos.y,
+                unit: unit.clone(),
+            })
+            .collect();
+        let properties = tiles
+            .filter(|(_, tile)| tile.terrain.is_capturable())
+            .map(|(entity, tile)| PropertySnapshot { id: entity.to_bits(), tile: tile.clone() })
+            .collect();
+
+        BattleSnapshot {
+            faction,
+            turn: self.turn,
+            funds: self.funds,
+            weather: self.weather,
+            commander: self.commander,
+            commanders: self.commanders,
+            co_bonuses: self.co_bonuses,
+            power_ready: self.power_ready,
+            map: self.map.clone(),
+            units,
+            properties,
+            visible,
+            attacks,
+            banned_units: self.banned_units,
+        }
+    }
+}
+
+// ============================================================================
+// COMMANDS
+// ============================================================================
+
+/// An order from a controller. Units are named by id, places by tile; partners for
+/// loading and joining are whoever stands on `move_to`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum BotCommand {
+    Move { unit: ObjectId, to: (i32, i32) },
+    Attack { unit: ObjectId, move_to: (i32, i32), target: ObjectId },
+    Capture { unit: ObjectId, move_to: (i32, i32) },
+    Load { unit: ObjectId, move_to: (i32, i32) },
+    Unload { unit: ObjectId, move_to: (i32, i32), drop_at: (i32, i32) },
+    Join { unit: ObjectId, move_to: (i32, i32) },
+    Resupply { unit: ObjectId, move_to: (i32, i32) },
+    Wait { unit: ObjectId },
+    Build { x: i32, y: i32, unit_type: UnitType },
+    ActivatePower,
+    EndTurn,
+}
+
+impl BotCommand {
+    /// The same command with every unit id passed through `map`; None if one has no counterpart
+    pub fn map_ids(&self, mut map: impl FnMut(ObjectId) -> Option<ObjectId>) -> Option<BotCommand> {
+        let mut command = self.clone();
+        match &mut command {
+            BotCommand::Attack { unit, target, .. } => {
+                *unit = map(*unit)?;
+                *target = map(*target)?;
+            }
+            BotCommand::Move { unit, .. }
+            | BotCommand::Capture { unit, .. }
+            | BotCommand::Load { unit, .. }
+            | BotCommand::Unload { unit, .. }
+            | BotCommand::Join { unit, .. }
+            | BotCommand::Resupply { unit, .. }
+            | BotCommand::Wait { unit } => *unit = map(*unit)?,
+            BotCommand::Build { .. } | BotCommand::ActivatePower | BotCommand::EndTurn => {}
+        }
+        Some(command)
+    }
+
+    /// Unit the command orders about, if any
+    pub fn unit(&self) -> Option<ObjectId> {
+        match self {
+            BotCommand::Move { unit, .. }
+            | BotCommand::Attack { unit, .. }
+            | BotCommand::Capture { unit, .. }
+            | BotCommand::Load { unit, .. }
+            | BotCommand::Unload { unit, .. }
+            | BotCommand::Join { unit, .. }
+            | BotCommand::Resupply { unit, .. }
+            | BotCommand::Wait { unit } => Some(*unit),
+            BotCommand::Build { .. } | BotCommand::ActivatePower | BotCommand::EndTurn => None,
+        }
+    }
+
+    /// Tile the unit moves to before acting, if any
+    pub fn destination(&self) -> Option<(i32, i32)> {
+        match self {
+            BotCommand::Move { to, .. } => Some(*to),
+            BotCommand::Attack { move_to, .. }
+            | BotCommand::Capture { move_to, .. }
+            | BotCommand::Load { move_to, .. }
+            | BotCommand::Unload { move_to, .. }
+            | BotCommand::Join { move_to, .. }
+            | BotCommand::Resupply { move_to, .. } => Some(*move_to),
+            _ => None,
+        }
+    }
+}
+
+/// Check a command against the rules, as far as the controller's snapshot can tell.
+/// Units hidden from the controller are the runtime's business, not an error.
+pub fn validate_command(command: &BotCommand, snapshot: &BattleSnapshot, game_data: &GameData) -> Result<(), String> {
+    match command {
+        BotCommand::EndTurn => return Ok(()),
+        BotCommand::ActivatePower => {
+            return if snapshot.power_ready { Ok(()) } else { Err("CO power is not charged".to_string()) };
+        }
+        BotCommand::Build { x, y, unit_type } => return validate_build(*x, *y, *unit_type, snapshot, game_data),
+        _ => {}
+    }
+
+    let Some(id) = command.unit() else { return Ok(()) };
+    let unit = snapshot.unit(id).ok_or_else(|| format!("No unit {}", id))?;
+    if unit.faction != snapshot.faction {
+        return Err(format!("Unit {} belongs to {:?}", id, unit.faction));
+    }
+    if unit.unit.exhausted {
+        return Err(format!("{:?} has already acted", unit.unit.unit_type));
+    }
+
+    let Some(dest) = command.destination() else { return Ok(()) };
+    if unit.unit.moved && dest != (unit.x, unit.y) {
+        return Err(format!("{:?} has already moved", unit.unit.unit_type));
+    }
+
+    // Loading and joining end on a friendly unit's tile; everything else needs it empty
+    let partner = match command {
+        BotCommand::Load { .. } | BotCommand::Join { .. } => {
+            let partner = snapshot.unit_at(dest.0, dest.1)
+                .filter(|p| p.id != id && p.faction == snapshot.faction)
+                .ok_or_else(|| format!("No friendly unit at ({}, {})", dest.0, dest.1))?;
+            Some(partner)
+        }
+        _ => {
+            if snapshot.unit_at(dest.0, dest.1).is_some_and(|other| other.id != id) {
+                return Err(format!("({}, {}) is occupied", dest.0, dest.1));
+            }
+            None
+        }
+    };
+
+    let stats = unit.unit.unit_type.stats();
+    let blockers: HashMap<(i32, i32), Entity> = snapshot.units.iter()
+        .filter(|u| u.id != id && Some(u.id) != partner.map(|p| p.id))
+        .map(|u| ((u.x, u.y), Entity::from_bits(u.id)))
+        .collect();
+    let movement = effective_movement(stats.movement, unit.unit.stamina);
+    let reachable = calculate_movement_range(&GridPosition::new(unit.x, unit.y), movement, &snapshot.map, &blockers, stats.class, game_data);
+    if dest != (unit.x, unit.y) && !reachable.contains(&dest) {
+        return Err(format!("{:?} can't reach ({}, {})", unit.unit.unit_type, dest.0, dest.1));
+    }
+
+    check_action(command, unit, dest, partner, snapshot, game_data)
+}
+
+/// Rules for what a unit may do once it stands on `dest`
+fn check_action(
+    command: &BotCommand,
+    unit: &UnitSnapshot,
+    dest: (i32, i32),
+    partner: Option<&UnitSnapshot>,
+    snapshot: &BattleSnapshot,
+    game_data: &GameData,
+) -> Result<(), String> {
+    let stats = unit.unit.unit_type.stats();
+    match command {
+        BotCommand::Attack { target, .. } => {
+            let target = snapshot.unit(*target).ok_or_else(|| format!("No visible unit {}", target))?;
+            if target.faction == snapshot.faction {
+                return Err("Can't attack a friendly unit".to_string());
+            }
+            if stats.attack == 0 || (stats.max_ammo > 0 && unit.unit.ammo == 0) {
+                return Err(format!("{:?} can't attack", unit.unit.unit_type));
+            }
+            let dist = ((dest.0 - target.x).abs() + (dest.1 - target.y).abs()) as u32;
+            if dist < stats.attack_range.0 || dist > stats.attack_range.1 {
+                return Err("Target is out of range".to_string());
+            }
+        }
+        BotCommand::Capture { .. } => {
+            if !stats.can_capture {
+                return Err(format!("{:?} can't capture", unit.unit.unit_type));
+            }
+            let property = snapshot.property_at(dest.0, dest.1)
+                .ok_or_else(|| format!("Nothing to capture at ({}, {})", dest.0, dest.1))?;
+            if property.tile.owner == Some(snapshot.faction) {
+                return Err("Already own that property".to_string());
+            }
+        }
+        BotCommand::Load { .. } => {
+            let transport = partner.map(|p| &p.unit);
+            if !unit.unit.can_be_transported() || !transport.is_some_and(|t| t.is_transport() && !t.has_cargo()) {
+                return Err("Can't board there".to_string());
+            }
+        }
+        BotCommand::Join { .. } => {
+            if !partner.is_some_and(|p| p.unit.can_join(&unit.unit)) {
+                return Err("Can only join a unit of the same type".to_string());
+            }
+        }
+        BotCommand::Unload { drop_at, .. } => {
+            let Some(cargo) = &unit.unit.cargo else {
+                return Err("Nothing to unload".to_string());
+            };
+            if (drop_at.0 - dest.0).abs() + (drop_at.1 - dest.1).abs() != 1 {
+                return Err("Drop tile must be next to the transport".to_string());
+            }
+            let passable = snapshot.map.get(drop_at.0, drop_at.1)
+                .is_some_and(|t| game_data.is_passable(t, cargo.unit_type.stats().class));
+            if !passable || snapshot.unit_at(drop_at.0, drop_at.1).is_some() {
+                return Err(format!("Can't drop cargo at ({}, {})", drop_at.0, drop_at.1));
+            }
+        }
+        BotCommand::Resupply { .. } => {
+            if unit.unit.unit_type != UnitType::Supplier {
+                return Err("Only Suppliers resupply".to_string());
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Every command that would pass validation right now, ending with `EndTurn`
+pub fn legal_commands(snapshot: &BattleSnapshot, game_data: &GameData) -> Vec<BotCommand> {
+    let mut legal = Vec::new();
+
+    for unit in snapshot.own_units().filter(|u| !u.unit.exhausted) {
+        let id = unit.id;
+        let here = (unit.x, unit.y);
+        let stats = unit.unit.unit_type.stats();
+
+        let mut dests = vec![here];
+        if !unit.unit.moved {
+            let blockers: HashMap<(i32, i32), Entity> = snapshot.units.iter()
+                .filter(|u| u.id != id)
+                .map(|u| ((u.x, u.y), Entity::from_bits(u.id)))
+                .collect();
+            let movement = effective_movement(stats.movement, unit.unit.stamina);
+            let mut reachable: Vec<_> = calculate_movement_range(&GridPosition::new(unit.x, unit.y), movement, &snapshot.map, &blockers, stats.class, game_data)
+                .into_iter()
+                .filter(|&(x, y)| snapshot.unit_at(x, y).is_none())
+                .collect();
+            reachable.sort();
+            dests.extend(reachable);
+        }
+
+        legal.push(BotCommand::Wait { unit: id });
+        for &dest in &dests {
+            if dest != here {
+                legal.push(BotCommand::Move { unit: id, to: dest });
+            }
+            let mut candidates: Vec<BotCommand> = snapshot.units.iter()
+                .filter(|t| t.faction != snapshot.faction)
+                .map(|t| BotCommand::Attack { unit: id, move_to: dest, target: t.id })
+                .collect();
+            candidates.push(BotCommand::Capture { unit: id, move_to: dest });
+            candidates.push(BotCommand::Resupply { unit: id, move_to: dest });
+            for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                candidates.push(BotCommand::Unload { unit: id, move_to: dest, drop_at: (dest.0 + dx, dest.1 + dy) });
+            }
+            legal.extend(candidates.into_iter()
+                .filter(|c| check_action(c, unit, dest, None, snapshot, game_data).is_ok()));
+        }
+
+        // Boarding and joining end on a friendly unit's tile, which needs its own range check
+        for partner in snapshot.own_units().filter(|p| p.id != id) {
+            let move_to = (partner.x, partner.y);
+            for command in [BotCommand::Load { unit: id, move_to }, BotCommand::Join { unit: id, move_to }] {
+                if validate_command(&command, snapshot, game_data).is_ok() {
+                    legal.push(command);
+                }
+            }
+        }
+    }
+
+    for base in &snapshot.properties {
+        let (x, y) = (base.tile.position.x, base.tile.position.y);
+        for unit_type in UnitType::base_buildable() {
+            if validate_build(x, y, *unit_type, snapshot, game_data).is_ok() {
+                legal.push(BotCommand::Build { x, y, unit_type: *unit_type });
+            }
+        }
+    }
+
+    if snapshot.power_ready {
+        legal.push(BotCommand::ActivatePower);
+    }
+    legal.push(BotCommand::EndTurn);
+    legal
+}
+
+fn validate_build(x: i32, y: i32, unit_type: UnitType, snapshot: &BattleSnapshot, game_data: &GameData) -> Result<(), String> {
+    let base = snapshot.property_at(x, y)
+        .filter(|p| p.tile.terrain == Terrain::Base && p.tile.owner == Some(snapshot.faction))
+        .ok_or_else(|| format!("No owned base at ({}, {})", x, y))?;
+    if snapshot.unit_at(base.tile.position.x, base.tile.position.y).is_some() {
+        return Err("Base is occupied".to_string());
+    }
+    let buildable = UnitType::base_buildable().contains(&unit_type)
+        && game_data.unit_stats(unit_type).is_some_and(|s| game_data.is_passable(Terrain::Base, s.class));
+    if !buildable {
+        return Err(format!("{:?} can't be built at a base", unit_type));
+    }
+    if snapshot.banned_units.contains(&unit_type) {
+        return Err(format!("{:?} is banned on this map", unit_type));
+    }
+    if snapshot.build_cost(unit_type, game_data) > snapshot.funds {
+        return Err(format!("Can't afford {:?}", unit_type));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// CONTROLLERS
+// ============================================================================
+
+/// A bot that plays one faction. Controllers never touch the world: they read a snapshot
+/// and answer with commands, which are validated and carried out one at a time.
+pub trait AiController: Send + Sync {
+    /// Name shown in battle setup and logs
+    fn name(&self) -> &str;
+
+    /// Called once as the faction's turn begins
+    fn begin_turn(&mut self, _snapshot: &BattleSnapshot, _game_data: &GameData) {}
+
+    /// Commands to carry out next. An empty list means "still thinking, ask again next frame";
+    /// `BotCommand::EndTurn` finishes the turn.
+    fn next_commands(&mut self, snapshot: &BattleSnapshot, game_data: &GameData) -> Vec<BotCommand>;
+
+    /// Called when one of the controller's commands fails validation
+    fn command_rejected(&mut self, _command: &BotCommand, _reason: &str) {}
+
+    /// Turn recording of the controller's reasoning on or off
+    fn set_debug(&mut self, _enabled: bool) {}
+
+    /// What the controller was last thinking, if it records that
+    fn debug_view(&self) -> Option<&AiDebugView> {
+        None
+    }
+
+    /// Whether the controller only ever sees what its faction sees, even when the AI vision
+    /// setting lets the built-in AI see through fog
+    fn always_fogged(&self) -> bool {
+        false
+    }
+}
+
+/// The controller playing each AI faction
+#[derive(Resource)]
+pub struct AiControllers {
+    controllers: HashMap<Faction, Box<dyn AiController>>,
+}
+
+impl Default for AiControllers {
+    fn default() -> Self {
+        let mut controllers = Self { controllers: HashMap::new() };
+        controllers.set(Faction::Northern, Box::new(UtilityAi::new(AiDifficulty::default(), 0)));
+        controllers
+    }
+}
+
+impl AiControllers {
+    pub fn set(&mut self, faction: Faction, controller: Box<dyn AiController>) {
+        info!("{:?} is played by {}", faction, controller.name());
+        self.controllers.insert(faction, controller);
+    }
+
+    pub fn remove(&mut self, faction: Faction) {
+        self.controllers.remove(&faction);
+    }
+
+    /// Hand every faction back to human players
+    pub fn clear(&mut self) {
+        self.controllers.clear();
+    }
+
+    pub fn controls(&self, faction: Faction) -> bool {
+        self.controllers.contains_key(&faction)
+    }
+
+    /// Factions currently played by a controller
+    pub fn factions(&self) -> impl Iterator<Item = Faction> + '_ {
+        self.controllers.keys().copied()
+    }
+
+    pub fn name(&self, faction: Faction) -> Option<&str> {
+        self.controllers.get(&faction).map(|c| c.name())
+    }
+
+    pub fn get_mut(&mut self, faction: Faction) -> Option<&mut Box<dyn AiController>> {
+        self.controllers.get_mut(&faction)
+    }
+
+    pub fn set_debug(&mut self, enabled: bool) {
+        for controller in self.controllers.values_mut() {
+            controller.set_debug(enabled);
+        }
+    }
+
+    pub fn debug_view(&self, faction: Faction) -> Option<&AiDebugView> {
+        self.controllers.get(&faction).and_then(|c| c.debug_view())
+    }
+
+    pub fn always_fogged(&self, faction: Faction) -> bool {
+        self.controllers.get(&faction).is_some_and(|c| c.always_fogged())
+    }
+}
+
+/// What battle setup knows when it creates a controller
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerSettings {
+    pub faction: Faction,
+    pub difficulty: AiDifficulty,
+    pub seed: u64,
+}
+
+type ControllerFactory = Box<dyn Fn(&ControllerSettings) -> Box<dyn AiController> + Send + Sync>;
+
+/// A controller that can be picked in battle setup
+pub struct ControllerEntry {
+    pub name: String,
+    pub description: String,
+    factory: ControllerFactory,
+}
+
+/// Controllers offered in battle setup
+#[derive(Resource)]
+pub struct ControllerCatalog {
+    entries: Vec<ControllerEntry>,
+}
+
+impl Default for ControllerCatalog {
+    fn default() -> Self {
+        let mut catalog = Self { entries: Vec::new() };
+        catalog.register(
+            "Utility AI",
+            "Built-in planner: influence maps, goals, opponent modelling",
+            |settings| Box::new(UtilityAi::new(settings.difficulty, settings.seed)),
+        );
+        catalog
+    }
+}
+
+impl ControllerCatalog {
+    /// Offer another controller in battle setup
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        factory: impl Fn(&ControllerSettings) -> Box<dyn AiController> + Send + Sync + 'static,
+    ) {
+        self.entries.push(ControllerEntry {
+            name: name.into(),
+            description: description.into(),
+            factory: Box::new(factory),
+        });
+    }
+
+    pub fn entries(&self) -> &[ControllerEntry] {
+        &self.entries
+    }
+
+    /// Create the controller at `index`, falling back to the built-in one
+    pub fn build(&self, index: usize, settings: &ControllerSettings) -> Box<dyn AiController> {
+        let entry = self.entries.get(index).or(self.entries.first());
+        match entry {
+            Some(entry) => (entry.factory)(settings),
+            None => Box::new(UtilityAi::new(settings.difficulty, settings.seed)),
+        }
+    }
+}
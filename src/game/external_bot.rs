@@ -0,0 +1,352 @@
+//! External bots: controllers that run as a separate process and talk JSON over stdin/stdout
+//!
+//! Bots are listed in `bots/*.ron` and show up in battle setup next to the built-in AI.
+//! The protocol is line based, one JSON object per line:
+//!
+//! - The game sends `{"type": "hello", "protocol": 1, "faction": "Northern"}` once at startup.
+//! - Whenever it wants orders it sends `{"type": "turn", "snapshot": {...}, "legal": [...],
+//!   "rejected": [...]}` and waits for exactly one reply line.
+//! - The bot replies with one command (`{"command": "move", "unit": 3, "to": [3, 4]}`)
+//!   or an array of them. Commands are validated like human input; rejected ones are listed
+//!   with a reason in the next `turn` message. The turn ends when the bot sends
+//!   `{"command": "end_turn"}` (an empty array counts as the same).
+//! - The game sends `{"type": "quit"}` when the battle ends.
+//!
+//! Units and properties are named by small ids, numbered from 0 as the bot first hears of
+//! them and kept for the whole battle. Bots only ever see what their faction sees through fog.
+//!
+//! Anything the bot writes to stderr goes straight to the game's console.
+//! Not available on WASM builds.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{Faction, AiController, BattleSnapshot, BotCommand, ControllerCatalog, ControllerSettings, UtilityAi};
+
+pub struct ExternalBotPlugin;
+
+impl Plugin for ExternalBotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, register_external_bots);
+    }
+}
+
+/// Version of the line protocol, sent in the `hello` message
+pub const BOT_PROTOCOL_VERSION: u32 = 1;
+
+/// A bot listed in `bots/*.ron`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Program to launch, e.g. `python3`
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Why a command was refused, as reported back to the bot
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedCommand {
+    pub command: BotCommand,
+    pub reason: String,
+}
+
+/// Messages from the game to a bot
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum GameMessage<'a> {
+    Hello { protocol: u32, faction: Faction },
+    Turn { snapshot: &'a BattleSnapshot, legal: Vec<BotCommand>, rejected: &'a [RejectedCommand] },
+    Quit,
+}
+
+/// A bot's reply: one command or several
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BotReply {
+    One(BotCommand),
+    Many(Vec<BotCommand>),
+}
+
+/// Parse one reply line into commands; an empty list ends the turn
+pub fn parse_bot_reply(line: &str) -> Result<Vec<BotCommand>, String> {
+    let commands = match serde_json::from_str::<BotReply>(line).map_err(|e| e.to_string())? {
+        BotReply::One(command) => vec![command],
+        BotReply::Many(commands) => commands,
+    };
+    Ok(if commands.is_empty() { vec![BotCommand::EndTurn] } else { commands })
+}
+
+/// Add every bot in the `bots` directory to battle setup
+fn register_external_bots(mut catalog: ResMut<ControllerCatalog>) {
+    for manifest in load_bot_manifests() {
+        info!("Found external bot: {} ({})", manifest.name, manifest.command);
+        let description = if manifest.description.is_empty() {
+            format!("External bot: {}", manifest.command)
+        } else {
+            manifest.description.clone()
+        };
+        let name = manifest.name.clone();
+        catalog.register(name, description, move |settings| start_bot(&manifest, settings));
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn start_bot(manifest: &BotManifest, settings: &ControllerSettings) -> Box<dyn AiController> {
+    match ExternalBot::spawn(manifest, settings.faction) {
+        Ok(bot) => Box::new(bot),
+        Err(e) => {
+            warn!("Couldn't start {}: {}; the Utility AI plays instead", manifest.name, e);
+            Box::new(UtilityAi::new(settings.difficulty, settings.seed))
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn start_bot(_manifest: &BotManifest, settings: &ControllerSettings) -> Box<dyn AiController> {
+    Box::new(UtilityAi::new(settings.difficulty, settings.seed))
+}
+
+/// Read bot manifests from the `bots` directory (native builds only)
+#[cfg(not(target_arch = "wasm32"))]
+fn load_bot_manifests() -> Vec<BotManifest> {
+    use std::fs;
+    use std::path::Path;
+
+    let Ok(entries) = fs::read_dir(Path::new("bots")) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<_> = entries.flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+        .collect();
+    paths.sort();
+
+    paths.iter()
+        .filter_map(|path| {
+            let content = fs::read_to_string(path).ok()?;
+            match ron::from_str::<BotManifest>(&content) {
+                Ok(manifest) => Some(manifest),
+                Err(e) => {
+                    warn!("Failed to parse {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// No external processes on WASM
+#[cfg(target_arch = "wasm32")]
+fn load_bot_manifests() -> Vec<BotManifest> {
+    Vec::new()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::ExternalBot;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use bevy::prelude::*;
+    use bevy::platform::time::Instant;
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Child, ChildStdin, Command, Stdio};
+    use std::sync::mpsc::{self, Receiver, TryRecvError};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use super::{BotManifest, GameMessage, RejectedCommand, parse_bot_reply, BOT_PROTOCOL_VERSION};
+    use crate::game::{Faction, AiController, BattleSnapshot, BotCommand, GameData, ObjectId, legal_commands};
+
+    /// How long a bot may think before its turn is ended for it
+    const BOT_REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// A controller running in another process
+    pub struct ExternalBot {
+        name: String,
+        child: Child,
+        stdin: ChildStdin,
+        /// Lines the bot has written, read on a background thread
+        replies: Mutex<Receiver<String>>,
+        /// When the outstanding `turn` message was sent
+        awaiting: Option<Instant>,
+        /// Replies to timed-out requests, discarded when they turn up
+        stale_replies: usize,
+        rejected: Vec<RejectedCommand>,
+        /// Game ids of the objects the bot knows, indexed by the id the bot uses for them
+        ids: Vec<ObjectId>,
+        /// The process has exited or stopped listening
+        gone: bool,
+    }
+
+    impl ExternalBot {
+        pub fn spawn(manifest: &BotManifest, faction: Faction) -> Result<Self, String> {
+            let mut child = Command::new(&manifest.command)
+                .args(&manifest.args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::inherit())
+                .spawn()
+                .map_err(|e| e.to_string())?;
+            let stdin = child.stdin.take().ok_or("No stdin")?;
+            let stdout = child.stdout.take().ok_or("No stdout")?;
+
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if !line.trim().is_empty() && sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let mut bot = Self {
+                name: manifest.name.clone(),
+                child,
+                stdin,
+                replies: Mutex::new(receiver),
+                awaiting: None,
+                stale_replies: 0,
+                rejected: Vec::new(),
+                ids: Vec::new(),
+                gone: false,
+            };
+            bot.send(&GameMessage::Hello { protocol: BOT_PROTOCOL_VERSION, faction })?;
+            info!("Started external bot {} for {:?}", bot.name, faction);
+            Ok(bot)
+        }
+
+        fn send(&mut self, message: &GameMessage) -> Result<(), String> {
+            let line = serde_json::to_string(message).map_err(|e| e.to_string())?;
+            writeln!(self.stdin, "{}", line)
+                .and_then(|_| self.stdin.flush())
+                .map_err(|e| e.to_string())
+        }
+
+        /// Mark the bot as unusable; every later request just ends the turn
+        fn lost(&mut self, reason: &str) -> Vec<BotCommand> {
+            if !self.gone {
+                warn!("External bot {} {}; ending its turns from now on", self.name, reason);
+                self.gone = true;
+            }
+            vec![BotCommand::EndTurn]
+        }
+
+        /// The bot's id for a game object, handing out the next one the first time it's seen
+        fn bot_id(&mut self, id: ObjectId) -> ObjectId {
+            let index = self.ids.iter().position(|&known| known == id).unwrap_or_else(|| {
+                self.ids.push(id);
+                self.ids.len() - 1
+            });
+            index as ObjectId
+        }
+
+        /// The game id behind one of the bot's ids
+        fn game_id(&self, id: ObjectId) -> Option<ObjectId> {
+            usize::try_from(id).ok().and_then(|i| self.ids.get(i)).copied()
+        }
+
+        /// Turn the bot's commands into the game's, rejecting any that name an unknown object
+        fn resolve_commands(&mut self, commands: Vec<BotCommand>) -> Vec<BotCommand> {
+            let mut resolved = Vec::with_capacity(commands.len());
+            for command in commands {
+                match command.map_ids(|id| self.game_id(id)) {
+                    Some(command) => resolved.push(command),
+                    None => self.rejected.push(RejectedCommand { command, reason: "Unknown id".to_string() }),
+                }
+            }
+            resolved
+        }
+
+        fn try_recv(&mut self) -> Result<String, TryRecvError> {
+            let replies = self.replies.get_mut().map_err(|_| TryRecvError::Disconnected)?;
+            loop {
+                let line = replies.try_recv()?;
+                if self.stale_replies == 0 {
+                    return Ok(line);
+                }
+                self.stale_replies -= 1;
+            }
+        }
+    }
+
+    impl AiController for ExternalBot {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn next_commands(&mut self, snapshot: &BattleSnapshot, game_data: &GameData) -> Vec<BotCommand> {
+            if self.gone {
+                return vec![BotCommand::EndTurn];
+            }
+
+            let Some(sent) = self.awaiting else {
+                let rejected = std::mem::take(&mut self.rejected);
+                let legal: Vec<BotCommand> = legal_commands(snapshot, game_data).iter()
+                    .filter_map(|command| command.map_ids(|id| Some(self.bot_id(id))))
+                    .collect();
+                let snapshot = snapshot.map_ids(|id| self.bot_id(id));
+                let message = GameMessage::Turn {
+                    snapshot: &snapshot,
+                    legal,
+                    rejected: &rejected,
+                };
+                if let Err(e) = self.send(&message) {
+                    return self.lost(&format!("stopped listening ({})", e));
+                }
+                self.awaiting = Some(Instant::now());
+                return Vec::new();
+            };
+
+            match self.try_recv() {
+                Ok(line) => {
+                    self.awaiting = None;
+                    match parse_bot_reply(&line) {
+                        Ok(commands) => self.resolve_commands(commands),
+                        Err(e) => {
+                            warn!("External bot {} sent an unreadable reply ({}): {}", self.name, e, line);
+                            vec![BotCommand::EndTurn]
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) if sent.elapsed() < BOT_REPLY_TIMEOUT => Vec::new(),
+                Err(TryRecvError::Empty) => {
+                    warn!("External bot {} took longer than {:?}; ending its turn", self.name, BOT_REPLY_TIMEOUT);
+                    self.awaiting = None;
+                    self.stale_replies += 1;
+                    vec![BotCommand::EndTurn]
+                }
+                Err(TryRecvError::Disconnected) => self.lost("exited"),
+            }
+        }
+
+        fn command_rejected(&mut self, command: &BotCommand, reason: &str) {
+            let Some(command) = command.map_ids(|id| Some(self.bot_id(id))) else { return };
+            self.rejected.push(RejectedCommand { command, reason: reason.to_string() });
+        }
+
+        fn always_fogged(&self) -> bool {
+            true
+        }
+    }
+
+    impl Drop for ExternalBot {
+        fn drop(&mut self) {
+            let _ = self.send(&GameMessage::Quit);
+            // Give the bot a moment to exit on its own before it's killed
+            let deadline = Instant::now() + Duration::from_millis(200);
+            while Instant::now() < deadline {
+                if matches!(self.child.try_wait(), Ok(Some(_))) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use super::{Faction, FactionMember, Unit, GridPosition, GameMap, Tile, Terrain, TurnState, Commanders, Weather, TerrainFeature};
+use super::{Faction, FactionMember, Unit, GridPosition, GameMap, Tile, Terrain, TurnState, Commanders, Weather, TerrainFeature, GameData, AiControllers, AiState};
 
 pub struct FogPlugin;
 
@@ -9,6 +9,7 @@ impl Plugin for FogPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<FogOfWar>()
             .add_systems(Update, (
+                follow_human_turns,
                 update_fog_of_war,
                 apply_fog_to_tiles,
                 apply_fog_to_features,
@@ -29,15 +30,24 @@ pub enum TileVisibility {
     Visible,
 }
 
+/// Tiles per faction, in the form saves store them
+pub type SavedTiles = HashMap<Faction, Vec<(i32, i32)>>;
+
 /// Resource tracking fog of war state
 #[derive(Resource)]
 pub struct FogOfWar {
     /// Whether fog of war is enabled
     pub enabled: bool,
-    /// Visibility state for each tile (x, y) for the player (Eastern faction)
-    visibility: HashSet<(i32, i32)>,
-    /// Tiles that have been explored (seen at least once)
-    explored: HashSet<(i32, i32)>,
+    /// Faction whose view is rendered on this screen
+    pub viewer: Faction,
+    /// Tiles (x, y) each faction can currently see
+    visibility: HashMap<Faction, HashSet<(i32, i32)>>,
+    /// Tiles each faction has explored (seen at least once)
+    explored: HashMap<Faction, HashSet<(i32, i32)>>,
+    /// Tiles each faction sees for the rest of the battle, whatever its units see
+    revealed: HashMap<Faction, HashSet<(i32, i32)>>,
+    /// The player's own setting while a map's rules force fog on or off
+    player_setting: Option<bool>,
     /// Map dimensions for bounds checking
     width: u32,
     height: u32,
@@ -47,8 +57,11 @@ impl Default for FogOfWar {
     fn default() -> Self {
         Self {
             enabled: false,  // Disabled by default for easier testing
-            visibility: HashSet::new(),
-            explored: HashSet::new(),
+            viewer: Faction::Eastern,
+            visibility: HashMap::new(),
+            explored: HashMap::new(),
+            revealed: HashMap::new(),
+            player_setting: None,
             width: 0,
             height: 0,
         }
@@ -56,31 +69,80 @@ impl Default for FogOfWar {
 }
 
 impl FogOfWar {
-    /// Get visibility state for a tile
+    /// Get visibility state for a tile from the local viewer's perspective
     pub fn get_visibility(&self, x: i32, y: i32) -> TileVisibility {
+        self.visibility_for(self.viewer, x, y)
+    }
+
+    /// Get visibility state for a tile from a faction's perspective
+    pub fn visibility_for(&self, faction: Faction, x: i32, y: i32) -> TileVisibility {
         if !self.enabled {
             return TileVisibility::Visible;
         }
 
-        if self.visibility.contains(&(x, y)) {
+        if self.visible_to(faction, x, y) {
             TileVisibility::Visible
-        } else if self.explored.contains(&(x, y)) {
+        } else if self.explored_by(faction, x, y) {
             TileVisibility::Fogged
         } else {
             TileVisibility::Unexplored
         }
     }
 
-    /// Check if a position is currently visible
+    /// Check if a position is currently visible to the local viewer
     #[allow(dead_code)]
     pub fn is_visible(&self, x: i32, y: i32) -> bool {
-        !self.enabled || self.visibility.contains(&(x, y))
+        self.visible_to(self.viewer, x, y)
+    }
+
+    /// Check if a faction can currently see a position
+    pub fn visible_to(&self, faction: Faction, x: i32, y: i32) -> bool {
+        !self.enabled || self.visibility.get(&faction).is_some_and(|v| v.contains(&(x, y)))
+    }
+
+    /// Every tile a faction can currently see
+    pub fn visible_tiles(&self, faction: Faction) -> HashSet<(i32, i32)> {
+        self.visibility.get(&faction).cloned().unwrap_or_default()
+    }
+
+    /// Check if a faction has ever seen a position
+    pub fn explored_by(&self, faction: Faction, x: i32, y: i32) -> bool {
+        !self.enabled || self.explored.get(&faction).is_some_and(|e| e.contains(&(x, y)))
+    }
+
+    /// Check if a tile is hidden from a faction's view
+    pub fn is_hidden_from(&self, viewer: Faction, x: i32, y: i32) -> bool {
+        !self.visible_to(viewer, x, y)
+    }
+
+    /// Mark a tile as explored by a faction (for CO powers that reveal the map)
+    pub fn mark_explored(&mut self, faction: Faction, x: i32, y: i32) {
+        self.explored.entry(faction).or_default().insert((x, y));
+        self.visibility.entry(faction).or_default().insert((x, y));  // Also make currently visible
+    }
+
+    /// Keep a tile visible to a faction for the rest of the battle (for map triggers)
+    pub fn reveal(&mut self, faction: Faction, x: i32, y: i32) {
+        self.revealed.entry(faction).or_default().insert((x, y));
+        self.mark_explored(faction, x, y);
+    }
+
+    /// Forget tiles revealed by an earlier battle
+    pub fn clear_revealed(&mut self) {
+        self.revealed.clear();
     }
 
-    /// Mark a tile as explored (for CO powers that reveal the map)
-    pub fn mark_explored(&mut self, x: i32, y: i32) {
-        self.explored.insert((x, y));
-        self.visibility.insert((x, y));  // Also make currently visible
+    /// Turn fog on or off for one battle, keeping the player's setting to go back to
+    pub fn force(&mut self, enabled: bool) {
+        self.player_setting.get_or_insert(self.enabled);
+        self.enabled = enabled;
+    }
+
+    /// Go back to the player's setting after a battle that forced fog
+    pub fn release(&mut self) {
+        if let Some(enabled) = self.player_setting.take() {
+            self.enabled = enabled;
+        }
     }
 
     /// Clear current visibility (called at start of turn)
@@ -88,8 +150,28 @@ impl FogOfWar {
         self.visibility.clear();
     }
 
+    /// Snapshot of (visible, explored, revealed) tiles per faction for saving
+    pub fn snapshot(&self) -> (SavedTiles, SavedTiles, SavedTiles) {
+        let to_vecs = |sets: &HashMap<Faction, HashSet<(i32, i32)>>| {
+            sets.iter().map(|(f, tiles)| (*f, tiles.iter().copied().collect())).collect()
+        };
+        (to_vecs(&self.visibility), to_vecs(&self.explored), to_vecs(&self.revealed))
+    }
+
+    /// Restore per-faction visibility from a save
+    pub fn restore(&mut self, visibility: SavedTiles, explored: SavedTiles, revealed: SavedTiles) {
+        let to_sets = |vecs: SavedTiles| {
+            vecs.into_iter().map(|(f, tiles)| (f, tiles.into_iter().collect())).collect()
+        };
+        self.visibility = to_sets(visibility);
+        self.explored = to_sets(explored);
+        self.revealed = to_sets(revealed);
+    }
+
     /// Add visibility around a position with given range
-    fn add_vision(&mut self, x: i32, y: i32, range: u32, map: &GameMap) {
+    /// Sight is blocked by terrain along the way, and concealing terrain
+    /// (thickets, tall grass) can only be seen into from an adjacent tile
+    fn add_vision(&mut self, faction: Faction, x: i32, y: i32, range: u32, map: &GameMap, game_data: &GameData) {
         let range = range as i32;
         for dx in -range..=range {
             for dy in -range..=range {
@@ -98,11 +180,16 @@ impl FogOfWar {
                     let tx = x + dx;
                     let ty = y + dy;
                     if tx >= 0 && tx < map.width as i32 && ty >= 0 && ty < map.height as i32 {
-                        // Check if vision is blocked by terrain (forests reduce vision)
-                        let can_see = self.check_line_of_sight(x, y, tx, ty, map, range as u32);
-                        if can_see {
-                            self.visibility.insert((tx, ty));
-                            self.explored.insert((tx, ty));
+                        if !self.check_line_of_sight(x, y, tx, ty, map, game_data) {
+                            continue;
+                        }
+
+                        // Concealed tiles show their terrain but not who is hiding there
+                        self.explored.entry(faction).or_default().insert((tx, ty));
+                        let concealed = dist > 1 && map.get(tx, ty)
+                            .is_some_and(|t| game_data.terrain_conceals(t));
+                        if !concealed {
+                            self.visibility.entry(faction).or_default().insert((tx, ty));
                         }
                     }
                 }
@@ -110,15 +197,20 @@ impl FogOfWar {
         }
     }
 
-    /// Simple line of sight check - forests block vision beyond them
-    fn check_line_of_sight(&self, _x1: i32, _y1: i32, x2: i32, y2: i32, map: &GameMap, _range: u32) -> bool {
-        // Simplified: just check if the target tile is within range
-        // In a full implementation, you'd trace a line and check for blocking terrain
-
-        // For now, forests reduce vision by 1 (you can see into a forest but not through it easily)
-        if let Some(_terrain) = map.get(x2, y2) {
-            // Can always see into any terrain directly
-            return true;
+    /// Trace a line between two tiles and check no sight-blocking terrain lies between them
+    /// The end tiles never block - you can see into (but not past) a thicket
+    fn check_line_of_sight(&self, x1: i32, y1: i32, x2: i32, y2: i32, map: &GameMap, game_data: &GameData) -> bool {
+        let steps = (x2 - x1).abs().max((y2 - y1).abs()) * 2;
+        for i in 1..steps {
+            let t = i as f32 / steps as f32;
+            let x = (x1 as f32 + (x2 - x1) as f32 * t).round() as i32;
+            let y = (y1 as f32 + (y2 - y1) as f32 * t).round() as i32;
+            if (x, y) == (x1, y1) || (x, y) == (x2, y2) {
+                continue;
+            }
+            if map.get(x, y).is_some_and(|terrain| game_data.terrain_blocks_sight(terrain)) {
+                return false;
+            }
         }
         true
     }
@@ -128,6 +220,57 @@ impl FogOfWar {
         self.width = width;
         self.height = height;
     }
+
+    /// Recompute what every faction can see
+    /// `units` gives each unit's faction, position and vision before terrain and weather
+    pub fn recompute<'a>(
+        &mut self,
+        map: &GameMap,
+        units: impl IntoIterator<Item = (Faction, (i32, i32), u32)>,
+        tiles: impl IntoIterator<Item = &'a Tile>,
+        weather: &Weather,
+        game_data: &GameData,
+    ) {
+        // Update dimensions if needed
+        if self.width != map.width || self.height != map.height {
+            self.set_dimensions(map.width, map.height);
+        }
+
+        // Clear current visibility
+        self.clear_visibility();
+
+        // Tiles revealed by map triggers stay in sight
+        for (faction, tiles) in &self.revealed {
+            self.visibility.entry(*faction).or_default().extend(tiles.iter().copied());
+            self.explored.entry(*faction).or_default().extend(tiles.iter().copied());
+        }
+
+        // Add vision from every faction's units
+        for (faction, (x, y), base_vision) in units {
+            // Vantage points (boulders, hollows) extend vision
+            let vantage = map.get(x, y).map_or(0, |terrain| game_data.terrain_vantage(terrain));
+
+            // Apply weather effects to vision
+            let vision = weather.apply_vision(base_vision + vantage);
+            self.add_vision(faction, x, y, vision, map, game_data);
+        }
+
+        // Owned properties also provide vision (like in Advance Wars)
+        for tile in tiles {
+            let Some(owner) = tile.owner else { continue };
+            if tile.terrain.is_capturable() {
+                // Bases see furthest, storehouses least
+                let base_vision = match tile.terrain {
+                    Terrain::Base | Terrain::HQ => 3,
+                    Terrain::Outpost => 2,
+                    Terrain::Storehouse => 1,
+                    _ => 1,
+                };
+                let vision = weather.apply_vision(base_vision);
+                self.add_vision(owner, tile.position.x, tile.position.y, vision, map, game_data);
+            }
+        }
+    }
 }
 
 /// Component to track visibility for rendering (for future fog overlay sprites)
@@ -135,7 +278,24 @@ impl FogOfWar {
 #[allow(dead_code)]
 pub struct FogOverlay;
 
-/// System to update fog of war based on player unit positions
+/// Hot-seat: whenever a human side takes its turn, render the fog from that side's point of view
+fn follow_human_turns(
+    mut fog: ResMut<FogOfWar>,
+    turn_state: Res<TurnState>,
+    controllers: Res<AiControllers>,
+    ai_state: Res<AiState>,
+) {
+    if !turn_state.is_changed() {
+        return;
+    }
+    let faction = turn_state.current_faction;
+    let human = !ai_state.enabled || !controllers.controls(faction);
+    if human && fog.viewer != faction {
+        fog.viewer = faction;
+    }
+}
+
+/// System to update every faction's fog of war from its units and properties
 fn update_fog_of_war(
     mut fog: ResMut<FogOfWar>,
     map: Res<GameMap>,
@@ -144,57 +304,17 @@ fn update_fog_of_war(
     tiles: Query<&Tile>,
     commanders: Res<Commanders>,
     weather: Res<Weather>,
+    game_data: Res<GameData>,
 ) {
     if !fog.enabled {
         return;
     }
 
-    // Update dimensions if needed
-    if fog.width != map.width || fog.height != map.height {
-        fog.set_dimensions(map.width, map.height);
-    }
-
-    // Clear current visibility
-    fog.clear_visibility();
-
-    // Get CO vision bonus for player faction
-    let co_bonuses = commanders.get_bonuses(Faction::Eastern);
-
-    // Add vision from all player (Eastern) units
-    for (pos, unit, faction) in units.iter() {
-        if faction.faction == Faction::Eastern {
-            let stats = unit.unit_type.stats();
-            let mut base_vision = stats.vision + co_bonuses.vision;
-
-            // Terrain affects vision - boulders provide height advantage
-            if let Some(terrain) = map.get(pos.x, pos.y) {
-                match terrain {
-                    Terrain::Boulder => base_vision += 1, // Height advantage
-                    Terrain::Hollow => base_vision += 1, // Elevated position
-                    _ => {}
-                }
-            }
-
-            // Apply weather effects to vision
-            let vision = weather.apply_vision(base_vision);
-            fog.add_vision(pos.x, pos.y, vision, &map);
-        }
-    }
-
-    // Properties we own also provide vision (like in Advance Wars)
-    for tile in tiles.iter() {
-        if tile.owner == Some(Faction::Eastern) && tile.terrain.is_capturable() {
-            // Bases and outposts provide 2 vision
-            let base_vision = match tile.terrain {
-                Terrain::Base => 3,
-                Terrain::Outpost => 2,
-                Terrain::Storehouse => 1,
-                _ => 1,
-            };
-            let vision = weather.apply_vision(base_vision);
-            fog.add_vision(tile.position.x, tile.position.y, vision, &map);
-        }
-    }
+    let unit_vision = units.iter().map(|(pos, unit, faction)| {
+        let co_bonuses = commanders.get_bonuses(faction.faction);
+        (faction.faction, (pos.x, pos.y), unit.unit_type.stats().vision + co_bonuses.vision)
+    });
+    fog.recompute(&map, unit_vision, tiles.iter(), &weather, &game_data);
 }
 
 /// Apply fog visual effect to tiles
@@ -308,8 +428,8 @@ fn apply_fog_to_units(
     }
 
     for (pos, faction, mut vis, children) in units.iter_mut() {
-        // Player units are always visible to the player
-        if faction.faction == Faction::Eastern {
+        // The viewer's own units are always visible
+        if faction.faction == fog.viewer {
             *vis = Visibility::Visible;
             for child in children.iter() {
                 if let Ok(mut child_vis) = child_visibility.get_mut(child) {
@@ -0,0 +1,1158 @@
+//! Headless battle environment for reinforcement learning
+//!
+//! `BattleEnv` plays a battle in plain Rust, without the ECS, behind a gym-style API:
+//! `reset` starts an episode and `step` takes one action index. Observations are
+//! fixed-shape per-tile feature planes and come with a mask of the legal actions.
+//!
+//! Rules follow the full game (movement, stamina, ammo, counters, captures, joins,
+//! transports, income and base repairs), except that there are no CO powers, the weather
+//! stays clear and structures can't be attacked. Luck comes from the env's own seeded
+//! RNG, so an episode replays exactly from its seed.
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    Faction, FactionMember, Unit, UnitType, CargoUnit, GridPosition, GameMap, Tile, Terrain, MapData,
+    GameData, Weather, FogOfWar, CoBonuses, AiController, AiDifficulty, UtilityAi, BattleSnapshot,
+    SnapshotSource, BotCommand, ObjectId, AttackReport, calculate_damage_with_luck,
+    calculate_attack_targets, calculate_movement_range, calculate_movement_range_with_costs,
+    effective_movement, validate_command, route_from_costs, trap_stop, MapId, get_builtin_map,
+    MAX_COMMANDS_PER_TURN,
+};
+
+/// The two sides of an env battle, in turn order
+const SIDES: [Faction; 2] = [Faction::Eastern, Faction::Northern];
+
+/// Times an opponent may answer "still thinking" in a row before its turn is ended
+const MAX_IDLE_POLLS: usize = 10_000;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Who plays the side the agent doesn't
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvOpponent {
+    /// The agent plays both sides; observations and rewards follow the side to move
+    SelfPlay,
+    /// Ends every turn without acting
+    Passive,
+    /// The built-in Utility AI
+    Utility(AiDifficulty),
+}
+
+/// How `step` turns the battle into a reward
+#[derive(Debug, Clone, Copy)]
+pub struct RewardShaping {
+    pub win: f32,
+    pub loss: f32,
+    /// Given to both sides when the turn limit runs out
+    pub draw: f32,
+    /// Per property owned beyond the opponent's count
+    pub property_weight: f32,
+    /// Per 1000 funds of unit value (cost scaled by HP) beyond the opponent's
+    pub unit_value_weight: f32,
+    /// Added when the action is masked out; the state doesn't change
+    pub illegal_action: f32,
+}
+
+impl Default for RewardShaping {
+    fn default() -> Self {
+        Self {
+            win: 1.0,
+            loss: -1.0,
+            draw: 0.0,
+            property_weight: 0.02,
+            unit_value_weight: 0.05,
+            illegal_action: -0.01,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnvConfig {
+    /// Side the agent plays (its first side in self-play)
+    pub agent: Faction,
+    pub opponent: EnvOpponent,
+    /// Hide enemy units outside each side's vision
+    pub fog: bool,
+    /// Days before the episode ends in a draw
+    pub max_turns: u32,
+    pub starting_funds: u32,
+    pub reward: RewardShaping,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            agent: Faction::Eastern,
+            opponent: EnvOpponent::Utility(AiDifficulty::Cub),
+            fog: false,
+            max_turns: 40,
+            starting_funds: 100,
+            reward: RewardShaping::default(),
+        }
+    }
+}
+
+// ============================================================================
+// ACTIONS AND OBSERVATIONS
+// ============================================================================
+
+/// Unload directions, in action order: north, east, south, west
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// One step of a turn. Units are ordered in three steps (select, move, act) so the
+/// action space stays a fixed size whatever the map holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvAction {
+    /// Pick a ready unit, or an empty owned base to build from
+    Select(i32, i32),
+    /// Move the selected unit; its own tile stays put, a friendly unit's tile boards or joins it
+    MoveTo(i32, i32),
+    /// Attack the unit on a tile
+    Attack(i32, i32),
+    Capture,
+    Resupply,
+    /// Drop cargo in one of `DIRECTIONS`
+    Unload(usize),
+    /// Finish the move without acting
+    Wait,
+    /// Drop the selection (and any move not yet carried out)
+    Cancel,
+    EndTurn,
+    Build(UnitType),
+}
+
+/// What one side sees, as `channels` planes of `height × width` floats.
+///
+/// Planes, in order: terrain one-hot; property owned by us / by the enemy / neutral;
+/// capture progress; our unit / enemy unit; unit type one-hot; HP, ammo and stamina as
+/// fractions of max; unit can still act; unit carries cargo; tile is visible.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    /// Side whose point of view this is
+    pub faction: Faction,
+    pub width: u32,
+    pub height: u32,
+    pub channels: usize,
+    pub planes: Vec<f32>,
+    /// Legal actions for the side to move, indexed like `BattleEnv::decode`
+    pub action_mask: Vec<bool>,
+    pub funds: u32,
+    pub turn: u32,
+}
+
+impl Observation {
+    /// Number of feature planes
+    pub fn channel_count() -> usize {
+        Terrain::all().len() + 4 + 2 + UnitType::all().len() + 6
+    }
+
+    pub fn get(&self, channel: usize, x: i32, y: i32) -> f32 {
+        self.planes[self.index(channel, x, y)]
+    }
+
+    fn index(&self, channel: usize, x: i32, y: i32) -> usize {
+        (channel * self.height as usize + y as usize) * self.width as usize + x as usize
+    }
+
+    fn set(&mut self, channel: usize, x: i32, y: i32, value: f32) {
+        let i = self.index(channel, x, y);
+        self.planes[i] = value;
+    }
+}
+
+// ============================================================================
+// ENVIRONMENT
+// ============================================================================
+
+struct EnvUnit {
+    id: ObjectId,
+    faction: Faction,
+    pos: GridPosition,
+    unit: Unit,
+}
+
+/// Where the side to move is within its current order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Idle,
+    Selected { unit: ObjectId },
+    Moved { unit: ObjectId, to: (i32, i32) },
+    Producing { x: i32, y: i32 },
+}
+
+/// A battle that runs without rendering, stepped one action at a time
+pub struct BattleEnv {
+    game_data: GameData,
+    config: EnvConfig,
+    map: GameMap,
+    /// Capturable tiles, with their ids
+    properties: Vec<(ObjectId, Tile)>,
+    units: Vec<EnvUnit>,
+    funds: HashMap<Faction, u32>,
+    current: Faction,
+    turn: u32,
+    stage: Stage,
+    next_id: u64,
+    rng: StdRng,
+    weather: Weather,
+    fog: FogOfWar,
+    /// Units the map forbids building
+    banned_units: Vec<UnitType>,
+    opponent: Option<Box<dyn AiController>>,
+    /// Attacks the opponent controller hasn't been told about yet
+    opponent_inbox: Vec<AttackReport>,
+    mask: Vec<bool>,
+    /// Snapshot the mask was worked out from, reused by the next action since nothing has changed since
+    mask_snapshot: Option<BattleSnapshot>,
+    winner: Option<Faction>,
+    done: bool,
+}
+
+impl Default for BattleEnv {
+    fn default() -> Self {
+        Self::new(GameData::default())
+    }
+}
+
+impl BattleEnv {
+    pub fn new(game_data: GameData) -> Self {
+        Self {
+            game_data,
+            config: EnvConfig::default(),
+            map: GameMap::default(),
+            properties: Vec::new(),
+            units: Vec::new(),
+            funds: HashMap::new(),
+            current: SIDES[0],
+            turn: 1,
+            stage: Stage::Idle,
+            next_id: 0,
+            rng: StdRng::seed_from_u64(0),
+            weather: Weather::default(),
+            fog: FogOfWar::default(),
+            banned_units: Vec::new(),
+            opponent: None,
+            opponent_inbox: Vec::new(),
+            mask: Vec::new(),
+            mask_snapshot: None,
+            winner: None,
+            done: true,
+        }
+    }
+
+    /// Start a new episode. If the opponent moves first, its turn is played before this returns.
+    pub fn reset(&mut self, map_data: &MapData, seed: u64, config: EnvConfig) -> Observation {
+        self.config = config;
+        self.map = GameMap::new(map_data.width, map_data.height);
+        self.map.tiles = map_data.terrain.clone();
+        self.rng = StdRng::seed_from_u64(seed);
+        self.next_id = 0;
+        self.units.clear();
+        self.properties.clear();
+        self.opponent_inbox.clear();
+
+        let owners: HashMap<(i32, i32), Faction> = map_data.properties.iter()
+            .map(|p| ((p.x, p.y), p.owner))
+            .collect();
+        for y in 0..self.map.height as i32 {
+            for x in 0..self.map.width as i32 {
+                let Some(terrain) = self.map.get(x, y) else { continue };
+                if !terrain.is_capturable() {
+                    continue;
+                }
+                let tile = Tile {
+                    terrain,
+                    position: IVec2::new(x, y),
+                    owner: owners.get(&(x, y)).copied(),
+                    capture_progress: 0,
+                    capturing_faction: None,
+                    damage: 0,
+                };
+                let id = self.new_id();
+                self.properties.push((id, tile));
+            }
+        }
+        for placement in &map_data.units {
+            let id = self.new_id();
+            self.units.push(EnvUnit {
+                id,
+                faction: placement.faction,
+                pos: GridPosition::new(placement.x, placement.y),
+                unit: Unit::new(placement.unit_type),
+            });
+        }
+
+        self.funds = SIDES.iter().map(|&f| (f, config.starting_funds)).collect();
+        self.fog = FogOfWar::default();
+        self.fog.enabled = config.fog;
+        self.banned_units = map_data.rules.banned_units.clone();
+        self.opponent = match config.opponent {
+            EnvOpponent::Utility(difficulty) => Some(Box::new(UtilityAi::new(difficulty, seed))),
+            EnvOpponent::SelfPlay | EnvOpponent::Passive => None,
+        };
+        self.current = SIDES[0];
+        self.turn = 1;
+        self.stage = Stage::Idle;
+        self.winner = None;
+        self.done = false;
+
+        self.start_turn();
+        self.refresh_fog();
+        if self.opponent_to_move() {
+            self.play_opponent_turn();
+        }
+        self.refresh_mask();
+        self.observe()
+    }
+
+    /// Take one action by index. Returns the next observation, the reward for the side that
+    /// acted, and whether the episode is over.
+    pub fn step(&mut self, action: usize) -> (Observation, f32, bool) {
+        if self.done {
+            return (self.observe(), 0.0, true);
+        }
+
+        let side = self.current;
+        let before = self.shaped_score(side);
+        let mut reward = 0.0;
+
+        if self.mask.get(action).copied().unwrap_or(false) {
+            if let Some(action) = self.decode(action) {
+                self.apply_action(action);
+            }
+        } else {
+            reward += self.config.reward.illegal_action;
+        }
+
+        reward += self.shaped_score(side) - before;
+        if self.done {
+            let shaping = &self.config.reward;
+            reward += match self.winner {
+                Some(winner) if winner == side => shaping.win,
+                Some(_) => shaping.loss,
+                None => shaping.draw,
+            };
+        }
+
+        self.refresh_mask();
+        (self.observe(), reward, self.done)
+    }
+
+    /// Legal actions for the side to move
+    pub fn action_mask(&self) -> &[bool] {
+        &self.mask
+    }
+
+    /// Size of the action space for the current map
+    pub fn action_count(&self) -> usize {
+        3 * self.tile_count() + 9 + UnitType::all().len()
+    }
+
+    pub fn current_faction(&self) -> Faction {
+        self.current
+    }
+
+    pub fn winner(&self) -> Option<Faction> {
+        self.winner
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Turn an action index into an action.
+    ///
+    /// With `N` tiles (row-major): `[0, N)` select, `[N, 2N)` move, `[2N, 3N)` attack,
+    /// then capture, resupply, four unloads, wait, cancel, end turn, and one build per unit type.
+    pub fn decode(&self, index: usize) -> Option<EnvAction> {
+        let n = self.tile_count();
+        let tile = |i: usize| ((i % self.map.width as usize) as i32, (i / self.map.width as usize) as i32);
+        let action = match index {
+            i if i < n => { let (x, y) = tile(i); EnvAction::Select(x, y) }
+            i if i < 2 * n => { let (x, y) = tile(i - n); EnvAction::MoveTo(x, y) }
+            i if i < 3 * n => { let (x, y) = tile(i - 2 * n); EnvAction::Attack(x, y) }
+            i => match i - 3 * n {
+                0 => EnvAction::Capture,
+                1 => EnvAction::Resupply,
+                d @ 2..=5 => EnvAction::Unload(d - 2),
+                6 => EnvAction::Wait,
+                7 => EnvAction::Cancel,
+                8 => EnvAction::EndTurn,
+                k => EnvAction::Build(*UnitType::all().get(k - 9)?),
+            },
+        };
+        Some(action)
+    }
+
+    /// Inverse of `decode`
+    pub fn encode(&self, action: EnvAction) -> usize {
+        let n = self.tile_count();
+        let tile = |x: i32, y: i32| y as usize * self.map.width as usize + x as usize;
+        match action {
+            EnvAction::Select(x, y) => tile(x, y),
+            EnvAction::MoveTo(x, y) => n + tile(x, y),
+            EnvAction::Attack(x, y) => 2 * n + tile(x, y),
+            EnvAction::Capture => 3 * n,
+            EnvAction::Resupply => 3 * n + 1,
+            EnvAction::Unload(dir) => 3 * n + 2 + dir.min(3),
+            EnvAction::Wait => 3 * n + 6,
+            EnvAction::Cancel => 3 * n + 7,
+            EnvAction::EndTurn => 3 * n + 8,
+            EnvAction::Build(unit_type) => {
+                3 * n + 9 + UnitType::all().iter().position(|t| *t == unit_type).unwrap_or(0)
+            }
+        }
+    }
+
+    /// The battle as a controller on `faction`'s side would see it
+    pub fn snapshot(&self, faction: Faction) -> BattleSnapshot {
+        self.snapshot_with(faction, Vec::new())
+    }
+
+    fn snapshot_with(&self, faction: Faction, attacks: Vec<AttackReport>) -> BattleSnapshot {
+        SnapshotSource {
+            faction,
+            turn: self.turn,
+            funds: self.funds.get(&faction).copied().unwrap_or(0),
+            weather: self.weather.current,
+            commander: None,
+            commanders: HashMap::new(),
+            co_bonuses: CoBonuses::none(),
+            power_ready: false,
+            map: &self.map,
+            visible: self.fog.enabled.then(|| self.fog.visible_tiles(faction)),
+            banned_units: self.banned_units.clone(),
+        }
+        .build(
+            self.units.iter().map(|u| (Entity::from_bits(u.id), &u.pos, u.faction, &u.unit)),
+            self.properties.iter().map(|(id, tile)| (Entity::from_bits(*id), tile)),
+            attacks,
+        )
+    }
+
+    /// Feature planes from the point of view of the side to move
+    pub fn observe(&self) -> Observation {
+        let faction = self.current;
+        let channels = Observation::channel_count();
+        let (width, height) = (self.map.width, self.map.height);
+        let mut obs = Observation {
+            faction,
+            width,
+            height,
+            channels,
+            planes: vec![0.0; channels * (width * height) as usize],
+            action_mask: self.mask.clone(),
+            funds: self.funds.get(&faction).copied().unwrap_or(0),
+            turn: self.turn,
+        };
+
+        let terrains = Terrain::all();
+        let property_plane = terrains.len();
+        let unit_plane = property_plane + 4;
+        let type_plane = unit_plane + 2;
+        let stat_plane = type_plane + UnitType::all().len();
+        let visible_plane = stat_plane + 5;
+
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                if let Some(t) = self.map.get(x, y).and_then(|t| terrains.iter().position(|&o| o == t)) {
+                    obs.set(t, x, y, 1.0);
+                }
+                if self.fog.visible_to(faction, x, y) {
+                    obs.set(visible_plane, x, y, 1.0);
+                }
+            }
+        }
+
+        for (_, tile) in &self.properties {
+            let (x, y) = (tile.position.x, tile.position.y);
+            let owner = match tile.owner {
+                Some(owner) if owner == faction => 0,
+                Some(_) => 1,
+                None => 2,
+            };
+            obs.set(property_plane + owner, x, y, 1.0);
+            let required = tile.terrain.capture_points().max(1);
+            obs.set(property_plane + 3, x, y, tile.capture_progress as f32 / required as f32);
+        }
+
+        for unit in &self.units {
+            let (x, y) = (unit.pos.x, unit.pos.y);
+            let own = unit.faction == faction;
+            if !own && !self.fog.visible_to(faction, x, y) {
+                continue;
+            }
+            let stats = unit.unit.unit_type.stats();
+            obs.set(unit_plane + if own { 0 } else { 1 }, x, y, 1.0);
+            if let Some(k) = UnitType::all().iter().position(|&t| t == unit.unit.unit_type) {
+                obs.set(type_plane + k, x, y, 1.0);
+            }
+            obs.set(stat_plane, x, y, unit.unit.hp as f32 / stats.max_hp.max(1) as f32);
+            obs.set(stat_plane + 1, x, y, if stats.max_ammo > 0 { unit.unit.ammo as f32 / stats.max_ammo as f32 } else { 1.0 });
+            obs.set(stat_plane + 2, x, y, unit.unit.stamina as f32 / stats.max_stamina.max(1) as f32);
+            if !unit.unit.exhausted {
+                obs.set(stat_plane + 3, x, y, 1.0);
+            }
+            if unit.unit.has_cargo() {
+                obs.set(stat_plane + 4, x, y, 1.0);
+            }
+        }
+
+        obs
+    }
+
+    fn tile_count(&self) -> usize {
+        (self.map.width * self.map.height) as usize
+    }
+
+    fn new_id(&mut self) -> ObjectId {
+        // A nonzero generation keeps ids valid as `Entity` bits
+        self.next_id += 1;
+        (1 << 32) | self.next_id
+    }
+
+    fn opponent_to_move(&self) -> bool {
+        !self.done && self.config.opponent != EnvOpponent::SelfPlay && self.current != self.config.agent
+    }
+
+    fn unit_index(&self, id: ObjectId) -> Option<usize> {
+        self.units.iter().position(|u| u.id == id)
+    }
+
+    fn unit_at(&self, x: i32, y: i32) -> Option<&EnvUnit> {
+        self.units.iter().find(|u| u.pos.x == x && u.pos.y == y)
+    }
+
+    fn owned_base_at(&self, faction: Faction, x: i32, y: i32) -> bool {
+        self.properties.iter().any(|(_, t)| {
+            t.terrain == Terrain::Base && t.owner == Some(faction) && t.position == IVec2::new(x, y)
+        })
+    }
+
+    // ------------------------------------------------------------------------
+    // Action masks
+    // ------------------------------------------------------------------------
+
+    /// Work out the legal actions, building the step's one snapshot for the side to move
+    fn refresh_mask(&mut self) {
+        if self.done {
+            self.mask = vec![false; self.action_count()];
+            self.mask_snapshot = None;
+            return;
+        }
+        let snapshot = self.snapshot(self.current);
+        self.mask = self.compute_mask(&snapshot);
+        self.mask_snapshot = Some(snapshot);
+    }
+
+    fn compute_mask(&self, snapshot: &BattleSnapshot) -> Vec<bool> {
+        let mut mask = vec![false; self.action_count()];
+        let faction = self.current;
+        let mut allow = |action: EnvAction| mask[self.encode(action)] = true;
+
+        match self.stage {
+            Stage::Idle => {
+                for unit in snapshot.own_units().filter(|u| !u.unit.exhausted) {
+                    allow(EnvAction::Select(unit.x, unit.y));
+                }
+                for property in &snapshot.properties {
+                    let (x, y) = (property.tile.position.x, property.tile.position.y);
+                    if self.buildable(snapshot, x, y).next().is_some() {
+                        allow(EnvAction::Select(x, y));
+                    }
+                }
+                allow(EnvAction::EndTurn);
+            }
+            Stage::Selected { unit } => {
+                let Some(selected) = snapshot.unit(unit) else {
+                    allow(EnvAction::Cancel);
+                    return mask;
+                };
+                allow(EnvAction::MoveTo(selected.x, selected.y));
+                if !selected.unit.moved {
+                    let stats = selected.unit.unit_type.stats();
+                    let blockers: HashMap<(i32, i32), Entity> = snapshot.units.iter()
+                        .filter(|u| u.id != unit)
+                        .map(|u| ((u.x, u.y), Entity::from_bits(u.id)))
+                        .collect();
+                    let movement = effective_movement(stats.movement, selected.unit.stamina);
+                    let reachable = calculate_movement_range(&GridPosition::new(selected.x, selected.y), movement, &snapshot.map, &blockers, stats.class, &self.game_data);
+                    for (x, y) in reachable {
+                        if snapshot.unit_at(x, y).is_none() {
+                            allow(EnvAction::MoveTo(x, y));
+                        }
+                    }
+                }
+                for partner in snapshot.own_units().filter(|p| p.id != unit) {
+                    if self.merge_command(snapshot, unit, (partner.x, partner.y)).is_some() {
+                        allow(EnvAction::MoveTo(partner.x, partner.y));
+                    }
+                }
+                allow(EnvAction::Cancel);
+            }
+            Stage::Moved { unit, to } => {
+                if let Some(selected) = snapshot.unit(unit) {
+                    let stats = selected.unit.unit_type.stats();
+                    if stats.max_ammo == 0 || selected.unit.ammo > 0 {
+                        let others: Vec<(Entity, GridPosition, FactionMember)> = snapshot.units.iter()
+                            .map(|u| (Entity::from_bits(u.id), GridPosition::new(u.x, u.y), FactionMember { faction: u.faction }))
+                            .collect();
+                        let targets = calculate_attack_targets(&selected.unit, &GridPosition::new(to.0, to.1), &FactionMember { faction }, &others, &[], &self.game_data);
+                        for target in snapshot.units.iter().filter(|u| targets.contains(&Entity::from_bits(u.id))) {
+                            allow(EnvAction::Attack(target.x, target.y));
+                        }
+                    }
+                    let check = |command: BotCommand| validate_command(&command, snapshot, &self.game_data).is_ok();
+                    if check(BotCommand::Capture { unit, move_to: to }) {
+                        allow(EnvAction::Capture);
+                    }
+                    if check(BotCommand::Resupply { unit, move_to: to }) {
+                        allow(EnvAction::Resupply);
+                    }
+                    for (dir, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+                        if check(BotCommand::Unload { unit, move_to: to, drop_at: (to.0 + dx, to.1 + dy) }) {
+                            allow(EnvAction::Unload(dir));
+                        }
+                    }
+                    allow(EnvAction::Wait);
+                }
+                allow(EnvAction::Cancel);
+            }
+            Stage::Producing { x, y } => {
+                for unit_type in self.buildable(snapshot, x, y) {
+                    allow(EnvAction::Build(unit_type));
+                }
+                allow(EnvAction::Cancel);
+            }
+        }
+        mask
+    }
+
+    /// Unit types the side to move can build at a tile right now
+    fn buildable<'a>(&'a self, snapshot: &'a BattleSnapshot, x: i32, y: i32) -> impl Iterator<Item = UnitType> + 'a {
+        UnitType::all().iter().copied().filter(move |&unit_type| {
+            validate_command(&BotCommand::Build { x, y, unit_type }, snapshot, &self.game_data).is_ok()
+        })
+    }
+
+    /// Boarding or joining the friendly unit at `at`, if either is allowed
+    fn merge_command(&self, snapshot: &BattleSnapshot, unit: ObjectId, at: (i32, i32)) -> Option<BotCommand> {
+        [BotCommand::Load { unit, move_to: at }, BotCommand::Join { unit, move_to: at }]
+            .into_iter()
+            .find(|c| validate_command(c, snapshot, &self.game_data).is_ok())
+    }
+
+    // ------------------------------------------------------------------------
+    // Stepping
+    // ------------------------------------------------------------------------
+
+    fn apply_action(&mut self, action: EnvAction) {
+        let faction = self.current;
+        let snapshot = self.mask_snapshot.take().unwrap_or_else(|| self.snapshot(faction));
+        let command = match (self.stage, action) {
+            (_, EnvAction::Cancel) => {
+                self.stage = Stage::Idle;
+                return;
+            }
+            (Stage::Idle, EnvAction::EndTurn) => {
+                self.end_turn();
+                while self.opponent_to_move() {
+                    self.play_opponent_turn();
+                }
+                return;
+            }
+            (Stage::Idle, EnvAction::Select(x, y)) => {
+                self.stage = match self.unit_at(x, y) {
+                    Some(unit) => Stage::Selected { unit: unit.id },
+                    None => Stage::Producing { x, y },
+                };
+                return;
+            }
+            (Stage::Selected { unit }, EnvAction::MoveTo(x, y)) => {
+                // Only the units the side can see decide between moving and boarding or joining
+                let occupied = snapshot.unit_at(x, y).is_some_and(|u| u.id != unit);
+                if !occupied {
+                    if self.spring_trap(&snapshot, unit, (x, y)) {
+                        self.stage = Stage::Idle;
+                        self.refresh_fog();
+                    } else {
+                        self.stage = Stage::Moved { unit, to: (x, y) };
+                    }
+                    return;
+                }
+                match self.merge_command(&snapshot, unit, (x, y)) {
+                    Some(command) => command,
+                    None => {
+                        warn!("Env: nothing to board or join at ({}, {})", x, y);
+                        return;
+                    }
+                }
+            }
+            (Stage::Moved { unit, to }, EnvAction::Attack(x, y)) => {
+                let Some(target) = self.unit_at(x, y).map(|u| u.id) else { return };
+                BotCommand::Attack { unit, move_to: to, target }
+            }
+            (Stage::Moved { unit, to }, EnvAction::Capture) => BotCommand::Capture { unit, move_to: to },
+            (Stage::Moved { unit, to }, EnvAction::Resupply) => BotCommand::Resupply { unit, move_to: to },
+            (Stage::Moved { unit, to }, EnvAction::Unload(dir)) => {
+                let (dx, dy) = DIRECTIONS[dir.min(3)];
+                BotCommand::Unload { unit, move_to: to, drop_at: (to.0 + dx, to.1 + dy) }
+            }
+            (Stage::Moved { unit, to }, EnvAction::Wait) => {
+                let here = self.unit_index(unit).map(|i| (self.units[i].pos.x, self.units[i].pos.y));
+                if here == Some(to) { BotCommand::Wait { unit } } else { BotCommand::Move { unit, to } }
+            }
+            (Stage::Producing { x, y }, EnvAction::Build(unit_type)) => BotCommand::Build { x, y, unit_type },
+            _ => return,
+        };
+
+        self.stage = Stage::Idle;
+        if let Err(e) = self.execute_on(&snapshot, faction, &command) {
+            warn!("Env rejected a masked action {:?}: {}", command, e);
+        }
+    }
+
+    /// Validate a command for `faction` and carry it out
+    fn execute(&mut self, faction: Faction, command: &BotCommand) -> Result<(), String> {
+        let snapshot = self.snapshot(faction);
+        self.execute_on(&snapshot, faction, command)
+    }
+
+    /// Validate a command against a snapshot of the current state and carry it out
+    fn execute_on(&mut self, snapshot: &BattleSnapshot, faction: Faction, command: &BotCommand) -> Result<(), String> {
+        validate_command(command, snapshot, &self.game_data)?;
+
+        if let (Some(unit), Some(to)) = (command.unit(), command.destination()) {
+            if self.spring_trap(snapshot, unit, to) {
+                self.refresh_fog();
+                return Ok(());
+            }
+        }
+
+        match *command {
+            BotCommand::EndTurn | BotCommand::ActivatePower => {}
+            BotCommand::Build { x, y, unit_type } => {
+                let funds = self.funds.entry(faction).or_default();
+                *funds = funds.saturating_sub(self.game_data.build_cost(unit_type, faction, &CoBonuses::none()));
+                let id = self.new_id();
+                self.units.push(EnvUnit { id, faction, pos: GridPosition::new(x, y), unit: Unit::new(unit_type) });
+            }
+            BotCommand::Wait { unit } => {
+                if let Some(i) = self.unit_index(unit) {
+                    self.units[i].unit.exhausted = true;
+                }
+            }
+            BotCommand::Move { unit, to } => {
+                let i = self.move_unit(unit, to, false)?;
+                self.units[i].unit.exhausted = true;
+            }
+            BotCommand::Attack { unit, move_to, target } => {
+                self.move_unit(unit, move_to, false)?;
+                self.resolve_attack(unit, target);
+            }
+            BotCommand::Capture { unit, move_to } => {
+                let i = self.move_unit(unit, move_to, false)?;
+                self.units[i].unit.exhausted = true;
+                let power = self.units[i].unit.hp;
+                if let Some((_, tile)) = self.properties.iter_mut().find(|(_, t)| t.position == IVec2::new(move_to.0, move_to.1)) {
+                    if tile.capturing_faction != Some(faction) {
+                        tile.capture_progress = 0;
+                        tile.capturing_faction = Some(faction);
+                    }
+                    tile.capture_progress += power;
+                    if tile.capture_progress >= tile.terrain.capture_points() {
+                        tile.owner = Some(faction);
+                        tile.capture_progress = 0;
+                        tile.capturing_faction = None;
+                    }
+                }
+            }
+            BotCommand::Load { unit, move_to } => {
+                let transport = self.unit_at(move_to.0, move_to.1).map(|u| u.id).ok_or("No transport")?;
+                let i = self.move_unit(unit, move_to, true)?;
+                let passenger = self.units.remove(i);
+                let t = self.unit_index(transport).ok_or("No transport")?;
+                self.units[t].unit.cargo = Some(CargoUnit::from_unit(&passenger.unit));
+                self.units[t].unit.attacked = true;
+            }
+            BotCommand::Join { unit, move_to } => {
+                let target = self.unit_at(move_to.0, move_to.1).map(|u| u.id).ok_or("No unit to join")?;
+                let i = self.move_unit(unit, move_to, true)?;
+                let source = self.units.remove(i);
+                let t = self.unit_index(target).ok_or("No unit to join")?;
+                let stats = source.unit.unit_type.stats();
+                let merged = &mut self.units[t].unit;
+                merged.hp = (merged.hp + source.unit.hp).min(stats.max_hp);
+                merged.stamina = (merged.stamina + source.unit.stamina).min(stats.max_stamina);
+                merged.ammo = (merged.ammo + source.unit.ammo).min(stats.max_ammo);
+                merged.vip |= source.unit.vip;
+                merged.exhausted = true;
+            }
+            BotCommand::Unload { unit, move_to, drop_at } => {
+                let i = self.move_unit(unit, move_to, false)?;
+                self.units[i].unit.exhausted = true;
+                if let Some(cargo) = self.units[i].unit.cargo.take() {
+                    let id = self.new_id();
+                    self.units.push(EnvUnit { id, faction, pos: GridPosition::new(drop_at.0, drop_at.1), unit: cargo.to_unit() });
+                }
+            }
+            BotCommand::Resupply { unit, move_to } => {
+                let i = self.move_unit(unit, move_to, false)?;
+                self.units[i].unit.exhausted = true;
+                let at = GridPosition::new(move_to.0, move_to.1);
+                for other in self.units.iter_mut().filter(|u| u.faction == faction && u.pos.distance_to(&at) == 1) {
+                    let stats = other.unit.unit_type.stats();
+                    other.unit.stamina = stats.max_stamina;
+                    other.unit.ammo = stats.max_ammo;
+                }
+            }
+        }
+
+        self.check_victory();
+        self.refresh_fog();
+        Ok(())
+    }
+
+    /// Move a unit, paying stamina for the path, and return its index.
+    /// The real board decides, not the snapshot: only a boarding or joining partner (`sharing`)
+    /// may stand on the destination, and it must be in reach around every unit
+    fn move_unit(&mut self, id: ObjectId, to: (i32, i32), sharing: bool) -> Result<usize, String> {
+        let i = self.unit_index(id).ok_or_else(|| format!("No unit {}", id))?;
+        let from = self.units[i].pos;
+        if (from.x, from.y) == to {
+            return Ok(i);
+        }
+        if !sharing && self.unit_at(to.0, to.1).is_some() {
+            return Err(format!("({}, {}) is occupied", to.0, to.1));
+        }
+
+        let unit = &self.units[i].unit;
+        let stats = unit.unit_type.stats();
+        let blockers: HashMap<(i32, i32), Entity> = self.units.iter()
+            .filter(|u| u.id != id && (u.pos.x, u.pos.y) != to)
+            .map(|u| ((u.pos.x, u.pos.y), Entity::from_bits(u.id)))
+            .collect();
+        let movement = effective_movement(stats.movement, unit.stamina);
+        let (_, costs) = calculate_movement_range_with_costs(&from, movement, &self.map, &blockers, stats.class, &self.game_data);
+        let cost = costs.get(&to).copied().ok_or_else(|| format!("({}, {}) is out of reach", to.0, to.1))?;
+
+        let moved = &mut self.units[i];
+        moved.unit.stamina = moved.unit.stamina.saturating_sub(cost);
+        moved.unit.moved = true;
+        moved.pos = GridPosition::new(to.0, to.1);
+        Ok(i)
+    }
+
+    /// Stop a unit short of an enemy its side couldn't see on the way to `to`, ending its
+    /// action, by the same rule as a live battle's trap. Returns whether it was stopped
+    fn spring_trap(&mut self, snapshot: &BattleSnapshot, id: ObjectId, to: (i32, i32)) -> bool {
+        let Some(i) = self.unit_index(id) else { return false };
+        let faction = self.units[i].faction;
+        let hidden_enemies: HashSet<(i32, i32)> = self.units.iter()
+            .filter(|u| u.faction != faction && snapshot.unit(u.id).is_none())
+            .map(|u| (u.pos.x, u.pos.y))
+            .collect();
+        if hidden_enemies.is_empty() {
+            return false;
+        }
+
+        // Route the move as the side planned it, around the units it can see
+        let from = self.units[i].pos;
+        let stats = self.units[i].unit.unit_type.stats();
+        let known: HashMap<(i32, i32), Entity> = snapshot.units.iter()
+            .filter(|u| u.id != id && (u.x, u.y) != to)
+            .map(|u| ((u.x, u.y), Entity::from_bits(u.id)))
+            .collect();
+        let movement = effective_movement(stats.movement, self.units[i].unit.stamina);
+        let (_, costs) = calculate_movement_range_with_costs(&from, movement, &self.map, &known, stats.class, &self.game_data);
+        let route = route_from_costs(IVec2::new(from.x, from.y), IVec2::new(to.0, to.1), &costs, &self.map, stats.class, &self.game_data);
+
+        let occupied: HashSet<(i32, i32)> = self.units.iter()
+            .filter(|u| u.id != id)
+            .map(|u| (u.pos.x, u.pos.y))
+            .collect();
+        let Some((index, _)) = trap_stop(&route, &hidden_enemies, &occupied) else { return false };
+        let stop = route[index];
+        let Some(&cost) = costs.get(&(stop.x, stop.y)) else { return false };
+
+        let trapped = &mut self.units[i];
+        trapped.unit.stamina = trapped.unit.stamina.saturating_sub(cost);
+        trapped.unit.moved = true;
+        trapped.unit.exhausted = true;
+        trapped.pos = GridPosition::new(stop.x, stop.y);
+        true
+    }
+
+    /// Damage, counter-attack and removal of destroyed units, as in a live battle
+    fn resolve_attack(&mut self, attacker_id: ObjectId, defender_id: ObjectId) {
+        let (Some(a), Some(d)) = (self.unit_index(attacker_id), self.unit_index(defender_id)) else { return };
+        let none = CoBonuses::none();
+
+        let attacker_stats = self.units[a].unit.unit_type.stats();
+        if attacker_stats.max_ammo > 0 {
+            self.units[a].unit.ammo = self.units[a].unit.ammo.saturating_sub(1);
+        }
+        self.units[a].unit.attacked = true;
+        self.units[a].unit.exhausted = true;
+
+        let defender_terrain = self.map.get(self.units[d].pos.x, self.units[d].pos.y).unwrap_or(Terrain::Grass);
+        let luck = self.rng.gen_range(0..=9);
+        let damage = calculate_damage_with_luck(&self.units[a].unit, &self.units[d].unit, defender_terrain, &none, &none, &self.weather, &self.game_data, luck);
+        self.units[d].unit.hp -= damage;
+        if self.units[d].unit.hp <= 0 {
+            self.units[a].unit.promote();
+        }
+
+        let mut counter_damage = 0;
+        if self.units[d].unit.hp > 0 {
+            let defender_stats = self.units[d].unit.unit_type.stats();
+            let distance = self.units[a].pos.distance_to(&self.units[d].pos);
+            let (min_range, max_range) = defender_stats.attack_range;
+            let can_counter = defender_stats.attack > 0
+                && distance >= min_range && distance <= max_range
+                && (defender_stats.max_ammo == 0 || self.units[d].unit.ammo > 0);
+            if can_counter {
+                if defender_stats.max_ammo > 0 {
+                    self.units[d].unit.ammo = self.units[d].unit.ammo.saturating_sub(1);
+                }
+                let attacker_terrain = self.map.get(self.units[a].pos.x, self.units[a].pos.y).unwrap_or(Terrain::Grass);
+                let luck = self.rng.gen_range(0..=9);
+                counter_damage = calculate_damage_with_luck(&self.units[d].unit, &self.units[a].unit, attacker_terrain, &none, &none, &self.weather, &self.game_data, luck);
+                self.units[a].unit.hp -= counter_damage;
+                if self.units[a].unit.hp <= 0 {
+                    self.units[d].unit.promote();
+                }
+            }
+        }
+
+        let report = AttackReport {
+            attacker: attacker_id,
+            defender: defender_id,
+            attacker_faction: self.units[a].faction,
+            defender_faction: self.units[d].faction,
+            defender_type: self.units[d].unit.unit_type,
+            damage,
+            counter_damage,
+            defender_destroyed: self.units[d].unit.hp <= 0,
+            attacker_destroyed: self.units[a].unit.hp <= 0,
+        };
+        if self.opponent.is_some() {
+            self.opponent_inbox.push(report);
+        }
+        self.units.retain(|u| u.unit.hp > 0);
+    }
+
+    fn end_turn(&mut self) {
+        self.stage = Stage::Idle;
+        let ending = self.current;
+        for unit in self.units.iter_mut().filter(|u| u.faction == ending) {
+            unit.unit.moved = false;
+            unit.unit.attacked = false;
+            unit.unit.exhausted = false;
+        }
+
+        self.current = if ending == SIDES[0] { SIDES[1] } else { SIDES[0] };
+        if self.current == SIDES[0] {
+            self.turn += 1;
+            if self.turn > self.config.max_turns {
+                self.done = true;
+                return;
+            }
+        }
+        self.start_turn();
+        self.refresh_fog();
+    }
+
+    /// Income, then free resupply and paid repairs on owned bases and storehouses
+    fn start_turn(&mut self) {
+        let faction = self.current;
+        let income: u32 = self.properties.iter()
+            .filter(|(_, t)| t.owner == Some(faction))
+            .map(|(_, t)| t.terrain.income_value())
+            .sum();
+        let funds = self.funds.entry(faction).or_default();
+        *funds += income;
+
+        let depots: Vec<IVec2> = self.properties.iter()
+            .filter(|(_, t)| t.owner == Some(faction) && matches!(t.terrain, Terrain::Base | Terrain::HQ | Terrain::Storehouse))
+            .map(|(_, t)| t.position)
+            .collect();
+        for unit in self.units.iter_mut().filter(|u| u.faction == faction) {
+            if !depots.contains(&IVec2::new(unit.pos.x, unit.pos.y)) {
+                continue;
+            }
+            let stats = unit.unit.unit_type.stats();
+            unit.unit.stamina = stats.max_stamina;
+            unit.unit.ammo = stats.max_ammo;
+
+            let hp_needed = stats.max_hp - unit.unit.hp;
+            if hp_needed <= 0 {
+                continue;
+            }
+            let unit_cost = unit.unit.unit_type.cost();
+            let heal = 20.min(hp_needed);
+            let heal_cost = (heal as u32 * unit_cost) / stats.max_hp as u32;
+            if *funds >= heal_cost {
+                unit.unit.hp += heal;
+                *funds -= heal_cost;
+            } else if *funds > 0 {
+                let affordable = ((*funds * stats.max_hp as u32) / unit_cost.max(1)) as i32;
+                let partial = affordable.min(hp_needed);
+                if partial > 0 {
+                    unit.unit.hp += partial;
+                    let partial_cost = (partial as u32 * unit_cost) / stats.max_hp as u32;
+                    *funds = funds.saturating_sub(partial_cost.max(1));
+                }
+            }
+        }
+    }
+
+    /// Let the opponent controller play out its turn
+    fn play_opponent_turn(&mut self) {
+        let faction = self.current;
+        let Some(mut controller) = self.opponent.take() else {
+            self.end_turn();
+            return;
+        };
+
+        let attacks = std::mem::take(&mut self.opponent_inbox);
+        controller.begin_turn(&self.snapshot_with(faction, attacks), &self.game_data);
+
+        let mut issued = 0;
+        let mut idle = 0;
+        'turn: while issued < MAX_COMMANDS_PER_TURN && idle < MAX_IDLE_POLLS {
+            let attacks = std::mem::take(&mut self.opponent_inbox);
+            let commands = controller.next_commands(&self.snapshot_with(faction, attacks), &self.game_data);
+            if commands.is_empty() {
+                idle += 1;
+                continue;
+            }
+            idle = 0;
+            for command in commands {
+                issued += 1;
+                if command == BotCommand::EndTurn {
+                    break 'turn;
+                }
+                if let Err(reason) = self.execute(faction, &command) {
+                    controller.command_rejected(&command, &reason);
+                    if let Some(i) = command.unit().and_then(|id| self.unit_index(id)) {
+                        self.units[i].unit.exhausted = true;
+                    }
+                }
+                if self.done {
+                    break 'turn;
+                }
+            }
+        }
+
+        self.opponent = Some(controller);
+        if !self.done {
+            self.end_turn();
+        }
+    }
+
+    fn refresh_fog(&mut self) {
+        if !self.fog.enabled {
+            return;
+        }
+        let units = self.units.iter().map(|u| (u.faction, (u.pos.x, u.pos.y), u.unit.unit_type.stats().vision));
+        self.fog.recompute(&self.map, units, self.properties.iter().map(|(_, t)| t), &self.weather, &self.game_data);
+    }
+
+    /// Elimination, or one side holding every HQ (every base, on maps without HQs), as in a live battle
+    fn check_victory(&mut self) {
+        let count = |f: Faction| self.units.iter().filter(|u| u.faction == f).count();
+        let (first, second) = (count(SIDES[0]), count(SIDES[1]));
+        if first == 0 && second > 0 {
+            self.winner = Some(SIDES[1]);
+        } else if second == 0 && first > 0 {
+            self.winner = Some(SIDES[0]);
+        } else {
+            let has_hq = self.properties.iter().any(|(_, t)| t.terrain == Terrain::HQ);
+            let hq_terrain = if has_hq { Terrain::HQ } else { Terrain::Base };
+            let bases: Vec<Option<Faction>> = self.properties.iter()
+                .filter(|(_, t)| t.terrain == hq_terrain)
+                .map(|(_, t)| t.owner)
+                .collect();
+            if bases.len() >= 2 {
+                self.winner = SIDES.into_iter().find(|&f| bases.iter().all(|&o| o == Some(f)));
+            }
+        }
+        if self.winner.is_some() {
+            self.done = true;
+        }
+    }
+
+    /// Property and unit-value lead of `faction`, weighted by the reward shaping
+    fn shaped_score(&self, faction: Faction) -> f32 {
+        let shaping = &self.config.reward;
+        let properties: f32 = self.properties.iter()
+            .filter_map(|(_, t)| t.owner)
+            .map(|owner| if owner == faction { 1.0 } else { -1.0 })
+            .sum();
+        let value: f32 = self.units.iter()
+            .map(|u| {
+                let mut value = unit_value(u.unit.unit_type, u.unit.hp);
+                if let Some(cargo) = &u.unit.cargo {
+                    value += unit_value(cargo.unit_type, cargo.hp);
+                }
+                if u.faction == faction { value } else { -value }
+            })
+            .sum();
+        shaping.property_weight * properties + shaping.unit_value_weight * value / 1000.0
+    }
+}
+
+/// Build cost scaled by remaining HP
+fn unit_value(unit_type: UnitType, hp: i32) -> f32 {
+    unit_type.cost() as f32 * hp.max(0) as f32 / unit_type.stats().max_hp.max(1) as f32
+}
+
+/// `--gym-bench [EPISODES]`: play uniformly random legal actions for both sides on the built-in
+/// maps in turn and report how many episodes a minute the env gets through
+pub fn gym_bench_cli(args: &[String]) -> i32 {
+    let episodes: usize = match args.first().map(|arg| arg.parse()) {
+        None => 200,
+        Some(Ok(episodes)) => episodes,
+        Some(Err(_)) => {
+            eprintln!("Usage: --gym-bench [EPISODES]");
+            return 2;
+        }
+    };
+
+    let mut game_data = GameData::load_defaults();
+    game_data.load_mods();
+    let maps: Vec<MapData> = MapId::all_builtin().into_iter().map(get_builtin_map).collect();
+    let config = EnvConfig { opponent: EnvOpponent::SelfPlay, ..EnvConfig::default() };
+    let mut env = BattleEnv::new(game_data);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let started = Instant::now();
+    let mut steps = 0usize;
+    for episode in 0..episodes {
+        env.reset(&maps[episode % maps.len()], episode as u64, config);
+        while !env.is_done() {
+            let legal: Vec<usize> = env.action_mask().iter().enumerate()
+                .filter(|(_, &legal)| legal)
+                .map(|(i, _)| i)
+                .collect();
+            if legal.is_empty() {
+                break;
+            }
+            env.step(legal[rng.gen_range(0..legal.len())]);
+            steps += 1;
+        }
+    }
+
+    let seconds = started.elapsed().as_secs_f64().max(f64::EPSILON);
+    println!("{} episodes ({} steps) on {} maps in {:.2}s: {:.0} episodes/min, {:.0} steps/s",
+        episodes, steps, maps.len(), seconds, episodes as f64 * 60.0 / seconds, steps as f64 / seconds);
+    0
+}
@@ -1,11 +1,11 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::{Faction, spawn_unit};
-use super::maps::{MapData, SelectedMap, get_builtin_map};
+use super::{Faction, Unit, spawn_unit_with_state};
+use super::maps::{MapData, MapLibrary, MapRules, SelectedMap, load_map_library};
 
 /// Tileset theme affects the foundation/ground color
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Resource)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Resource, Serialize, Deserialize)]
 pub enum TilesetTheme {
     #[default]
     Woodland,   // Forest/temperate - brown earth
@@ -16,6 +16,26 @@ pub enum TilesetTheme {
 }
 
 impl TilesetTheme {
+    pub fn all() -> &'static [TilesetTheme] {
+        &[
+            TilesetTheme::Woodland,
+            TilesetTheme::Steppe,
+            TilesetTheme::Desert,
+            TilesetTheme::Tundra,
+            TilesetTheme::Swamp,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TilesetTheme::Woodland => "Woodland",
+            TilesetTheme::Steppe => "Steppe",
+            TilesetTheme::Desert => "Desert",
+            TilesetTheme::Tundra => "Tundra",
+            TilesetTheme::Swamp => "Swamp",
+        }
+    }
+
     pub fn ground_color(&self) -> Color {
         match self {
             TilesetTheme::Woodland => Color::srgb(0.35, 0.25, 0.15),
@@ -36,27 +56,13 @@ impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<GameMap>()
             .init_resource::<SelectedMap>()
-            .init_resource::<TilesetTheme>();
+            .init_resource::<MapLibrary>()
+            .init_resource::<TilesetTheme>()
+            .init_resource::<MapRules>()
+            .add_systems(Startup, load_map_library);
     }
 }
 
-/// Spawn the map based on the selected map ID
-#[allow(dead_code)]
-pub fn spawn_map_from_selection(
-    commands: &mut Commands,
-    game_map: &mut ResMut<GameMap>,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    sprite_assets: &super::SpriteAssets,
-    images: &Assets<Image>,
-    selected: &SelectedMap,
-    tileset_theme: TilesetTheme,
-) -> MapData {
-    let map_data = get_builtin_map(selected.map_id);
-    spawn_map_from_data(commands, game_map, meshes, materials, sprite_assets, images, &map_data, tileset_theme);
-    map_data
-}
-
 /// Spawn map entities from MapData
 pub fn spawn_map_from_data(
     commands: &mut Commands,
@@ -72,6 +78,7 @@ pub fn spawn_map_from_data(
     game_map.width = map_data.width;
     game_map.height = map_data.height;
     game_map.tiles = map_data.terrain.clone();
+    game_map.tunnels.clear();
 
     // Offsets to center the map (grid Y -> world Z)
     let offset_x = -(game_map.width as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
@@ -97,6 +104,7 @@ pub fn spawn_map_from_data(
             ..default()
         })),
         Transform::from_xyz(0.0, -FOUNDATION_DEPTH / 2.0, 0.0),
+        BoardFoundation,
     ));
 
     // Spawn tile entities as 3D cuboid meshes
@@ -108,11 +116,7 @@ pub fn spawn_map_from_data(
 
             let owner = property_owners.get(&(x as i32, y as i32)).copied();
 
-            let tile_color = if let Some(faction) = owner {
-                blend_color(terrain.color(), faction.color(), 0.3)
-            } else {
-                terrain.color()
-            };
+            let tile_color = tile_color(terrain, owner);
 
             // Get tile height based on terrain type
             let tile_height = terrain.tile_height();
@@ -135,6 +139,7 @@ pub fn spawn_map_from_data(
                     owner,
                     capture_progress: 0,
                     capturing_faction: None,
+                    damage: 0,
                 },
             ));
         }
@@ -152,7 +157,7 @@ pub fn spawn_units_from_data(
     map_data: &MapData,
 ) {
     for placement in &map_data.units {
-        spawn_unit(
+        spawn_unit_with_state(
             commands,
             game_map,
             meshes,
@@ -160,7 +165,7 @@ pub fn spawn_units_from_data(
             sprite_assets,
             images,
             placement.faction,
-            placement.unit_type,
+            Unit { vip: placement.vip, ..Unit::new(placement.unit_type) },
             placement.x,
             placement.y,
         );
@@ -197,10 +202,16 @@ pub enum Terrain {
     // === SPECIAL TERRAIN ===
     /// Fortified base - spawn point, high defense
     Base,
+    /// Headquarters - losing it can lose the battle; produces nothing
+    HQ,
     /// Small fortified outpost - can be captured for income
     Outpost,
     /// Supply cache - can be captured for resources
     Storehouse,
+
+    // === OBSTACLES ===
+    /// Barricade of sticks and stones - blocks ground movement until destroyed
+    Barricade,
 }
 
 impl Terrain {
@@ -217,8 +228,10 @@ impl Terrain {
             Terrain::Pond => "Pond",
             Terrain::Shore => "Shore",
             Terrain::Base => "Base",
+            Terrain::HQ => "HQ",
             Terrain::Outpost => "Outpost",
             Terrain::Storehouse => "Storehouse",
+            Terrain::Barricade => "Barricade",
         }
     }
 
@@ -235,8 +248,10 @@ impl Terrain {
             Terrain::Pond => 0,
             Terrain::Shore => 0,
             Terrain::Base => 4,
+            Terrain::HQ => 4,
             Terrain::Outpost => 2,
             Terrain::Storehouse => 1,
+            Terrain::Barricade => 0,
         }
     }
 
@@ -253,26 +268,29 @@ impl Terrain {
             Terrain::Pond => 99,     // Impassable for ground
             Terrain::Shore => 2,
             Terrain::Base => 1,
+            Terrain::HQ => 1,
             Terrain::Outpost => 1,
             Terrain::Storehouse => 1,
+            Terrain::Barricade => 99, // Impassable until destroyed
         }
     }
 
     /// Whether this terrain can be captured
     pub fn is_capturable(&self) -> bool {
-        matches!(self, Terrain::Base | Terrain::Outpost | Terrain::Storehouse)
+        matches!(self, Terrain::Base | Terrain::HQ | Terrain::Outpost | Terrain::Storehouse)
     }
 
     /// Whether this terrain blocks ground movement entirely
     #[allow(dead_code)]
     pub fn blocks_ground(&self) -> bool {
-        matches!(self, Terrain::Pond)
+        matches!(self, Terrain::Pond | Terrain::Barricade)
     }
 
     /// Capture points required to take this terrain (0 = not capturable)
     pub fn capture_points(&self) -> i32 {
         match self {
             Terrain::Base => 20,
+            Terrain::HQ => 20,
             Terrain::Outpost => 20,
             Terrain::Storehouse => 20,
             _ => 0,
@@ -283,6 +301,7 @@ impl Terrain {
     pub fn income_value(&self) -> u32 {
         match self {
             Terrain::Base => 10,
+            Terrain::HQ => 10,
             Terrain::Outpost => 10,
             Terrain::Storehouse => 5,
             _ => 0,
@@ -302,8 +321,10 @@ impl Terrain {
             Terrain::Pond => Color::srgb(0.25, 0.45, 0.70),       // Deeper blue
             Terrain::Shore => Color::srgb(0.60, 0.55, 0.40),      // Sandy brown
             Terrain::Base => Color::srgb(0.65, 0.45, 0.30),       // Fortified brown
+            Terrain::HQ => Color::srgb(0.70, 0.35, 0.30),         // Banner red
             Terrain::Outpost => Color::srgb(0.55, 0.50, 0.40),    // Stone gray-brown
             Terrain::Storehouse => Color::srgb(0.50, 0.45, 0.35), // Weathered wood
+            Terrain::Barricade => Color::srgb(0.45, 0.35, 0.25),  // Lashed sticks
         }
     }
 
@@ -311,8 +332,8 @@ impl Terrain {
     pub fn has_feature(&self) -> bool {
         matches!(self,
             Terrain::Thicket | Terrain::Brambles | Terrain::Boulder |
-            Terrain::Hollow | Terrain::Log | Terrain::Base |
-            Terrain::Outpost | Terrain::Storehouse
+            Terrain::Hollow | Terrain::Log | Terrain::Base | Terrain::HQ |
+            Terrain::Outpost | Terrain::Storehouse | Terrain::Barricade
         )
     }
 
@@ -325,8 +346,10 @@ impl Terrain {
             Terrain::Hollow => 36.0,     // Stump/cave entrance
             Terrain::Log => 16.0,        // Low fallen log
             Terrain::Base => 48.0,       // Tall building
+            Terrain::HQ => 56.0,         // Keep with a tower
             Terrain::Outpost => 40.0,    // Medium building
             Terrain::Storehouse => 32.0, // Small building
+            Terrain::Barricade => 24.0,  // Low wall
             _ => 0.0,
         }
     }
@@ -345,8 +368,10 @@ impl Terrain {
             Terrain::Boulder => 10.0,
             Terrain::Hollow => 6.0,
             Terrain::Base => 8.0,
+            Terrain::HQ => 9.0,
             Terrain::Outpost => 6.0,
             Terrain::Storehouse => 5.0,
+            Terrain::Barricade => 8.0,
         }
     }
 
@@ -364,8 +389,10 @@ impl Terrain {
             Terrain::Pond => "w",
             Terrain::Shore => ",",
             Terrain::Base => "B",
+            Terrain::HQ => "H",
             Terrain::Outpost => "P",
             Terrain::Storehouse => "S",
+            Terrain::Barricade => "X",
         }
     }
 
@@ -383,8 +410,10 @@ impl Terrain {
             Terrain::Pond => "pond",
             Terrain::Shore => "shore",
             Terrain::Base => "base",
+            Terrain::HQ => "hq",
             Terrain::Outpost => "outpost",
             Terrain::Storehouse => "storehouse",
+            Terrain::Barricade => "barricade",
         }
     }
 
@@ -402,12 +431,18 @@ impl Terrain {
             Terrain::Pond,
             Terrain::Shore,
             Terrain::Base,
+            Terrain::HQ,
             Terrain::Outpost,
             Terrain::Storehouse,
+            Terrain::Barricade,
         ]
     }
 }
 
+/// Marker for the foundation slab beneath the board
+#[derive(Component)]
+pub struct BoardFoundation;
+
 /// A single tile on the map
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Tile {
@@ -416,20 +451,24 @@ pub struct Tile {
     pub owner: Option<Faction>,  // Faction that owns this tile
     pub capture_progress: i32,   // Current capture progress (0 = not being captured)
     pub capturing_faction: Option<Faction>, // Faction currently capturing
+    pub damage: i32,             // Damage taken by destructible terrain
 }
 
 /// The game map resource
-#[derive(Resource, Default, Serialize, Deserialize)]
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
 pub struct GameMap {
     pub width: u32,
     pub height: u32,
     pub tiles: Vec<Vec<Terrain>>,
+    /// Dug tunnels, each linking two Hollow tiles for foot movement
+    #[serde(default)]
+    pub tunnels: Vec<((i32, i32), (i32, i32))>,
 }
 
 impl GameMap {
     pub fn new(width: u32, height: u32) -> Self {
         let tiles = vec![vec![Terrain::Grass; width as usize]; height as usize];
-        Self { width, height, tiles }
+        Self { width, height, tiles, tunnels: Vec::new() }
     }
 
     pub fn get(&self, x: i32, y: i32) -> Option<Terrain> {
@@ -445,10 +484,39 @@ impl GameMap {
             self.tiles[y as usize][x as usize] = terrain;
         }
     }
+
+    /// Tiles reachable through a tunnel from the given position
+    pub fn tunnel_exits(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        self.tunnels.iter()
+            .filter_map(|&(a, b)| {
+                if a == (x, y) {
+                    Some(b)
+                } else if b == (x, y) {
+                    Some(a)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the given position is already a tunnel entrance
+    pub fn has_tunnel(&self, x: i32, y: i32) -> bool {
+        self.tunnels.iter().any(|&(a, b)| a == (x, y) || b == (x, y))
+    }
 }
 
 pub const TILE_SIZE: f32 = 48.0;
 
+/// Base color of a tile, tinted with its owner's color for properties
+pub fn tile_color(terrain: Terrain, owner: Option<Faction>) -> Color {
+    if let Some(faction) = owner {
+        blend_color(terrain.color(), faction.color(), 0.3)
+    } else {
+        terrain.color()
+    }
+}
+
 /// Blend two colors together
 fn blend_color(base: Color, tint: Color, amount: f32) -> Color {
     let base_rgba = base.to_srgba();
@@ -0,0 +1,434 @@
+//! Map editor operations on `MapData`, kept free of the ECS so the editor UI stays thin
+//!
+//! Terrain, unit and property edits honour the symmetry setting. Fill, line and rectangle
+//! tools pick the tiles to paint; maps can be resized, shifted and cropped; a rectangle can be
+//! copied and pasted; and `MapHistory` keeps the undo and redo snapshots.
+
+use std::collections::{HashSet, VecDeque};
+
+use super::{Faction, MapData, PropertyOwnership, Terrain, UnitPlacement};
+
+/// Maximum number of undo steps kept by the editor
+pub const MAX_HISTORY: usize = 100;
+
+/// Smallest map the editor allows
+pub const MIN_MAP_WIDTH: u32 = 8;
+pub const MIN_MAP_HEIGHT: u32 = 6;
+/// Largest map in either dimension
+pub const MAX_MAP_SIZE: u32 = 64;
+
+// ============================================================================
+// SYMMETRY
+// ============================================================================
+
+/// Symmetry applied to every edit, so both halves of a map stay fair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// Mirror left to right
+    Horizontal,
+    /// Mirror top to bottom
+    Vertical,
+    /// Rotate 180° around the map centre
+    Rotational,
+}
+
+impl Symmetry {
+    pub fn all() -> [Symmetry; 4] {
+        [Symmetry::None, Symmetry::Horizontal, Symmetry::Vertical, Symmetry::Rotational]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::None => "None",
+            Symmetry::Horizontal => "Horizontal",
+            Symmetry::Vertical => "Vertical",
+            Symmetry::Rotational => "180°",
+        }
+    }
+
+    /// The tile mirroring (x, y), or None without symmetry
+    pub fn mirror(&self, x: i32, y: i32, width: u32, height: u32) -> Option<(i32, i32)> {
+        let (w, h) = (width as i32, height as i32);
+        match self {
+            Symmetry::None => None,
+            Symmetry::Horizontal => Some((w - 1 - x, y)),
+            Symmetry::Vertical => Some((x, h - 1 - y)),
+            Symmetry::Rotational => Some((w - 1 - x, h - 1 - y)),
+        }
+    }
+}
+
+/// The faction that owns the mirrored half of a symmetric map
+pub fn mirror_faction(faction: Faction) -> Faction {
+    match faction {
+        Faction::Eastern => Faction::Northern,
+        Faction::Northern => Faction::Eastern,
+        Faction::Western => Faction::Southern,
+        Faction::Southern => Faction::Western,
+        Faction::Nether => Faction::Nether,
+    }
+}
+
+// ============================================================================
+// EDIT OPERATIONS
+// ============================================================================
+
+/// Paint terrain on the given tiles and their mirrors
+pub fn paint_terrain(map: &mut MapData, tiles: &[(i32, i32)], terrain: Terrain, symmetry: Symmetry) {
+    for &(x, y) in tiles {
+        map.set_terrain(x, y, terrain);
+        if let Some((mx, my)) = symmetry.mirror(x, y, map.width, map.height) {
+            map.set_terrain(mx, my, terrain);
+        }
+        // Ownership only makes sense on capturable terrain
+        if !terrain.is_capturable() {
+            map.properties.retain(|p| (p.x, p.y) != (x, y));
+            if let Some((mx, my)) = symmetry.mirror(x, y, map.width, map.height) {
+                map.properties.retain(|p| (p.x, p.y) != (mx, my));
+            }
+        }
+    }
+}
+
+/// Place a unit, and a copy for the opposite faction on the mirrored tile
+pub fn place_unit(map: &mut MapData, placement: UnitPlacement, symmetry: Symmetry) {
+    let mut placements = Vec::new();
+    if let Some((mx, my)) = symmetry.mirror(placement.x, placement.y, map.width, map.height) {
+        if (mx, my) != (placement.x, placement.y) {
+            placements.push(UnitPlacement { x: mx, y: my, faction: mirror_faction(placement.faction), ..placement.clone() });
+        }
+    }
+    placements.push(placement);
+    for placement in placements {
+        map.units.retain(|u| (u.x, u.y) != (placement.x, placement.y));
+        map.units.push(placement);
+    }
+}
+
+/// Remove the unit at a tile and on its mirror
+pub fn remove_unit(map: &mut MapData, x: i32, y: i32, symmetry: Symmetry) {
+    let mirror = symmetry.mirror(x, y, map.width, map.height);
+    map.units.retain(|u| (u.x, u.y) != (x, y) && Some((u.x, u.y)) != mirror);
+}
+
+/// Set a property owner, giving the mirrored property to the opposite faction
+pub fn set_owner(map: &mut MapData, x: i32, y: i32, owner: Faction, symmetry: Symmetry) {
+    let mut owners = vec![(x, y, owner)];
+    if let Some((mx, my)) = symmetry.mirror(x, y, map.width, map.height) {
+        if (mx, my) != (x, y) {
+            owners.push((mx, my, mirror_faction(owner)));
+        }
+    }
+    for (x, y, owner) in owners {
+        if map.get_terrain(x, y).is_some_and(|t| t.is_capturable()) {
+            map.properties.retain(|p| (p.x, p.y) != (x, y));
+            map.add_property(x, y, owner);
+        }
+    }
+}
+
+/// Make a property (and its mirror) neutral again
+pub fn clear_owner(map: &mut MapData, x: i32, y: i32, symmetry: Symmetry) {
+    let mirror = symmetry.mirror(x, y, map.width, map.height);
+    map.properties.retain(|p| (p.x, p.y) != (x, y) && Some((p.x, p.y)) != mirror);
+}
+
+// ============================================================================
+// SHAPES
+// ============================================================================
+
+/// Connected tiles sharing the terrain at (x, y)
+pub fn flood_fill_tiles(map: &MapData, x: i32, y: i32) -> Vec<(i32, i32)> {
+    let Some(target) = map.get_terrain(x, y) else { return Vec::new() };
+    let mut visited = HashSet::from([(x, y)]);
+    let mut queue = VecDeque::from([(x, y)]);
+    let mut tiles = Vec::new();
+
+    while let Some((cx, cy)) = queue.pop_front() {
+        tiles.push((cx, cy));
+        for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            let next = (cx + dx, cy + dy);
+            if map.get_terrain(next.0, next.1) == Some(target) && visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    tiles
+}
+
+/// Tiles on a straight line between two points (Bresenham)
+pub fn line_tiles(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let sx = if x < to.0 { 1 } else { -1 };
+    let sy = if y < to.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    let mut tiles = vec![(x, y)];
+
+    while (x, y) != to {
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        tiles.push((x, y));
+    }
+    tiles
+}
+
+/// Tiles in the rectangle spanned by two corners, filled or as an outline
+pub fn rect_tiles(a: (i32, i32), b: (i32, i32), filled: bool) -> Vec<(i32, i32)> {
+    let (min_x, max_x) = (a.0.min(b.0), a.0.max(b.0));
+    let (min_y, max_y) = (a.1.min(b.1), a.1.max(b.1));
+    let mut tiles = Vec::new();
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if filled || x == min_x || x == max_x || y == min_y || y == max_y {
+                tiles.push((x, y));
+            }
+        }
+    }
+    tiles
+}
+
+// ============================================================================
+// RESIZING
+// ============================================================================
+
+/// Which part of the map stays put when it is resized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeAnchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl ResizeAnchor {
+    /// Anchors in reading order, for a 3x3 picker
+    pub fn grid() -> [[ResizeAnchor; 3]; 3] {
+        [
+            [ResizeAnchor::TopLeft, ResizeAnchor::Top, ResizeAnchor::TopRight],
+            [ResizeAnchor::Left, ResizeAnchor::Center, ResizeAnchor::Right],
+            [ResizeAnchor::BottomLeft, ResizeAnchor::Bottom, ResizeAnchor::BottomRight],
+        ]
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ResizeAnchor::TopLeft => "↖",
+            ResizeAnchor::Top => "↑",
+            ResizeAnchor::TopRight => "↗",
+            ResizeAnchor::Left => "←",
+            ResizeAnchor::Center => "•",
+            ResizeAnchor::Right => "→",
+            ResizeAnchor::BottomLeft => "↙",
+            ResizeAnchor::Bottom => "↓",
+            ResizeAnchor::BottomRight => "↘",
+        }
+    }
+
+    /// How far existing content moves when the map grows (or shrinks) by the given amount
+    fn offset(&self, grow_x: i32, grow_y: i32) -> (i32, i32) {
+        let (col, row) = match self {
+            ResizeAnchor::TopLeft => (0, 0),
+            ResizeAnchor::Top => (1, 0),
+            ResizeAnchor::TopRight => (2, 0),
+            ResizeAnchor::Left => (0, 1),
+            ResizeAnchor::Center => (1, 1),
+            ResizeAnchor::Right => (2, 1),
+            ResizeAnchor::BottomLeft => (0, 2),
+            ResizeAnchor::Bottom => (1, 2),
+            ResizeAnchor::BottomRight => (2, 2),
+        };
+        (grow_x * col / 2, grow_y * row / 2)
+    }
+}
+
+/// Copy of the map with content moved by (dx, dy) onto a board of the given size.
+/// Uncovered tiles become grass; anything pushed off the board is dropped, along with
+/// objectives and triggers left with no tile on it.
+fn remap(map: &MapData, width: u32, height: u32, dx: i32, dy: i32) -> MapData {
+    let mut resized = MapData::new(&map.name, width, height);
+    resized.description = map.description.clone();
+    resized.rules = map.rules.clone();
+    resized.rules.objectives = map.rules.objectives.iter()
+        .filter_map(|o| o.shifted(dx, dy, width, height))
+        .collect();
+    resized.triggers = map.triggers.iter()
+        .filter_map(|t| t.shifted(dx, dy, width, height))
+        .collect();
+    for (y, row) in map.terrain.iter().enumerate() {
+        for (x, &terrain) in row.iter().enumerate() {
+            resized.set_terrain(x as i32 + dx, y as i32 + dy, terrain);
+        }
+    }
+    let inside = |x: i32, y: i32| x >= 0 && y >= 0 && x < width as i32 && y < height as i32;
+    resized.units = map.units.iter()
+        .map(|u| UnitPlacement { x: u.x + dx, y: u.y + dy, ..u.clone() })
+        .filter(|u| inside(u.x, u.y))
+        .collect();
+    resized.properties = map.properties.iter()
+        .map(|p| PropertyOwnership { x: p.x + dx, y: p.y + dy, owner: p.owner })
+        .filter(|p| inside(p.x, p.y))
+        .collect();
+    resized
+}
+
+/// Resize a map, keeping its content pinned to the anchor
+pub fn resize_map(map: &MapData, width: u32, height: u32, anchor: ResizeAnchor) -> MapData {
+    let width = width.clamp(MIN_MAP_WIDTH, MAX_MAP_SIZE);
+    let height = height.clamp(MIN_MAP_HEIGHT, MAX_MAP_SIZE);
+    let (dx, dy) = anchor.offset(width as i32 - map.width as i32, height as i32 - map.height as i32);
+    remap(map, width, height, dx, dy)
+}
+
+/// Move all content by (dx, dy) without changing the map size
+pub fn shift_map(map: &MapData, dx: i32, dy: i32) -> MapData {
+    remap(map, map.width, map.height, dx, dy)
+}
+
+/// Crop the map to the rectangle spanned by two corners, within the size limits
+pub fn crop_map(map: &MapData, a: (i32, i32), b: (i32, i32)) -> MapData {
+    let min_x = a.0.min(b.0).max(0);
+    let min_y = a.1.min(b.1).max(0);
+    let width = ((a.0.max(b.0) - min_x + 1).max(1) as u32).clamp(MIN_MAP_WIDTH, MAX_MAP_SIZE);
+    let height = ((a.1.max(b.1) - min_y + 1).max(1) as u32).clamp(MIN_MAP_HEIGHT, MAX_MAP_SIZE);
+    remap(map, width, height, -min_x, -min_y)
+}
+
+// ============================================================================
+// CLIPBOARD
+// ============================================================================
+
+/// A copied region of a map, with positions relative to its top-left corner
+#[derive(Debug, Clone)]
+pub struct MapClipboard {
+    pub width: u32,
+    pub height: u32,
+    pub terrain: Vec<Vec<Terrain>>,
+    pub units: Vec<UnitPlacement>,
+    pub properties: Vec<PropertyOwnership>,
+}
+
+impl MapClipboard {
+    /// Copy the rectangle spanned by two corners, clipped to the map
+    pub fn copy(map: &MapData, a: (i32, i32), b: (i32, i32)) -> Option<Self> {
+        let min_x = a.0.min(b.0).max(0);
+        let min_y = a.1.min(b.1).max(0);
+        let max_x = a.0.max(b.0).min(map.width as i32 - 1);
+        let max_y = a.1.max(b.1).min(map.height as i32 - 1);
+        if min_x > max_x || min_y > max_y {
+            return None;
+        }
+
+        let inside = |x: i32, y: i32| x >= min_x && x <= max_x && y >= min_y && y <= max_y;
+        Some(Self {
+            width: (max_x - min_x + 1) as u32,
+            height: (max_y - min_y + 1) as u32,
+            terrain: (min_y..=max_y)
+                .map(|y| (min_x..=max_x).map(|x| map.terrain[y as usize][x as usize]).collect())
+                .collect(),
+            units: map.units.iter()
+                .filter(|u| inside(u.x, u.y))
+                .map(|u| UnitPlacement { x: u.x - min_x, y: u.y - min_y, ..u.clone() })
+                .collect(),
+            properties: map.properties.iter()
+                .filter(|p| inside(p.x, p.y))
+                .map(|p| PropertyOwnership { x: p.x - min_x, y: p.y - min_y, owner: p.owner })
+                .collect(),
+        })
+    }
+
+    /// Paste with the top-left corner at (x, y); anything off the map is dropped
+    pub fn paste(&self, map: &mut MapData, x: i32, y: i32) {
+        let (width, height) = (map.width as i32, map.height as i32);
+        let inside = |px: i32, py: i32| px >= 0 && py >= 0 && px < width && py < height;
+        let covered = |px: i32, py: i32| {
+            px >= x && py >= y && px < x + self.width as i32 && py < y + self.height as i32
+        };
+
+        map.units.retain(|u| !covered(u.x, u.y));
+        map.properties.retain(|p| !covered(p.x, p.y));
+
+        for (dy, row) in self.terrain.iter().enumerate() {
+            for (dx, &terrain) in row.iter().enumerate() {
+                map.set_terrain(x + dx as i32, y + dy as i32, terrain);
+            }
+        }
+        for unit in &self.units {
+            let (ux, uy) = (x + unit.x, y + unit.y);
+            if inside(ux, uy) {
+                map.units.push(UnitPlacement { x: ux, y: uy, ..unit.clone() });
+            }
+        }
+        for property in &self.properties {
+            let (px, py) = (x + property.x, y + property.y);
+            if inside(px, py) {
+                map.add_property(px, py, property.owner);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// HISTORY
+// ============================================================================
+
+/// Undo/redo stack of map snapshots
+#[derive(Debug, Clone, Default)]
+pub struct MapHistory {
+    undo: Vec<MapData>,
+    redo: Vec<MapData>,
+}
+
+impl MapHistory {
+    /// Remember the map as it is before an edit
+    pub fn record(&mut self, map: &MapData) {
+        self.undo.push(map.clone());
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Restore the previous snapshot. Returns false when there is nothing to undo.
+    pub fn undo(&mut self, map: &mut MapData) -> bool {
+        let Some(previous) = self.undo.pop() else { return false };
+        self.redo.push(std::mem::replace(map, previous));
+        true
+    }
+
+    /// Re-apply the last undone edit. Returns false when there is nothing to redo.
+    pub fn redo(&mut self, map: &mut MapData) -> bool {
+        let Some(next) = self.redo.pop() else { return false };
+        self.undo.push(std::mem::replace(map, next));
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
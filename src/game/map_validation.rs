@@ -0,0 +1,451 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use super::{Faction, GameData, MapData, Objective, Region, Terrain, TriggerAction, TriggerCondition, UnitClass, UnitType};
+#[cfg(not(target_arch = "wasm32"))]
+use super::{MapId, MapLibrary, get_builtin_map};
+
+/// How serious a validation finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// The map can't be played as-is
+    Error,
+    /// Playable, but probably not what the author intended
+    Warning,
+}
+
+/// A single problem found on a map
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+    /// Tile the issue is about, if any
+    pub position: Option<(i32, i32)>,
+}
+
+/// Balance numbers for one faction
+#[derive(Debug, Clone)]
+pub struct FactionReport {
+    pub faction: Faction,
+    pub bases: usize,
+    pub properties: usize,
+    /// Funds earned per turn from starting properties
+    pub income: u32,
+    pub units: usize,
+    /// Total production cost of starting units
+    pub unit_value: u32,
+    /// Ground distance from each base to its nearest neutral property
+    pub base_to_neutral: Vec<Option<u32>>,
+    /// Neutral properties this faction reaches strictly first
+    pub closer_neutrals: usize,
+    /// Ground units can walk to an enemy HQ
+    pub reaches_enemy_hq: bool,
+}
+
+/// Result of validating a map: playability issues plus a fairness report
+#[derive(Debug, Clone, Default)]
+pub struct MapReport {
+    pub issues: Vec<ValidationIssue>,
+    pub factions: Vec<FactionReport>,
+    pub neutral_properties: usize,
+    /// 0.0 = both sides start identical, 1.0 = one side has everything
+    pub asymmetry: f32,
+}
+
+impl MapReport {
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == IssueSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == IssueSeverity::Warning)
+    }
+
+    /// No errors were found
+    pub fn is_playable(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Plain-text report, as printed by the CLI
+    pub fn summary(&self, map: &MapData) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{} ({}x{})", map.name, map.width, map.height);
+        for issue in &self.issues {
+            let label = match issue.severity {
+                IssueSeverity::Error => "error",
+                IssueSeverity::Warning => "warning",
+            };
+            match issue.position {
+                Some((x, y)) => { let _ = writeln!(out, "  {} at ({}, {}): {}", label, x, y, issue.message); }
+                None => { let _ = writeln!(out, "  {}: {}", label, issue.message); }
+            }
+        }
+        for side in &self.factions {
+            let distances: Vec<String> = side.base_to_neutral.iter()
+                .map(|d| d.map_or("-".to_string(), |d| d.to_string()))
+                .collect();
+            let _ = writeln!(
+                out,
+                "  {:?}: {} bases, {} properties, income {}/turn, {} units worth {}, nearest neutral per base [{}], {} closer neutrals{}",
+                side.faction, side.bases, side.properties, side.income, side.units, side.unit_value,
+                distances.join(", "), side.closer_neutrals,
+                if side.reaches_enemy_hq { "" } else { ", CANNOT reach enemy HQ" },
+            );
+        }
+        let _ = writeln!(out, "  neutral properties: {}, asymmetry: {:.0}%", self.neutral_properties, self.asymmetry * 100.0);
+        let _ = writeln!(out, "  {}", if self.is_playable() { "OK" } else { "NOT PLAYABLE" });
+        out
+    }
+
+    fn error(&mut self, message: String, position: Option<(i32, i32)>) {
+        self.issues.push(ValidationIssue { severity: IssueSeverity::Error, message, position });
+    }
+
+    fn warning(&mut self, message: String, position: Option<(i32, i32)>) {
+        self.issues.push(ValidationIssue { severity: IssueSeverity::Warning, message, position });
+    }
+}
+
+/// Check a map for playability problems and measure how fair it is
+pub fn validate_map(map: &MapData, game_data: &GameData) -> MapReport {
+    let mut report = MapReport::default();
+
+    // === BOUNDS ===
+    if map.terrain.len() != map.height as usize || map.terrain.iter().any(|row| row.len() != map.width as usize) {
+        report.error(format!("terrain grid does not match the declared {}x{} size", map.width, map.height), None);
+        // Nothing below can be trusted on a malformed grid
+        return report;
+    }
+    let in_bounds = |x: i32, y: i32| map.get_terrain(x, y).is_some();
+
+    // === UNITS ===
+    let mut occupied = HashSet::new();
+    for unit in &map.units {
+        let pos = (unit.x, unit.y);
+        let Some(terrain) = map.get_terrain(unit.x, unit.y) else {
+            report.error(format!("{:?} unit is outside the map", unit.unit_type), Some(pos));
+            continue;
+        };
+        if !occupied.insert(pos) {
+            report.error("two units share this tile".to_string(), Some(pos));
+        }
+        if !game_data.is_passable(terrain, unit_class(game_data, unit.unit_type)) {
+            report.error(format!("{:?} cannot stand on {}", unit.unit_type, game_data.terrain_name(terrain)), Some(pos));
+        }
+    }
+
+    // === PROPERTIES ===
+    let mut owners: HashMap<(i32, i32), Faction> = HashMap::new();
+    for property in &map.properties {
+        let pos = (property.x, property.y);
+        if !in_bounds(property.x, property.y) {
+            report.error(format!("{:?} property is outside the map", property.owner), Some(pos));
+            continue;
+        }
+        let terrain = map.terrain[property.y as usize][property.x as usize];
+        if !game_data.terrain_capturable(terrain) {
+            report.error(format!("{} cannot be owned", game_data.terrain_name(terrain)), Some(pos));
+            continue;
+        }
+        if owners.insert(pos, property.owner).is_some() {
+            report.warning("property has more than one owner entry".to_string(), Some(pos));
+        }
+    }
+
+    // === FACTIONS AND BASES ===
+    let factions = map.factions();
+    if factions.len() < 2 {
+        report.error(format!("needs at least two factions, found {}", factions.len()), None);
+    } else if factions.len() != map.rules.players as usize {
+        report.warning(format!("rules say {} players but {} factions are placed", map.rules.players, factions.len()), None);
+    }
+    let owned_of = |faction: Faction, terrain: Terrain| -> Vec<(i32, i32)> {
+        owners.iter()
+            .filter(|(&(x, y), &owner)| owner == faction && map.terrain[y as usize][x as usize] == terrain)
+            .map(|(&pos, _)| pos)
+            .collect()
+    };
+    let bases_of = |faction: Faction| owned_of(faction, Terrain::Base);
+    // Maps with HQ terrain are won by taking HQs; without it every Base counts as one
+    let has_hq = map.terrain.iter().flatten().any(|&t| t == Terrain::HQ);
+    let hqs_of = |faction: Faction| owned_of(faction, if has_hq { Terrain::HQ } else { Terrain::Base });
+
+    let neutrals: Vec<(i32, i32)> = tiles(map)
+        .filter(|&(x, y)| game_data.terrain_capturable(map.terrain[y as usize][x as usize]) && !owners.contains_key(&(x, y)))
+        .collect();
+    report.neutral_properties = neutrals.len();
+
+    // Ground distances from every faction's starting positions
+    let ground = |terrain: Terrain| game_data.is_passable(terrain, UnitClass::Foot);
+    let mut distances: HashMap<Faction, HashMap<(i32, i32), u32>> = HashMap::new();
+    for &faction in &factions {
+        let starts: Vec<(i32, i32)> = bases_of(faction).into_iter()
+            .chain(map.units.iter().filter(|u| u.faction == faction).map(|u| (u.x, u.y)))
+            .filter(|&(x, y)| in_bounds(x, y))
+            .collect();
+        distances.insert(faction, ground_distances(map, &starts, ground));
+    }
+
+    for &faction in &factions {
+        let bases = bases_of(faction);
+        if bases.is_empty() {
+            report.error(format!("{:?} owns no Base", faction), None);
+        }
+
+        let reach = &distances[&faction];
+        if has_hq && hqs_of(faction).is_empty() {
+            report.warning(format!("{:?} owns no HQ", faction), None);
+        }
+        let enemy_hqs: Vec<(i32, i32)> = factions.iter()
+            .filter(|&&f| f != faction)
+            .flat_map(|&f| hqs_of(f))
+            .collect();
+        let reaches_enemy_hq = enemy_hqs.iter().any(|pos| reach.contains_key(pos));
+        if !enemy_hqs.is_empty() && !reaches_enemy_hq {
+            report.error(format!("{:?} cannot reach an enemy HQ by ground", faction), None);
+        }
+
+        let base_to_neutral = bases.iter()
+            .map(|&base| {
+                let from_base = ground_distances(map, &[base], ground);
+                neutrals.iter().filter_map(|pos| from_base.get(pos).copied()).min()
+            })
+            .collect();
+
+        let closer_neutrals = neutrals.iter()
+            .filter(|pos| {
+                let Some(&mine) = reach.get(*pos) else { return false };
+                factions.iter()
+                    .filter(|&&f| f != faction)
+                    .all(|f| distances[f].get(*pos).is_none_or(|&theirs| mine < theirs))
+            })
+            .count();
+
+        let owned: Vec<Terrain> = owners.iter()
+            .filter(|(_, &owner)| owner == faction)
+            .map(|(&(x, y), _)| map.terrain[y as usize][x as usize])
+            .collect();
+        let units: Vec<UnitType> = map.units.iter().filter(|u| u.faction == faction).map(|u| u.unit_type).collect();
+
+        report.factions.push(FactionReport {
+            faction,
+            bases: bases.len(),
+            properties: owned.len(),
+            income: owned.iter().map(|&t| game_data.terrain_income(t)).sum(),
+            units: units.len(),
+            unit_value: units.iter().map(|&t| unit_cost(game_data, t)).sum(),
+            base_to_neutral,
+            closer_neutrals,
+            reaches_enemy_hq,
+        });
+    }
+
+    // === OBJECTIVES ===
+    if map.rules.objectives.is_empty() && map.rules.turn_limit.is_none() {
+        report.error("no objectives and no turn limit, so the battle can't be won".to_string(), None);
+    }
+    let has_vip = |faction: Faction| map.units.iter().any(|u| u.vip && u.faction == faction);
+    for objective in &map.rules.objectives {
+        let name = objective.goal.name();
+        let sides: Vec<Faction> = factions.iter().copied().filter(|&f| objective.applies_to(f)).collect();
+        if let Some(faction) = objective.faction.filter(|f| !factions.contains(f)) {
+            report.warning(format!("{} objective is for {:?}, which isn't on the map", name, faction), None);
+        }
+        match &objective.goal {
+            Objective::HoldTile { x, y, .. } => {
+                match map.get_terrain(*x, *y) {
+                    None => report.error(format!("{} tile is outside the map", name), Some((*x, *y))),
+                    Some(terrain) if !ground(terrain) => {
+                        report.error(format!("{} tile can't be reached on foot", name), Some((*x, *y)));
+                    }
+                    Some(_) => {}
+                }
+            }
+            Objective::EscortVip(exits) => {
+                if exits.is_empty() {
+                    report.error(format!("{} objective has no exit tiles", name), None);
+                }
+                for &(x, y) in exits.iter().filter(|&&(x, y)| !in_bounds(x, y)) {
+                    report.error(format!("{} exit is outside the map", name), Some((x, y)));
+                }
+                for &side in sides.iter().filter(|&&f| !has_vip(f)) {
+                    report.error(format!("{} objective, but {:?} has no VIP", name, side), None);
+                }
+            }
+            Objective::DestroyVip => {
+                for &side in &sides {
+                    if !factions.iter().any(|&f| f != side && has_vip(f)) {
+                        report.error(format!("{} objective, but {:?} has no enemy VIP to hunt", name, side), None);
+                    }
+                }
+            }
+            Objective::OwnProperties(count) => {
+                let capturable = tiles(map).filter(|&(x, y)| game_data.terrain_capturable(map.terrain[y as usize][x as usize])).count();
+                if *count as usize > capturable {
+                    report.error(format!("{} needs {} properties but the map has {}", name, count, capturable), None);
+                }
+            }
+            Objective::SurviveUntil(_) => {
+                // Every side with units left would meet it on the same turn
+                if objective.faction.is_none() {
+                    report.error(format!("{} objective must be for one side", name), None);
+                }
+            }
+            Objective::Eliminate | Objective::CaptureHq => {}
+        }
+    }
+
+    // === TRIGGERS ===
+    for trigger in &map.triggers {
+        let name = if trigger.name.is_empty() { "unnamed" } else { trigger.name.as_str() };
+        check_trigger_condition(&mut report, map, game_data, name, &trigger.when);
+        if trigger.actions.is_empty() {
+            report.warning(format!("trigger '{}' has no actions", name), None);
+        }
+        for action in &trigger.actions {
+            match action {
+                TriggerAction::SpawnUnits(placements) => {
+                    for unit in placements {
+                        match map.get_terrain(unit.x, unit.y) {
+                            None => report.error(format!("trigger '{}' spawns a unit outside the map", name), Some((unit.x, unit.y))),
+                            Some(terrain) if !game_data.is_passable(terrain, unit_class(game_data, unit.unit_type)) => {
+                                report.error(format!("trigger '{}' spawns {:?} on {}", name, unit.unit_type, game_data.terrain_name(terrain)), Some((unit.x, unit.y)));
+                            }
+                            Some(_) => {}
+                        }
+                    }
+                }
+                TriggerAction::RevealFog { region, .. } => check_region(&mut report, map, name, region),
+                TriggerAction::Dialogue { .. }
+                | TriggerAction::SetWeather { .. }
+                | TriggerAction::GrantFunds { .. }
+                | TriggerAction::Victory(_) => {}
+            }
+        }
+    }
+
+    // === FAIRNESS ===
+    report.asymmetry = asymmetry(&report.factions);
+    if report.asymmetry > 0.25 {
+        report.warning(format!("sides are {:.0}% asymmetric in income, army or expansion", report.asymmetry * 100.0), None);
+    }
+
+    report
+}
+
+/// Check a trigger condition refers to real tiles and properties
+fn check_trigger_condition(report: &mut MapReport, map: &MapData, game_data: &GameData, name: &str, condition: &TriggerCondition) {
+    match condition {
+        TriggerCondition::UnitEnters { region, .. } => check_region(report, map, name, region),
+        TriggerCondition::PropertyCaptured { x, y, by } => match map.get_terrain(*x, *y) {
+            Some(terrain) if game_data.terrain_capturable(terrain) => {
+                if map.properties.iter().any(|p| (p.x, p.y) == (*x, *y) && p.owner == *by) {
+                    report.warning(format!("trigger '{}' waits for a capture by {:?}, who starts with the property", name, by), Some((*x, *y)));
+                }
+            }
+            _ => report.error(format!("trigger '{}' waits for a capture on a tile that isn't a property", name), Some((*x, *y))),
+        },
+        TriggerCondition::All(conditions) | TriggerCondition::Any(conditions) => {
+            for condition in conditions {
+                check_trigger_condition(report, map, game_data, name, condition);
+            }
+        }
+        TriggerCondition::TurnReached { .. } | TriggerCondition::UnitCountBelow { .. } => {}
+    }
+}
+
+/// Check a trigger region overlaps the map
+fn check_region(report: &mut MapReport, map: &MapData, name: &str, region: &Region) {
+    if region.width == 0 || region.height == 0 || !region.tiles().any(|(x, y)| map.get_terrain(x, y).is_some()) {
+        report.error(format!("trigger '{}' uses a region that covers no tiles", name), Some((region.x, region.y)));
+    }
+}
+
+/// Validate map files from the command line (`--validate-map [PATH...]`).
+/// Without paths, checks the built-in maps and the map library.
+/// Returns the process exit code: 1 if any map is unplayable.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn validate_maps_cli(paths: &[String]) -> i32 {
+    let mut game_data = GameData::load_defaults();
+    game_data.load_mods();
+
+    let maps: Vec<Result<MapData, String>> = if paths.is_empty() {
+        MapId::all_builtin().into_iter().map(|id| Ok(get_builtin_map(id)))
+            .chain(MapLibrary::scan().maps.into_iter().map(|entry| Ok(entry.map)))
+            .collect()
+    } else {
+        paths.iter()
+            .map(|path| MapData::load_from_file(std::path::Path::new(path)).map_err(|e| format!("{}: {}", path, e)))
+            .collect()
+    };
+
+    let mut failed = false;
+    for map in maps {
+        match map {
+            Ok(map) => {
+                let report = validate_map(&map, &game_data);
+                failed |= !report.is_playable();
+                print!("{}", report.summary(&map));
+            }
+            Err(e) => {
+                failed = true;
+                eprintln!("{}", e);
+            }
+        }
+    }
+    if failed { 1 } else { 0 }
+}
+
+/// Largest relative gap between the best and worst side on any fairness metric
+fn asymmetry(factions: &[FactionReport]) -> f32 {
+    let gap = |values: Vec<f32>| -> f32 {
+        let max = values.iter().cloned().fold(0.0, f32::max);
+        let min = values.iter().cloned().fold(f32::MAX, f32::min);
+        if max <= 0.0 { 0.0 } else { (max - min) / max }
+    };
+    if factions.len() < 2 {
+        return 0.0;
+    }
+    [
+        gap(factions.iter().map(|f| f.income as f32).collect()),
+        gap(factions.iter().map(|f| f.unit_value as f32).collect()),
+        gap(factions.iter().map(|f| f.closer_neutrals as f32).collect()),
+    ]
+    .into_iter()
+    .fold(0.0, f32::max)
+}
+
+fn tiles(map: &MapData) -> impl Iterator<Item = (i32, i32)> + '_ {
+    (0..map.height as i32).flat_map(move |y| (0..map.width as i32).map(move |x| (x, y)))
+}
+
+/// Step counts over passable tiles from any of the starting tiles
+fn ground_distances(
+    map: &MapData,
+    starts: &[(i32, i32)],
+    passable: impl Fn(Terrain) -> bool,
+) -> HashMap<(i32, i32), u32> {
+    let mut distances: HashMap<(i32, i32), u32> = starts.iter().map(|&pos| (pos, 0)).collect();
+    let mut queue: VecDeque<(i32, i32)> = starts.iter().copied().collect();
+
+    while let Some((x, y)) = queue.pop_front() {
+        let next_distance = distances[&(x, y)] + 1;
+        for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            let next = (x + dx, y + dy);
+            let Some(terrain) = map.get_terrain(next.0, next.1) else { continue };
+            if passable(terrain) && !distances.contains_key(&next) {
+                distances.insert(next, next_distance);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+fn unit_class(game_data: &GameData, unit_type: UnitType) -> UnitClass {
+    game_data.unit_stats(unit_type).map(|s| s.class).unwrap_or_else(|| unit_type.class())
+}
+
+fn unit_cost(game_data: &GameData, unit_type: UnitType) -> u32 {
+    game_data.unit_stats(unit_type).map(|s| s.cost).unwrap_or_else(|| unit_type.cost())
+}
@@ -1,9 +1,13 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
-use super::{Faction, Terrain, UnitType};
+use super::{
+    Faction, MapObjective, ScoreMethod, Terrain, TilesetTheme, Trigger, UnitType, WeatherRule, default_objectives,
+    load_triggers,
+};
 
 /// Complete map definition including terrain, units, and metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,14 +22,82 @@ pub struct MapData {
     pub units: Vec<UnitPlacement>,
     /// Initial property ownership
     pub properties: Vec<PropertyOwnership>,
+    /// Rules the map is played under
+    #[serde(default)]
+    pub rules: MapRules,
+    /// Scripted events; also read from a `<map>.triggers.ron` file beside the map
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Funds a faction starts with when the map doesn't say otherwise
+pub const DEFAULT_STARTING_FUNDS: u32 = 100;
+
+/// Rules a map is played under. As a resource, the rules of the battle in progress.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapRules {
+    /// Starting funds per faction; factions not listed get `DEFAULT_STARTING_FUNDS`
+    pub starting_funds: HashMap<Faction, u32>,
+    /// Scales income from properties
+    pub income_multiplier: f32,
+    /// Forces fog of war on or off for the battle only; `None` keeps the player's setting
+    pub fog: Option<bool>,
+    pub weather: WeatherRule,
+    pub theme: TilesetTheme,
+    /// Ways to win; the first one a side meets ends the battle
+    pub objectives: Vec<MapObjective>,
+    /// The battle ends once this many turns have been played
+    pub turn_limit: Option<u32>,
+    /// How sides are ranked when the turn limit runs out
+    pub score: ScoreMethod,
+    /// Units no base may build
+    pub banned_units: Vec<UnitType>,
+    /// Number of players the map is made for
+    pub players: u32,
+}
+
+impl Default for MapRules {
+    fn default() -> Self {
+        Self {
+            starting_funds: HashMap::new(),
+            income_multiplier: 1.0,
+            fog: None,
+            weather: WeatherRule::Random,
+            theme: TilesetTheme::Woodland,
+            objectives: default_objectives(),
+            turn_limit: None,
+            score: ScoreMethod::Properties,
+            banned_units: Vec::new(),
+            players: 2,
+        }
+    }
+}
+
+impl MapRules {
+    pub fn starting_funds(&self, faction: Faction) -> u32 {
+        self.starting_funds.get(&faction).copied().unwrap_or(DEFAULT_STARTING_FUNDS)
+    }
+
+    pub fn is_banned(&self, unit_type: UnitType) -> bool {
+        self.banned_units.contains(&unit_type)
+    }
+
+    /// Whether a battle on turn `turn` has run past the turn limit
+    pub fn turn_limit_reached(&self, turn: u32) -> bool {
+        self.turn_limit.is_some_and(|limit| turn > limit)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UnitPlacement {
     pub unit_type: UnitType,
     pub faction: Faction,
     pub x: i32,
     pub y: i32,
+    /// Flagged for escort and assassination objectives
+    #[serde(default)]
+    pub vip: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +118,8 @@ impl MapData {
             terrain: vec![vec![Terrain::Grass; width as usize]; height as usize],
             units: Vec::new(),
             properties: Vec::new(),
+            rules: MapRules::default(),
+            triggers: Vec::new(),
         }
     }
 
@@ -67,7 +141,7 @@ impl MapData {
 
     /// Add a unit placement
     pub fn add_unit(&mut self, unit_type: UnitType, faction: Faction, x: i32, y: i32) {
-        self.units.push(UnitPlacement { unit_type, faction, x, y });
+        self.units.push(UnitPlacement { unit_type, faction, x, y, vip: false });
     }
 
     /// Add property ownership
@@ -84,12 +158,31 @@ impl MapData {
         Ok(())
     }
 
-    /// Load map from JSON file
+    /// Load map from JSON file, taking its triggers from a `.triggers.ron` file beside it if there is one
     pub fn load_from_file(path: &Path) -> Result<Self, String> {
         let json = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read map file: {}", e))?;
-        serde_json::from_str(&json)
-            .map_err(|e| format!("Failed to parse map: {}", e))
+        let mut map: MapData = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse map: {}", e))?;
+        let triggers_path = path.with_extension("triggers.ron");
+        if triggers_path.exists() {
+            map.triggers = load_triggers(&triggers_path)?;
+        }
+        Ok(map)
+    }
+
+    /// Factions with starting units or properties, in order of first appearance
+    pub fn factions(&self) -> Vec<Faction> {
+        let mut seen = HashSet::new();
+        self.units.iter().map(|u| u.faction)
+            .chain(self.properties.iter().map(|p| p.owner))
+            .filter(|f| seen.insert(*f))
+            .collect()
+    }
+
+    /// Number of factions with starting units or properties
+    pub fn player_count(&self) -> usize {
+        self.factions().len()
     }
 }
 
@@ -99,8 +192,8 @@ pub struct SelectedMap {
     pub map_id: MapId,
 }
 
-/// Identifier for built-in maps
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Identifier for built-in maps, or an index into the `MapLibrary`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum MapId {
     #[default]
     Woodland,
@@ -143,6 +236,194 @@ impl MapId {
     }
 }
 
+// ============================================================================
+// MAP LIBRARY
+// ============================================================================
+
+/// Directory scanned for custom maps on native builds
+pub const MAP_LIBRARY_DIR: &str = "maps";
+
+/// A custom map in the library
+#[derive(Debug, Clone)]
+pub struct LibraryMap {
+    /// File name without extension, used as the storage key
+    pub file_name: String,
+    pub map: MapData,
+}
+
+/// Custom maps saved from the editor (`maps/` natively, localStorage on the web)
+#[derive(Resource, Default)]
+pub struct MapLibrary {
+    pub maps: Vec<LibraryMap>,
+}
+
+impl MapLibrary {
+    /// Read every map in the library, skipping ones that fail to load
+    pub fn scan() -> Self {
+        let mut maps: Vec<LibraryMap> = list_library_maps()
+            .into_iter()
+            .filter_map(|file_name| match load_library_map(&file_name) {
+                Ok(map) => Some(LibraryMap { file_name, map }),
+                Err(e) => {
+                    warn!("Skipping library map '{}': {}", file_name, e);
+                    None
+                }
+            })
+            .collect();
+        maps.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        Self { maps }
+    }
+
+    /// Look up a custom map by its `MapId::Custom` index
+    pub fn get(&self, index: usize) -> Option<&LibraryMap> {
+        self.maps.get(index)
+    }
+
+    /// Map ids for every map in the library
+    pub fn ids(&self) -> impl Iterator<Item = MapId> + '_ {
+        (0..self.maps.len()).map(MapId::Custom)
+    }
+
+    /// Write a map into the library, replacing any map with the same file name.
+    /// New maps are appended so the ids of maps already listed stay valid. Returns the map's id.
+    pub fn save(&mut self, file_name: &str, map: &MapData) -> Result<MapId, String> {
+        let file_name = sanitize_file_name(file_name);
+        let json = serde_json::to_string_pretty(map)
+            .map_err(|e| format!("Failed to serialize map: {}", e))?;
+        save_library_map(&file_name, &json)?;
+
+        let entry = LibraryMap { file_name: file_name.clone(), map: map.clone() };
+        let index = match self.maps.iter().position(|m| m.file_name == file_name) {
+            Some(index) => {
+                self.maps[index] = entry;
+                index
+            }
+            None => {
+                self.maps.push(entry);
+                self.maps.len() - 1
+            }
+        };
+        Ok(MapId::Custom(index))
+    }
+
+    /// Display name for any map id
+    pub fn map_name(&self, id: MapId) -> String {
+        match id {
+            MapId::Custom(index) => self.get(index)
+                .map(|m| m.map.name.clone())
+                .unwrap_or_else(|| id.name().to_string()),
+            _ => id.name().to_string(),
+        }
+    }
+
+    /// Resolve a map id to its data, built-in or custom
+    pub fn resolve(&self, id: MapId) -> MapData {
+        match id {
+            MapId::Custom(index) => match self.get(index) {
+                Some(entry) => entry.map.clone(),
+                None => {
+                    warn!("Custom map {} not in library, using fallback", index);
+                    get_builtin_map(id)
+                }
+            },
+            _ => get_builtin_map(id),
+        }
+    }
+}
+
+/// Scan the map library once at startup
+pub fn load_map_library(mut library: ResMut<MapLibrary>) {
+    *library = MapLibrary::scan();
+    info!("Loaded {} custom map(s) from {}", library.maps.len(), library_location());
+}
+
+/// Keep file names to characters that are safe as paths and storage keys
+fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name.trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() { "custom_map".to_string() } else { cleaned }
+}
+
+/// Storage key prefix for library maps on the web
+#[cfg(target_arch = "wasm32")]
+const MAP_KEY_PREFIX: &str = "paw_and_claw_map_";
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    let window = web_sys::window().ok_or("No window object")?;
+    window
+        .local_storage()
+        .map_err(|_| "Failed to access localStorage")?
+        .ok_or_else(|| "localStorage not available".to_string())
+}
+
+/// File names of every map in the library
+#[cfg(target_arch = "wasm32")]
+fn list_library_maps() -> Vec<String> {
+    let Ok(storage) = local_storage() else { return Vec::new() };
+    let count = storage.length().unwrap_or(0);
+    (0..count)
+        .filter_map(|i| storage.key(i).ok().flatten())
+        .filter_map(|key| key.strip_prefix(MAP_KEY_PREFIX).map(str::to_string))
+        .collect()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn list_library_maps() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(MAP_LIBRARY_DIR) else { return Vec::new() };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_library_map(file_name: &str) -> Result<MapData, String> {
+    let json = local_storage()?
+        .get_item(&format!("{}{}", MAP_KEY_PREFIX, file_name))
+        .map_err(|_| "Failed to read from localStorage")?
+        .ok_or_else(|| format!("No map named {}", file_name))?;
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse map: {}", e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_library_map(file_name: &str) -> Result<MapData, String> {
+    let path = Path::new(MAP_LIBRARY_DIR).join(format!("{}.json", file_name));
+    MapData::load_from_file(&path)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_library_map(file_name: &str, json: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(&format!("{}{}", MAP_KEY_PREFIX, file_name), json)
+        .map_err(|_| "Failed to write to localStorage".to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_library_map(file_name: &str, json: &str) -> Result<(), String> {
+    fs::create_dir_all(MAP_LIBRARY_DIR)
+        .map_err(|e| format!("Failed to create map library: {}", e))?;
+    let path = Path::new(MAP_LIBRARY_DIR).join(format!("{}.json", file_name));
+    fs::write(&path, json)
+        .map_err(|e| format!("Failed to write map file: {}", e))
+}
+
+/// Where a library map is stored, for status messages
+#[cfg(target_arch = "wasm32")]
+pub fn library_location() -> String {
+    "browser storage".to_string()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn library_location() -> String {
+    format!("{}/", MAP_LIBRARY_DIR)
+}
+
 // ============================================================================
 // BUILT-IN MAPS
 // ============================================================================
@@ -359,7 +640,7 @@ fn create_twin_bases_map() -> MapData {
 /// The Fortress - Asymmetric map where one side defends a fortified position
 fn create_fortress_map() -> MapData {
     let mut map = MapData::new("The Fortress", 14, 12);
-    map.description = "Asymmetric assault map. Eastern attacks the Northern fortress!".to_string();
+    map.description = "Asymmetric assault map. Eastern breaches the Northern fortress!".to_string();
 
     // Northern fortress - heavily defended area (top right)
     // Walls of brambles
@@ -369,8 +650,9 @@ fn create_fortress_map() -> MapData {
     for y in 8..11 {
         map.set_terrain(8, y, Terrain::Brambles);
     }
-    // Gate opening
-    map.set_terrain(10, 8, Terrain::Grass);
+    // Barricaded gates - break them down to storm the fortress
+    map.set_terrain(10, 8, Terrain::Barricade);
+    map.set_terrain(8, 10, Terrain::Barricade);
 
     // Fortress interior - boulders for cover
     map.set_terrain(10, 10, Terrain::Boulder);
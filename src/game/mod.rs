@@ -2,12 +2,18 @@ use bevy::prelude::*;
 
 mod map;
 mod maps;
+mod map_edit;
+mod map_validation;
 mod unit;
 mod faction;
 mod combat;
 mod movement;
 mod turn;
 mod ai;
+mod ai_debug;
+mod controller;
+mod external_bot;
+mod gym;
 mod fog;
 mod commander;
 mod weather;
@@ -15,15 +21,26 @@ mod sprites;
 mod assets;
 mod save;
 mod modding;
+mod terrain_actions;
+mod objectives;
+mod triggers;
+mod campaign;
+mod roguelike;
 
 pub use map::*;
 pub use maps::*;
+pub use map_edit::*;
+pub use map_validation::*;
 pub use unit::*;
 pub use faction::*;
 pub use combat::*;
 pub use movement::*;
 pub use turn::*;
 pub use ai::*;
+pub use ai_debug::*;
+pub use controller::*;
+pub use external_bot::*;
+pub use gym::*;
 pub use fog::*;
 pub use commander::*;
 pub use weather::*;
@@ -31,6 +48,11 @@ pub use sprites::*;
 pub use assets::*;
 pub use save::*;
 pub use modding::*;
+pub use terrain_actions::*;
+pub use objectives::*;
+pub use triggers::*;
+pub use campaign::*;
+pub use roguelike::*;
 
 // Future: use crate::states::GameState;
 
@@ -43,13 +65,20 @@ impl Plugin for GamePlugin {
             .add_plugins(MapPlugin)
             .add_plugins(UnitPlugin)
             .add_plugins(TurnPlugin)
+            .add_plugins(ObjectivePlugin)
+            .add_plugins(TriggerPlugin)
+            .add_plugins(CampaignPlugin)
+            .add_plugins(RoguelikePlugin)
             .add_plugins(MovementPlugin)
             .add_plugins(CombatPlugin)
             .add_plugins(AiPlugin)
+            .add_plugins(ExternalBotPlugin)
+            .add_plugins(AiDebugPlugin)
             .add_plugins(FogPlugin)
             .add_plugins(CommanderPlugin)
             .add_plugins(WeatherPlugin)
             .add_plugins(SpritePlugin)
-            .add_plugins(SavePlugin);
+            .add_plugins(SavePlugin)
+            .add_plugins(TerrainActionPlugin);
     }
 }
@@ -5,6 +5,7 @@
 //! - Unit names, stats, descriptions
 //! - Terrain names, properties, colors
 //! - Commander names, abilities, stats
+//! - Terrain actions (bridging, clearing, tunnelling)
 //!
 //! For WASM builds, default data is embedded at compile time.
 //! For native builds, data is loaded from filesystem and can be overridden by mods.
@@ -13,7 +14,7 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::{Faction, UnitType, UnitClass, Terrain, CommanderId, AiPersonality};
+use super::{Faction, UnitType, UnitClass, Terrain, CommanderId, AiPersonality, CoBonuses};
 
 pub struct ModdingPlugin;
 
@@ -45,6 +46,12 @@ const DEFAULT_DAMAGE_TABLES_RON: &str = include_str!("../../assets/data/damage_t
 /// Default movement costs embedded at compile time
 const DEFAULT_MOVEMENT_COSTS_RON: &str = include_str!("../../assets/data/movement_costs.ron");
 
+/// Default terrain actions embedded at compile time
+const DEFAULT_TERRAIN_ACTIONS_RON: &str = include_str!("../../assets/data/terrain_actions.ron");
+
+/// Damage table column used for destructible terrain
+const STRUCTURE_DAMAGE_KEY: &str = "structure";
+
 // ============================================================================
 // DATA STRUCTURES
 // ============================================================================
@@ -117,6 +124,44 @@ pub struct TerrainData {
     pub tile_height: f32,
     /// Asset filename (without extension)
     pub asset_name: String,
+    /// Hit points if the terrain can be destroyed (0 = indestructible)
+    #[serde(default)]
+    pub hp: u32,
+    /// Terrain left behind when destroyed
+    #[serde(default)]
+    pub becomes: Option<Terrain>,
+    /// Blocks line of sight past this tile
+    #[serde(default)]
+    pub blocks_sight: bool,
+    /// Units here are only visible to adjacent enemies
+    #[serde(default)]
+    pub conceals: bool,
+    /// Extra vision for units standing here
+    #[serde(default)]
+    pub vantage: u32,
+}
+
+/// Moddable terrain action (build a bridge, clear brambles, dig a tunnel)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerrainActionData {
+    pub name: String,
+    pub description: String,
+    /// Terrain the target tile must have
+    pub from_terrain: Terrain,
+    /// Terrain the tile becomes when the work completes
+    pub to_terrain: Terrain,
+    /// Funds spent when the work is ordered
+    pub cost: u32,
+    /// Turns until the work completes (counted at the owner's turn start)
+    pub duration: u32,
+    /// Unit types allowed to perform this action
+    pub units: Vec<UnitType>,
+    /// Factions allowed to perform this action (empty = any faction)
+    #[serde(default)]
+    pub factions: Vec<Faction>,
+    /// Max distance to the partner Hollow when digging a tunnel (0 = not a tunnel)
+    #[serde(default)]
+    pub tunnel_range: u32,
 }
 
 /// CO Power effect types
@@ -237,6 +282,12 @@ pub struct MovementCostsRegistry {
     pub costs: HashMap<String, HashMap<String, u32>>,
 }
 
+/// Container for terrain actions, keyed by action id
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TerrainActionsRegistry {
+    pub actions: HashMap<String, TerrainActionData>,
+}
+
 // ============================================================================
 // GAME DATA RESOURCE
 // ============================================================================
@@ -250,6 +301,7 @@ pub struct GameData {
     pub commanders: CommandersRegistry,
     pub damage_tables: DamageTablesRegistry,
     pub movement_costs: MovementCostsRegistry,
+    pub terrain_actions: TerrainActionsRegistry,
     /// Whether mods have been loaded
     pub mods_loaded: bool,
     /// List of loaded mod names
@@ -277,6 +329,8 @@ impl GameData {
             .expect("Failed to parse embedded damage_tables.ron");
         let movement_costs = ron::from_str(DEFAULT_MOVEMENT_COSTS_RON)
             .expect("Failed to parse embedded movement_costs.ron");
+        let terrain_actions = ron::from_str(DEFAULT_TERRAIN_ACTIONS_RON)
+            .expect("Failed to parse embedded terrain_actions.ron");
 
         Self {
             factions,
@@ -285,6 +339,7 @@ impl GameData {
             commanders,
             damage_tables,
             movement_costs,
+            terrain_actions,
             mods_loaded: false,
             loaded_mods: Vec::new(),
         }
@@ -385,6 +440,22 @@ impl GameData {
                 }
             }
 
+            // Load terrain action overrides
+            let terrain_actions_path = path.join("terrain_actions.ron");
+            if terrain_actions_path.exists() {
+                if let Ok(content) = fs::read_to_string(&terrain_actions_path) {
+                    match ron::from_str::<TerrainActionsRegistry>(&content) {
+                        Ok(mod_actions) => {
+                            for (id, data) in mod_actions.actions {
+                                info!("  Overriding terrain action: {}", id);
+                                self.terrain_actions.actions.insert(id, data);
+                            }
+                        }
+                        Err(e) => warn!("Failed to parse {}: {}", terrain_actions_path.display(), e),
+                    }
+                }
+            }
+
             self.loaded_mods.push(mod_name);
         }
 
@@ -467,6 +538,12 @@ impl GameData {
             .unwrap_or(1.0)
     }
 
+    /// Price a faction pays to build a unit: data cost with faction and CO modifiers
+    pub fn build_cost(&self, unit_type: UnitType, faction: Faction, co_bonuses: &CoBonuses) -> u32 {
+        let base_cost = self.unit_stats(unit_type).map_or(unit_type.cost(), |s| s.cost);
+        (base_cost as f32 * self.faction_cost_modifier(faction) * co_bonuses.cost).round() as u32
+    }
+
     /// Get unit name
     pub fn unit_name(&self, unit_type: UnitType) -> &str {
         self.get_unit(unit_type)
@@ -535,6 +612,41 @@ impl GameData {
             .unwrap_or(0)
     }
 
+    /// Get hit points of destructible terrain (0 = indestructible)
+    pub fn terrain_hp(&self, terrain: Terrain) -> u32 {
+        self.get_terrain(terrain)
+            .map(|t| t.hp)
+            .unwrap_or(0)
+    }
+
+    /// Get the terrain a destroyed tile turns into
+    pub fn terrain_becomes(&self, terrain: Terrain) -> Option<Terrain> {
+        self.get_terrain(terrain)
+            .and_then(|t| t.becomes)
+            .filter(|_| self.terrain_hp(terrain) > 0)
+    }
+
+    /// Check if terrain blocks line of sight
+    pub fn terrain_blocks_sight(&self, terrain: Terrain) -> bool {
+        self.get_terrain(terrain)
+            .map(|t| t.blocks_sight)
+            .unwrap_or(false)
+    }
+
+    /// Check if terrain hides units from non-adjacent enemies
+    pub fn terrain_conceals(&self, terrain: Terrain) -> bool {
+        self.get_terrain(terrain)
+            .map(|t| t.conceals)
+            .unwrap_or(false)
+    }
+
+    /// Get extra vision granted by standing on terrain
+    pub fn terrain_vantage(&self, terrain: Terrain) -> u32 {
+        self.get_terrain(terrain)
+            .map(|t| t.vantage)
+            .unwrap_or(0)
+    }
+
     /// Get commander name
     pub fn commander_name(&self, id: CommanderId) -> &str {
         self.get_commander(id)
@@ -582,6 +694,15 @@ impl GameData {
         self.get_base_damage(attacker, defender).is_some()
     }
 
+    /// Get base damage percentage against destructible terrain
+    /// Returns None if the unit cannot attack structures
+    pub fn get_structure_damage(&self, attacker: UnitType) -> Option<u32> {
+        self.damage_tables.tables
+            .get(unit_type_to_key(attacker))
+            .and_then(|targets| targets.get(STRUCTURE_DAMAGE_KEY))
+            .copied()
+    }
+
     // === MOVEMENT COST LOOKUPS ===
 
     /// Get movement cost for a unit class on a terrain type
@@ -613,6 +734,20 @@ impl GameData {
             .map(|cost| cost < 99)
             .unwrap_or(true) // Default to passable if no entry
     }
+
+    // === TERRAIN ACTION LOOKUPS ===
+
+    /// Get terrain action data by id
+    pub fn get_terrain_action(&self, id: &str) -> Option<&TerrainActionData> {
+        self.terrain_actions.actions.get(id)
+    }
+
+    /// Check if a unit of the given faction may perform a terrain action
+    pub fn can_perform_terrain_action(&self, id: &str, unit_type: UnitType, faction: Faction) -> bool {
+        self.get_terrain_action(id)
+            .map(|a| a.units.contains(&unit_type) && (a.factions.is_empty() || a.factions.contains(&faction)))
+            .unwrap_or(false)
+    }
 }
 
 // ============================================================================
@@ -667,8 +802,10 @@ fn terrain_to_key(terrain: Terrain) -> &'static str {
         Terrain::Pond => "pond",
         Terrain::Shore => "shore",
         Terrain::Base => "base",
+        Terrain::HQ => "hq",
         Terrain::Outpost => "outpost",
         Terrain::Storehouse => "storehouse",
+        Terrain::Barricade => "barricade",
     }
 }
 
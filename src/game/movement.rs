@@ -3,9 +3,17 @@ use bevy::ecs::system::SystemParam;
 use bevy_egui::input::EguiWantsInput;
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use super::{GameMap, GridPosition, Unit, FactionMember, TurnState, TurnPhase, AttackEvent, Tile, Terrain, TILE_SIZE, GameResult, Commanders, Weather, UnitAnimation, Faction, GameData, UnitClass};
+use super::{GameMap, GridPosition, Unit, FactionMember, TurnState, TurnPhase, AttackEvent, Tile, Terrain, TILE_SIZE, GameResult, Commanders, Weather, UnitAnimation, Faction, GameData, UnitClass, FogOfWar};
 use crate::states::GameState;
 
+/// Message fired when a moving unit runs into a hidden enemy and is stopped
+#[derive(Message)]
+pub struct TrapEvent {
+    pub unit: Entity,
+    pub position: (i32, i32),  // Where the unit was stopped
+    pub ambusher: (i32, i32),  // The hidden enemy that blocked the way
+}
+
 /// Message to cancel a unit's move and return it to original position
 #[derive(Message)]
 pub struct CancelMoveEvent {
@@ -19,6 +27,7 @@ pub struct GameStateContext<'w> {
     pub game_result: Res<'w, GameResult>,
     pub commanders: Res<'w, Commanders>,
     pub weather: Res<'w, Weather>,
+    pub fog: Res<'w, FogOfWar>,
 }
 
 /// Bundled input state for systems with many parameters
@@ -135,8 +144,11 @@ impl MovementPath {
         let dx = (pos.x - last.x).abs();
         let dy = (pos.y - last.y).abs();
 
-        if (dx == 1 && dy == 0) || (dx == 0 && dy == 1) {
-            // Adjacent - add to path
+        let through_tunnel = unit_class == UnitClass::Foot
+            && map.tunnel_exits(last.x, last.y).contains(&(pos.x, pos.y));
+
+        if (dx == 1 && dy == 0) || (dx == 0 && dy == 1) || through_tunnel {
+            // Adjacent (or linked by tunnel) - add to path
             self.path.push(pos);
             self.total_cost += tile_costs.get(&(pos.x, pos.y)).copied().unwrap_or(1);
             return true;
@@ -172,6 +184,16 @@ pub fn screen_to_grid(
     camera_transform: &GlobalTransform,
     map: &GameMap,
 ) -> Option<IVec2> {
+    let hit = cursor_ground_hit(window, camera, camera_transform)?;
+    world_to_grid(hit, map.width, map.height)
+}
+
+/// Point on the Y=0 ground plane under the mouse cursor
+pub fn cursor_ground_hit(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec3> {
     let cursor_pos = window.cursor_position()?;
     let ray = camera.viewport_to_world(camera_transform, cursor_pos).ok()?;
 
@@ -186,16 +208,19 @@ pub fn screen_to_grid(
     if t < 0.0 {
         return None; // Intersection behind camera
     }
-    let hit = ray.origin + ray.direction * t;
+    Some(ray.origin + ray.direction * t)
+}
 
-    // Convert world position to grid coordinates
-    let offset_x = -(map.width as f32 * TILE_SIZE) / 2.0;
-    let offset_z = -(map.height as f32 * TILE_SIZE) / 2.0;
+/// Grid tile containing a world position on a board of the given size,
+/// centred on the origin like every spawned board
+pub fn world_to_grid(hit: Vec3, width: u32, height: u32) -> Option<IVec2> {
+    let offset_x = -(width as f32 * TILE_SIZE) / 2.0;
+    let offset_z = -(height as f32 * TILE_SIZE) / 2.0;
 
     let gx = ((hit.x - offset_x) / TILE_SIZE).floor() as i32;
     let gy = ((hit.z - offset_z) / TILE_SIZE).floor() as i32;  // World Z -> Grid Y
 
-    if gx >= 0 && gx < map.width as i32 && gy >= 0 && gy < map.height as i32 {
+    if gx >= 0 && gx < width as i32 && gy >= 0 && gy < height as i32 {
         Some(IVec2::new(gx, gy))
     } else {
         None
@@ -207,6 +232,7 @@ pub struct MovementPlugin;
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<CancelMoveEvent>()
+            .add_message::<TrapEvent>()
             .init_resource::<MovementHighlights>()
             .init_resource::<GridCursor>()
             .init_resource::<CursorRepeat>()
@@ -214,16 +240,19 @@ impl Plugin for MovementPlugin {
             .init_resource::<ProductionState>()
             .init_resource::<CameraZoom>()
             .init_resource::<CameraAngle>()
+            .init_resource::<CameraBounds>()
             .init_resource::<InputMode>()
             .init_resource::<MovementPath>()
             // Split systems into smaller groups to avoid tuple size limits
             // Camera systems can run independently
+            // The editor shares the battle camera controls
             .add_systems(Update, (
+                update_camera_bounds.run_if(in_state(GameState::Battle)),
                 handle_camera_movement,
                 handle_camera_zoom,
                 handle_camera_angle_toggle,
                 update_camera_angle,
-            ).run_if(in_state(GameState::Battle)))
+            ).chain().run_if(in_state(GameState::Battle).or(in_state(GameState::Editor))))
             // Input handling - registered separately due to parameter count limits
             .add_systems(Update, handle_keyboard_input.run_if(in_state(GameState::Battle)))
             .add_systems(Update, handle_keyboard_path_drawing.run_if(in_state(GameState::Battle)))
@@ -249,7 +278,7 @@ impl Plugin for MovementPlugin {
 /// Check if unit animation is complete and transition to Action phase
 fn check_animation_complete(
     mut turn_state: ResMut<TurnState>,
-    pending_action: Res<PendingAction>,
+    mut pending_action: ResMut<PendingAction>,
     units: Query<&UnitAnimation>,
 ) {
     // Only check during Animating phase
@@ -264,6 +293,13 @@ fn check_animation_complete(
 
     // If unit no longer has animation component, animation is complete
     if units.get(unit_entity).is_err() {
+        // A trapped unit has already lost its action
+        if pending_action.trapped {
+            *pending_action = PendingAction::default();
+            turn_state.phase = TurnPhase::Select;
+            info!("Animation complete, trapped unit ends its turn");
+            return;
+        }
         turn_state.phase = TurnPhase::Action;
         info!("Animation complete, entering action phase");
     }
@@ -315,6 +351,47 @@ impl Default for CameraZoom {
     }
 }
 
+/// Board extents the camera is kept within, so large maps can be explored
+/// without losing the board off-screen
+#[derive(Resource)]
+pub struct CameraBounds {
+    /// Half the board size in world units (X, Z)
+    pub half_extent: Vec2,
+    /// Furthest zoom-out, scaled up for boards larger than 20 tiles
+    pub max_zoom: f32,
+}
+
+impl Default for CameraBounds {
+    fn default() -> Self {
+        Self::for_board(20, 16)
+    }
+}
+
+impl CameraBounds {
+    pub fn for_board(width: u32, height: u32) -> Self {
+        let longest = width.max(height) as f32;
+        Self {
+            half_extent: Vec2::new(width as f32, height as f32) * TILE_SIZE / 2.0,
+            max_zoom: CameraZoom::default().max * (longest / 20.0).max(1.0),
+        }
+    }
+
+    /// Keep the look-at point over the board
+    pub fn clamp(&self, point: Vec2) -> Vec2 {
+        point.clamp(-self.half_extent, self.half_extent)
+    }
+}
+
+/// Fit the camera bounds to the battle map whenever it changes
+fn update_camera_bounds(
+    map: Res<GameMap>,
+    mut bounds: ResMut<CameraBounds>,
+) {
+    if map.is_changed() {
+        *bounds = CameraBounds::for_board(map.width, map.height);
+    }
+}
+
 /// Resource for cursor key repeat timing
 #[derive(Resource)]
 pub struct CursorRepeat {
@@ -389,6 +466,7 @@ pub struct PendingAction {
     pub can_join: bool,            // Whether the unit can join another unit
     pub join_target: Option<Entity>, // The unit entity that can be joined
     pub original_position: Option<(i32, i32)>, // Where unit was before moving (for cancel)
+    pub trapped: bool,             // Unit ran into a hidden enemy and loses its action
 }
 
 /// Tracks when production menu should be shown
@@ -427,12 +505,132 @@ impl Default for GridCursor {
     }
 }
 
-/// Calculate which enemies can be attacked from current position
+/// Collect destructible terrain that can be targeted as (tile entity, position, owner)
+/// Neutral properties are left out - only enemy-held outposts can be sieged
+pub fn structure_targets<'a>(
+    tiles: impl Iterator<Item = (Entity, &'a Tile)>,
+    game_data: &GameData,
+) -> Vec<(Entity, GridPosition, Option<Faction>)> {
+    tiles
+        .filter(|(_, tile)| game_data.terrain_hp(tile.terrain) > 0)
+        .filter(|(_, tile)| !(tile.terrain.is_capturable() && tile.owner.is_none()))
+        .map(|(e, tile)| (e, GridPosition::new(tile.position.x, tile.position.y), tile.owner))
+        .collect()
+}
+
+/// Whether a unit is known to the viewing faction (own units are always known)
+fn is_known_to(fog: &FogOfWar, viewer: Faction, owner: Faction, x: i32, y: i32) -> bool {
+    owner == viewer || !fog.is_hidden_from(viewer, x, y)
+}
+
+/// Walk a planned route and find the first tile held by a hidden enemy
+/// Returns the index of the last safe tile and the ambusher's position
+pub fn find_trap(route: &[IVec2], hidden_enemies: &HashSet<(i32, i32)>) -> Option<(usize, IVec2)> {
+    route.iter()
+        .enumerate()
+        .skip(1)
+        .find(|(_, p)| hidden_enemies.contains(&(p.x, p.y)))
+        .map(|(i, p)| (i - 1, *p))
+}
+
+/// Where a unit moving along a route stops when a hidden enemy lies in wait on it:
+/// the last tile before the enemy that no other unit holds, falling back to its start
+/// Returns the stop's index in the route and the ambusher's position
+pub fn trap_stop(
+    route: &[IVec2],
+    hidden_enemies: &HashSet<(i32, i32)>,
+    occupied: &HashSet<(i32, i32)>,
+) -> Option<(usize, IVec2)> {
+    let (mut safe_index, ambusher) = find_trap(route, hidden_enemies)?;
+    while safe_index > 0 && occupied.contains(&(route[safe_index].x, route[safe_index].y)) {
+        safe_index -= 1;
+    }
+    Some((safe_index, ambusher))
+}
+
+/// Rebuild the cheapest route to a destination from BFS tile costs
+/// Falls back to a direct hop when the costs don't lead back to the start
+pub fn route_from_costs(
+    start: IVec2,
+    dest: IVec2,
+    tile_costs: &HashMap<(i32, i32), u32>,
+    map: &GameMap,
+    unit_class: UnitClass,
+    game_data: &GameData,
+) -> Vec<IVec2> {
+    let mut route = vec![dest];
+    let mut current = dest;
+    while current != start {
+        let Some(&cost) = tile_costs.get(&(current.x, current.y)) else {
+            return vec![start, dest];
+        };
+        let step = map.get(current.x, current.y)
+            .map(|t| game_data.movement_cost_or_default(t, unit_class))
+            .unwrap_or(1);
+
+        let mut neighbors: Vec<(i32, i32)> = [(0, 1), (0, -1), (1, 0), (-1, 0)].iter()
+            .map(|(dx, dy)| (current.x + dx, current.y + dy))
+            .collect();
+        if unit_class == UnitClass::Foot {
+            neighbors.extend(map.tunnel_exits(current.x, current.y));
+        }
+
+        let prev = neighbors.into_iter()
+            .find(|n| cost > 0 && step <= cost && tile_costs.get(n) == Some(&(cost - step)));
+        let Some((px, py)) = prev else {
+            return vec![start, dest];
+        };
+        current = IVec2::new(px, py);
+        route.push(current);
+    }
+    route.reverse();
+    route
+}
+
+/// Check a planned move for hidden enemies, cutting the path short at the first one
+/// Updates the destination and returns the ambusher's position if the unit is trapped
+fn spring_trap(
+    path: &mut MovementPath,
+    start: IVec2,
+    dest: &mut (i32, i32),
+    hidden_enemies: &HashSet<(i32, i32)>,
+    occupied: &HashSet<(i32, i32)>,
+    tile_costs: &HashMap<(i32, i32), u32>,
+    map: &GameMap,
+    unit_class: UnitClass,
+    game_data: &GameData,
+) -> Option<(i32, i32)> {
+    let target = IVec2::new(dest.0, dest.1);
+    let route = if path.path.len() >= 2 && path.destination() == Some(target) {
+        path.path.clone()
+    } else {
+        route_from_costs(start, target, tile_costs, map, unit_class, game_data)
+    };
+
+    let (safe_index, ambusher) = trap_stop(&route, hidden_enemies, occupied)?;
+    path.path = route[..=safe_index].to_vec();
+    path.recalculate_cost(map, unit_class, game_data);
+
+    let stop = route[safe_index];
+    *dest = (stop.x, stop.y);
+    Some((ambusher.x, ambusher.y))
+}
+
+/// Find a targeted destructible tile at a grid position
+fn structure_at(tiles: &Query<(Entity, &Tile)>, x: i32, y: i32, targets: &HashSet<Entity>) -> Option<Entity> {
+    tiles.iter()
+        .find(|(e, t)| t.position.x == x && t.position.y == y && targets.contains(e))
+        .map(|(e, _)| e)
+}
+
+/// Calculate which enemies and destructible tiles can be attacked from current position
 pub fn calculate_attack_targets(
     attacker: &Unit,
     attacker_pos: &GridPosition,
     attacker_faction: &FactionMember,
     units: &[(Entity, GridPosition, FactionMember)],
+    structures: &[(Entity, GridPosition, Option<Faction>)],
+    game_data: &GameData,
 ) -> HashSet<Entity> {
     let mut targets = HashSet::new();
     let stats = attacker.unit_type.stats();
@@ -456,6 +654,23 @@ pub fn calculate_attack_targets(
         }
     }
 
+    // Structures need their own damage table entry
+    if game_data.get_structure_damage(attacker.unit_type).is_none() {
+        return targets;
+    }
+
+    for (entity, pos, owner) in structures {
+        // Can't attack own properties
+        if *owner == Some(attacker_faction.faction) {
+            continue;
+        }
+
+        let distance = attacker_pos.distance_to(pos);
+        if distance >= min_range && distance <= max_range {
+            targets.insert(*entity);
+        }
+    }
+
     targets
 }
 
@@ -506,10 +721,13 @@ pub fn calculate_movement_range_with_costs(
             reachable.insert((x, y));
         }
 
-        for (dx, dy) in directions {
-            let nx = x + dx;
-            let ny = y + dy;
+        // Foot units can also pass through dug tunnels
+        let mut neighbors: Vec<(i32, i32)> = directions.iter().map(|(dx, dy)| (x + dx, y + dy)).collect();
+        if unit_class == UnitClass::Foot {
+            neighbors.extend(map.tunnel_exits(x, y));
+        }
 
+        for (nx, ny) in neighbors {
             if let Some(terrain) = map.get(nx, ny) {
                 // Use GameData for movement cost based on unit class
                 let move_cost = game_data.movement_cost_or_default(terrain, unit_class);
@@ -548,9 +766,9 @@ pub fn calculate_movement_range_with_joins(
     start: &GridPosition,
     movement: u32,
     map: &GameMap,
-    moving_unit_type: super::UnitType,
+    moving_unit: &Unit,
     moving_faction: Faction,
-    all_units: &[(Entity, (i32, i32), Faction, super::UnitType)],
+    all_units: &[(Entity, (i32, i32), Faction, &Unit)],
     unit_class: UnitClass,
     game_data: &GameData,
 ) -> (HashSet<(i32, i32)>, HashMap<(i32, i32), u32>) {
@@ -563,11 +781,11 @@ pub fn calculate_movement_range_with_joins(
     let mut blocked: HashSet<(i32, i32)> = HashSet::new();
     let mut joinable: HashSet<(i32, i32)> = HashSet::new();
 
-    for (_, (x, y), faction, unit_type) in all_units {
+    for (_, (x, y), faction, unit) in all_units {
         if (*x, *y) == (start.x, start.y) {
             continue; // Skip self
         }
-        if *faction == moving_faction && *unit_type == moving_unit_type {
+        if *faction == moving_faction && unit.can_join(moving_unit) {
             joinable.insert((*x, *y));
         } else {
             blocked.insert((*x, *y));
@@ -584,10 +802,13 @@ pub fn calculate_movement_range_with_joins(
             reachable.insert((x, y));
         }
 
-        for (dx, dy) in directions {
-            let nx = x + dx;
-            let ny = y + dy;
+        // Foot units can also pass through dug tunnels
+        let mut neighbors: Vec<(i32, i32)> = directions.iter().map(|(dx, dy)| (x + dx, y + dy)).collect();
+        if unit_class == UnitClass::Foot {
+            neighbors.extend(map.tunnel_exits(x, y));
+        }
 
+        for (nx, ny) in neighbors {
             if let Some(terrain) = map.get(nx, ny) {
                 // Use GameData for movement cost based on unit class
                 let move_cost = game_data.movement_cost_or_default(terrain, unit_class);
@@ -638,6 +859,7 @@ fn handle_keyboard_input(
     tiles: Query<(Entity, &Tile)>,
     map: Res<GameMap>,
     mut attack_events: MessageWriter<AttackEvent>,
+    mut trap_events: MessageWriter<TrapEvent>,
     game_ctx: GameStateContext,
     game_data: Res<GameData>,
     mut input: InputState,
@@ -783,21 +1005,26 @@ fn handle_keyboard_input(
                 let weather_movement = game_ctx.weather.apply_movement(base_movement);
                 let total_movement = effective_movement(weather_movement, unit.stamina);
 
+                // Destructible terrain, collected before the tile query is shadowed
+                let structures = structure_targets(tiles.iter(), &game_data);
+
                 // Build unit list for join-aware movement
                 let all_unit_info: Vec<_> = units.iter()
-                    .map(|(e, p, _, f, u)| (e, (p.x, p.y), f.faction, u.unit_type))
+                    .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
+                    .map(|(e, p, _, f, u)| (e, (p.x, p.y), f.faction, u))
                     .collect();
                 let unit_class = stats.class;
                 let (tiles, tile_costs) = calculate_movement_range_with_joins(
-                    &pos, total_movement, &map, unit.unit_type, faction.faction, &all_unit_info,
+                    &pos, total_movement, &map, &unit, faction.faction, &all_unit_info,
                     unit_class, &game_data
                 );
 
                 // Calculate attack targets
                 let all_units: Vec<_> = units.iter()
+                    .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
                     .map(|(e, p, _, f, _)| (e, p.clone(), f.clone()))
                     .collect();
-                let attack_targets = calculate_attack_targets(&unit, &pos, &faction, &all_units);
+                let attack_targets = calculate_attack_targets(&unit, &pos, &faction, &all_units, &structures, &game_data);
 
                 highlights.selected_unit = Some(entity);
                 highlights.selected_unit_class = Some(unit_class);
@@ -838,7 +1065,8 @@ fn handle_keyboard_input(
             // Check if target is an attack target (before moving)
             let target_entity = units.iter()
                 .find(|(e, p, _, _, _)| p.x == target_x && p.y == target_y && highlights.attack_targets.contains(e))
-                .map(|(e, _, _, _, _)| e);
+                .map(|(e, _, _, _, _)| e)
+                .or_else(|| structure_at(&tiles, target_x, target_y, &highlights.attack_targets));
 
             if let Some(target) = target_entity {
                 // Attack!
@@ -863,16 +1091,34 @@ fn handle_keyboard_input(
 
             // Try to move to path destination or cursor position
             if highlights.tiles.contains(&(target_x, target_y)) {
+                // Follow the planned route - a hidden enemy on the way springs a trap
+                let hidden_enemies: HashSet<(i32, i32)> = units.iter()
+                    .filter(|(_, p, _, f, _)| !is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
+                    .map(|(_, p, _, _, _)| (p.x, p.y))
+                    .collect();
+                let occupied: HashSet<(i32, i32)> = units.iter()
+                    .filter(|(e, _, _, _, _)| *e != selected_entity)
+                    .map(|(_, p, _, _, _)| (p.x, p.y))
+                    .collect();
+                let mut dest = (target_x, target_y);
+                let trap = units.get(selected_entity).ok().and_then(|(_, p, _, _, u)| {
+                    spring_trap(
+                        &mut input.movement_path, IVec2::new(p.x, p.y), &mut dest, &hidden_enemies, &occupied,
+                        &highlights.tile_costs, &map, u.unit_type.stats().class, &game_data,
+                    )
+                });
+                let (target_x, target_y) = dest;
+
                 // Update cursor to match target
                 input.cursor.x = target_x;
                 input.cursor.y = target_y;
                 // Check if target has a joinable unit (friendly same-type)
-                let selected_unit_info = units.get(selected_entity).map(|(_, _, _, f, u)| (f.faction, u.unit_type));
+                let selected_unit_info = units.get(selected_entity).map(|(_, _, _, f, u)| (f.faction, u));
                 let joinable_unit = units.iter()
                     .find(|(e, p, _, f, u)| {
                         *e != selected_entity
                             && p.x == input.cursor.x && p.y == input.cursor.y
-                            && selected_unit_info.map_or(false, |(sf, su)| f.faction == sf && u.unit_type == su)
+                            && selected_unit_info.is_some_and(|(sf, su)| f.faction == sf && u.can_join(su))
                     })
                     .map(|(e, _, _, _, _)| e);
 
@@ -938,11 +1184,43 @@ fn handle_keyboard_input(
                         return;
                     }
 
+                    // Trap! The unit stops short of the hidden enemy and loses its action
+                    if let Some(ambusher) = trap {
+                        if let Ok((_, _, _, _, mut unit)) = units.get_mut(selected_entity) {
+                            unit.exhausted = true;
+                        }
+                        trap_events.write(TrapEvent {
+                            unit: selected_entity,
+                            position: (input.cursor.x, input.cursor.y),
+                            ambusher,
+                        });
+                        info!("Trap! Unit stopped at ({}, {}) by a hidden enemy at {:?}", input.cursor.x, input.cursor.y, ambusher);
+
+                        highlights.selected_unit = None;
+                        highlights.selected_unit_class = None;
+                        highlights.tiles.clear();
+                        highlights.tile_costs.clear();
+                        highlights.attack_targets.clear();
+                        input.movement_path.clear();
+
+                        *pending_action = PendingAction::default();
+                        if staying_in_place {
+                            turn_state.phase = TurnPhase::Select;
+                        } else {
+                            pending_action.unit = Some(selected_entity);
+                            pending_action.trapped = true;
+                            turn_state.phase = TurnPhase::Animating;
+                        }
+                        return;
+                    }
+
                     // Calculate attack targets from current/new position
                     let all_units: Vec<_> = units.iter()
+                        .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
                         .map(|(e, p, _, f, _)| (e, p.clone(), f.clone()))
                         .collect();
-                    let targets = calculate_attack_targets(&unit_copy, &new_pos, &faction_copy, &all_units);
+                    let structures = structure_targets(tiles.iter(), &game_data);
+                    let targets = calculate_attack_targets(&unit_copy, &new_pos, &faction_copy, &all_units, &structures, &game_data);
 
                     // Check if unit can capture the tile
                     let (can_capture, capture_tile) = if unit_copy.unit_type.stats().can_capture {
@@ -1005,21 +1283,26 @@ fn handle_keyboard_input(
                     // Limit movement by stamina
                     let total_movement = effective_movement(weather_movement, unit.stamina);
 
+                    // Destructible terrain, collected before the tile query is shadowed
+                    let structures = structure_targets(tiles.iter(), &game_data);
+
                     // Build unit list for join-aware movement
                     let all_unit_info: Vec<_> = units.iter()
-                        .map(|(e, p, _, f, u)| (e, (p.x, p.y), f.faction, u.unit_type))
+                        .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
+                        .map(|(e, p, _, f, u)| (e, (p.x, p.y), f.faction, u))
                         .collect();
                     let unit_class = unit.unit_type.stats().class;
                     let (tiles, tile_costs) = calculate_movement_range_with_joins(
-                        &pos, total_movement, &map, unit.unit_type, faction.faction, &all_unit_info,
+                        &pos, total_movement, &map, &unit, faction.faction, &all_unit_info,
                         unit_class, &game_data
                     );
 
                     // Calculate attack targets
                     let all_units: Vec<_> = units.iter()
+                        .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
                         .map(|(e, p, _, f, _)| (e, p.clone(), f.clone()))
                         .collect();
-                    let attack_targets = calculate_attack_targets(&unit, &pos, &faction, &all_units);
+                    let attack_targets = calculate_attack_targets(&unit, &pos, &faction, &all_units, &structures, &game_data);
 
                     found_unit = Some((entity, tiles, tile_costs, attack_targets, unit_class));
                     break;
@@ -1045,6 +1328,7 @@ fn handle_camera_movement(
     keyboard: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
     cursor: Res<GridCursor>,
+    bounds: Res<CameraBounds>,
     mut angle: ResMut<CameraAngle>,
 ) {
     let shift_held = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
@@ -1074,7 +1358,7 @@ fn handle_camera_movement(
     }
 
     if movement != Vec2::ZERO {
-        angle.look_at += movement.normalize() * speed;
+        angle.look_at = bounds.clamp(angle.look_at + movement.normalize() * speed);
     }
 }
 
@@ -1084,6 +1368,7 @@ fn handle_camera_zoom(
     mut scroll_events: EventReader<bevy::input::mouse::MouseWheel>,
     keyboard: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
+    bounds: Res<CameraBounds>,
     mut zoom: ResMut<CameraZoom>,
 ) {
     let mut zoom_delta = 0.0;
@@ -1110,7 +1395,8 @@ fn handle_camera_zoom(
 
     if zoom_delta.abs() > 0.0001 {
         // Update zoom level with clamping - camera position updated by update_camera_angle
-        zoom.current = (zoom.current + zoom_delta).clamp(zoom.min, zoom.max);
+        let max = zoom.max.max(bounds.max_zoom);
+        zoom.current = (zoom.current + zoom_delta).clamp(zoom.min, max);
     }
 }
 
@@ -1242,6 +1528,7 @@ fn handle_click_input(
     mut turn_state: ResMut<TurnState>,
     map: Res<GameMap>,
     mut attack_events: MessageWriter<AttackEvent>,
+    mut trap_events: MessageWriter<TrapEvent>,
     game_ctx: GameStateContext,
     mut input: InputState,
     game_data: Res<GameData>,
@@ -1275,7 +1562,8 @@ fn handle_click_input(
         if let Some(acting_entity) = pending_action.unit {
             let target_entity = units.iter()
                 .find(|(e, p, _, _, _)| p.x == grid_x && p.y == grid_y && pending_action.targets.contains(e))
-                .map(|(e, _, _, _, _)| e);
+                .map(|(e, _, _, _, _)| e)
+                .or_else(|| structure_at(&tiles, grid_x, grid_y, &pending_action.targets));
 
             if let Some(target) = target_entity {
                 // Attack!
@@ -1319,7 +1607,8 @@ fn handle_click_input(
         // Check if clicking on an attack target (before moving)
         let target_entity = units.iter()
             .find(|(e, p, _, _, _)| p.x == grid_x && p.y == grid_y && highlights.attack_targets.contains(e))
-            .map(|(e, _, _, _, _)| e);
+            .map(|(e, _, _, _, _)| e)
+            .or_else(|| structure_at(&tiles, grid_x, grid_y, &highlights.attack_targets));
 
         if let Some(target) = target_entity {
             // Attack!
@@ -1395,19 +1684,22 @@ fn handle_click_input(
                 {
                     let unit_positions: HashMap<(i32, i32), Entity> = units
                         .iter()
+                        .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
                         .map(|(e, p, _, _, _)| ((p.x, p.y), e))
                         .collect();
                     let stats = unit.unit_type.stats();
                     let co_bonuses = game_ctx.commanders.get_bonuses(turn_state.current_faction);
                     let base_movement = (stats.movement as i32 + co_bonuses.movement).max(1) as u32;
                     let total_movement = game_ctx.weather.apply_movement(base_movement);
+                    let structures = structure_targets(tiles.iter(), &game_data);
                     let tiles = calculate_movement_range(&pos, total_movement, &map, &unit_positions, stats.class, &game_data);
 
                     // Calculate attack targets
                     let all_units: Vec<_> = units.iter()
+                        .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
                         .map(|(e, p, _, f, _)| (e, p.clone(), f.clone()))
                         .collect();
-                    let attack_targets = calculate_attack_targets(&unit, &pos, &faction, &all_units);
+                    let attack_targets = calculate_attack_targets(&unit, &pos, &faction, &all_units, &structures, &game_data);
 
                     switch_to = Some((entity, tiles, attack_targets, stats.class));
                     break;
@@ -1425,13 +1717,33 @@ fn handle_click_input(
                 return;
             }
 
+            // Follow the planned route - a hidden enemy on the way springs a trap
+            let hidden_enemies: HashSet<(i32, i32)> = units.iter()
+                .filter(|(_, p, _, f, _)| !is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
+                .map(|(_, p, _, _, _)| (p.x, p.y))
+                .collect();
+            let occupied: HashSet<(i32, i32)> = units.iter()
+                .filter(|(e, _, _, _, _)| *e != selected_entity)
+                .map(|(_, p, _, _, _)| (p.x, p.y))
+                .collect();
+            let mut dest = (grid_x, grid_y);
+            let trap = units.get(selected_entity).ok().and_then(|(_, p, _, _, u)| {
+                spring_trap(
+                    &mut input.movement_path, IVec2::new(p.x, p.y), &mut dest, &hidden_enemies, &occupied,
+                    &highlights.tile_costs, &map, u.unit_type.stats().class, &game_data,
+                )
+            });
+            let (grid_x, grid_y) = dest;
+            input.cursor.x = grid_x;
+            input.cursor.y = grid_y;
+
             // Check if target has a joinable unit (friendly same-type)
-            let selected_unit_info = units.get(selected_entity).map(|(_, _, _, f, u)| (f.faction, u.unit_type));
+            let selected_unit_info = units.get(selected_entity).map(|(_, _, _, f, u)| (f.faction, u));
             let joinable_unit = units.iter()
                 .find(|(e, p, _, f, u)| {
                     *e != selected_entity
                         && p.x == grid_x && p.y == grid_y
-                        && selected_unit_info.map_or(false, |(sf, su)| f.faction == sf && u.unit_type == su)
+                        && selected_unit_info.is_some_and(|(sf, su)| f.faction == sf && u.can_join(su))
                 })
                 .map(|(e, _, _, _, _)| e);
 
@@ -1491,11 +1803,43 @@ fn handle_click_input(
                 return;
             }
 
+            // Trap! The unit stops short of the hidden enemy and loses its action
+            if let Some(ambusher) = trap {
+                if let Ok((_, _, _, _, mut unit)) = units.get_mut(selected_entity) {
+                    unit.exhausted = true;
+                }
+                trap_events.write(TrapEvent {
+                    unit: selected_entity,
+                    position: (grid_x, grid_y),
+                    ambusher,
+                });
+                info!("Trap! Unit stopped at ({}, {}) by a hidden enemy at {:?}", grid_x, grid_y, ambusher);
+
+                highlights.selected_unit = None;
+                highlights.selected_unit_class = None;
+                highlights.tiles.clear();
+                highlights.tile_costs.clear();
+                highlights.attack_targets.clear();
+                input.movement_path.clear();
+
+                *pending_action = PendingAction::default();
+                if staying_in_place {
+                    turn_state.phase = TurnPhase::Select;
+                } else {
+                    pending_action.unit = Some(selected_entity);
+                    pending_action.trapped = true;
+                    turn_state.phase = TurnPhase::Animating;
+                }
+                return;
+            }
+
             // Calculate attack targets from new position
             let all_units: Vec<_> = units.iter()
+                .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
                 .map(|(e, p, _, f, _)| (e, p.clone(), f.clone()))
                 .collect();
-            let targets = calculate_attack_targets(&unit_copy, &new_pos, &faction_copy, &all_units);
+            let structures = structure_targets(tiles.iter(), &game_data);
+            let targets = calculate_attack_targets(&unit_copy, &new_pos, &faction_copy, &all_units, &structures, &game_data);
 
             // Check if unit can capture the tile it moved to
             let (can_capture, capture_tile) = if unit_copy.unit_type.stats().can_capture {
@@ -1561,18 +1905,21 @@ fn handle_click_input(
 
             // Build unit list for join-aware movement
             let all_unit_info: Vec<_> = units.iter()
-                .map(|(e, p, _, f, u)| (e, (p.x, p.y), f.faction, u.unit_type))
+                .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
+                .map(|(e, p, _, f, u)| (e, (p.x, p.y), f.faction, u))
                 .collect();
             let (move_tiles, move_costs) = calculate_movement_range_with_joins(
-                &pos, total_movement, &map, unit.unit_type, faction.faction, &all_unit_info,
+                &pos, total_movement, &map, &unit, faction.faction, &all_unit_info,
                 stats.class, &game_data
             );
 
             // Calculate attack targets
             let all_units: Vec<_> = units.iter()
+                .filter(|(_, p, _, f, _)| is_known_to(&game_ctx.fog, turn_state.current_faction, f.faction, p.x, p.y))
                 .map(|(e, p, _, f, _)| (e, p.clone(), f.clone()))
                 .collect();
-            let attack_targets = calculate_attack_targets(&unit, &pos, &faction, &all_units);
+            let structures = structure_targets(tiles.iter(), &game_data);
+            let attack_targets = calculate_attack_targets(&unit, &pos, &faction, &all_units, &structures, &game_data);
 
             select_unit = Some((entity, move_tiles, move_costs, attack_targets, pos.x, pos.y, stats.class));
             break;
@@ -1627,6 +1974,7 @@ fn spawn_movement_highlight_meshes(
     highlights: Res<MovementHighlights>,
     map: Res<GameMap>,
     units: Query<(&GridPosition, &FactionMember)>,
+    tiles: Query<&Tile>,
     existing_move_highlights: Query<Entity, With<MovementHighlightMesh>>,
     existing_attack_highlights: Query<Entity, With<AttackHighlightMesh>>,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -1691,7 +2039,11 @@ fn spawn_movement_highlight_meshes(
 
     // Spawn attack target highlight meshes
     for target_entity in &highlights.attack_targets {
-        if let Ok((pos, _)) = units.get(*target_entity) {
+        // Targets are either units or destructible tiles
+        let target_pos = units.get(*target_entity)
+            .map(|(p, _)| *p)
+            .or_else(|_| tiles.get(*target_entity).map(|t| GridPosition::new(t.position.x, t.position.y)));
+        if let Ok(pos) = target_pos {
             let world_x = pos.x as f32 * TILE_SIZE + offset_x;
             let world_z = pos.y as f32 * TILE_SIZE + offset_z;
 
@@ -2106,6 +2458,7 @@ fn draw_action_targets(
     mut gizmos: Gizmos,
     map: Res<GameMap>,
     units: Query<&GridPosition>,
+    tiles: Query<&Tile>,
 ) {
     // Only draw during Action phase
     if turn_state.phase != TurnPhase::Action {
@@ -2120,7 +2473,10 @@ fn draw_action_targets(
 
     // Draw attack targets with pulsing red highlight
     for target_entity in &pending_action.targets {
-        if let Ok(pos) = units.get(*target_entity) {
+        let target_pos = units.get(*target_entity)
+            .copied()
+            .or_else(|_| tiles.get(*target_entity).map(|t| GridPosition::new(t.position.x, t.position.y)));
+        if let Ok(pos) = target_pos {
             let world_x = pos.x as f32 * TILE_SIZE + offset_x;
             let world_z = pos.y as f32 * TILE_SIZE + offset_z;
 
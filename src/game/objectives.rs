@@ -0,0 +1,322 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    Faction, FactionMember, GameResult, GridPosition, MapData, MapRules, Terrain, Tile, TurnStartEvent,
+    TurnState, Unit, VictoryType,
+};
+
+pub struct ObjectivePlugin;
+
+impl Plugin for ObjectivePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ObjectiveProgress>()
+            .add_systems(Update, (track_held_tiles, check_victory_condition).chain());
+    }
+}
+
+// ============================================================================
+// OBJECTIVES
+// ============================================================================
+
+/// A way to win a battle, set per map
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Objective {
+    /// Destroy every enemy unit
+    Eliminate,
+    /// Own every HQ (every Base, on maps without HQs)
+    CaptureHq,
+    /// Own at least this many properties
+    OwnProperties(u32),
+    /// Keep a unit on a tile through this many of the side's turn starts in a row
+    HoldTile { x: i32, y: i32, turns: u32 },
+    /// Still have units when this turn begins
+    SurviveUntil(u32),
+    /// Destroy every enemy VIP
+    DestroyVip,
+    /// Bring one of the side's VIPs onto any of these tiles
+    EscortVip(Vec<(i32, i32)>),
+}
+
+impl Objective {
+    /// One of each kind, with starting parameters, for the editor's palette
+    pub fn templates() -> Vec<Objective> {
+        vec![
+            Objective::Eliminate,
+            Objective::CaptureHq,
+            Objective::OwnProperties(10),
+            Objective::HoldTile { x: 0, y: 0, turns: 3 },
+            Objective::SurviveUntil(15),
+            Objective::DestroyVip,
+            Objective::EscortVip(vec![(0, 0)]),
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::Eliminate => "Eliminate",
+            Objective::CaptureHq => "Capture HQ",
+            Objective::OwnProperties(_) => "Own Properties",
+            Objective::HoldTile { .. } => "Hold Tile",
+            Objective::SurviveUntil(_) => "Survive",
+            Objective::DestroyVip => "Destroy VIP",
+            Objective::EscortVip(_) => "Escort VIP",
+        }
+    }
+
+    /// Short description for the HUD and battle setup
+    pub fn describe(&self) -> String {
+        match self {
+            Objective::Eliminate => "Destroy every enemy unit".to_string(),
+            Objective::CaptureHq => "Capture every HQ".to_string(),
+            Objective::OwnProperties(count) => format!("Own {} properties", count),
+            Objective::HoldTile { x, y, turns } => format!("Hold ({}, {}) for {} turns", x, y, turns),
+            Objective::SurviveUntil(turn) => format!("Survive until turn {}", turn),
+            Objective::DestroyVip => "Destroy the enemy VIP".to_string(),
+            Objective::EscortVip(exits) => match exits.as_slice() {
+                [(x, y)] => format!("Escort the VIP to ({}, {})", x, y),
+                _ => format!("Escort the VIP to one of {} exits", exits.len()),
+            },
+        }
+    }
+
+    fn victory_type(&self) -> VictoryType {
+        match self {
+            Objective::Eliminate => VictoryType::Elimination,
+            Objective::CaptureHq => VictoryType::HQCapture,
+            Objective::OwnProperties(_) => VictoryType::Properties,
+            Objective::HoldTile { .. } => VictoryType::HoldTile,
+            Objective::SurviveUntil(_) => VictoryType::Survival,
+            Objective::DestroyVip => VictoryType::VipDestroyed,
+            Objective::EscortVip(_) => VictoryType::VipEscorted,
+        }
+    }
+}
+
+/// An objective, for one side or for every side
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapObjective {
+    /// Side the objective is for; `None` means every side
+    #[serde(default)]
+    pub faction: Option<Faction>,
+    pub goal: Objective,
+}
+
+impl MapObjective {
+    pub fn for_all(goal: Objective) -> Self {
+        Self { faction: None, goal }
+    }
+
+    pub fn applies_to(&self, faction: Faction) -> bool {
+        self.faction.is_none_or(|f| f == faction)
+    }
+
+    /// The objective with its tiles moved by (dx, dy) onto a board of the given size;
+    /// `None` once none of its tiles are left on the board
+    pub fn shifted(&self, dx: i32, dy: i32, width: u32, height: u32) -> Option<MapObjective> {
+        let inside = |x: i32, y: i32| x >= 0 && y >= 0 && x < width as i32 && y < height as i32;
+        let goal = match &self.goal {
+            Objective::HoldTile { x, y, turns } => {
+                let (x, y) = (x + dx, y + dy);
+                if !inside(x, y) {
+                    return None;
+                }
+                Objective::HoldTile { x, y, turns: *turns }
+            }
+            Objective::EscortVip(exits) => {
+                let exits: Vec<_> = exits.iter().map(|(x, y)| (x + dx, y + dy)).filter(|&(x, y)| inside(x, y)).collect();
+                if exits.is_empty() {
+                    return None;
+                }
+                Objective::EscortVip(exits)
+            }
+            other => other.clone(),
+        };
+        Some(MapObjective { faction: self.faction, goal })
+    }
+}
+
+/// Objectives of maps that don't set their own: wipe out the enemy or take its HQ
+pub fn default_objectives() -> Vec<MapObjective> {
+    vec![MapObjective::for_all(Objective::Eliminate), MapObjective::for_all(Objective::CaptureHq)]
+}
+
+/// How sides are ranked when the turn limit runs out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScoreMethod {
+    /// Number of properties held
+    #[default]
+    Properties,
+    /// Production cost of surviving units, scaled by their HP
+    UnitValue,
+    /// Property income plus unit value
+    Combined,
+}
+
+impl ScoreMethod {
+    pub fn all() -> &'static [ScoreMethod] {
+        &[ScoreMethod::Properties, ScoreMethod::UnitValue, ScoreMethod::Combined]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoreMethod::Properties => "Properties",
+            ScoreMethod::UnitValue => "Unit Value",
+            ScoreMethod::Combined => "Combined",
+        }
+    }
+}
+
+// ============================================================================
+// PROGRESS
+// ============================================================================
+
+/// Objective state that builds up over a battle
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObjectiveProgress {
+    /// Sides that started the battle
+    pub sides: Vec<Faction>,
+    /// Sides that started with a VIP
+    pub vip_sides: HashSet<Faction>,
+    /// For each hold objective (by index), who is on the tile and for how many turns
+    pub held: HashMap<usize, (Faction, u32)>,
+}
+
+impl ObjectiveProgress {
+    pub fn new(map_data: &MapData) -> Self {
+        Self {
+            sides: map_data.factions(),
+            vip_sides: map_data.units.iter().filter(|u| u.vip).map(|u| u.faction).collect(),
+            held: HashMap::new(),
+        }
+    }
+}
+
+/// Count turns a side has kept a unit on each hold objective's tile
+fn track_held_tiles(
+    mut events: MessageReader<TurnStartEvent>,
+    units: Query<(&GridPosition, &FactionMember), With<Unit>>,
+    rules: Res<MapRules>,
+    mut progress: ResMut<ObjectiveProgress>,
+) {
+    for event in events.read() {
+        for (index, objective) in rules.objectives.iter().enumerate() {
+            let Objective::HoldTile { x, y, .. } = objective.goal else { continue };
+            if !objective.applies_to(event.faction) {
+                continue;
+            }
+            let holding = units.iter().any(|(pos, member)| member.faction == event.faction && (pos.x, pos.y) == (x, y));
+            let previous = progress.held.get(&index).copied();
+            match previous {
+                Some((holder, turns)) if holding && holder == event.faction => {
+                    progress.held.insert(index, (holder, turns + 1));
+                }
+                _ if holding => {
+                    progress.held.insert(index, (event.faction, 1));
+                }
+                Some((holder, _)) if holder == event.faction => {
+                    progress.held.remove(&index);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// ============================================================================
+// VICTORY
+// ============================================================================
+
+/// End the battle once a side meets one of the map's objectives, or the turn limit runs out
+fn check_victory_condition(
+    units: Query<(&Unit, &GridPosition, &FactionMember)>,
+    tiles: Query<&Tile>,
+    mut game_result: ResMut<GameResult>,
+    turn_state: Res<TurnState>,
+    rules: Res<MapRules>,
+    progress: Res<ObjectiveProgress>,
+) {
+    // Skip if game is already over
+    if game_result.game_over {
+        return;
+    }
+
+    // Sides that started the battle; saves from before objectives fall back to who's on the board
+    let mut sides = progress.sides.clone();
+    if sides.is_empty() {
+        for faction in units.iter().map(|(_, _, m)| m.faction).chain(tiles.iter().filter_map(|t| t.owner)) {
+            if !sides.contains(&faction) {
+                sides.push(faction);
+            }
+        }
+    }
+    if sides.len() < 2 {
+        return;
+    }
+
+    let unit_count = |faction: Faction| units.iter().filter(|(_, _, m)| m.faction == faction).count();
+    // A VIP riding in a transport is still alive, and travels with it
+    let vips_of = |faction: Faction| units.iter()
+        .filter(move |(u, _, m)| (u.vip || u.cargo.as_ref().is_some_and(|c| c.vip)) && m.faction == faction);
+
+    // HQ terrain decides HQ capture; maps without it fall back to every Base
+    let hq_terrain = if tiles.iter().any(|t| t.terrain == Terrain::HQ) { Terrain::HQ } else { Terrain::Base };
+    let hqs: Vec<&Tile> = tiles.iter().filter(|t| t.terrain == hq_terrain).collect();
+
+    for (index, objective) in rules.objectives.iter().enumerate() {
+        for &side in sides.iter().filter(|&&f| objective.applies_to(f)) {
+            let enemies = sides.iter().copied().filter(|&f| f != side);
+            let met = match &objective.goal {
+                Objective::Eliminate => unit_count(side) > 0 && enemies.clone().all(|f| unit_count(f) == 0),
+                Objective::CaptureHq => hqs.len() >= 2 && hqs.iter().all(|t| t.owner == Some(side)),
+                Objective::OwnProperties(count) => {
+                    tiles.iter().filter(|t| t.owner == Some(side)).count() >= *count as usize
+                }
+                Objective::HoldTile { turns, .. } => progress.held.get(&index)
+                    .is_some_and(|&(holder, held)| holder == side && held >= *turns),
+                Objective::SurviveUntil(turn) => turn_state.turn_number >= *turn && unit_count(side) > 0,
+                Objective::DestroyVip => {
+                    let mut targets = enemies.clone().filter(|f| progress.vip_sides.contains(f)).peekable();
+                    targets.peek().is_some() && targets.all(|f| vips_of(f).next().is_none())
+                }
+                Objective::EscortVip(exits) => vips_of(side).any(|(_, pos, _)| exits.contains(&(pos.x, pos.y))),
+            };
+            if met {
+                game_result.game_over = true;
+                game_result.winner = Some(side);
+                game_result.victory_type = objective.goal.victory_type();
+                info!("{:?} wins: {}", side, objective.goal.describe());
+                return;
+            }
+        }
+    }
+
+    // Out of turns: the best score wins, a tie is a draw
+    if rules.turn_limit_reached(turn_state.turn_number) {
+        let score = |faction: Faction| -> u32 {
+            let properties = tiles.iter().filter(|t| t.owner == Some(faction));
+            let unit_value: u32 = units.iter()
+                .filter(|(_, _, m)| m.faction == faction)
+                .map(|(u, _, _)| u.unit_type.cost() * u.hp.max(0) as u32 / u.unit_type.stats().max_hp.max(1) as u32)
+                .sum();
+            match rules.score {
+                ScoreMethod::Properties => properties.count() as u32,
+                ScoreMethod::UnitValue => unit_value,
+                ScoreMethod::Combined => properties.map(|t| t.terrain.income_value()).sum::<u32>() + unit_value,
+            }
+        };
+        let scores: Vec<(Faction, u32)> = sides.iter().map(|&f| (f, score(f))).collect();
+        let best = scores.iter().map(|(_, s)| *s).max().unwrap_or(0);
+        let mut leaders = scores.iter().filter(|(_, s)| *s == best).map(|(f, _)| *f);
+        let winner = leaders.next().filter(|_| leaders.next().is_none());
+
+        game_result.game_over = true;
+        game_result.winner = winner;
+        game_result.victory_type = VictoryType::TurnLimit;
+        match winner {
+            Some(faction) => info!("Turn limit reached, {:?} wins on {}!", faction, rules.score.name()),
+            None => info!("Turn limit reached, the battle is a draw"),
+        }
+    }
+}
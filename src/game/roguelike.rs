@@ -0,0 +1,971 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+use super::{
+    AiDifficulty, CoBonuses, CommanderId, Faction, FactionMember, GameResult, MapData, MapId, Terrain, TurnState, Unit,
+    UnitPlacement, UnitType, get_builtin_map, MAX_VETERANCY,
+};
+
+pub struct RoguelikePlugin;
+
+impl Plugin for RoguelikePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoguelikeSession>()
+            .add_systems(Startup, load_run)
+            .add_systems(Update, record_battle_result);
+    }
+}
+
+/// Sides every run battle is fought between, matching the curated maps' armies;
+/// the drafted CO is what sets the player's army apart
+pub const RUN_PLAYER: Faction = Faction::Eastern;
+pub const RUN_ENEMY: Faction = Faction::Northern;
+
+/// Layers of nodes between the start and the boss (the boss is the last layer)
+const RUN_LAYERS: usize = 8;
+
+/// Most units a roster can hold; deployment gets crowded past this
+pub const MAX_ROSTER: usize = 10;
+
+/// Funds a run starts with, spent in shops and events
+const STARTING_RUN_FUNDS: u32 = 100;
+
+/// Price to heal every unit in the roster
+pub const HEAL_PRICE: u32 = 30;
+/// Price to raise one unit's veterancy
+pub const TRAIN_PRICE: u32 = 40;
+/// Price of the perk a shop sells
+pub const SHOP_PERK_PRICE: u32 = 80;
+
+// ============================================================================
+// RUN DEFINITIONS
+// ============================================================================
+
+/// What waits at a node on the run map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeKind {
+    Battle,
+    /// A tougher battle with better rewards
+    Elite,
+    Shop,
+    Event,
+    /// The final battle; winning it wins the run
+    Boss,
+}
+
+impl NodeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Battle => "Battle",
+            NodeKind::Elite => "Elite Battle",
+            NodeKind::Shop => "Shop",
+            NodeKind::Event => "Event",
+            NodeKind::Boss => "Boss",
+        }
+    }
+
+    pub fn is_battle(&self) -> bool {
+        matches!(self, NodeKind::Battle | NodeKind::Elite | NodeKind::Boss)
+    }
+}
+
+/// A node on the run map
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunNode {
+    pub kind: NodeKind,
+    /// Indices of the nodes it leads to in the next layer
+    pub next: Vec<usize>,
+}
+
+/// A unit in the army carried from battle to battle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RosterEntry {
+    pub id: u32,
+    pub unit_type: UnitType,
+    pub hp: i32,
+    pub veterancy: u32,
+}
+
+impl RosterEntry {
+    pub fn max_hp(&self) -> i32 {
+        self.unit_type.stats().max_hp
+    }
+
+    /// The unit as it takes the field
+    pub fn to_unit(&self) -> Unit {
+        Unit {
+            hp: self.hp,
+            veterancy: self.veterancy,
+            roster_id: Some(self.id),
+            ..Unit::new(self.unit_type)
+        }
+    }
+}
+
+/// Upgrades drafted between battles; they stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Perk {
+    /// +10% attack
+    Sharpened,
+    /// +10% defense
+    Entrenched,
+    /// +1 movement
+    ForcedMarch,
+    /// +1 vision
+    KeenEyes,
+    /// +20% income
+    WarChest,
+    /// -10% unit cost
+    Quartermaster,
+    /// Units recover 20 HP after each battle won
+    FieldMedics,
+    /// +25% funds from battles won
+    Bounty,
+}
+
+impl Perk {
+    pub fn all() -> &'static [Perk] {
+        &[
+            Perk::Sharpened,
+            Perk::Entrenched,
+            Perk::ForcedMarch,
+            Perk::KeenEyes,
+            Perk::WarChest,
+            Perk::Quartermaster,
+            Perk::FieldMedics,
+            Perk::Bounty,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Perk::Sharpened => "Sharpened Claws",
+            Perk::Entrenched => "Entrenched",
+            Perk::ForcedMarch => "Forced March",
+            Perk::KeenEyes => "Keen Eyes",
+            Perk::WarChest => "War Chest",
+            Perk::Quartermaster => "Quartermaster",
+            Perk::FieldMedics => "Field Medics",
+            Perk::Bounty => "Bounty Hunters",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Perk::Sharpened => "+10% attack",
+            Perk::Entrenched => "+10% defense",
+            Perk::ForcedMarch => "+1 movement",
+            Perk::KeenEyes => "+1 vision",
+            Perk::WarChest => "+20% income in battle",
+            Perk::Quartermaster => "-10% cost of units built in battle",
+            Perk::FieldMedics => "Units recover 20 HP after each battle won",
+            Perk::Bounty => "+25% funds from battles won",
+        }
+    }
+
+    /// Fold this perk into CO bonuses
+    fn apply(&self, bonuses: &mut CoBonuses) {
+        match self {
+            Perk::Sharpened => bonuses.attack *= 1.1,
+            Perk::Entrenched => bonuses.defense *= 1.1,
+            Perk::ForcedMarch => bonuses.movement += 1,
+            Perk::KeenEyes => bonuses.vision += 1,
+            Perk::WarChest => bonuses.income *= 1.2,
+            Perk::Quartermaster => bonuses.cost *= 0.9,
+            Perk::FieldMedics | Perk::Bounty => {}
+        }
+    }
+}
+
+/// Something that happens on an event node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RunEvent {
+    /// Supplies left behind: take them
+    Cache { funds: u32 },
+    /// A lone soldier asks to join
+    Deserter { unit_type: UnitType },
+    /// Pay to have the army drilled: every unit gains veterancy
+    TrainingGrounds { price: u32 },
+    /// Raiders strike: pay them off or every unit takes damage
+    Ambush { toll: u32, damage: i32 },
+    /// A wandering strategist offers a perk for a price
+    Strategist { perk: Perk, price: u32 },
+}
+
+impl RunEvent {
+    pub fn title(&self) -> &'static str {
+        match self {
+            RunEvent::Cache { .. } => "Abandoned Cache",
+            RunEvent::Deserter { .. } => "Deserter",
+            RunEvent::TrainingGrounds { .. } => "Training Grounds",
+            RunEvent::Ambush { .. } => "Ambush!",
+            RunEvent::Strategist { .. } => "Wandering Strategist",
+        }
+    }
+
+    pub fn text(&self) -> String {
+        match self {
+            RunEvent::Cache { funds } => format!("Your scouts find a supply cache worth {} funds.", funds),
+            RunEvent::Deserter { unit_type } => format!("A {} from a broken army asks to join you.", unit_type.name()),
+            RunEvent::TrainingGrounds { price } => {
+                format!("An old drill ground. For {} funds, every unit can be drilled to the next veterancy level.", price)
+            }
+            RunEvent::Ambush { toll, damage } => {
+                format!("Raiders block the road. Pay {} funds, or fight through and every unit loses {} HP.", toll, damage)
+            }
+            RunEvent::Strategist { perk, price } => {
+                format!("A strategist teaches {} ({}) for {} funds.", perk.name(), perk.description(), price)
+            }
+        }
+    }
+
+    /// Funds an option costs
+    pub fn option_price(&self, option: usize) -> u32 {
+        match (self, option) {
+            (RunEvent::TrainingGrounds { price }, 0) | (RunEvent::Strategist { price, .. }, 0) => *price,
+            (RunEvent::Ambush { toll, .. }, 0) => *toll,
+            _ => 0,
+        }
+    }
+
+    /// Choices offered, in order; the index is passed to `Run::resolve_event`
+    pub fn options(&self) -> Vec<String> {
+        match self {
+            RunEvent::Cache { .. } => vec!["Take it".to_string()],
+            RunEvent::Deserter { .. } => vec!["Welcome them".to_string(), "Send them away".to_string()],
+            RunEvent::TrainingGrounds { price } => vec![format!("Drill ({} funds)", price), "March on".to_string()],
+            RunEvent::Ambush { toll, .. } => vec![format!("Pay ({} funds)", toll), "Fight through".to_string()],
+            RunEvent::Strategist { price, .. } => vec![format!("Learn ({} funds)", price), "Decline".to_string()],
+        }
+    }
+}
+
+/// What a shop has left for sale
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShopStock {
+    pub recruits: Vec<UnitType>,
+    pub perk: Option<Perk>,
+    /// The roster has been healed here already
+    pub healed: bool,
+}
+
+/// What the player is doing right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RunStage {
+    /// Choosing the next node
+    Map,
+    /// About to fight; `started` once the battle is under way
+    Battle { started: bool },
+    Shop(ShopStock),
+    Event(RunEvent),
+    /// Picking one perk after a battle won
+    Draft(Vec<Perk>),
+}
+
+/// How a run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RunOutcome {
+    Victory,
+    Defeat,
+}
+
+/// A battle as generated for a node
+#[derive(Debug, Clone)]
+pub struct RunBattle {
+    /// Terrain and properties only; units are placed separately
+    pub map: MapData,
+    pub enemy_co: CommanderId,
+    pub enemies: Vec<UnitPlacement>,
+    /// Veterancy of every enemy unit
+    pub enemy_veterancy: u32,
+    pub difficulty: AiDifficulty,
+    /// Tiles for the roster, best first
+    pub deployment: Vec<(i32, i32)>,
+}
+
+/// A roguelike run: seeded, and saved after every step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub seed: u64,
+    pub commander: CommanderId,
+    /// Funds for shops and events (battles have their own)
+    pub funds: u32,
+    pub roster: Vec<RosterEntry>,
+    pub next_roster_id: u32,
+    pub perks: Vec<Perk>,
+    /// The run map, layer by layer; the last layer is the boss
+    pub layers: Vec<Vec<RunNode>>,
+    /// Node reached (layer, index); `None` before the first step
+    pub position: Option<(usize, usize)>,
+    pub stage: RunStage,
+    /// Units lost for good
+    pub fallen: Vec<RosterEntry>,
+    pub battles_won: u32,
+    pub outcome: Option<RunOutcome>,
+}
+
+impl Run {
+    /// Start a run: build the map and the starting roster from the seed
+    pub fn new(seed: u64, commander: CommanderId) -> Self {
+        let mut run = Self {
+            seed,
+            commander,
+            funds: STARTING_RUN_FUNDS,
+            roster: Vec::new(),
+            next_roster_id: 0,
+            perks: Vec::new(),
+            layers: generate_layers(&mut StdRng::seed_from_u64(seed)),
+            position: None,
+            stage: RunStage::Map,
+            fallen: Vec::new(),
+            battles_won: 0,
+            outcome: None,
+        };
+        for unit_type in [
+            UnitType::Scout,
+            UnitType::Scout,
+            UnitType::Shocktrooper,
+            UnitType::Recon,
+            UnitType::Ironclad,
+            UnitType::Siege,
+        ] {
+            run.recruit(unit_type);
+        }
+        run
+    }
+
+    /// Random numbers for one node, the same every time it's asked for
+    fn node_rng(&self, layer: usize, index: usize) -> StdRng {
+        let node = ((layer as u64) << 32) | index as u64;
+        StdRng::seed_from_u64(self.seed ^ node.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    pub fn node(&self, layer: usize, index: usize) -> Option<&RunNode> {
+        self.layers.get(layer).and_then(|nodes| nodes.get(index))
+    }
+
+    /// Nodes the player can move to next
+    pub fn choices(&self) -> Vec<(usize, usize)> {
+        match self.position {
+            None => (0..self.layers.first().map_or(0, Vec::len)).map(|i| (0, i)).collect(),
+            Some((layer, index)) => self.node(layer, index)
+                .map_or(Vec::new(), |node| node.next.iter().map(|&i| (layer + 1, i)).collect()),
+        }
+    }
+
+    /// Move to a node and set up what waits there
+    pub fn enter(&mut self, layer: usize, index: usize) {
+        if !self.choices().contains(&(layer, index)) {
+            return;
+        }
+        let Some(kind) = self.node(layer, index).map(|n| n.kind) else { return };
+        self.position = Some((layer, index));
+        let mut rng = self.node_rng(layer, index);
+        self.stage = match kind {
+            NodeKind::Battle | NodeKind::Elite | NodeKind::Boss => RunStage::Battle { started: false },
+            NodeKind::Shop => {
+                let pool = unit_pool(layer);
+                RunStage::Shop(ShopStock {
+                    recruits: (0..3).filter_map(|_| pool.choose(&mut rng).copied()).collect(),
+                    perk: Perk::all().choose(&mut rng).copied(),
+                    healed: false,
+                })
+            }
+            NodeKind::Event => RunStage::Event(generate_event(&mut rng, layer)),
+        };
+    }
+
+    /// The battle at a node: map, enemy army and deployment, escalating with depth
+    pub fn battle(&self, layer: usize, index: usize) -> Option<RunBattle> {
+        let kind = self.node(layer, index)?.kind;
+        if !kind.is_battle() {
+            return None;
+        }
+        let mut rng = self.node_rng(layer, index);
+
+        // Curated maps now and then; otherwise a fresh one
+        let mut map = if rng.gen_bool(0.4) {
+            let curated = [MapId::Woodland, MapId::RiverCrossing, MapId::TwinBases, MapId::Fortress, MapId::MountainPass,
+                MapId::MarshLands, MapId::AncientRuins];
+            get_builtin_map(*curated.choose(&mut rng)?)
+        } else {
+            generate_map(&mut rng, &format!("Wilds {}-{}", layer + 1, index + 1))
+        };
+        let player_anchors = anchors(&map, RUN_PLAYER);
+        let enemy_anchors = anchors(&map, RUN_ENEMY);
+        map.units.clear();
+        map.triggers.clear();
+
+        let strength = match kind {
+            NodeKind::Elite => 1.5,
+            NodeKind::Boss => 2.0,
+            _ => 1.0,
+        };
+        let budget = ((60 + 25 * layer as u32) as f32 * strength) as u32;
+        map.rules.starting_funds.insert(RUN_PLAYER, 50);
+        map.rules.starting_funds.insert(RUN_ENEMY, 40 + 15 * layer as u32);
+
+        let difficulty_steps = layer / 2 + usize::from(kind == NodeKind::Elite) + if kind == NodeKind::Boss { 3 } else { 0 };
+        let difficulty = AiDifficulty::all()[difficulty_steps.min(3)];
+
+        let enemy_cos: Vec<CommanderId> = RUN_FACTIONS.iter()
+            .flat_map(|&f| CommanderId::for_faction(f))
+            .filter(|&co| co != self.commander)
+            .collect();
+        let enemy_co = *enemy_cos.choose(&mut rng)?;
+
+        // Spend the budget on units the layer allows, one per tile nearest the enemy's base
+        let pool = unit_pool(layer);
+        let enemy_tiles = deployment_tiles(&map, &enemy_anchors, &HashSet::new());
+        let mut enemies = Vec::new();
+        let mut remaining = budget;
+        for &(x, y) in enemy_tiles.iter().take(MAX_ROSTER + 2) {
+            let affordable: Vec<UnitType> = pool.iter().copied().filter(|u| u.cost() <= remaining).collect();
+            let Some(&unit_type) = affordable.choose(&mut rng) else { break };
+            remaining -= unit_type.cost();
+            enemies.push(UnitPlacement { unit_type, faction: RUN_ENEMY, x, y, vip: false });
+        }
+
+        let taken: HashSet<(i32, i32)> = enemies.iter().map(|u| (u.x, u.y)).collect();
+        let deployment = deployment_tiles(&map, &player_anchors, &taken);
+        let enemy_veterancy = match kind {
+            NodeKind::Elite => 1,
+            NodeKind::Boss => 2,
+            _ => (layer as u32) / 4,
+        };
+
+        Some(RunBattle { map, enemy_co, enemies, enemy_veterancy, difficulty, deployment })
+    }
+
+    /// Add a fresh unit to the roster, if there's room
+    pub fn recruit(&mut self, unit_type: UnitType) -> bool {
+        if self.roster.len() >= MAX_ROSTER {
+            return false;
+        }
+        self.roster.push(RosterEntry {
+            id: self.next_roster_id,
+            unit_type,
+            hp: unit_type.stats().max_hp,
+            veterancy: 0,
+        });
+        self.next_roster_id += 1;
+        true
+    }
+
+    /// Take funds if there are enough
+    pub fn spend(&mut self, amount: u32) -> bool {
+        if self.funds < amount {
+            return false;
+        }
+        self.funds -= amount;
+        true
+    }
+
+    /// Heal every roster entry by `amount` HP (`None` for full)
+    fn heal_all(&mut self, amount: Option<i32>) {
+        for entry in &mut self.roster {
+            let max = entry.max_hp();
+            entry.hp = amount.map_or(max, |a| (entry.hp + a).min(max));
+        }
+    }
+
+    /// Buy something from the current shop
+    pub fn buy(&mut self, item: ShopItem) {
+        let RunStage::Shop(stock) = &self.stage else { return };
+        let mut stock = stock.clone();
+        match item {
+            ShopItem::Recruit(slot) => {
+                let Some(&unit_type) = stock.recruits.get(slot) else { return };
+                if self.roster.len() >= MAX_ROSTER || !self.spend(unit_type.cost()) {
+                    return;
+                }
+                self.recruit(unit_type);
+                stock.recruits.remove(slot);
+            }
+            ShopItem::Heal => {
+                if stock.healed || !self.spend(HEAL_PRICE) {
+                    return;
+                }
+                self.heal_all(None);
+                stock.healed = true;
+            }
+            ShopItem::Train(roster_id) => {
+                let Some(index) = self.roster.iter().position(|e| e.id == roster_id) else { return };
+                if self.roster[index].veterancy >= MAX_VETERANCY || !self.spend(TRAIN_PRICE) {
+                    return;
+                }
+                self.roster[index].veterancy += 1;
+            }
+            ShopItem::Perk => {
+                let Some(perk) = stock.perk else { return };
+                if !self.spend(SHOP_PERK_PRICE) {
+                    return;
+                }
+                self.perks.push(perk);
+                stock.perk = None;
+            }
+        }
+        self.stage = RunStage::Shop(stock);
+    }
+
+    /// Pick one of the event's options
+    pub fn resolve_event(&mut self, option: usize) {
+        let RunStage::Event(event) = &self.stage else { return };
+        match (event.clone(), option) {
+            (RunEvent::Cache { funds }, _) => self.funds += funds,
+            (RunEvent::Deserter { unit_type }, 0) => {
+                self.recruit(unit_type);
+            }
+            (RunEvent::TrainingGrounds { price }, 0) => {
+                if !self.spend(price) {
+                    return;
+                }
+                for entry in &mut self.roster {
+                    entry.veterancy = (entry.veterancy + 1).min(MAX_VETERANCY);
+                }
+            }
+            (RunEvent::Ambush { toll, damage }, choice) => {
+                if choice != 0 || !self.spend(toll) {
+                    // Nobody dies on the road; they limp on
+                    for entry in &mut self.roster {
+                        entry.hp = (entry.hp - damage).max(10);
+                    }
+                }
+            }
+            (RunEvent::Strategist { perk, price }, 0) => {
+                if !self.spend(price) {
+                    return;
+                }
+                self.perks.push(perk);
+            }
+            _ => {}
+        }
+        self.stage = RunStage::Map;
+    }
+
+    /// Take one perk from the draft
+    pub fn draft(&mut self, choice: usize) {
+        let RunStage::Draft(perks) = &self.stage else { return };
+        let Some(&perk) = perks.get(choice) else { return };
+        self.perks.push(perk);
+        self.stage = RunStage::Map;
+    }
+
+    /// Leave a shop (or skip a draft) and return to the run map
+    pub fn leave(&mut self) {
+        if matches!(self.stage, RunStage::Shop(_) | RunStage::Draft(_)) {
+            self.stage = RunStage::Map;
+        }
+    }
+
+    /// Every drafted perk combined, to stack on the CO's own bonuses
+    pub fn perk_bonuses(&self) -> CoBonuses {
+        let mut bonuses = CoBonuses::none();
+        for perk in &self.perks {
+            perk.apply(&mut bonuses);
+        }
+        bonuses
+    }
+
+    fn perk_count(&self, perk: Perk) -> usize {
+        self.perks.iter().filter(|&&p| p == perk).count()
+    }
+
+    /// Carry the battle's survivors back into the roster and pay out.
+    /// Roster units missing from `survivors` have fallen; survivors without a roster id were built in battle.
+    pub fn finish_battle(&mut self, won: bool, survivors: &[Unit]) -> BattleReport {
+        let mut report = BattleReport { won, ..Default::default() };
+
+        let mut roster = Vec::new();
+        for entry in self.roster.drain(..) {
+            match survivors.iter().find(|u| u.roster_id == Some(entry.id)) {
+                Some(unit) => {
+                    if unit.veterancy > entry.veterancy {
+                        report.promoted.push(unit.unit_type);
+                    }
+                    roster.push(RosterEntry { hp: unit.hp, veterancy: unit.veterancy, ..entry });
+                }
+                None => {
+                    report.fallen.push(entry.unit_type);
+                    self.fallen.push(entry);
+                }
+            }
+        }
+        self.roster = roster;
+        for unit in survivors.iter().filter(|u| u.roster_id.is_none()) {
+            if self.recruit(unit.unit_type) {
+                if let Some(entry) = self.roster.last_mut() {
+                    entry.hp = unit.hp;
+                    entry.veterancy = unit.veterancy;
+                }
+                report.joined.push(unit.unit_type);
+            }
+        }
+
+        let Some((layer, index)) = self.position else { return report };
+        let kind = self.node(layer, index).map_or(NodeKind::Battle, |n| n.kind);
+        if !won || self.roster.is_empty() {
+            self.outcome = Some(RunOutcome::Defeat);
+            return report;
+        }
+
+        self.battles_won += 1;
+        let base = 40 + 10 * layer as u32;
+        let base = if kind == NodeKind::Elite { base * 2 } else { base };
+        report.funds = base + base * self.perk_count(Perk::Bounty) as u32 / 4;
+        self.funds += report.funds;
+        for _ in 0..self.perk_count(Perk::FieldMedics) {
+            self.heal_all(Some(20));
+        }
+
+        if kind == NodeKind::Boss {
+            self.outcome = Some(RunOutcome::Victory);
+            self.stage = RunStage::Map;
+        } else {
+            let mut rng = self.node_rng(layer, index);
+            let mut perks = Perk::all().to_vec();
+            perks.shuffle(&mut rng);
+            perks.truncate(3);
+            self.stage = RunStage::Draft(perks);
+        }
+        report
+    }
+
+    /// Walking away from a started battle loses the run
+    pub fn abandon_battle(&mut self) {
+        if matches!(self.stage, RunStage::Battle { started: true }) && self.outcome.is_none() {
+            self.outcome = Some(RunOutcome::Defeat);
+        }
+    }
+
+    /// Read the saved run; a run left mid-battle counts as abandoned
+    pub fn load() -> Option<Self> {
+        let json = load_run_from_storage().ok()?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(mut run) => {
+                run.abandon_battle();
+                Some(run)
+            }
+            Err(e) => {
+                warn!("Failed to parse saved run: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize run: {}", e))?;
+        save_run_to_storage(&json)
+    }
+}
+
+/// Something for sale in a shop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShopItem {
+    /// Index into the shop's recruits
+    Recruit(usize),
+    Heal,
+    /// Roster id of the unit to train
+    Train(u32),
+    Perk,
+}
+
+/// What a battle did to the run, shown once the player is back on the run screen
+#[derive(Debug, Clone, Default)]
+pub struct BattleReport {
+    pub won: bool,
+    pub funds: u32,
+    pub fallen: Vec<UnitType>,
+    pub promoted: Vec<UnitType>,
+    /// Units built in battle that joined the roster
+    pub joined: Vec<UnitType>,
+}
+
+// ============================================================================
+// GENERATION
+// ============================================================================
+
+/// Factions whose COs can be drafted or met
+pub const RUN_FACTIONS: [Faction; 5] = [Faction::Eastern, Faction::Northern, Faction::Western, Faction::Southern, Faction::Nether];
+
+/// Units met or sold at a given depth; stronger ones appear further in
+fn unit_pool(layer: usize) -> Vec<UnitType> {
+    let mut pool = vec![UnitType::Scout, UnitType::Shocktrooper, UnitType::Recon];
+    if layer >= 2 {
+        pool.extend([UnitType::Ironclad, UnitType::Siege, UnitType::Flak]);
+    }
+    if layer >= 4 {
+        pool.extend([UnitType::Juggernaut, UnitType::Barrage, UnitType::Skywing]);
+    }
+    if layer >= 6 {
+        pool.extend([UnitType::Behemoth, UnitType::Stinger, UnitType::Raptor]);
+    }
+    pool
+}
+
+/// Lay out the run map: a few nodes per layer, each leading to one or two in the next
+fn generate_layers(rng: &mut StdRng) -> Vec<Vec<RunNode>> {
+    let mut layers: Vec<Vec<RunNode>> = (0..RUN_LAYERS)
+        .map(|layer| {
+            let count = if layer == RUN_LAYERS - 1 { 1 } else { rng.gen_range(2..=4) };
+            (0..count).map(|_| {
+                let kind = if layer == RUN_LAYERS - 1 {
+                    NodeKind::Boss
+                } else if layer == 0 {
+                    NodeKind::Battle
+                } else {
+                    match rng.gen_range(0..100) {
+                        0..=44 => NodeKind::Battle,
+                        45..=59 => NodeKind::Elite,
+                        60..=79 => NodeKind::Shop,
+                        _ => NodeKind::Event,
+                    }
+                };
+                RunNode { kind, next: Vec::new() }
+            }).collect()
+        })
+        .collect();
+
+    for layer in 0..RUN_LAYERS - 1 {
+        let (count, next_count) = (layers[layer].len(), layers[layer + 1].len());
+        // Where node i sits in the next layer, keeping paths from crossing
+        let across = |i: usize| if count > 1 { i * (next_count - 1) / (count - 1) } else { next_count / 2 };
+        for i in 0..count {
+            let target = across(i);
+            let mut next = vec![target];
+            if rng.gen_bool(0.5) {
+                let side = if rng.gen_bool(0.5) { target.checked_sub(1) } else { Some(target + 1) };
+                if let Some(side) = side.filter(|&s| s < next_count) {
+                    next.push(side);
+                }
+            }
+            layers[layer][i].next = next;
+        }
+        // Every node needs a way in
+        for j in 0..next_count {
+            if !layers[layer].iter().any(|n| n.next.contains(&j)) {
+                let from = (0..count).min_by_key(|&i| across(i).abs_diff(j)).unwrap_or(0);
+                layers[layer][from].next.push(j);
+            }
+        }
+        for node in &mut layers[layer] {
+            node.next.sort_unstable();
+            node.next.dedup();
+        }
+    }
+    layers
+}
+
+fn generate_event(rng: &mut StdRng, layer: usize) -> RunEvent {
+    let depth = layer as u32;
+    match rng.gen_range(0..5) {
+        0 => RunEvent::Cache { funds: 30 + 10 * depth },
+        1 => RunEvent::Deserter { unit_type: *unit_pool(layer).choose(rng).unwrap_or(&UnitType::Scout) },
+        2 => RunEvent::TrainingGrounds { price: 50 + 10 * depth },
+        3 => RunEvent::Ambush { toll: 20 + 5 * depth, damage: 20 },
+        _ => RunEvent::Strategist { perk: *Perk::all().choose(rng).unwrap_or(&Perk::Sharpened), price: 50 },
+    }
+}
+
+/// A two-sided map mirrored through its centre, Eastern on the left and Northern on the right
+fn generate_map(rng: &mut StdRng, name: &str) -> MapData {
+    let width = rng.gen_range(12..=16);
+    let height = rng.gen_range(8..=11);
+    let mut map = MapData::new(name, width, height);
+    map.description = "Uncharted ground.".to_string();
+    let (w, h) = (width as i32, height as i32);
+    let mirror = |x: i32, y: i32| (w - 1 - x, h - 1 - y);
+
+    for y in 0..h {
+        for x in 0..w {
+            if y * w + x >= (w * h) / 2 {
+                continue;
+            }
+            let terrain = match rng.gen_range(0..100) {
+                0..=49 => Terrain::Grass,
+                50..=64 => Terrain::TallGrass,
+                65..=74 => Terrain::Thicket,
+                75..=78 => Terrain::Brambles,
+                79..=83 => Terrain::Boulder,
+                84..=87 => Terrain::Log,
+                88..=90 => Terrain::Hollow,
+                91..=96 => Terrain::Creek,
+                _ => Terrain::Pond,
+            };
+            let (mx, my) = mirror(x, y);
+            map.set_terrain(x, y, terrain);
+            map.set_terrain(mx, my, terrain);
+        }
+    }
+
+    // Bases and HQ on open ground, then a few outposts to fight over
+    let mid = h / 2;
+    let mut properties = vec![(1, mid, Terrain::HQ), (2, mid - 2, Terrain::Base), (2, mid + 1, Terrain::Base)];
+    let outposts = rng.gen_range(2..=3);
+    for _ in 0..outposts {
+        properties.push((rng.gen_range(3..w / 2), rng.gen_range(0..h), Terrain::Outpost));
+    }
+    properties.push((rng.gen_range(3..w / 2), rng.gen_range(0..h), Terrain::Storehouse));
+
+    for &(x, y, _) in &properties[..3] {
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (-1, 0), (0, -1)] {
+            let (mx, my) = mirror(x + dx, y + dy);
+            map.set_terrain(x + dx, y + dy, Terrain::Grass);
+            map.set_terrain(mx, my, Terrain::Grass);
+        }
+    }
+    let mut placed = HashSet::new();
+    for (x, y, terrain) in properties {
+        if !placed.insert((x, y)) {
+            continue;
+        }
+        let (mx, my) = mirror(x, y);
+        map.set_terrain(x, y, terrain);
+        map.set_terrain(mx, my, terrain);
+        if matches!(terrain, Terrain::HQ | Terrain::Base) {
+            map.add_property(x, y, RUN_PLAYER);
+            map.add_property(mx, my, RUN_ENEMY);
+        }
+    }
+    map
+}
+
+/// Where a side's army gathers: its starting units and properties
+fn anchors(map: &MapData, faction: Faction) -> Vec<(i32, i32)> {
+    map.units.iter().filter(|u| u.faction == faction).map(|u| (u.x, u.y))
+        .chain(map.properties.iter().filter(|p| p.owner == faction).map(|p| (p.x, p.y)))
+        .collect()
+}
+
+/// Land tiles spreading out from the anchors, nearest first, skipping `taken`
+fn deployment_tiles(map: &MapData, anchors: &[(i32, i32)], taken: &HashSet<(i32, i32)>) -> Vec<(i32, i32)> {
+    let open = |x: i32, y: i32| map.get_terrain(x, y).is_some_and(|t| !matches!(t, Terrain::Pond | Terrain::Barricade));
+    let mut seen: HashSet<(i32, i32)> = HashSet::new();
+    let mut queue: VecDeque<(i32, i32)> = anchors.iter().copied().filter(|&(x, y)| open(x, y)).collect();
+    seen.extend(queue.iter().copied());
+    let mut tiles = Vec::new();
+    while let Some((x, y)) = queue.pop_front() {
+        if !taken.contains(&(x, y)) {
+            tiles.push((x, y));
+        }
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let next = (x + dx, y + dy);
+            if open(next.0, next.1) && seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    tiles
+}
+
+// ============================================================================
+// STORAGE
+// ============================================================================
+
+/// Storage key for the run on the web
+#[cfg(target_arch = "wasm32")]
+const RUN_KEY: &str = "paw_and_claw_roguelike";
+
+/// File for the run on native builds, next to (not among) the save slots
+#[cfg(not(target_arch = "wasm32"))]
+const RUN_FILE: &str = "saves/roguelike_run.json";
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    let window = web_sys::window().ok_or("No window object")?;
+    window
+        .local_storage()
+        .map_err(|_| "Failed to access localStorage")?
+        .ok_or_else(|| "localStorage not available".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_run_to_storage(json: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(RUN_KEY, json)
+        .map_err(|_| "Failed to write to localStorage".to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_run_to_storage(json: &str) -> Result<(), String> {
+    std::fs::create_dir_all("saves").map_err(|e| e.to_string())?;
+    std::fs::write(RUN_FILE, json).map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_run_from_storage() -> Result<String, String> {
+    local_storage()?
+        .get_item(RUN_KEY)
+        .map_err(|_| "Failed to read from localStorage")?
+        .ok_or_else(|| "No saved run".to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_run_from_storage() -> Result<String, String> {
+    std::fs::read_to_string(RUN_FILE).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// BATTLE SESSION
+// ============================================================================
+
+/// The run and its link to the battle being fought
+#[derive(Resource, Default)]
+pub struct RoguelikeSession {
+    pub run: Option<Run>,
+    /// The run screen asked for the current node's battle, taken when the battle starts
+    pub pending: bool,
+    /// A run battle is being fought; `true` once its result is recorded
+    pub active: Option<bool>,
+    /// Outcome of the last battle, until the player dismisses it
+    pub last_report: Option<BattleReport>,
+}
+
+impl RoguelikeSession {
+    /// Save the run, logging rather than failing
+    pub fn save(&self) {
+        if let Some(run) = &self.run {
+            if let Err(e) = run.save() {
+                error!("Failed to save run: {}", e);
+            }
+        }
+    }
+}
+
+fn load_run(mut session: ResMut<RoguelikeSession>) {
+    session.run = Run::load();
+    // An abandoned battle was turned into a defeat; keep that on disk
+    session.save();
+}
+
+/// Carry the survivors back into the roster once the battle ends
+fn record_battle_result(
+    mut session: ResMut<RoguelikeSession>,
+    game_result: Res<GameResult>,
+    turn_state: Res<TurnState>,
+    units: Query<(&Unit, &FactionMember)>,
+) {
+    if session.active != Some(false) || !game_result.game_over {
+        return;
+    }
+    session.active = Some(true);
+    let Some(run) = session.run.as_mut() else { return };
+
+    let won = game_result.winner == Some(RUN_PLAYER);
+    let mut survivors = Vec::new();
+    for (unit, _) in units.iter().filter(|(_, m)| m.faction == RUN_PLAYER) {
+        survivors.push(unit.clone());
+        if let Some(cargo) = &unit.cargo {
+            survivors.push(cargo.to_unit());
+        }
+    }
+    let report = run.finish_battle(won, &survivors);
+    info!("Run battle {} in {} turns; {} units lost", if won { "won" } else { "lost" }, turn_state.turn_number, report.fallen.len());
+    session.last_report = Some(report);
+    session.save();
+}
@@ -1,11 +1,12 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::{
     Faction, FactionMember, GameMap, GridPosition, Terrain, Tile, TurnState, Unit, UnitType,
     FactionFunds, Commanders, CommanderId, Weather, WeatherType, TurnPhase, VictoryType,
-    GameResult,
+    GameResult, TerrainWork, TerrainWorks, FogOfWar, MapRules, ObjectiveProgress, MapTriggers, CoBonuses,
 };
 
 pub struct SavePlugin;
@@ -46,6 +47,21 @@ pub struct SaveGameData {
     pub funds: HashMap<Faction, u32>,
     pub commanders: SavedCommanders,
     pub weather: SavedWeather,
+    /// Terrain actions still in progress
+    #[serde(default)]
+    pub terrain_works: Vec<TerrainWork>,
+    /// What each faction can see and has explored
+    #[serde(default)]
+    pub fog: Option<SavedFog>,
+    /// Rules of the map being played
+    #[serde(default)]
+    pub rules: MapRules,
+    /// Progress toward the map's objectives
+    #[serde(default)]
+    pub objectives: ObjectiveProgress,
+    /// The map's triggers, with those already fired
+    #[serde(default)]
+    pub triggers: MapTriggers,
 }
 
 impl SaveGameData {
@@ -57,6 +73,22 @@ pub struct SavedMap {
     pub width: u32,
     pub height: u32,
     pub tiles: Vec<Vec<Terrain>>,
+    /// Tunnels linking Hollow tiles
+    #[serde(default)]
+    pub tunnels: Vec<((i32, i32), (i32, i32))>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedFog {
+    pub enabled: bool,
+    pub visibility: HashMap<Faction, Vec<(i32, i32)>>,
+    pub explored: HashMap<Faction, Vec<(i32, i32)>>,
+    /// Tiles revealed by map triggers
+    #[serde(default)]
+    pub revealed: HashMap<Faction, Vec<(i32, i32)>>,
+    /// Side whose view was on screen; older saves keep the current one
+    #[serde(default)]
+    pub viewer: Option<Faction>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -67,6 +99,8 @@ pub struct SavedTile {
     pub owner: Option<Faction>,
     pub capture_progress: i32,
     pub capturing_faction: Option<Faction>,
+    #[serde(default)]
+    pub damage: i32,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -81,6 +115,12 @@ pub struct SavedUnit {
     pub moved: bool,
     pub attacked: bool,
     pub cargo: Option<SavedCargoUnit>,
+    #[serde(default)]
+    pub vip: bool,
+    #[serde(default)]
+    pub veterancy: u32,
+    #[serde(default)]
+    pub roster_id: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -89,6 +129,12 @@ pub struct SavedCargoUnit {
     pub hp: i32,
     pub stamina: u32,
     pub ammo: u32,
+    #[serde(default)]
+    pub vip: bool,
+    #[serde(default)]
+    pub veterancy: u32,
+    #[serde(default)]
+    pub roster_id: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -96,6 +142,8 @@ pub struct SavedTurnState {
     pub current_faction: Faction,
     pub turn_number: u32,
     pub phase: SavedTurnPhase,
+    #[serde(default)]
+    pub order: Vec<Faction>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -133,6 +181,8 @@ pub struct SavedCommanders {
     pub active: HashMap<Faction, CommanderId>,
     pub power_meter: HashMap<Faction, u32>,
     pub power_active: HashMap<Faction, bool>,
+    #[serde(default)]
+    pub perks: HashMap<Faction, CoBonuses>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -141,6 +191,8 @@ pub struct SavedWeather {
     pub turns_remaining: u32,
     pub dynamic_weather: bool,
     pub change_chance: u32,
+    #[serde(default)]
+    pub schedule: Vec<(u32, WeatherType)>,
 }
 
 // ============================================================================
@@ -232,8 +284,15 @@ fn handle_save_game(
     funds: Res<FactionFunds>,
     commanders: Res<Commanders>,
     weather: Res<Weather>,
+    terrain_works: Res<TerrainWorks>,
+    fog: Res<FogOfWar>,
+    rules: Res<MapRules>,
+    objectives: Res<ObjectiveProgress>,
+    triggers: Res<MapTriggers>,
 ) {
     for event in events.read() {
+        let (visibility, explored, revealed) = fog.snapshot();
+
         // Build save data
         let save_data = SaveGameData {
             version: SaveGameData::CURRENT_VERSION,
@@ -241,6 +300,7 @@ fn handle_save_game(
                 width: game_map.width,
                 height: game_map.height,
                 tiles: game_map.tiles.clone(),
+                tunnels: game_map.tunnels.clone(),
             },
             tiles: tiles.iter().map(|t| SavedTile {
                 x: t.position.x,
@@ -249,6 +309,7 @@ fn handle_save_game(
                 owner: t.owner,
                 capture_progress: t.capture_progress,
                 capturing_faction: t.capturing_faction,
+                damage: t.damage,
             }).collect(),
             units: units.iter().map(|(u, pos, fac)| SavedUnit {
                 unit_type: u.unit_type,
@@ -265,12 +326,19 @@ fn handle_save_game(
                     hp: c.hp,
                     stamina: c.stamina,
                     ammo: c.ammo,
+                    vip: c.vip,
+                    veterancy: c.veterancy,
+                    roster_id: c.roster_id,
                 }),
+                vip: u.vip,
+                veterancy: u.veterancy,
+                roster_id: u.roster_id,
             }).collect(),
             turn_state: SavedTurnState {
                 current_faction: turn_state.current_faction,
                 turn_number: turn_state.turn_number,
                 phase: turn_state.phase.into(),
+                order: turn_state.order.clone(),
             },
             funds: [
                 (Faction::Eastern, funds.get(Faction::Eastern)),
@@ -283,13 +351,26 @@ fn handle_save_game(
                 active: commanders.active.clone(),
                 power_meter: commanders.power_meter.clone(),
                 power_active: commanders.power_active.clone(),
+                perks: commanders.perks.clone(),
             },
             weather: SavedWeather {
                 current: weather.current,
                 turns_remaining: weather.turns_remaining,
                 dynamic_weather: weather.dynamic_weather,
                 change_chance: weather.change_chance,
+                schedule: weather.schedule.clone(),
             },
+            terrain_works: terrain_works.works.clone(),
+            fog: Some(SavedFog {
+                enabled: fog.enabled,
+                visibility,
+                explored,
+                revealed,
+                viewer: Some(fog.viewer),
+            }),
+            rules: rules.clone(),
+            objectives: objectives.clone(),
+            triggers: triggers.clone(),
         };
 
         // Serialize and save
@@ -305,6 +386,16 @@ fn handle_save_game(
     }
 }
 
+/// Battle progress restored alongside the map
+#[derive(SystemParam)]
+struct LoadedProgress<'w> {
+    terrain_works: ResMut<'w, TerrainWorks>,
+    fog: ResMut<'w, FogOfWar>,
+    rules: ResMut<'w, MapRules>,
+    objectives: ResMut<'w, ObjectiveProgress>,
+    triggers: ResMut<'w, MapTriggers>,
+}
+
 /// Handle load game event
 fn handle_load_game(
     mut commands: Commands,
@@ -322,6 +413,7 @@ fn handle_load_game(
     mut materials: ResMut<Assets<StandardMaterial>>,
     sprite_assets: Res<super::SpriteAssets>,
     images: Res<Assets<Image>>,
+    mut progress: LoadedProgress,
 ) {
     for event in events.read() {
         // Load from storage
@@ -354,11 +446,32 @@ fn handle_load_game(
         game_map.width = save_data.map.width;
         game_map.height = save_data.map.height;
         game_map.tiles = save_data.map.tiles;
+        game_map.tunnels = save_data.map.tunnels;
+
+        // Restore terrain works in progress
+        progress.terrain_works.works = save_data.terrain_works;
+
+        // Restore each faction's fog of war
+        if let Some(saved_fog) = save_data.fog {
+            progress.fog.enabled = saved_fog.enabled;
+            if let Some(viewer) = saved_fog.viewer {
+                progress.fog.viewer = viewer;
+            }
+            progress.fog.restore(saved_fog.visibility, saved_fog.explored, saved_fog.revealed);
+        }
+
+        // Restore the map's rules
+        *progress.rules = save_data.rules;
+        *progress.objectives = save_data.objectives;
+        *progress.triggers = save_data.triggers;
 
         // Restore TurnState
         turn_state.current_faction = save_data.turn_state.current_faction;
         turn_state.turn_number = save_data.turn_state.turn_number;
         turn_state.phase = save_data.turn_state.phase.into();
+        if save_data.turn_state.order.len() >= 2 {
+            turn_state.order = save_data.turn_state.order;
+        }
 
         // Restore funds
         for (faction, amount) in save_data.funds {
@@ -374,12 +487,14 @@ fn handle_load_game(
         commanders.active = save_data.commanders.active;
         commanders.power_meter = save_data.commanders.power_meter;
         commanders.power_active = save_data.commanders.power_active;
+        commanders.perks = save_data.commanders.perks;
 
         // Restore weather
         weather.set(save_data.weather.current);
         weather.turns_remaining = save_data.weather.turns_remaining;
         weather.dynamic_weather = save_data.weather.dynamic_weather;
         weather.change_chance = save_data.weather.change_chance;
+        weather.schedule = save_data.weather.schedule;
 
         // Reset game result (in case we're loading a saved game that was still in progress)
         game_result.game_over = false;
@@ -415,6 +530,7 @@ fn handle_load_game(
                     owner: saved_tile.owner,
                     capture_progress: saved_tile.capture_progress,
                     capturing_faction: saved_tile.capturing_faction,
+                    damage: saved_tile.damage,
                 },
             ));
 
@@ -444,11 +560,17 @@ fn handle_load_game(
             unit.ammo = saved_unit.ammo;
             unit.moved = saved_unit.moved;
             unit.attacked = saved_unit.attacked;
+            unit.vip = saved_unit.vip;
+            unit.veterancy = saved_unit.veterancy;
+            unit.roster_id = saved_unit.roster_id;
             unit.cargo = saved_unit.cargo.as_ref().map(|c| super::CargoUnit {
                 unit_type: c.unit_type,
                 hp: c.hp,
                 stamina: c.stamina,
                 ammo: c.ammo,
+                vip: c.vip,
+                veterancy: c.veterancy,
+                roster_id: c.roster_id,
             });
 
             super::spawn_unit_with_state(
@@ -155,8 +155,10 @@ fn get_procedural_feature_color(terrain: Terrain) -> Color {
         Terrain::Hollow => Color::srgb(0.35, 0.25, 0.18),
         Terrain::Log => Color::srgb(0.45, 0.32, 0.20),
         Terrain::Base => Color::srgb(0.50, 0.45, 0.40),
+        Terrain::HQ => Color::srgb(0.55, 0.35, 0.32),
         Terrain::Outpost => Color::srgb(0.55, 0.50, 0.45),
         Terrain::Storehouse => Color::srgb(0.48, 0.42, 0.35),
+        Terrain::Barricade => Color::srgb(0.40, 0.30, 0.20),
         _ => Color::WHITE,
     }
 }
@@ -188,6 +190,10 @@ fn get_procedural_feature_params(terrain: Terrain) -> (Color, Vec2) {
             Color::srgb(0.50, 0.45, 0.40),
             Vec2::new(TILE_SIZE * 0.85, 48.0)
         ),
+        Terrain::HQ => (
+            Color::srgb(0.55, 0.35, 0.32),
+            Vec2::new(TILE_SIZE * 0.9, 56.0)
+        ),
         Terrain::Outpost => (
             Color::srgb(0.55, 0.50, 0.45),
             Vec2::new(TILE_SIZE * 0.75, 40.0)
@@ -196,6 +202,10 @@ fn get_procedural_feature_params(terrain: Terrain) -> (Color, Vec2) {
             Color::srgb(0.48, 0.42, 0.35),
             Vec2::new(TILE_SIZE * 0.65, 32.0)
         ),
+        Terrain::Barricade => (
+            Color::srgb(0.40, 0.30, 0.20),
+            Vec2::new(TILE_SIZE * 0.9, 24.0)
+        ),
         _ => (Color::WHITE, Vec2::ZERO),
     }
 }
@@ -209,7 +219,7 @@ fn spawn_faction_flag_3d(
     terrain: Terrain,
 ) {
     let flag_height = match terrain {
-        Terrain::Base => 12.0,
+        Terrain::Base | Terrain::HQ => 12.0,
         Terrain::Outpost => 10.0,
         Terrain::Storehouse => 8.0,
         _ => 8.0,
@@ -0,0 +1,297 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use serde::{Deserialize, Serialize};
+
+use crate::states::GameState;
+use super::{
+    Faction, FactionMember, FactionFunds, GameData, GameMap, GridPosition, Terrain, Tile,
+    TerrainFeature, TurnStartEvent, Unit, SpriteAssets, TILE_SIZE, spawn_terrain_feature, tile_color,
+};
+
+pub struct TerrainActionPlugin;
+
+impl Plugin for TerrainActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainWorks>()
+            .add_message::<TerrainActionEvent>()
+            .add_systems(OnEnter(GameState::Battle), clear_terrain_works)
+            .add_systems(Update, (start_terrain_works, progress_terrain_works).chain());
+    }
+}
+
+/// Message fired when a unit orders a terrain action on a tile
+#[derive(Message)]
+pub struct TerrainActionEvent {
+    pub unit: Entity,
+    pub action_id: String,
+    pub position: (i32, i32),
+}
+
+/// A paid-for terrain action that is still under way
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerrainWork {
+    pub action_id: String,
+    pub position: (i32, i32),
+    pub faction: Faction,
+    pub turns_remaining: u32,
+}
+
+/// All terrain works currently in progress
+#[derive(Resource, Default)]
+pub struct TerrainWorks {
+    pub works: Vec<TerrainWork>,
+}
+
+impl TerrainWorks {
+    /// Check if a tile already has work in progress
+    pub fn is_under_work(&self, x: i32, y: i32) -> bool {
+        self.works.iter().any(|w| w.position == (x, y))
+    }
+}
+
+/// Bundled read-only state for offering terrain actions in menus
+#[derive(SystemParam)]
+pub struct TerrainActionContext<'w> {
+    pub works: Res<'w, TerrainWorks>,
+    pub funds: Res<'w, FactionFunds>,
+}
+
+/// Bundled access for swapping a tile's terrain mid-battle
+#[derive(SystemParam)]
+pub struct TerrainChanger<'w, 's> {
+    commands: Commands<'w, 's>,
+    pub tiles: Query<'w, 's, (Entity, &'static mut Tile, &'static mut Mesh3d, &'static mut MeshMaterial3d<StandardMaterial>, &'static mut Transform)>,
+    features: Query<'w, 's, (Entity, &'static TerrainFeature)>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    sprite_assets: Res<'w, SpriteAssets>,
+    images: Res<'w, Assets<Image>>,
+}
+
+impl TerrainChanger<'_, '_> {
+    /// Change the terrain at a position, rebuilding its tile and feature
+    pub fn change_terrain(&mut self, map: &mut GameMap, x: i32, y: i32, terrain: Terrain) {
+        map.set(x, y, terrain);
+
+        let offset_x = -(map.width as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
+        let offset_z = -(map.height as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
+
+        // Rebuild the tile cuboid for the new terrain
+        let mut owner = None;
+        for (_, mut tile, mut mesh, mut material, mut transform) in self.tiles.iter_mut() {
+            if tile.position != IVec2::new(x, y) {
+                continue;
+            }
+            tile.terrain = terrain;
+            tile.damage = 0;
+            if !terrain.is_capturable() {
+                tile.owner = None;
+                tile.capture_progress = 0;
+                tile.capturing_faction = None;
+            }
+            owner = tile.owner;
+
+            let tile_height = terrain.tile_height();
+            mesh.0 = self.meshes.add(Cuboid::new(TILE_SIZE - 2.0, tile_height, TILE_SIZE - 2.0));
+            material.0 = self.materials.add(StandardMaterial {
+                base_color: tile_color(terrain, tile.owner),
+                unlit: true,
+                ..default()
+            });
+            transform.translation.y = tile_height / 2.0;
+        }
+
+        // Replace the old feature sprite with the new terrain's, whatever stood there before
+        for (entity, feature) in self.features.iter() {
+            if feature.grid_position == IVec2::new(x, y) {
+                self.commands.entity(entity).despawn();
+            }
+        }
+        if terrain.has_feature() {
+            spawn_terrain_feature(
+                &mut self.commands,
+                &mut self.meshes,
+                &mut self.materials,
+                &self.sprite_assets,
+                &self.images,
+                x as u32,
+                y as u32,
+                terrain,
+                owner,
+                offset_x,
+                offset_z,
+            );
+        }
+    }
+}
+
+/// List the terrain actions a unit can order from its position
+/// Returns (action id, target tile) pairs for the unit's own tile and its neighbours
+pub fn available_terrain_actions(
+    unit: &Unit,
+    faction: Faction,
+    pos: (i32, i32),
+    map: &GameMap,
+    works: &TerrainWorks,
+    funds: u32,
+    game_data: &GameData,
+) -> Vec<(String, (i32, i32))> {
+    let mut ids: Vec<&String> = game_data.terrain_actions.actions.keys().collect();
+    ids.sort();
+
+    let candidates = [
+        pos,
+        (pos.0 - 1, pos.1),
+        (pos.0 + 1, pos.1),
+        (pos.0, pos.1 - 1),
+        (pos.0, pos.1 + 1),
+    ];
+
+    let mut result = Vec::new();
+    for id in ids {
+        if !game_data.can_perform_terrain_action(id, unit.unit_type, faction) {
+            continue;
+        }
+        let Some(action) = game_data.get_terrain_action(id) else { continue };
+        if action.cost > funds {
+            continue;
+        }
+
+        for &(x, y) in &candidates {
+            if map.get(x, y) != Some(action.from_terrain) || works.is_under_work(x, y) {
+                continue;
+            }
+            // Tunnels need an unlinked partner Hollow in range
+            if action.tunnel_range > 0
+                && (map.has_tunnel(x, y) || find_tunnel_partner(map, works, (x, y), action.tunnel_range).is_none())
+            {
+                continue;
+            }
+            result.push((id.clone(), (x, y)));
+        }
+    }
+    result
+}
+
+/// Find the nearest Hollow in range that is not yet a tunnel entrance
+fn find_tunnel_partner(map: &GameMap, works: &TerrainWorks, from: (i32, i32), range: u32) -> Option<(i32, i32)> {
+    let mut best: Option<((i32, i32), u32)> = None;
+    for y in 0..map.height as i32 {
+        for x in 0..map.width as i32 {
+            if (x, y) == from || map.get(x, y) != Some(Terrain::Hollow) || map.has_tunnel(x, y) {
+                continue;
+            }
+            // Don't pair with a hollow that is being dug from the other end
+            if works.is_under_work(x, y) {
+                continue;
+            }
+            let distance = ((x - from.0).abs() + (y - from.1).abs()) as u32;
+            if distance <= range && best.is_none_or(|(_, d)| distance < d) {
+                best = Some(((x, y), distance));
+            }
+        }
+    }
+    best.map(|(p, _)| p)
+}
+
+/// Drop works left over from a previous battle
+fn clear_terrain_works(mut works: ResMut<TerrainWorks>) {
+    works.works.clear();
+}
+
+/// Validate terrain action orders, pay for them and queue the work
+fn start_terrain_works(
+    mut events: MessageReader<TerrainActionEvent>,
+    units: Query<(&Unit, &GridPosition, &FactionMember)>,
+    map: Res<GameMap>,
+    game_data: Res<GameData>,
+    mut funds: ResMut<FactionFunds>,
+    mut works: ResMut<TerrainWorks>,
+) {
+    for event in events.read() {
+        let Ok((unit, pos, faction)) = units.get(event.unit) else {
+            continue;
+        };
+
+        let available = available_terrain_actions(
+            unit, faction.faction, (pos.x, pos.y), &map, &works, funds.get(faction.faction), &game_data,
+        );
+        if !available.iter().any(|(id, target)| *id == event.action_id && *target == event.position) {
+            warn!("Terrain action {} not possible at {:?}", event.action_id, event.position);
+            continue;
+        }
+
+        let Some(action) = game_data.get_terrain_action(&event.action_id) else {
+            continue;
+        };
+        if !funds.spend(faction.faction, action.cost) {
+            continue;
+        }
+
+        works.works.push(TerrainWork {
+            action_id: event.action_id.clone(),
+            position: event.position,
+            faction: faction.faction,
+            turns_remaining: action.duration.max(1),
+        });
+        info!("{:?} started {} at {:?} ({} turns, cost {})",
+            faction.faction, action.name, event.position, action.duration.max(1), action.cost);
+    }
+}
+
+/// Advance terrain works at their owner's turn start and apply finished ones
+fn progress_terrain_works(
+    mut events: MessageReader<TurnStartEvent>,
+    mut works: ResMut<TerrainWorks>,
+    mut map: ResMut<GameMap>,
+    game_data: Res<GameData>,
+    mut terrain_changer: TerrainChanger,
+) {
+    for event in events.read() {
+        let mut finished = Vec::new();
+        works.works.retain_mut(|work| {
+            if work.faction != event.faction {
+                return true;
+            }
+            work.turns_remaining = work.turns_remaining.saturating_sub(1);
+            if work.turns_remaining == 0 {
+                finished.push(work.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        for work in finished {
+            let Some(action) = game_data.get_terrain_action(&work.action_id) else {
+                continue;
+            };
+            let (x, y) = work.position;
+
+            // The tile may have changed since the order was given
+            if map.get(x, y) != Some(action.from_terrain) {
+                warn!("{} at {:?} abandoned: terrain changed", action.name, work.position);
+                continue;
+            }
+
+            if action.tunnel_range > 0 {
+                match find_tunnel_partner(&map, &works, work.position, action.tunnel_range) {
+                    Some(partner) => {
+                        map.tunnels.push((work.position, partner));
+                        info!("Tunnel dug between {:?} and {:?}", work.position, partner);
+                    }
+                    None => {
+                        warn!("{} at {:?} abandoned: no hollow in range", action.name, work.position);
+                        continue;
+                    }
+                }
+            }
+
+            if action.to_terrain != action.from_terrain {
+                terrain_changer.change_terrain(&mut map, x, y, action.to_terrain);
+            }
+
+            info!("{:?} completed {} at {:?}", work.faction, action.name, work.position);
+        }
+    }
+}
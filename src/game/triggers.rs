@@ -0,0 +1,358 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+use super::{
+    Faction, FactionFunds, FactionMember, FogOfWar, GameMap, GameResult, GridPosition, MapData, ObjectiveProgress,
+    Tile, TurnState, Unit, UnitPlacement, VictoryType, Weather, WeatherType, spawn_unit_with_state,
+};
+use crate::states::GameState;
+
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapTriggers>()
+            .add_systems(Update, run_map_triggers.run_if(in_state(GameState::Battle)));
+    }
+}
+
+// ============================================================================
+// TRIGGER DEFINITIONS
+// ============================================================================
+
+/// A scripted event on a map: once its condition holds, its actions run (once)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trigger {
+    /// Shown in the log when the trigger fires
+    #[serde(default)]
+    pub name: String,
+    pub when: TriggerCondition,
+    pub actions: Vec<TriggerAction>,
+}
+
+/// Rectangle of tiles, from (x, y) spanning width x height
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width as i32 && y < self.y + self.height as i32
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (self.y..self.y + self.height as i32).flat_map(move |y| (self.x..self.x + self.width as i32).map(move |x| (x, y)))
+    }
+
+    /// The region moved by (dx, dy) and clipped to a board of the given size, if any of it is left
+    fn shifted(&self, dx: i32, dy: i32, width: u32, height: u32) -> Option<Region> {
+        let left = (self.x + dx).max(0);
+        let top = (self.y + dy).max(0);
+        let right = (self.x + dx + self.width as i32).min(width as i32);
+        let bottom = (self.y + dy + self.height as i32).min(height as i32);
+        (right > left && bottom > top)
+            .then(|| Region { x: left, y: top, width: (right - left) as u32, height: (bottom - top) as u32 })
+    }
+}
+
+/// When a trigger fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TriggerCondition {
+    /// The battle has reached this turn (on the given side's phase, if set)
+    TurnReached {
+        turn: u32,
+        #[serde(default)]
+        faction: Option<Faction>,
+    },
+    /// A unit (of the given side, if set) stands in the region
+    UnitEnters {
+        #[serde(default)]
+        faction: Option<Faction>,
+        region: Region,
+    },
+    /// The property on this tile is owned by the side
+    PropertyCaptured { x: i32, y: i32, by: Faction },
+    /// The side has fewer than this many units left
+    UnitCountBelow { faction: Faction, count: u32 },
+    /// Every condition holds
+    All(Vec<TriggerCondition>),
+    /// Any condition holds
+    Any(Vec<TriggerCondition>),
+}
+
+/// What a trigger does when it fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TriggerAction {
+    /// Bring in units; placements on occupied or missing tiles are skipped
+    SpawnUnits(Vec<UnitPlacement>),
+    /// Show a line of dialogue
+    Dialogue { speaker: String, text: String },
+    /// Change the weather, for a number of turns (0 = until it next changes)
+    SetWeather {
+        weather: WeatherType,
+        #[serde(default)]
+        turns: u32,
+    },
+    GrantFunds { faction: Faction, amount: u32 },
+    /// Lift the fog over a region for a side, for the rest of the battle
+    RevealFog { faction: Faction, region: Region },
+    /// End the battle; no winner is a draw
+    Victory(Option<Faction>),
+}
+
+impl Trigger {
+    /// Swap every side the trigger names, as when a map's sides are recolored
+    pub fn recolor(&mut self, recolor: &dyn Fn(Faction) -> Faction) {
+        self.when.recolor(recolor);
+        for action in &mut self.actions {
+            match action {
+                TriggerAction::SpawnUnits(placements) => {
+                    for placement in placements {
+                        placement.faction = recolor(placement.faction);
+                    }
+                }
+                TriggerAction::GrantFunds { faction, .. } | TriggerAction::RevealFog { faction, .. } => {
+                    *faction = recolor(*faction);
+                }
+                TriggerAction::Victory(winner) => *winner = winner.map(recolor),
+                TriggerAction::Dialogue { .. } | TriggerAction::SetWeather { .. } => {}
+            }
+        }
+    }
+
+    /// The trigger with every tile it names moved by (dx, dy) onto a board of the given size.
+    /// Regions are clipped and spawns pushed off the board dropped; `None` if the condition
+    /// could no longer be met, as when its property or whole region is gone.
+    pub fn shifted(&self, dx: i32, dy: i32, width: u32, height: u32) -> Option<Trigger> {
+        let when = self.when.shifted(dx, dy, width, height)?;
+        let inside = |x: i32, y: i32| x >= 0 && y >= 0 && x < width as i32 && y < height as i32;
+        let actions = self.actions.iter()
+            .filter_map(|action| match action {
+                TriggerAction::SpawnUnits(placements) => {
+                    let placements: Vec<_> = placements.iter()
+                        .map(|p| UnitPlacement { x: p.x + dx, y: p.y + dy, ..p.clone() })
+                        .filter(|p| inside(p.x, p.y))
+                        .collect();
+                    (!placements.is_empty()).then_some(TriggerAction::SpawnUnits(placements))
+                }
+                TriggerAction::RevealFog { faction, region } => region.shifted(dx, dy, width, height)
+                    .map(|region| TriggerAction::RevealFog { faction: *faction, region }),
+                other => Some(other.clone()),
+            })
+            .collect();
+        Some(Trigger { name: self.name.clone(), when, actions })
+    }
+}
+
+impl TriggerCondition {
+    fn recolor(&mut self, recolor: &dyn Fn(Faction) -> Faction) {
+        match self {
+            TriggerCondition::TurnReached { faction, .. } | TriggerCondition::UnitEnters { faction, .. } => {
+                *faction = faction.map(recolor);
+            }
+            TriggerCondition::PropertyCaptured { by: faction, .. } | TriggerCondition::UnitCountBelow { faction, .. } => {
+                *faction = recolor(*faction);
+            }
+            TriggerCondition::All(conditions) | TriggerCondition::Any(conditions) => {
+                for condition in conditions {
+                    condition.recolor(recolor);
+                }
+            }
+        }
+    }
+
+    fn shifted(&self, dx: i32, dy: i32, width: u32, height: u32) -> Option<TriggerCondition> {
+        match self {
+            TriggerCondition::UnitEnters { faction, region } => region.shifted(dx, dy, width, height)
+                .map(|region| TriggerCondition::UnitEnters { faction: *faction, region }),
+            TriggerCondition::PropertyCaptured { x, y, by } => {
+                let (x, y) = (x + dx, y + dy);
+                (x >= 0 && y >= 0 && x < width as i32 && y < height as i32)
+                    .then_some(TriggerCondition::PropertyCaptured { x, y, by: *by })
+            }
+            // Every part has to survive for the whole to still be reachable
+            TriggerCondition::All(conditions) => conditions.iter()
+                .map(|c| c.shifted(dx, dy, width, height))
+                .collect::<Option<Vec<_>>>()
+                .map(TriggerCondition::All),
+            TriggerCondition::Any(conditions) => {
+                let conditions: Vec<_> = conditions.iter().filter_map(|c| c.shifted(dx, dy, width, height)).collect();
+                (!conditions.is_empty()).then_some(TriggerCondition::Any(conditions))
+            }
+            TriggerCondition::TurnReached { .. } | TriggerCondition::UnitCountBelow { .. } => Some(self.clone()),
+        }
+    }
+}
+
+/// A line of dialogue waiting to be shown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DialogueLine {
+    pub speaker: String,
+    pub text: String,
+}
+
+/// Read triggers written in RON, as kept next to a map file
+pub fn load_triggers(path: &Path) -> Result<Vec<Trigger>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read triggers: {}", e))?;
+    ron::from_str(&content)
+        .map_err(|e| format!("Failed to parse triggers: {}", e))
+}
+
+// ============================================================================
+// CONDITIONS
+// ============================================================================
+
+/// What trigger conditions are checked against
+struct BattleView {
+    turn: u32,
+    current_faction: Faction,
+    units: Vec<(Faction, (i32, i32))>,
+    owners: HashMap<(i32, i32), Faction>,
+}
+
+impl TriggerCondition {
+    fn met(&self, view: &BattleView) -> bool {
+        match self {
+            TriggerCondition::TurnReached { turn, faction } => {
+                view.turn >= *turn && faction.is_none_or(|f| f == view.current_faction)
+            }
+            TriggerCondition::UnitEnters { faction, region } => view.units.iter()
+                .any(|&(f, (x, y))| faction.is_none_or(|wanted| wanted == f) && region.contains(x, y)),
+            TriggerCondition::PropertyCaptured { x, y, by } => view.owners.get(&(*x, *y)) == Some(by),
+            TriggerCondition::UnitCountBelow { faction, count } => {
+                view.units.iter().filter(|(f, _)| f == faction).count() < *count as usize
+            }
+            TriggerCondition::All(conditions) => conditions.iter().all(|c| c.met(view)),
+            TriggerCondition::Any(conditions) => conditions.iter().any(|c| c.met(view)),
+        }
+    }
+}
+
+// ============================================================================
+// RUNTIME
+// ============================================================================
+
+/// The battle's triggers and what they've done so far; saved with the battle
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MapTriggers {
+    pub triggers: Vec<Trigger>,
+    /// Indices of triggers that have fired
+    pub fired: HashSet<usize>,
+    /// Dialogue waiting for the player to read
+    pub dialogue: VecDeque<DialogueLine>,
+}
+
+impl MapTriggers {
+    pub fn new(map_data: &MapData) -> Self {
+        Self { triggers: map_data.triggers.clone(), ..Default::default() }
+    }
+}
+
+/// Everything a trigger's actions can change
+#[derive(SystemParam)]
+struct TriggerEffects<'w, 's> {
+    commands: Commands<'w, 's>,
+    game_map: Res<'w, GameMap>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    sprite_assets: Res<'w, super::SpriteAssets>,
+    images: Res<'w, Assets<Image>>,
+    funds: ResMut<'w, FactionFunds>,
+    weather: ResMut<'w, Weather>,
+    fog: ResMut<'w, FogOfWar>,
+    game_result: ResMut<'w, GameResult>,
+    objectives: ResMut<'w, ObjectiveProgress>,
+}
+
+/// Fire every trigger whose condition has come true
+fn run_map_triggers(
+    mut map_triggers: ResMut<MapTriggers>,
+    turn_state: Res<TurnState>,
+    units: Query<(&GridPosition, &FactionMember), With<Unit>>,
+    tiles: Query<&Tile>,
+    mut effects: TriggerEffects,
+) {
+    // Nothing to react to before the board is populated or after the battle ends
+    if map_triggers.triggers.is_empty() || effects.game_result.game_over || units.is_empty() {
+        return;
+    }
+
+    let mut view = BattleView {
+        turn: turn_state.turn_number,
+        current_faction: turn_state.current_faction,
+        units: units.iter().map(|(pos, member)| (member.faction, (pos.x, pos.y))).collect(),
+        owners: tiles.iter()
+            .filter_map(|t| t.owner.map(|owner| ((t.position.x, t.position.y), owner)))
+            .collect(),
+    };
+
+    for index in 0..map_triggers.triggers.len() {
+        if effects.game_result.game_over {
+            break;
+        }
+        if map_triggers.fired.contains(&index) || !map_triggers.triggers[index].when.met(&view) {
+            continue;
+        }
+        map_triggers.fired.insert(index);
+        let trigger = map_triggers.triggers[index].clone();
+        info!("Trigger fired: {}", if trigger.name.is_empty() { "(unnamed)" } else { &trigger.name });
+
+        for action in &trigger.actions {
+            match action {
+                TriggerAction::SpawnUnits(placements) => {
+                    for placement in placements {
+                        let pos = (placement.x, placement.y);
+                        if effects.game_map.get(pos.0, pos.1).is_none() || view.units.iter().any(|(_, p)| *p == pos) {
+                            warn!("Trigger could not place {:?} at {:?}", placement.unit_type, pos);
+                            continue;
+                        }
+                        let TriggerEffects { commands, game_map, meshes, materials, sprite_assets, images, .. } = &mut effects;
+                        spawn_unit_with_state(
+                            commands,
+                            game_map,
+                            meshes,
+                            materials,
+                            sprite_assets,
+                            images,
+                            placement.faction,
+                            Unit { vip: placement.vip, ..Unit::new(placement.unit_type) },
+                            placement.x,
+                            placement.y,
+                        );
+                        if placement.vip {
+                            effects.objectives.vip_sides.insert(placement.faction);
+                        }
+                        view.units.push((placement.faction, pos));
+                    }
+                }
+                TriggerAction::Dialogue { speaker, text } => {
+                    map_triggers.dialogue.push_back(DialogueLine { speaker: speaker.clone(), text: text.clone() });
+                }
+                TriggerAction::SetWeather { weather, turns } => {
+                    effects.weather.set_for_turns(*weather, *turns);
+                }
+                TriggerAction::GrantFunds { faction, amount } => {
+                    effects.funds.add(*faction, *amount);
+                }
+                TriggerAction::RevealFog { faction, region } => {
+                    for (x, y) in region.tiles() {
+                        effects.fog.reveal(*faction, x, y);
+                    }
+                }
+                TriggerAction::Victory(winner) => {
+                    effects.game_result.game_over = true;
+                    effects.game_result.winner = *winner;
+                    effects.game_result.victory_type = VictoryType::Scripted;
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
 
-use super::{Faction, FactionMember, Unit, Tile, Terrain, Commanders, GridPosition};
+use super::{Faction, FactionMember, Unit, Tile, Terrain, Commanders, GridPosition, MapRules};
 
 pub struct TurnPlugin;
 
@@ -11,7 +11,7 @@ impl Plugin for TurnPlugin {
             .init_resource::<FactionFunds>()
             .init_resource::<GameResult>()
             .add_event::<TurnStartEvent>()
-            .add_systems(Update, (check_victory_condition, generate_income, resupply_units));
+            .add_systems(Update, (generate_income, resupply_units));
     }
 }
 
@@ -28,7 +28,14 @@ pub enum VictoryType {
     #[default]
     None,
     Elimination,   // All enemy units destroyed
-    HQCapture,     // Enemy HQ (base) captured
+    HQCapture,     // Every HQ captured
+    Properties,    // Enough properties owned
+    HoldTile,      // Objective tile held long enough
+    Survival,      // Still standing at the set turn
+    VipDestroyed,  // Enemy VIP destroyed
+    VipEscorted,   // Own VIP brought to an exit
+    TurnLimit,     // Best score when the map's turn limit ran out
+    Scripted,      // Ended by a map trigger
 }
 
 /// Event fired when a faction's turn starts
@@ -62,6 +69,10 @@ impl FactionFunds {
         *self.funds.get(&faction).unwrap_or(&0)
     }
 
+    pub fn set(&mut self, faction: Faction, amount: u32) {
+        self.funds.insert(faction, amount);
+    }
+
     pub fn add(&mut self, faction: Faction, amount: u32) {
         *self.funds.entry(faction).or_insert(0) += amount;
     }
@@ -83,14 +94,20 @@ pub struct TurnState {
     pub current_faction: Faction,
     pub turn_number: u32,
     pub phase: TurnPhase,
+    /// Sides that take turns, in order; a new turn number starts with the first
+    pub order: Vec<Faction>,
 }
 
+/// Fixed seating order; whichever of these sides are on a map move in this order
+const SEAT_ORDER: [Faction; 5] = [Faction::Eastern, Faction::Northern, Faction::Western, Faction::Southern, Faction::Nether];
+
 impl Default for TurnState {
     fn default() -> Self {
         Self {
             current_faction: Faction::Eastern,
             turn_number: 1,
             phase: TurnPhase::Select,
+            order: vec![Faction::Eastern, Faction::Northern],
         }
     }
 }
@@ -105,6 +122,28 @@ pub enum TurnPhase {
 }
 
 impl TurnState {
+    /// Fresh turn state for a battle between these sides, seated in the fixed faction order
+    pub fn for_sides(sides: &[Faction]) -> Self {
+        let mut state = Self::default();
+        let order: Vec<Faction> = SEAT_ORDER.iter().copied().filter(|f| sides.contains(f)).collect();
+        if order.len() >= 2 {
+            state.order = order;
+        }
+        state.current_faction = state.order[0];
+        state
+    }
+
+    /// Hand the turn to the next side in the order
+    pub fn next_faction(&mut self) {
+        let index = self.order.iter().position(|&f| f == self.current_faction);
+        let next = index.map_or(0, |i| (i + 1) % self.order.len());
+        if next == 0 {
+            self.turn_number += 1;
+        }
+        self.current_faction = self.order.get(next).copied().unwrap_or(Faction::Eastern);
+        self.phase = TurnPhase::Select;
+    }
+
     #[allow(dead_code)]
     pub fn end_turn<'a>(&mut self, units: impl Iterator<Item = &'a mut Unit>) {
         // Reset all units of current faction
@@ -112,81 +151,26 @@ impl TurnState {
             unit.reset_turn();
         }
 
-        // Switch to other faction (simple 2-player for now)
-        self.current_faction = match self.current_faction {
-            Faction::Eastern => Faction::Northern,
-            Faction::Northern => {
-                self.turn_number += 1;
-                Faction::Eastern
-            }
-            _ => Faction::Eastern,
-        };
-
-        self.phase = TurnPhase::Select;
+        self.next_faction();
     }
 }
 
-fn check_victory_condition(
-    units: Query<(&Unit, &FactionMember)>,
-    tiles: Query<&Tile>,
-    mut game_result: ResMut<GameResult>,
-) {
-    // Skip if game is already over
-    if game_result.game_over {
-        return;
-    }
-
-    // Count units per faction
-    let mut eastern_units = 0;
-    let mut northern_units = 0;
-
-    for (_unit, faction) in units.iter() {
-        match faction.faction {
-            Faction::Eastern => eastern_units += 1,
-            Faction::Northern => northern_units += 1,
-            _ => {}
-        }
-    }
-
-    // Check elimination victory
-    if eastern_units == 0 && northern_units > 0 {
-        game_result.game_over = true;
-        game_result.winner = Some(Faction::Northern);
-        game_result.victory_type = VictoryType::Elimination;
-        info!("Northern Realm wins by elimination!");
-        return;
-    } else if northern_units == 0 && eastern_units > 0 {
-        game_result.game_over = true;
-        game_result.winner = Some(Faction::Eastern);
-        game_result.victory_type = VictoryType::Elimination;
-        info!("Eastern Empire wins by elimination!");
-        return;
-    }
-
-    // Check HQ capture victory - count bases owned by each faction
-    let eastern_bases = tiles.iter()
-        .filter(|t| t.terrain == Terrain::Base && t.owner == Some(Faction::Eastern))
-        .count();
-    let northern_bases = tiles.iter()
-        .filter(|t| t.terrain == Terrain::Base && t.owner == Some(Faction::Northern))
-        .count();
-
-    // If one faction owns all bases (2+), they win by HQ capture
-    let total_bases = tiles.iter().filter(|t| t.terrain == Terrain::Base).count();
-
-    if total_bases >= 2 {
-        if eastern_bases == total_bases {
-            game_result.game_over = true;
-            game_result.winner = Some(Faction::Eastern);
-            game_result.victory_type = VictoryType::HQCapture;
-            info!("Eastern Empire wins by capturing all HQs!");
-        } else if northern_bases == total_bases {
-            game_result.game_over = true;
-            game_result.winner = Some(Faction::Northern);
-            game_result.victory_type = VictoryType::HQCapture;
-            info!("Northern Realm wins by capturing all HQs!");
-        }
-    }
+/// Announce the turn of the side now to move, with the income its properties bring in.
+/// Every way of ending a turn, a player's or an AI's, goes through here
+pub fn announce_turn_start<'a>(
+    turn_state: &TurnState,
+    tiles: impl Iterator<Item = &'a Tile>,
+    events: &mut MessageWriter<TurnStartEvent>,
+) -> u32 {
+    let income: u32 = tiles
+        .filter(|t| t.owner == Some(turn_state.current_faction))
+        .map(|t| t.terrain.income_value())
+        .sum();
+    events.write(TurnStartEvent {
+        faction: turn_state.current_faction,
+        income,
+    });
+    income
 }
 
 /// Generate income from owned properties when turn start event fires
@@ -195,6 +179,7 @@ fn generate_income(
     tiles: Query<&Tile>,
     mut funds: ResMut<FactionFunds>,
     commanders: Res<Commanders>,
+    rules: Res<MapRules>,
 ) {
     for event in events.read() {
         // Calculate base income from owned properties
@@ -203,9 +188,9 @@ fn generate_income(
             .map(|t| t.terrain.income_value())
             .sum();
 
-        // Apply CO income bonus
+        // Apply CO income bonus and the map's income multiplier
         let co_bonuses = commanders.get_bonuses(event.faction);
-        let income = (base_income as f32 * co_bonuses.income).round() as u32;
+        let income = (base_income as f32 * co_bonuses.income * rules.income_multiplier).round() as u32;
 
         if income > 0 {
             funds.add(event.faction, income);
@@ -231,7 +216,7 @@ fn resupply_units(
         // Build a map of supply buildings owned by this faction
         let supply_buildings: HashMap<(i32, i32), Terrain> = tiles.iter()
             .filter(|t| t.owner == Some(event.faction))
-            .filter(|t| matches!(t.terrain, Terrain::Base | Terrain::Storehouse))
+            .filter(|t| matches!(t.terrain, Terrain::Base | Terrain::HQ | Terrain::Storehouse))
             .map(|t| ((t.position.x, t.position.y), t.terrain))
             .collect();
 
@@ -695,6 +695,17 @@ impl UnitType {
         ]
     }
 
+    /// Unit types a base can produce, for players and controllers alike
+    pub fn base_buildable() -> &'static [UnitType] {
+        &[
+            UnitType::Scout,
+            UnitType::Shocktrooper,
+            UnitType::Recon,
+            UnitType::Siege,
+            UnitType::Ironclad,
+        ]
+    }
+
     /// Get unit production cost (base cost before CO modifiers)
     pub fn cost(&self) -> u32 {
         match self {
@@ -744,7 +755,7 @@ pub struct UnitStats {
 }
 
 /// Component for unit entities
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Unit {
     pub unit_type: UnitType,
     pub hp: i32,
@@ -759,15 +770,33 @@ pub struct Unit {
     /// Cargo - for transport units, stores the type and HP of the loaded unit
     /// We store unit info rather than Entity to avoid complex entity relationships
     pub cargo: Option<CargoUnit>,
+    /// Flagged by the map for escort and assassination objectives
+    #[serde(default)]
+    pub vip: bool,
+    /// Veterancy level (0 to `MAX_VETERANCY`), gained by destroying enemies
+    #[serde(default)]
+    pub veterancy: u32,
+    /// Roguelike roster entry this unit stands for
+    #[serde(default)]
+    pub roster_id: Option<u32>,
 }
 
+/// Highest veterancy level a unit can reach
+pub const MAX_VETERANCY: u32 = 3;
+
 /// Represents a unit being carried by a transport
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CargoUnit {
     pub unit_type: UnitType,
     pub hp: i32,
     pub stamina: u32,
     pub ammo: u32,
+    #[serde(default)]
+    pub vip: bool,
+    #[serde(default)]
+    pub veterancy: u32,
+    #[serde(default)]
+    pub roster_id: Option<u32>,
 }
 
 impl CargoUnit {
@@ -777,6 +806,9 @@ impl CargoUnit {
             hp: unit.hp,
             stamina: unit.stamina,
             ammo: unit.ammo,
+            vip: unit.vip,
+            veterancy: unit.veterancy,
+            roster_id: unit.roster_id,
         }
     }
 
@@ -790,6 +822,9 @@ impl CargoUnit {
             attacked: false,
             exhausted: true,  // Unloaded units can't act this turn
             cargo: None,
+            vip: self.vip,
+            veterancy: self.veterancy,
+            roster_id: self.roster_id,
         }
     }
 }
@@ -806,9 +841,36 @@ impl Unit {
             attacked: false,
             exhausted: false,
             cargo: None,
+            vip: false,
+            veterancy: 0,
+            roster_id: None,
+        }
+    }
+
+    /// Attack and defense bonus from veterancy, in CO bonus points (100 = +100%)
+    pub fn veterancy_bonus(&self) -> f32 {
+        match self.veterancy {
+            0 => 0.0,
+            1 => 5.0,
+            2 => 10.0,
+            _ => 20.0,
+        }
+    }
+
+    /// Raise veterancy after destroying an enemy; only roguelike roster units carry it between battles,
+    /// so no one else gains any
+    pub fn promote(&mut self) {
+        if self.roster_id.is_some() {
+            self.veterancy = (self.veterancy + 1).min(MAX_VETERANCY);
         }
     }
 
+    /// Whether `other` can merge into this unit. Two roguelike roster units never join,
+    /// since the roster keeps one entry per unit.
+    pub fn can_join(&self, other: &Unit) -> bool {
+        self.unit_type == other.unit_type && !(self.roster_id.is_some() && other.roster_id.is_some())
+    }
+
     /// Check if this unit can carry other units
     pub fn is_transport(&self) -> bool {
         matches!(self.unit_type, UnitType::Carrier | UnitType::Ferrier | UnitType::Barge)
@@ -1000,6 +1062,8 @@ pub fn spawn_unit_with_state(
     let world_pos = grid_pos.to_world(map);
 
     let unit_type = unit.unit_type;
+    // VIPs get a gold frame so objectives are easy to spot
+    let border_color = if unit.vip { Color::srgba(0.95, 0.8, 0.2, 0.95) } else { Color::srgba(0.1, 0.1, 0.1, 0.9) };
     let unit_size = Vec2::new(TILE_SIZE * 0.7, TILE_SIZE * 0.6);
 
     let stats = unit_type.stats();
@@ -1044,7 +1108,7 @@ pub fn spawn_unit_with_state(
         parent.spawn((
             Mesh3d(border_mesh),
             MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: Color::srgba(0.1, 0.1, 0.1, 0.9),
+                base_color: border_color,
                 unlit: true,
                 alpha_mode: AlphaMode::Blend,
                 double_sided: true,
@@ -147,6 +147,28 @@ impl WeatherEffects {
     }
 }
 
+/// How a map drives the weather over a battle
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum WeatherRule {
+    /// Starts clear and changes at random
+    #[default]
+    Random,
+    /// The same weather all battle
+    Fixed(WeatherType),
+    /// (turn, weather) entries; each holds from its turn until the next
+    Schedule(Vec<(u32, WeatherType)>),
+}
+
+impl WeatherRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeatherRule::Random => "Random",
+            WeatherRule::Fixed(_) => "Fixed",
+            WeatherRule::Schedule(_) => "Schedule",
+        }
+    }
+}
+
 // ============================================================================
 // WEATHER RESOURCE & EVENTS
 // ============================================================================
@@ -162,6 +184,8 @@ pub struct Weather {
     pub dynamic_weather: bool,
     /// Chance (0-100) of weather changing each turn when dynamic
     pub change_chance: u32,
+    /// Weather set by the map on given turns, each holding until the next
+    pub schedule: Vec<(u32, WeatherType)>,
     /// Cached effects for current weather
     effects: WeatherEffects,
 }
@@ -173,6 +197,7 @@ impl Default for Weather {
             turns_remaining: 0,
             dynamic_weather: true,
             change_chance: 20, // 20% chance per turn
+            schedule: Vec::new(),
             effects: WeatherEffects::from_weather(WeatherType::Clear),
         }
     }
@@ -186,10 +211,41 @@ impl Weather {
             turns_remaining: 0,
             dynamic_weather: true,
             change_chance: 20,
+            schedule: Vec::new(),
             effects: WeatherEffects::from_weather(weather_type),
         }
     }
 
+    /// Starting weather for a battle played under a map's weather rule
+    pub fn from_rule(rule: &WeatherRule) -> Self {
+        match rule {
+            WeatherRule::Random => Self::default(),
+            WeatherRule::Fixed(weather_type) => Self {
+                dynamic_weather: false,
+                ..Self::new(*weather_type)
+            },
+            WeatherRule::Schedule(schedule) => {
+                let mut weather = Self {
+                    dynamic_weather: false,
+                    schedule: schedule.clone(),
+                    ..Self::default()
+                };
+                if let Some(first) = weather.scheduled(1) {
+                    weather.set(first);
+                }
+                weather
+            }
+        }
+    }
+
+    /// Weather the schedule calls for on a turn, if any entry has come up yet
+    pub fn scheduled(&self, turn: u32) -> Option<WeatherType> {
+        self.schedule.iter()
+            .filter(|(from, _)| *from <= turn)
+            .max_by_key(|(from, _)| *from)
+            .map(|(_, weather_type)| *weather_type)
+    }
+
     /// Set weather to a specific type
     pub fn set(&mut self, weather_type: WeatherType) {
         self.current = weather_type;
@@ -310,7 +366,15 @@ fn weather_change_system(
     }
 
     let old_weather = weather.current;
-    if let Some(new_weather) = weather.try_random_change() {
+    let changed = match weather.scheduled(turn_state.turn_number) {
+        Some(scheduled) if scheduled != old_weather => {
+            weather.set(scheduled);
+            Some(scheduled)
+        }
+        Some(_) => None,
+        None => weather.try_random_change(),
+    };
+    if let Some(new_weather) = changed {
         info!("Weather changed from {:?} to {:?}!", old_weather, new_weather);
         weather_events.write(WeatherChangedEvent {
             old_weather,
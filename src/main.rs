@@ -12,6 +12,18 @@ fn main() {
     #[cfg(target_arch = "wasm32")]
     console_error_panic_hook::set_once();
 
+    // `--validate-map [PATH...]` checks maps and `--gym-bench [EPISODES]` times the training
+    // environment; both exit without opening a window
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        match args.first().map(String::as_str) {
+            Some("--validate-map") => std::process::exit(game::validate_maps_cli(&args[1..])),
+            Some("--gym-bench") => std::process::exit(game::gym_bench_cli(&args[1..])),
+            _ => {}
+        }
+    }
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -1,16 +1,28 @@
 use bevy::prelude::*;
 use bevy::ecs::system::SystemParam;
 use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass, input::EguiWantsInput};
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
 
 use crate::game::{
     TurnState, TurnPhase, Unit, FactionMember, Faction, GridPosition,
     MovementHighlights, PendingAction, ProductionState, AttackEvent, CaptureEvent, JoinEvent, ResupplyEvent, LoadEvent, UnloadEvent,
-    TurnStartEvent, FactionFunds, GameMap, Terrain, Tile, UnitType, spawn_unit,
-    estimate_damage, AiState, GameResult, VictoryType, FogOfWar, Commanders,
-    PowerActivatedEvent, CommanderId, MapId, get_builtin_map,
+    TurnStartEvent, announce_turn_start, FactionFunds, GameMap, Terrain, Tile, UnitType, spawn_unit,
+    estimate_damage, calculate_structure_damage, AiState, AiDifficulty, AiControllers, ControllerCatalog, ControllerSettings, GameResult, VictoryType, FogOfWar, Commanders,
+    PowerActivatedEvent, CommanderId, MapId, MapLibrary, library_location,
     spawn_map_from_data, spawn_units_from_data, MapData, UnitPlacement, PropertyOwnership,
     TILE_SIZE, Weather, WeatherType, SpriteAssets, screen_to_grid, TilesetTheme,
-    InputMode, GameData, CancelMoveEvent, GridCursor,
+    InputMode, GameData, CancelMoveEvent, GridCursor, GameStateContext,
+    TerrainActionEvent, TerrainActionContext, available_terrain_actions, TrapEvent,
+    AiDebugOverlay, InfluenceLayer,
+    Symmetry, MapHistory, MapClipboard, paint_terrain, place_unit, remove_unit, set_owner, clear_owner,
+    flood_fill_tiles, line_tiles, rect_tiles, ResizeAnchor, resize_map, shift_map, crop_map,
+    MIN_MAP_WIDTH, MIN_MAP_HEIGHT, MAX_MAP_SIZE, CameraAngle, CameraBounds, cursor_ground_hit, world_to_grid,
+    tile_color, BoardFoundation, TerrainFeature, MapReport, validate_map, MapRules, WeatherRule,
+    MapObjective, Objective, ObjectiveProgress, ScoreMethod, default_objectives, MapTriggers,
+    CampaignLibrary, CampaignProgress, CampaignSession, ActiveMission, MissionMap,
+    RoguelikeSession, Run, RunStage, RunOutcome, NodeKind, ShopItem, spawn_unit_with_state,
+    RUN_PLAYER, RUN_ENEMY, RUN_FACTIONS, MAX_ROSTER, MAX_VETERANCY, HEAL_PRICE, TRAIN_PRICE, SHOP_PERK_PRICE, CoBonuses,
 };
 use crate::states::GameState;
 
@@ -23,6 +35,7 @@ pub enum MenuAction {
     Resupply,
     Load((i32, i32)),  // Position of transport
     Unload((i32, i32)), // Position to unload to
+    TerrainAction(String, (i32, i32)), // Terrain action id and target tile
     Wait,
     Cancel,  // Undo move - return unit to original position
     EndTurn,
@@ -49,6 +62,51 @@ pub struct ActionEvents<'w> {
     pub load: MessageWriter<'w, LoadEvent>,
     pub unload: MessageWriter<'w, UnloadEvent>,
     pub cancel_move: MessageWriter<'w, CancelMoveEvent>,
+    pub terrain_action: MessageWriter<'w, TerrainActionEvent>,
+}
+
+/// SystemParam bundle for spawning a battlefield from map data
+#[derive(SystemParam)]
+pub struct BattlefieldSpawner<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub game_map: ResMut<'w, GameMap>,
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub materials: ResMut<'w, Assets<StandardMaterial>>,
+    pub sprite_assets: Res<'w, SpriteAssets>,
+    pub images: Res<'w, Assets<Image>>,
+    pub tileset_theme: ResMut<'w, TilesetTheme>,
+    pub funds: ResMut<'w, FactionFunds>,
+    pub weather: ResMut<'w, Weather>,
+    pub fog: ResMut<'w, FogOfWar>,
+    pub rules: ResMut<'w, MapRules>,
+    pub objectives: ResMut<'w, ObjectiveProgress>,
+    pub triggers: ResMut<'w, MapTriggers>,
+    pub turn_state: ResMut<'w, TurnState>,
+}
+
+impl BattlefieldSpawner<'_, '_> {
+    /// Apply the map's rules, then spawn its tiles and starting units
+    pub fn spawn(&mut self, map_data: &MapData) {
+        let rules = &map_data.rules;
+        *self.funds = FactionFunds::default();
+        for faction in map_data.factions() {
+            self.funds.set(faction, rules.starting_funds(faction));
+        }
+        *self.weather = Weather::from_rule(&rules.weather);
+        match rules.fog {
+            Some(fog) => self.fog.force(fog),
+            None => self.fog.release(),
+        }
+        self.fog.clear_revealed();
+        *self.tileset_theme = rules.theme;
+        *self.rules = rules.clone();
+        *self.objectives = ObjectiveProgress::new(map_data);
+        *self.triggers = MapTriggers::new(map_data);
+        *self.turn_state = TurnState::for_sides(&map_data.factions());
+
+        spawn_map_from_data(&mut self.commands, &mut self.game_map, &mut self.meshes, &mut self.materials, &self.sprite_assets, &self.images, map_data, *self.tileset_theme);
+        spawn_units_from_data(&mut self.commands, &self.game_map, &mut self.meshes, &mut self.materials, &self.sprite_assets, &self.images, map_data);
+    }
 }
 
 /// Resource to track battle setup (CO + Map selection)
@@ -58,6 +116,24 @@ pub struct BattleSetupState {
     pub player_faction: Faction,
     pub player_co: Option<CommanderId>,
     pub selected_map: MapId,
+    pub ai_difficulty: AiDifficulty,
+    /// Seed for the AI, so a battle can be replayed exactly
+    pub ai_seed: u64,
+    /// Index into the controller catalog
+    pub ai_controller: usize,
+}
+
+/// Resource for playtesting the editor's map without leaving the editor session
+#[derive(Resource, Default)]
+pub struct PlaytestState {
+    /// Map handed over by the editor, taken when the battle starts
+    pub map: Option<MapData>,
+    /// Factions played by the AI; everyone else is human
+    pub ai_factions: HashSet<Faction>,
+    /// A playtest battle is running
+    pub active: bool,
+    /// The editor should keep its map when re-entered
+    pub returning: bool,
 }
 
 impl Default for BattleSetupState {
@@ -67,6 +143,9 @@ impl Default for BattleSetupState {
             player_faction: Faction::Eastern,
             player_co: Some(CommanderId::Kira),  // Pre-select first CO
             selected_map: MapId::Woodland,
+            ai_difficulty: AiDifficulty::default(),
+            ai_seed: rand::random(),
+            ai_controller: 0,
         }
     }
 }
@@ -80,6 +159,42 @@ pub enum EditorMode {
     Properties,
 }
 
+/// Painting tool for map editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EditorTool {
+    #[default]
+    Brush,
+    Fill,
+    Line,
+    Rectangle,
+    /// Select a region to copy; right click pastes
+    Select,
+}
+
+impl EditorTool {
+    pub fn all() -> [EditorTool; 5] {
+        [EditorTool::Brush, EditorTool::Fill, EditorTool::Line, EditorTool::Rectangle, EditorTool::Select]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EditorTool::Brush => "Brush",
+            EditorTool::Fill => "Fill",
+            EditorTool::Line => "Line",
+            EditorTool::Rectangle => "Rect",
+            EditorTool::Select => "Select",
+        }
+    }
+}
+
+/// A line, rectangle or selection being dragged out in the editor
+#[derive(Debug, Clone, Copy)]
+pub struct EditorDrag {
+    pub start: (i32, i32),
+    /// Dragged with the right button, painting grass
+    pub erase: bool,
+}
+
 /// Resource to track map editor state
 #[derive(Resource)]
 pub struct EditorState {
@@ -97,6 +212,30 @@ pub struct EditorState {
     pub map_name: String,
     /// Status message
     pub status: String,
+    /// Current painting tool
+    pub tool: EditorTool,
+    /// Rectangles are drawn as outlines instead of filled
+    pub outline_rect: bool,
+    /// Symmetry applied to every edit
+    pub symmetry: Symmetry,
+    /// Undo/redo snapshots
+    pub history: MapHistory,
+    /// Shape or selection currently being dragged
+    pub drag: Option<EditorDrag>,
+    /// Selected region (corners) for copying
+    pub selection: Option<((i32, i32), (i32, i32))>,
+    /// Last copied region
+    pub clipboard: Option<MapClipboard>,
+    /// Tile under the cursor
+    pub hovered: Option<(i32, i32)>,
+    /// Part of the map kept in place when resizing
+    pub anchor: ResizeAnchor,
+    /// Last validation result, refreshed on demand
+    pub report: Option<MapReport>,
+    /// Units placed now are flagged as VIPs
+    pub place_vip: bool,
+    /// The brush stroke in progress already has its undo step
+    pub stroke_recorded: bool,
 }
 
 impl Default for EditorState {
@@ -109,6 +248,103 @@ impl Default for EditorState {
             selected_faction: Faction::Eastern,
             map_name: "custom_map".to_string(),
             status: String::new(),
+            tool: EditorTool::Brush,
+            outline_rect: false,
+            symmetry: Symmetry::None,
+            history: MapHistory::default(),
+            drag: None,
+            selection: None,
+            clipboard: None,
+            hovered: None,
+            anchor: ResizeAnchor::TopLeft,
+            report: None,
+            place_vip: false,
+            stroke_recorded: false,
+        }
+    }
+}
+
+impl EditorState {
+    /// Snapshot the map before an edit
+    fn record(&mut self) {
+        self.history.record(&self.map);
+    }
+
+    /// Replace the whole map (open, clear, resize), keeping it undoable
+    pub fn replace_map(&mut self, map: MapData) {
+        self.record();
+        self.map = map;
+        self.drag = None;
+        self.selection = None;
+    }
+
+    fn undo(&mut self) {
+        self.drag = None;
+        self.status = if self.history.undo(&mut self.map) { "Undo".into() } else { "Nothing to undo".into() };
+    }
+
+    fn redo(&mut self) {
+        self.drag = None;
+        self.status = if self.history.redo(&mut self.map) { "Redo".into() } else { "Nothing to redo".into() };
+    }
+
+    fn copy_selection(&mut self) {
+        let Some((a, b)) = self.selection else {
+            self.status = "Select a region to copy".into();
+            return;
+        };
+        self.clipboard = MapClipboard::copy(&self.map, a, b);
+        if let Some(clipboard) = &self.clipboard {
+            self.status = format!("Copied {}x{} region", clipboard.width, clipboard.height);
+        }
+    }
+
+    fn paste_at(&mut self, (x, y): (i32, i32)) {
+        let Some(clipboard) = self.clipboard.take() else {
+            self.status = "Clipboard is empty".into();
+            return;
+        };
+        self.record();
+        clipboard.paste(&mut self.map, x, y);
+        self.status = format!("Pasted at ({}, {})", x, y);
+        self.clipboard = Some(clipboard);
+    }
+
+    /// Terrain painted by a drag: the brush, or grass when erasing
+    fn drag_terrain(&self, drag: EditorDrag) -> Terrain {
+        if drag.erase { Terrain::Grass } else { self.selected_terrain }
+    }
+
+    /// Tiles a line or rectangle drag would paint, including mirrors
+    fn drag_tiles(&self, drag: EditorDrag, end: (i32, i32)) -> Vec<(i32, i32)> {
+        let tiles = match self.tool {
+            EditorTool::Line => line_tiles(drag.start, end),
+            EditorTool::Rectangle => rect_tiles(drag.start, end, !self.outline_rect),
+            _ => return Vec::new(),
+        };
+        let mirrored: Vec<(i32, i32)> = tiles.iter()
+            .filter_map(|&(x, y)| self.symmetry.mirror(x, y, self.map.width, self.map.height))
+            .collect();
+        tiles.into_iter().chain(mirrored).collect()
+    }
+
+    /// Finish a drag where the mouse was released
+    fn finish_drag(&mut self, drag: EditorDrag) {
+        let Some(end) = self.hovered else { return };
+        match self.tool {
+            EditorTool::Line | EditorTool::Rectangle => {
+                let tiles = match self.tool {
+                    EditorTool::Line => line_tiles(drag.start, end),
+                    _ => rect_tiles(drag.start, end, !self.outline_rect),
+                };
+                let terrain = self.drag_terrain(drag);
+                self.record();
+                paint_terrain(&mut self.map, &tiles, terrain, self.symmetry);
+            }
+            EditorTool::Select => {
+                self.selection = Some((drag.start, end));
+            }
+            _ => {}
         }
     }
 }
@@ -120,6 +356,13 @@ pub struct HoveredUnit {
     pub screen_pos: (f32, f32),
 }
 
+/// Resource for the "Trap!" marker shown after a unit runs into a hidden enemy
+#[derive(Resource, Default)]
+pub struct TrapAlert {
+    pub position: Option<(i32, i32)>,
+    pub timer: f32,
+}
+
 /// Resource to track selected tile for info panel (right-click or hover)
 #[derive(Resource, Default)]
 pub struct SelectedTile {
@@ -127,6 +370,26 @@ pub struct SelectedTile {
     pub screen_pos: (f32, f32),
 }
 
+/// Campaign and mission selected on the campaign screen
+#[derive(Resource, Default)]
+pub struct CampaignMenuState {
+    pub campaign: usize,
+    pub mission: usize,
+    /// "Reset Progress" was clicked once and waits for confirmation
+    pub confirm_reset: bool,
+}
+
+/// Choices on the roguelike screen
+#[derive(Resource, Default)]
+pub struct RoguelikeMenuState {
+    /// Seed typed for a new run; blank picks one at random
+    pub seed: String,
+    /// Index into every CO, for a new run
+    pub commander: usize,
+    /// "Abandon Run" was clicked once and waits for confirmation
+    pub confirm_abandon: bool,
+}
+
 /// Resource to track in-game menu state
 #[derive(Resource, Default)]
 pub struct InGameMenuState {
@@ -160,10 +423,14 @@ impl Plugin for UiPlugin {
         app.add_plugins(EguiPlugin::default())
             .init_resource::<BattleSetupState>()
             .init_resource::<EditorState>()
+            .init_resource::<PlaytestState>()
+            .init_resource::<CampaignMenuState>()
+            .init_resource::<RoguelikeMenuState>()
             .init_resource::<HoveredUnit>()
             .init_resource::<SelectedTile>()
             .init_resource::<InGameMenuState>()
             .init_resource::<ActionMenuState>()
+            .init_resource::<TrapAlert>()
             .init_resource::<EguiReady>()
             .add_systems(Update, increment_egui_frame_counter)
             .add_systems(EguiPrimaryContextPass, (
@@ -176,7 +443,13 @@ impl Plugin for UiPlugin {
                 draw_unit_tooltip.run_if(in_state(GameState::Battle)),
                 draw_terrain_info_panel.run_if(in_state(GameState::Battle)),
                 draw_unit_hp_numbers.run_if(in_state(GameState::Battle)),
+                draw_trap_alert.run_if(in_state(GameState::Battle)),
+                draw_objectives.run_if(in_state(GameState::Battle)),
+                draw_dialogue.run_if(in_state(GameState::Battle)),
+                draw_ai_debug_window.run_if(in_state(GameState::Battle)),
                 draw_editor.run_if(in_state(GameState::Editor)),
+                draw_campaign.run_if(in_state(GameState::Campaign)),
+                draw_roguelike.run_if(in_state(GameState::Roguelike)),
             ).run_if(egui_is_ready))
             // Action menu registered separately
             .add_systems(EguiPrimaryContextPass,
@@ -188,10 +461,11 @@ impl Plugin for UiPlugin {
                 handle_fog_toggle.run_if(in_state(GameState::Battle)),
                 track_hovered_unit.run_if(in_state(GameState::Battle)),
                 track_hovered_tile.run_if(in_state(GameState::Battle)),
-                editor_paint.run_if(in_state(GameState::Editor)),
+                (editor_shortcuts, editor_paint, sync_editor_tiles).chain().run_if(in_state(GameState::Editor)),
             ))
             .add_systems(Startup, start_battle_for_testing)
-            .add_systems(OnEnter(GameState::Battle), trigger_battle_setup)
+            .add_systems(OnEnter(GameState::Battle), (trigger_battle_setup, start_playtest, start_campaign_mission, start_roguelike_battle).chain())
+            .add_systems(OnExit(GameState::Battle), (end_playtest, end_campaign_mission, end_roguelike_battle))
             .add_systems(OnEnter(GameState::Editor), setup_editor)
             .add_systems(OnExit(GameState::Editor), cleanup_editor);
     }
@@ -204,6 +478,222 @@ fn trigger_battle_setup(mut setup_state: ResMut<BattleSetupState>) {
     setup_state.selected_map = MapId::Woodland;
 }
 
+/// Start a playtest battle on the map handed over by the editor, skipping battle setup
+fn start_playtest(
+    mut playtest: ResMut<PlaytestState>,
+    mut setup_state: ResMut<BattleSetupState>,
+    mut spawner: BattlefieldSpawner,
+    mut commanders: ResMut<Commanders>,
+    mut ai_state: ResMut<AiState>,
+    mut controllers: ResMut<AiControllers>,
+    catalog: Res<ControllerCatalog>,
+) {
+    let Some(map_data) = playtest.map.take() else { return };
+    playtest.active = true;
+    setup_state.needs_setup = false;
+
+    ai_state.set_difficulty(setup_state.ai_difficulty, setup_state.ai_seed);
+    controllers.clear();
+
+    let factions = map_data.factions();
+    for &faction in &factions {
+        if let Some(&co) = CommanderId::for_faction(faction).first() {
+            commanders.set_commander(faction, co);
+        }
+        if playtest.ai_factions.contains(&faction) {
+            let settings = ControllerSettings {
+                faction,
+                difficulty: setup_state.ai_difficulty,
+                seed: setup_state.ai_seed,
+            };
+            controllers.set(faction, catalog.build(setup_state.ai_controller, &settings));
+        }
+    }
+
+    // Render fog from the first human player's point of view
+    if let Some(&human) = factions.iter().find(|f| !playtest.ai_factions.contains(f)) {
+        spawner.fog.viewer = human;
+    }
+
+    spawner.spawn(&map_data);
+    info!("Playtest started on {} (AI: {:?})", map_data.name, playtest.ai_factions);
+}
+
+/// Tear down a playtest battle so the editor gets a clean board back
+fn end_playtest(
+    mut commands: Commands,
+    mut playtest: ResMut<PlaytestState>,
+    mut controllers: ResMut<AiControllers>,
+    mut fog: ResMut<FogOfWar>,
+    battle_entities: Query<Entity, Or<(With<Unit>, With<Tile>, With<BoardFoundation>, With<TerrainFeature>)>>,
+) {
+    if !playtest.active {
+        return;
+    }
+    clear_battle(&mut commands, &mut controllers, &mut fog, battle_entities.iter());
+
+    playtest.active = false;
+    playtest.returning = true;
+    info!("Playtest ended, returning to editor");
+}
+
+/// Start the mission picked on the campaign screen, skipping battle setup
+fn start_campaign_mission(
+    mut session: ResMut<CampaignSession>,
+    library: Res<CampaignLibrary>,
+    map_library: Res<MapLibrary>,
+    mut setup_state: ResMut<BattleSetupState>,
+    mut spawner: BattlefieldSpawner,
+    mut commanders: ResMut<Commanders>,
+    mut ai_state: ResMut<AiState>,
+    mut controllers: ResMut<AiControllers>,
+    catalog: Res<ControllerCatalog>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some((campaign_index, mission_index)) = session.pending.take() else { return };
+    let Some(mission) = library.campaigns.get(campaign_index).and_then(|c| c.missions.get(mission_index)) else { return };
+    let map_data = match mission.build_map(&map_library) {
+        Ok(map_data) => map_data,
+        Err(e) => {
+            error!("Can't start mission '{}': {}", mission.name, e);
+            next_state.set(GameState::Campaign);
+            return;
+        }
+    };
+    setup_state.needs_setup = false;
+
+    // Every side but the player's is played by the built-in AI
+    let seed: u64 = rand::random();
+    ai_state.set_difficulty(mission.difficulty, seed);
+    controllers.clear();
+    for faction in map_data.factions() {
+        if let Some(co) = mission.commander(faction) {
+            commanders.set_commander(faction, co);
+        }
+        if faction != mission.player {
+            let settings = ControllerSettings { faction, difficulty: mission.difficulty, seed };
+            controllers.set(faction, catalog.build(0, &settings));
+        }
+    }
+
+    spawner.fog.viewer = mission.player;
+    spawner.spawn(&map_data);
+    session.last_result = None;
+    session.active = Some(ActiveMission {
+        campaign: campaign_index,
+        mission: mission_index,
+        player: mission.player,
+        starting_units: map_data.units.iter().filter(|u| u.faction == mission.player).count(),
+        finished: false,
+    });
+    info!("Mission started: {} ({:?} vs {:?})", mission.name, mission.player, mission.enemies);
+}
+
+/// Tear down a campaign battle so the next mission starts on a clean board
+fn end_campaign_mission(
+    mut commands: Commands,
+    mut session: ResMut<CampaignSession>,
+    mut controllers: ResMut<AiControllers>,
+    mut fog: ResMut<FogOfWar>,
+    battle_entities: Query<Entity, Or<(With<Unit>, With<Tile>, With<BoardFoundation>, With<TerrainFeature>)>>,
+) {
+    if session.active.take().is_none() {
+        return;
+    }
+    clear_battle(&mut commands, &mut controllers, &mut fog, battle_entities.iter());
+}
+
+/// Start the battle at the run's current node, skipping battle setup
+fn start_roguelike_battle(
+    mut session: ResMut<RoguelikeSession>,
+    mut setup_state: ResMut<BattleSetupState>,
+    mut spawner: BattlefieldSpawner,
+    mut commanders: ResMut<Commanders>,
+    mut ai_state: ResMut<AiState>,
+    mut controllers: ResMut<AiControllers>,
+    catalog: Res<ControllerCatalog>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !std::mem::take(&mut session.pending) {
+        return;
+    }
+    let Some(run) = session.run.as_mut() else { return };
+    let Some((layer, index)) = run.position else { return };
+    let Some(battle) = run.battle(layer, index) else {
+        error!("No battle at run node {}-{}", layer, index);
+        next_state.set(GameState::Roguelike);
+        return;
+    };
+    setup_state.needs_setup = false;
+
+    // The AI is seeded from the run too, so a run replays the same way
+    let seed = run.seed.wrapping_add(((layer as u64) << 8) | index as u64);
+    ai_state.set_difficulty(battle.difficulty, seed);
+    controllers.clear();
+    commanders.set_commander(RUN_PLAYER, run.commander);
+    commanders.set_commander(RUN_ENEMY, battle.enemy_co);
+    commanders.perks.insert(RUN_PLAYER, run.perk_bonuses());
+    let settings = ControllerSettings { faction: RUN_ENEMY, difficulty: battle.difficulty, seed };
+    controllers.set(RUN_ENEMY, catalog.build(0, &settings));
+
+    spawner.fog.viewer = RUN_PLAYER;
+    spawner.spawn(&battle.map);
+    let BattlefieldSpawner { commands, game_map, meshes, materials, sprite_assets, images, .. } = &mut spawner;
+    for (entry, &(x, y)) in run.roster.iter().zip(&battle.deployment) {
+        spawn_unit_with_state(commands, game_map, meshes, materials, sprite_assets, images, RUN_PLAYER, entry.to_unit(), x, y);
+    }
+    for placement in &battle.enemies {
+        let unit = Unit { veterancy: battle.enemy_veterancy, ..Unit::new(placement.unit_type) };
+        spawn_unit_with_state(commands, game_map, meshes, materials, sprite_assets, images, placement.faction, unit, placement.x, placement.y);
+    }
+
+    run.stage = RunStage::Battle { started: true };
+    info!("Run battle started on {} ({} enemies, {:?})", battle.map.name, battle.enemies.len(), battle.difficulty);
+    session.active = Some(false);
+    session.last_report = None;
+    session.save();
+}
+
+/// Tear down a run battle; leaving one before it's decided loses the run
+fn end_roguelike_battle(
+    mut commands: Commands,
+    mut session: ResMut<RoguelikeSession>,
+    mut controllers: ResMut<AiControllers>,
+    mut commanders: ResMut<Commanders>,
+    mut fog: ResMut<FogOfWar>,
+    battle_entities: Query<Entity, Or<(With<Unit>, With<Tile>, With<BoardFoundation>, With<TerrainFeature>)>>,
+) {
+    let Some(recorded) = session.active.take() else { return };
+    if !recorded {
+        if let Some(run) = session.run.as_mut() {
+            run.abandon_battle();
+        }
+        session.save();
+    }
+    clear_battle(&mut commands, &mut controllers, &mut fog, battle_entities.iter());
+    commanders.perks.clear();
+}
+
+/// Despawn a finished battle's board and reset its per-battle state for whatever comes next
+fn clear_battle(
+    commands: &mut Commands,
+    controllers: &mut AiControllers,
+    fog: &mut FogOfWar,
+    battle_entities: impl Iterator<Item = Entity>,
+) {
+    for entity in battle_entities {
+        commands.entity(entity).despawn();
+    }
+    commands.insert_resource(GameResult::default());
+    commands.insert_resource(TurnState::default());
+    commands.insert_resource(FactionFunds::default());
+    commands.insert_resource(MapRules::default());
+    commands.insert_resource(ObjectiveProgress::default());
+    commands.insert_resource(MapTriggers::default());
+    controllers.clear();
+    fog.release();
+}
+
 // Temporary: skip menu and go straight to battle for testing
 fn start_battle_for_testing(mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(GameState::Battle);
@@ -246,6 +736,499 @@ fn draw_main_menu(
     });
 }
 
+/// Campaign screen: pick an unlocked mission, read its briefing and start it
+fn draw_campaign(
+    mut contexts: EguiContexts,
+    library: Res<CampaignLibrary>,
+    mut progress: ResMut<CampaignProgress>,
+    mut session: ResMut<CampaignSession>,
+    mut menu: ResMut<CampaignMenuState>,
+    game_data: Res<GameData>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    // Debriefing for the mission just played
+    if let Some(result) = session.last_result.clone() {
+        let mut dismissed = false;
+        if let Some(mission) = library.campaigns.get(result.campaign).and_then(|c| c.missions.get(result.mission)) {
+            egui::Window::new("Debriefing")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.set_min_width(420.0);
+                    let (title, color) = if result.won {
+                        ("Mission Complete", egui::Color32::from_rgb(100, 255, 100))
+                    } else {
+                        ("Mission Failed", egui::Color32::from_rgb(255, 100, 100))
+                    };
+                    ui.label(egui::RichText::new(format!("{}: {}", title, mission.name)).size(20.0).strong().color(color));
+                    ui.label(format!("Turns: {} (par {})", result.turns, mission.par_turns));
+                    if let Some(rank) = result.rank {
+                        let best = if result.new_best { " - new best!" } else { "" };
+                        ui.label(egui::RichText::new(format!("Rank: {}{}", rank.name(), best)).size(16.0).strong());
+                    }
+                    ui.add_space(8.0);
+                    if result.won {
+                        for line in &mission.debriefing {
+                            ui.label(egui::RichText::new(format!("{}: {}", line.speaker, line.text)).size(13.0));
+                        }
+                        let campaign = &library.campaigns[result.campaign];
+                        let unlocked: Vec<&str> = mission.unlocks.iter()
+                            .filter_map(|id| campaign.mission(id))
+                            .map(|m| m.name.as_str())
+                            .collect();
+                        if !unlocked.is_empty() {
+                            ui.add_space(8.0);
+                            ui.label(egui::RichText::new(format!("Unlocked: {}", unlocked.join(", ")))
+                                .color(egui::Color32::from_rgb(255, 200, 50)));
+                        }
+                    } else {
+                        ui.label(egui::RichText::new("Regroup and try again.").weak());
+                    }
+                    ui.add_space(8.0);
+                    if ui.button("Continue").clicked() {
+                        dismissed = true;
+                    }
+                });
+        } else {
+            dismissed = true;
+        }
+        if dismissed {
+            session.last_result = None;
+        }
+    }
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui.button("< Main Menu").clicked() {
+                next_state.set(GameState::Menu);
+            }
+            ui.label(egui::RichText::new("Campaign").size(28.0).strong());
+        });
+        ui.separator();
+
+        if library.campaigns.is_empty() {
+            ui.label("No campaigns found.");
+            return;
+        }
+        menu.campaign = menu.campaign.min(library.campaigns.len() - 1);
+
+        // Campaign choice, when there's more than one
+        if library.campaigns.len() > 1 {
+            ui.horizontal(|ui| {
+                for (index, campaign) in library.campaigns.iter().enumerate() {
+                    if ui.selectable_label(menu.campaign == index, &campaign.name).clicked() && menu.campaign != index {
+                        menu.campaign = index;
+                        menu.mission = 0;
+                        menu.confirm_reset = false;
+                    }
+                }
+            });
+            ui.separator();
+        }
+
+        let campaign = &library.campaigns[menu.campaign];
+        ui.label(egui::RichText::new(&campaign.name).size(20.0).strong());
+        ui.label(egui::RichText::new(&campaign.description).italics().weak());
+        ui.add_space(10.0);
+
+        ui.horizontal_top(|ui| {
+            // === MISSION LIST ===
+            ui.vertical(|ui| {
+                ui.set_min_width(260.0);
+                ui.label(egui::RichText::new("Missions").size(16.0).strong());
+                for (index, mission) in campaign.missions.iter().enumerate() {
+                    if !progress.is_unlocked(campaign, &mission.id) {
+                        ui.label(egui::RichText::new(format!("  Locked - {}", mission.name)).weak());
+                        continue;
+                    }
+                    let rank = progress.best_rank(campaign, &mission.id)
+                        .map_or(String::new(), |r| format!("  [{}]", r.name()));
+                    if ui.selectable_label(menu.mission == index, format!("{}{}", mission.name, rank)).clicked() {
+                        menu.mission = index;
+                    }
+                }
+
+                ui.add_space(20.0);
+                let reset_text = if menu.confirm_reset { "Really reset?" } else { "Reset Progress" };
+                if ui.button(reset_text).clicked() {
+                    if menu.confirm_reset {
+                        progress.reset(campaign);
+                        if let Err(e) = progress.save() {
+                            error!("Failed to save campaign progress: {}", e);
+                        }
+                        menu.mission = 0;
+                    }
+                    menu.confirm_reset = !menu.confirm_reset;
+                }
+            });
+
+            ui.separator();
+
+            // === MISSION DETAILS ===
+            ui.vertical(|ui| {
+                let Some(mission) = campaign.missions.get(menu.mission) else { return };
+                ui.label(egui::RichText::new(&mission.name).size(18.0).strong());
+                ui.label(egui::RichText::new(&mission.description).italics());
+                ui.add_space(6.0);
+
+                let map_name = match &mission.map {
+                    MissionMap::Builtin(id) => id.name().to_string(),
+                    MissionMap::Library(file_name) => file_name.clone(),
+                };
+                ui.label(format!("Map: {}", map_name));
+                ui.label(format!("You: {} - {}",
+                    game_data.faction_name(mission.player), game_data.commander_name(mission.player_co)));
+                for &(faction, co) in &mission.enemies {
+                    ui.label(format!("Enemy: {} - {}", game_data.faction_name(faction), game_data.commander_name(co)));
+                }
+                ui.label(format!("Difficulty: {}", mission.difficulty.name()));
+                for objective in &mission.objectives {
+                    let side = objective.faction.map_or("All", |f| game_data.faction_name(f));
+                    ui.label(format!("Objective ({}): {}", side, objective.goal.describe()));
+                }
+                if let Some(limit) = mission.turn_limit {
+                    ui.label(format!("Turn limit: {}", limit));
+                }
+                ui.label(egui::RichText::new(format!("Par: {} turns", mission.par_turns)).weak());
+
+                if !mission.briefing.is_empty() {
+                    ui.add_space(10.0);
+                    ui.label(egui::RichText::new("Briefing").size(15.0).strong());
+                    for line in &mission.briefing {
+                        ui.label(egui::RichText::new(format!("{}: {}", line.speaker, line.text)).size(13.0));
+                    }
+                }
+
+                ui.add_space(15.0);
+                let unlocked = progress.is_unlocked(campaign, &mission.id);
+                ui.add_enabled_ui(unlocked, |ui| {
+                    if ui.add(egui::Button::new(egui::RichText::new("Start Mission").size(18.0).strong())
+                        .min_size(egui::vec2(200.0, 40.0))).clicked()
+                    {
+                        session.pending = Some((menu.campaign, menu.mission));
+                        next_state.set(GameState::Battle);
+                    }
+                });
+            });
+        });
+    });
+}
+
+/// Every CO that can lead a run, in faction order
+fn run_commanders() -> Vec<CommanderId> {
+    RUN_FACTIONS.iter().flat_map(|&f| CommanderId::for_faction(f)).collect()
+}
+
+fn draw_roguelike(
+    mut contexts: EguiContexts,
+    mut session: ResMut<RoguelikeSession>,
+    mut menu: ResMut<RoguelikeMenuState>,
+    game_data: Res<GameData>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    // Report on the battle just fought
+    if let Some(report) = session.last_report.clone() {
+        let mut dismissed = false;
+        egui::Window::new("Battle Report")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.set_min_width(360.0);
+                let (title, color) = if report.won {
+                    ("Battle Won", egui::Color32::from_rgb(100, 255, 100))
+                } else {
+                    ("Battle Lost", egui::Color32::from_rgb(255, 100, 100))
+                };
+                ui.label(egui::RichText::new(title).size(20.0).strong().color(color));
+                if report.funds > 0 {
+                    ui.label(format!("Funds earned: {}", report.funds));
+                }
+                let names = |units: &[UnitType]| units.iter().map(|&u| game_data.unit_name(u)).collect::<Vec<_>>().join(", ");
+                if !report.fallen.is_empty() {
+                    ui.label(egui::RichText::new(format!("Fallen: {}", names(&report.fallen)))
+                        .color(egui::Color32::from_rgb(255, 150, 150)));
+                }
+                if !report.promoted.is_empty() {
+                    ui.label(format!("Promoted: {}", names(&report.promoted)));
+                }
+                if !report.joined.is_empty() {
+                    ui.label(format!("Joined the roster: {}", names(&report.joined)));
+                }
+                ui.add_space(8.0);
+                if ui.button("Continue").clicked() {
+                    dismissed = true;
+                }
+            });
+        if dismissed {
+            session.last_report = None;
+        }
+    }
+
+    let mut changed = false;
+    let mut fight = false;
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui.button("< Main Menu").clicked() {
+                next_state.set(GameState::Menu);
+            }
+            ui.label(egui::RichText::new("Roguelike").size(28.0).strong());
+        });
+        ui.separator();
+
+        // === NEW RUN ===
+        let Some(run) = session.run.as_mut() else {
+            let commanders = run_commanders();
+            menu.commander = menu.commander.min(commanders.len().saturating_sub(1));
+            ui.label(egui::RichText::new("New Run").size(20.0).strong());
+            ui.label(egui::RichText::new("Lead a small army through battles, shops and events to the boss. Fallen units are gone for good.").italics());
+            ui.add_space(10.0);
+            ui.label("Commander:");
+            egui::Grid::new("run_commanders").num_columns(3).show(ui, |ui| {
+                for (index, &co) in commanders.iter().enumerate() {
+                    let data = co.data();
+                    if ui.selectable_label(menu.commander == index, game_data.commander_name(co)).clicked() {
+                        menu.commander = index;
+                    }
+                    ui.label(egui::RichText::new(game_data.faction_name(data.faction)).weak());
+                    ui.label(egui::RichText::new(format_co_bonuses(&data)).size(12.0));
+                    ui.end_row();
+                }
+            });
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.label("Seed:");
+                ui.add(egui::TextEdit::singleline(&mut menu.seed).hint_text("random").desired_width(160.0));
+            });
+            ui.add_space(10.0);
+            if ui.add(egui::Button::new(egui::RichText::new("Start Run").size(18.0).strong())
+                .min_size(egui::vec2(200.0, 40.0))).clicked()
+            {
+                let seed = menu.seed.trim().parse().unwrap_or_else(|_| rand::random());
+                if let Some(&co) = commanders.get(menu.commander) {
+                    session.run = Some(Run::new(seed, co));
+                    menu.confirm_abandon = false;
+                    changed = true;
+                    info!("New run started with seed {}", seed);
+                }
+            }
+            return;
+        };
+
+        // === RUN OVER ===
+        if let Some(outcome) = run.outcome {
+            let (title, color) = match outcome {
+                RunOutcome::Victory => ("Run Complete!", egui::Color32::from_rgb(100, 255, 100)),
+                RunOutcome::Defeat => ("Run Over", egui::Color32::from_rgb(255, 100, 100)),
+            };
+            ui.label(egui::RichText::new(title).size(24.0).strong().color(color));
+            ui.label(format!("Commander: {}", game_data.commander_name(run.commander)));
+            ui.label(format!("Battles won: {}", run.battles_won));
+            ui.label(format!("Units lost: {}", run.fallen.len()));
+            ui.label(format!("Seed: {}", run.seed));
+            ui.add_space(15.0);
+            if ui.add(egui::Button::new(egui::RichText::new("New Run").size(18.0)).min_size(egui::vec2(200.0, 40.0))).clicked() {
+                session.run = None;
+            }
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(game_data.commander_name(run.commander)).size(18.0).strong());
+            ui.separator();
+            ui.label(format!("Funds: {}", run.funds));
+            ui.separator();
+            ui.label(format!("Battles won: {}", run.battles_won));
+            ui.separator();
+            ui.label(egui::RichText::new(format!("Seed: {}", run.seed)).weak());
+        });
+        if !run.perks.is_empty() {
+            let perks: Vec<&str> = run.perks.iter().map(|p| p.name()).collect();
+            ui.label(egui::RichText::new(format!("Perks: {}", perks.join(", "))).color(egui::Color32::from_rgb(255, 200, 50)));
+        }
+        ui.add_space(8.0);
+
+        ui.horizontal_top(|ui| {
+            // === RUN MAP ===
+            ui.vertical(|ui| {
+                ui.set_min_width(300.0);
+                ui.label(egui::RichText::new("Route").size(16.0).strong());
+                let choices = run.choices();
+                let choosing = matches!(run.stage, RunStage::Map);
+                let mut entered = None;
+                for layer in (0..run.layers.len()).rev() {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(format!("{}", layer + 1)).weak());
+                        for (index, node) in run.layers[layer].iter().enumerate() {
+                            let here = run.position == Some((layer, index));
+                            let open = choosing && choices.contains(&(layer, index));
+                            let text = egui::RichText::new(node.kind.name()).color(match node.kind {
+                                NodeKind::Battle => egui::Color32::from_rgb(220, 220, 220),
+                                NodeKind::Elite => egui::Color32::from_rgb(255, 150, 80),
+                                NodeKind::Shop => egui::Color32::from_rgb(255, 220, 80),
+                                NodeKind::Event => egui::Color32::from_rgb(150, 200, 255),
+                                NodeKind::Boss => egui::Color32::from_rgb(255, 90, 90),
+                            });
+                            let text = if here { text.strong().underline() } else { text };
+                            let leads_to: Vec<String> = node.next.iter()
+                                .filter_map(|&i| run.layers.get(layer + 1).and_then(|l| l.get(i)))
+                                .map(|n| n.kind.name().to_string())
+                                .collect();
+                            let response = ui.add_enabled(open || here, egui::Button::new(text).selected(here));
+                            let response = if leads_to.is_empty() { response } else {
+                                response.on_hover_text(format!("Leads to: {}", leads_to.join(", ")))
+                            };
+                            if response.clicked() && open {
+                                entered = Some((layer, index));
+                            }
+                        }
+                    });
+                }
+                if let Some((layer, index)) = entered {
+                    run.enter(layer, index);
+                    changed = true;
+                }
+
+                ui.add_space(20.0);
+                let abandon_text = if menu.confirm_abandon { "Really abandon?" } else { "Abandon Run" };
+                if ui.button(abandon_text).clicked() {
+                    if menu.confirm_abandon {
+                        run.outcome = Some(RunOutcome::Defeat);
+                        changed = true;
+                    }
+                    menu.confirm_abandon = !menu.confirm_abandon;
+                }
+            });
+
+            ui.separator();
+
+            // === CURRENT NODE ===
+            ui.vertical(|ui| {
+                ui.set_min_width(320.0);
+                let mut train = None;
+                match run.stage.clone() {
+                    RunStage::Map => {
+                        ui.label(egui::RichText::new("Choose where to march next.").italics());
+                    }
+                    RunStage::Battle { .. } => {
+                        let Some((layer, index)) = run.position else { return };
+                        let Some(battle) = run.battle(layer, index) else { return };
+                        ui.label(egui::RichText::new(run.layers[layer][index].kind.name()).size(18.0).strong());
+                        ui.label(format!("Map: {} ({}x{})", battle.map.name, battle.map.width, battle.map.height));
+                        ui.label(format!("Enemy: {} - {} units", game_data.commander_name(battle.enemy_co), battle.enemies.len()));
+                        if battle.enemy_veterancy > 0 {
+                            ui.label(format!("Enemy veterancy: {}", battle.enemy_veterancy));
+                        }
+                        ui.label(format!("Difficulty: {}", battle.difficulty.name()));
+                        ui.label(egui::RichText::new("Leaving the battle before it ends loses the run.").weak());
+                        ui.add_space(10.0);
+                        if ui.add(egui::Button::new(egui::RichText::new("Fight").size(18.0).strong())
+                            .min_size(egui::vec2(200.0, 40.0))).clicked()
+                        {
+                            fight = true;
+                        }
+                    }
+                    RunStage::Shop(stock) => {
+                        ui.label(egui::RichText::new("Shop").size(18.0).strong());
+                        let room = run.roster.len() < MAX_ROSTER;
+                        for (slot, &unit_type) in stock.recruits.iter().enumerate() {
+                            let price = unit_type.cost();
+                            let label = format!("Recruit {} ({})", game_data.unit_name(unit_type), price);
+                            if ui.add_enabled(room && run.funds >= price, egui::Button::new(label)).clicked() {
+                                run.buy(ShopItem::Recruit(slot));
+                                changed = true;
+                            }
+                        }
+                        let label = format!("Heal all units ({})", HEAL_PRICE);
+                        if ui.add_enabled(!stock.healed && run.funds >= HEAL_PRICE, egui::Button::new(label)).clicked() {
+                            run.buy(ShopItem::Heal);
+                            changed = true;
+                        }
+                        if let Some(perk) = stock.perk {
+                            let label = format!("{} - {} ({})", perk.name(), perk.description(), SHOP_PERK_PRICE);
+                            if ui.add_enabled(run.funds >= SHOP_PERK_PRICE, egui::Button::new(label)).clicked() {
+                                run.buy(ShopItem::Perk);
+                                changed = true;
+                            }
+                        }
+                        ui.label(egui::RichText::new(format!("Train units in the roster for {} each.", TRAIN_PRICE)).weak());
+                        ui.add_space(10.0);
+                        if ui.button("Leave").clicked() {
+                            run.leave();
+                            changed = true;
+                        }
+                        train = Some(run.funds >= TRAIN_PRICE);
+                    }
+                    RunStage::Event(event) => {
+                        ui.label(egui::RichText::new(event.title()).size(18.0).strong());
+                        ui.label(event.text());
+                        ui.add_space(10.0);
+                        for (option, label) in event.options().into_iter().enumerate() {
+                            if ui.add_enabled(run.funds >= event.option_price(option), egui::Button::new(label)).clicked() {
+                                run.resolve_event(option);
+                                changed = true;
+                            }
+                        }
+                    }
+                    RunStage::Draft(perks) => {
+                        ui.label(egui::RichText::new("Choose a Perk").size(18.0).strong());
+                        for (choice, perk) in perks.iter().enumerate() {
+                            if ui.button(format!("{} - {}", perk.name(), perk.description())).clicked() {
+                                run.draft(choice);
+                                changed = true;
+                            }
+                        }
+                        ui.add_space(6.0);
+                        if ui.button("Skip").clicked() {
+                            run.leave();
+                            changed = true;
+                        }
+                    }
+                }
+
+                // === ROSTER ===
+                ui.add_space(15.0);
+                ui.label(egui::RichText::new(format!("Roster ({}/{})", run.roster.len(), MAX_ROSTER)).size(16.0).strong());
+                let mut trained = None;
+                egui::Grid::new("run_roster").num_columns(4).show(ui, |ui| {
+                    for entry in &run.roster {
+                        ui.label(game_data.unit_name(entry.unit_type));
+                        ui.label(format!("{}/{} HP", entry.hp, entry.max_hp()));
+                        ui.label("*".repeat(entry.veterancy as usize));
+                        if let Some(affordable) = train {
+                            let can_train = affordable && entry.veterancy < MAX_VETERANCY;
+                            if ui.add_enabled(can_train, egui::Button::new("Train")).clicked() {
+                                trained = Some(entry.id);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(id) = trained {
+                    run.buy(ShopItem::Train(id));
+                    changed = true;
+                }
+                if !run.fallen.is_empty() {
+                    ui.label(egui::RichText::new(format!("Fallen: {}", run.fallen.len())).weak());
+                }
+            });
+        });
+    });
+
+    if fight {
+        session.pending = true;
+        next_state.set(GameState::Battle);
+    }
+    if changed {
+        session.save();
+    }
+}
+
 /// Helper to format CO bonuses as string
 fn format_co_bonuses(co: &crate::game::Commander) -> String {
     let mut bonuses = Vec::new();
@@ -275,7 +1258,43 @@ fn format_co_bonuses(co: &crate::game::Commander) -> String {
     } else if co.cost_modifier > 1.0 {
         bonuses.push(format!("+{:.0}% Cost", (co.cost_modifier - 1.0) * 100.0));
     }
-    bonuses.join(" | ")
+    bonuses.join(" | ")
+}
+
+/// Helper to format the ways a map's rules differ from the defaults
+fn format_map_rules(rules: &MapRules, game_data: &GameData) -> String {
+    let defaults = MapRules::default();
+    let mut parts = Vec::new();
+    if !rules.starting_funds.is_empty() {
+        let mut funds: Vec<_> = rules.starting_funds.iter()
+            .map(|(faction, amount)| format!("{} {}", faction.name(), amount))
+            .collect();
+        funds.sort();
+        parts.push(format!("Funds: {}", funds.join(", ")));
+    }
+    if rules.income_multiplier != defaults.income_multiplier {
+        parts.push(format!("Income x{:.2}", rules.income_multiplier));
+    }
+    if let Some(fog) = rules.fog {
+        parts.push(if fog { "Fog ON".to_string() } else { "Fog OFF".to_string() });
+    }
+    match &rules.weather {
+        WeatherRule::Random => {}
+        WeatherRule::Fixed(weather) => parts.push(format!("Weather: {}", weather.name())),
+        WeatherRule::Schedule(schedule) => parts.push(format!("Weather: {} changes", schedule.len())),
+    }
+    if rules.objectives != defaults.objectives {
+        let goals: Vec<_> = rules.objectives.iter().map(|o| o.goal.describe()).collect();
+        parts.push(format!("Goals: {}", goals.join(", ")));
+    }
+    if let Some(limit) = rules.turn_limit {
+        parts.push(format!("Turn limit {} ({})", limit, rules.score.name()));
+    }
+    if !rules.banned_units.is_empty() {
+        let banned: Vec<_> = rules.banned_units.iter().map(|t| game_data.unit_name(*t)).collect();
+        parts.push(format!("Banned: {}", banned.join(", ")));
+    }
+    parts.join(" | ")
 }
 
 /// Draw battle setup screen (CO + Map selection)
@@ -283,19 +1302,26 @@ fn draw_battle_setup(
     mut contexts: EguiContexts,
     mut setup_state: ResMut<BattleSetupState>,
     mut commanders: ResMut<Commanders>,
-    mut commands: Commands,
-    mut game_map: ResMut<GameMap>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    sprite_assets: Res<SpriteAssets>,
-    images: Res<Assets<Image>>,
-    tileset_theme: Res<TilesetTheme>,
+    mut spawner: BattlefieldSpawner,
+    map_library: Res<MapLibrary>,
     game_data: Res<GameData>,
+    mut ai_state: ResMut<AiState>,
+    mut controllers: ResMut<AiControllers>,
+    catalog: Res<ControllerCatalog>,
+    mut map_choices: Local<Vec<(MapId, MapData)>>,
+    mut seed_text: Local<String>,
 ) {
     if !setup_state.needs_setup {
         return;
     }
 
+    // Building every map each frame is wasteful; only rebuild when the library changes
+    if map_choices.is_empty() || map_library.is_changed() {
+        *map_choices = MapId::all_builtin().into_iter().chain(map_library.ids())
+            .map(|id| (id, map_library.resolve(id)))
+            .collect();
+    }
+
     let Ok(ctx) = contexts.ctx_mut() else { return };
 
     egui::Window::new("Battle Setup")
@@ -348,8 +1374,8 @@ fn draw_battle_setup(
                     ui.add_space(5.0);
 
                     egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
-                        for map_id in MapId::all_builtin() {
-                            let map_data = get_builtin_map(map_id);
+                        for (map_id, map_data) in map_choices.iter() {
+                            let map_id = *map_id;
                             let is_selected = setup_state.selected_map == map_id;
 
                             let button_color = if is_selected {
@@ -366,8 +1392,13 @@ fn draw_battle_setup(
                                 }
 
                                 ui.vertical(|ui| {
-                                    ui.label(egui::RichText::new(map_id.name()).size(12.0).strong());
-                                    ui.label(egui::RichText::new(format!("{}x{}", map_data.width, map_data.height))
+                                    let name = match map_id {
+                                        MapId::Custom(_) => format!("{} (custom)", map_data.name),
+                                        _ => map_id.name().to_string(),
+                                    };
+                                    ui.label(egui::RichText::new(name).size(12.0).strong());
+                                    ui.label(egui::RichText::new(format!("{}x{} - {} players",
+                                        map_data.width, map_data.height, map_data.rules.players))
                                         .size(10.0).weak());
                                 });
                             });
@@ -376,6 +1407,11 @@ fn draw_battle_setup(
                                 ui.indent("map_desc", |ui| {
                                     ui.label(egui::RichText::new(&map_data.description)
                                         .size(10.0).weak().italics());
+                                    let rules = format_map_rules(&map_data.rules, &game_data);
+                                    if !rules.is_empty() {
+                                        ui.label(egui::RichText::new(rules)
+                                            .size(10.0).color(egui::Color32::from_rgb(150, 200, 150)));
+                                    }
                                 });
                             }
                             ui.add_space(3.0);
@@ -440,6 +1476,77 @@ fn draw_battle_setup(
                 });
             });
 
+            ui.add_space(10.0);
+            ui.separator();
+            ui.add_space(10.0);
+
+            // === AI DIFFICULTY ===
+            ui.label(egui::RichText::new("AI Difficulty").size(16.0).strong());
+            ui.add_space(5.0);
+
+            ui.horizontal(|ui| {
+                for difficulty in AiDifficulty::all() {
+                    let is_selected = setup_state.ai_difficulty == difficulty;
+                    let button_color = if is_selected {
+                        egui::Color32::from_rgb(80, 120, 180)
+                    } else {
+                        egui::Color32::from_rgb(60, 60, 60)
+                    };
+
+                    if ui.add(egui::Button::new(egui::RichText::new(difficulty.name()).size(13.0))
+                        .fill(button_color)
+                        .min_size(egui::vec2(100.0, 28.0))).clicked()
+                    {
+                        setup_state.ai_difficulty = difficulty;
+                    }
+                }
+
+                ui.add_space(20.0);
+                ui.label("Seed:");
+                // The text only becomes the seed once it parses; show the seed again when not editing
+                let seed_id = ui.make_persistent_id("ai_seed");
+                if !ui.memory(|m| m.has_focus(seed_id)) && seed_text.trim().parse::<u64>().ok() != Some(setup_state.ai_seed) {
+                    *seed_text = setup_state.ai_seed.to_string();
+                }
+                if ui.add(egui::TextEdit::singleline(&mut *seed_text).id(seed_id).desired_width(160.0)).changed() {
+                    if let Ok(seed) = seed_text.trim().parse() {
+                        setup_state.ai_seed = seed;
+                    }
+                }
+                if ui.button("🎲").on_hover_text("New random seed").clicked() {
+                    setup_state.ai_seed = rand::random();
+                }
+            });
+            ui.label(egui::RichText::new(setup_state.ai_difficulty.description())
+                .size(10.0).weak().italics());
+
+            ui.add_space(10.0);
+
+            // === AI CONTROLLER ===
+            ui.label(egui::RichText::new("AI Controller").size(16.0).strong());
+            ui.add_space(5.0);
+
+            ui.horizontal(|ui| {
+                for (index, entry) in catalog.entries().iter().enumerate() {
+                    let is_selected = setup_state.ai_controller == index;
+                    let button_color = if is_selected {
+                        egui::Color32::from_rgb(80, 120, 180)
+                    } else {
+                        egui::Color32::from_rgb(60, 60, 60)
+                    };
+
+                    if ui.add(egui::Button::new(egui::RichText::new(&entry.name).size(13.0))
+                        .fill(button_color)
+                        .min_size(egui::vec2(100.0, 28.0))).clicked()
+                    {
+                        setup_state.ai_controller = index;
+                    }
+                }
+            });
+            if let Some(entry) = catalog.entries().get(setup_state.ai_controller) {
+                ui.label(egui::RichText::new(&entry.description).size(10.0).weak().italics());
+            }
+
             ui.add_space(15.0);
             ui.separator();
             ui.add_space(10.0);
@@ -458,6 +1565,9 @@ fn draw_battle_setup(
                             // Set player CO
                             commanders.set_commander(player_faction, player_co);
 
+                            // Render fog from the player's point of view
+                            spawner.fog.viewer = player_faction;
+
                             // Determine AI faction (opposite of player)
                             let ai_faction = match player_faction {
                                 Faction::Eastern => Faction::Northern,
@@ -467,22 +1577,34 @@ fn draw_battle_setup(
                                 Faction::Nether => Faction::Eastern,  // Nether vs everyone
                             };
 
+                            // AI difficulty, seeded so the battle can be reproduced
+                            ai_state.set_difficulty(setup_state.ai_difficulty, setup_state.ai_seed);
+
+                            // Fresh controller for the AI faction; everyone else is human
+                            let settings = ControllerSettings {
+                                faction: ai_faction,
+                                difficulty: setup_state.ai_difficulty,
+                                seed: setup_state.ai_seed,
+                            };
+                            controllers.clear();
+                            controllers.set(ai_faction, catalog.build(setup_state.ai_controller, &settings));
+
                             // Assign random CO to AI
                             let ai_cos = CommanderId::for_faction(ai_faction);
                             if !ai_cos.is_empty() {
-                                let ai_co = ai_cos[rand::random::<usize>() % ai_cos.len()];
+                                let mut rng = rand::rngs::StdRng::seed_from_u64(setup_state.ai_seed);
+                                let ai_co = ai_cos[rng.gen_range(0..ai_cos.len())];
                                 commanders.set_commander(ai_faction, ai_co);
 
                                 info!("Battle started! Map: {}, Player ({:?}): {:?}, AI ({:?}): {:?}",
-                                    setup_state.selected_map.name(),
+                                    map_library.map_name(setup_state.selected_map),
                                     player_faction, player_co,
                                     ai_faction, ai_co);
                             }
 
                             // Load and spawn the selected map
-                            let map_data = get_builtin_map(setup_state.selected_map);
-                            spawn_map_from_data(&mut commands, &mut game_map, &mut meshes, &mut materials, &sprite_assets, &images, &map_data, *tileset_theme);
-                            spawn_units_from_data(&mut commands, &game_map, &mut meshes, &mut materials, &sprite_assets, &images, &map_data);
+                            let map_data = map_library.resolve(setup_state.selected_map);
+                            spawner.spawn(&map_data);
 
                             setup_state.needs_setup = false;
                         }
@@ -506,6 +1628,7 @@ fn draw_battle_ui(
     tiles: Query<&Tile>,
     mut turn_start_events: MessageWriter<TurnStartEvent>,
     ai_state: Res<AiState>,
+    controllers: Res<AiControllers>,
     game_result: Res<GameResult>,
     mut fog: ResMut<FogOfWar>,
     mut commanders: ResMut<Commanders>,
@@ -526,7 +1649,7 @@ fn draw_battle_ui(
 
     let Ok(ctx) = contexts.ctx_mut() else { return };
 
-    let is_ai_turn = ai_state.enabled && turn_state.current_faction == Faction::Northern;
+    let is_ai_turn = ai_state.enabled && controllers.controls(turn_state.current_faction);
 
     // Top panel - turn info (bigger header with two rows)
     egui::TopBottomPanel::top("turn_info")
@@ -657,31 +1780,14 @@ fn draw_battle_ui(
 
                     // Switch to next faction
                     let old_faction = turn_state.current_faction;
-                    turn_state.current_faction = match turn_state.current_faction {
-                        Faction::Eastern => Faction::Northern,
-                        Faction::Northern => {
-                            turn_state.turn_number += 1;
-                            Faction::Eastern
-                        }
-                        _ => Faction::Eastern,
-                    };
-                    turn_state.phase = TurnPhase::Select;
+                    turn_state.next_faction();
 
                     // Clear selection
                     highlights.selected_unit = None;
                     highlights.tiles.clear();
                     highlights.attack_targets.clear();
 
-                    // Calculate income for the new faction and fire event
-                    let income: u32 = tiles.iter()
-                        .filter(|t| t.owner == Some(turn_state.current_faction))
-                        .map(|t| t.terrain.income_value())
-                        .sum();
-
-                    turn_start_events.write(TurnStartEvent {
-                        faction: turn_state.current_faction,
-                        income,
-                    });
+                    let income = announce_turn_start(&turn_state, tiles.iter(), &mut turn_start_events);
 
                     info!("Turn ended. {} -> {} (+{} income)",
                         game_data.faction_name(old_faction), game_data.faction_name(turn_state.current_faction), income);
@@ -830,9 +1936,13 @@ fn draw_ingame_menu(
     mut menu_state: ResMut<InGameMenuState>,
     mut next_state: ResMut<NextState<GameState>>,
     mut fog: ResMut<FogOfWar>,
+    mut ai_state: ResMut<AiState>,
     mut input_mode: ResMut<InputMode>,
     setup_state: Res<BattleSetupState>,
     game_result: Res<GameResult>,
+    playtest: Res<PlaytestState>,
+    campaign: Res<CampaignSession>,
+    roguelike: Res<RoguelikeSession>,
 ) {
     // Don't show during setup or victory screen
     if setup_state.needs_setup || game_result.game_over {
@@ -885,6 +1995,14 @@ fn draw_ingame_menu(
 
                 ui.add_space(8.0);
 
+                // Whether the AI plays by the fog or sees everything
+                let ai_vision_text = if ai_state.respect_fog { "AI Vision: Fair" } else { "AI Vision: Full" };
+                if ui.add(egui::Button::new(egui::RichText::new(ai_vision_text).size(18.0)).min_size(button_size)).clicked() {
+                    ai_state.respect_fog = !ai_state.respect_fog;
+                }
+
+                ui.add_space(8.0);
+
                 // Input Mode toggle
                 let input_text = format!("Controls: {}", input_mode.name());
                 if ui.add(egui::Button::new(egui::RichText::new(input_text).size(18.0)).min_size(button_size)).clicked() {
@@ -902,14 +2020,23 @@ fn draw_ingame_menu(
                 ui.separator();
                 ui.add_space(8.0);
 
-                // Surrender button - highlighted in red
+                // Surrender button - highlighted in red; a playtest goes back to the editor
+                let surrender_text = if playtest.active { "End Playtest" } else { "Surrender" };
                 if ui.add(egui::Button::new(
-                    egui::RichText::new("Surrender")
+                    egui::RichText::new(surrender_text)
                         .size(18.0)
                         .color(egui::Color32::from_rgb(255, 100, 100))
                 ).min_size(button_size)).clicked() {
                     menu_state.open = false;
-                    next_state.set(GameState::Menu);
+                    next_state.set(if playtest.active {
+                        GameState::Editor
+                    } else if campaign.active.is_some() {
+                        GameState::Campaign
+                    } else if roguelike.active.is_some() {
+                        GameState::Roguelike
+                    } else {
+                        GameState::Menu
+                    });
                 }
 
                 ui.add_space(10.0);
@@ -927,9 +2054,8 @@ fn draw_action_menu(
     tiles: Query<&Tile>,
     mut events: ActionEvents,
     map: Res<GameMap>,
-    game_result: Res<GameResult>,
-    commanders: Res<Commanders>,
-    weather: Res<Weather>,
+    game_ctx: GameStateContext,
+    terrain_ctx: TerrainActionContext,
     game_data: Res<GameData>,
     keyboard: Res<ButtonInput<KeyCode>>,
     mut menu_state: ResMut<ActionMenuState>,
@@ -938,7 +2064,7 @@ fn draw_action_menu(
     mut cursor: ResMut<GridCursor>,
 ) {
     // Don't show if game is over
-    if game_result.game_over {
+    if game_ctx.game_result.game_over {
         return;
     }
 
@@ -979,24 +2105,18 @@ fn draw_action_menu(
     let nav_cancel = !skip_input && keyboard.just_pressed(KeyCode::Escape);
 
     // Get CO bonuses for damage calculation
-    let attacker_co = commanders.get_bonuses(turn_state.current_faction);
-    // Defender faction - opposite of current
-    let defender_faction = match turn_state.current_faction {
-        Faction::Eastern => Faction::Northern,
-        Faction::Northern => Faction::Eastern,
-        _ => Faction::Northern,
-    };
-    let defender_co = commanders.get_bonuses(defender_faction);
+    let attacker_co = game_ctx.commanders.get_bonuses(turn_state.current_faction);
 
     // Collect target info with damage estimates (min-max range due to luck)
     let target_info: Vec<_> = pending_action.targets.iter()
         .filter_map(|&entity| {
-            units.get(entity).ok().map(|(unit, _faction, pos)| {
+            units.get(entity).ok().map(|(unit, faction, pos)| {
                 let pos_xy = pos.map(|p| (p.x, p.y)).unwrap_or((0, 0));
+                let defender_co = faction.map_or_else(CoBonuses::none, |f| game_ctx.commanders.get_bonuses(f.faction));
 
                 // Calculate damage estimate range (with CO bonuses and weather)
                 let defender_terrain = map.get(pos_xy.0, pos_xy.1).unwrap_or(Terrain::Grass);
-                let (damage_min, damage_max) = estimate_damage(&attacker_unit, &unit, defender_terrain, &attacker_co, &defender_co, &weather, &game_data);
+                let (damage_min, damage_max) = estimate_damage(&attacker_unit, &unit, defender_terrain, &attacker_co, &defender_co, &game_ctx.weather, &game_data);
 
                 // Calculate counter-attack damage range (if defender can counter)
                 let defender_stats = unit.unit_type.stats();
@@ -1009,7 +2129,7 @@ fn draw_action_menu(
                         // Create a temporary unit with reduced HP for counter calculation
                         let mut temp_defender = unit.clone();
                         temp_defender.hp = defender_hp_after;
-                        let (counter_min, counter_max) = estimate_damage(&temp_defender, &attacker_unit, attacker_terrain, &defender_co, &attacker_co, &weather, &game_data);
+                        let (counter_min, counter_max) = estimate_damage(&temp_defender, &attacker_unit, attacker_terrain, &defender_co, &attacker_co, &game_ctx.weather, &game_data);
                         Some((counter_min, counter_max))
                     } else {
                         None // Defender will be destroyed, no counter
@@ -1027,6 +2147,14 @@ fn draw_action_menu(
                 )
             })
         })
+        .chain(pending_action.targets.iter().filter_map(|&entity| {
+            // Destructible terrain targets: fixed damage, never counter
+            tiles.get(entity).ok().map(|tile| {
+                let hp = (game_data.terrain_hp(tile.terrain) as i32 - tile.damage).max(0);
+                let damage = calculate_structure_damage(&attacker_unit, &attacker_co, &game_ctx.weather, &game_data);
+                (entity, game_data.terrain_name(tile.terrain).to_string(), hp, (damage, damage), None)
+            })
+        }))
         .collect();
 
     // Track which action was taken
@@ -1133,6 +2261,18 @@ fn draw_action_menu(
         None
     };
 
+    // Terrain actions this unit can order from its position (bridge, clear, tunnel)
+    let terrain_actions: Vec<(String, (i32, i32))> = available_terrain_actions(
+        &attacker_unit,
+        turn_state.current_faction,
+        attacker_pos,
+        &map,
+        &terrain_ctx.works,
+        terrain_ctx.funds.get(turn_state.current_faction),
+        &game_data,
+    );
+    let mut terrain_action_choice: Option<(String, (i32, i32))> = None;
+
     // Build list of available actions for keyboard navigation
     menu_state.actions.clear();
 
@@ -1166,6 +2306,11 @@ fn draw_action_menu(
         menu_state.actions.push(MenuAction::Unload(*pos));
     }
 
+    // Add terrain actions
+    for (id, pos) in &terrain_actions {
+        menu_state.actions.push(MenuAction::TerrainAction(id.clone(), *pos));
+    }
+
     // Always add Wait
     menu_state.actions.push(MenuAction::Wait);
 
@@ -1397,6 +2542,33 @@ fn draw_action_menu(
                 ui.separator();
             }
 
+            // Show terrain actions (bridging, clearing, tunnelling)
+            if !terrain_actions.is_empty() {
+                ui.heading("Terrain");
+                ui.separator();
+
+                for (id, (tx, ty)) in &terrain_actions {
+                    let is_selected = current_idx == menu_state.selected_index;
+                    let Some(action) = game_data.get_terrain_action(id) else { continue };
+
+                    ui.label(egui::RichText::new(&action.description).weak().size(11.0));
+                    ui.label(egui::RichText::new(format!("Cost: {}  Turns: {}", action.cost, action.duration.max(1)))
+                        .color(egui::Color32::from_rgb(200, 180, 120)));
+                    let label = format!("{} ({}, {})", action.name, tx, ty);
+                    let button_text = if is_selected { format!("> {}", label) } else { label };
+                    let button = egui::Button::new(egui::RichText::new(button_text).size(12.0))
+                        .min_size(egui::vec2(180.0, 24.0))
+                        .fill(if is_selected { egui::Color32::from_rgb(80, 80, 40) } else { egui::Color32::from_rgb(40, 40, 40) });
+
+                    if ui.add(button).clicked() {
+                        terrain_action_choice = Some((id.clone(), (*tx, *ty)));
+                    }
+                    current_idx += 1;
+                }
+
+                ui.separator();
+            }
+
             ui.add_space(10.0);
 
             // Wait button
@@ -1552,6 +2724,26 @@ fn draw_action_menu(
             unit.exhausted = true;
         }
 
+        pending_action.unit = None;
+        pending_action.targets.clear();
+        pending_action.can_capture = false;
+        pending_action.capture_tile = None;
+        pending_action.can_join = false;
+        pending_action.join_target = None;
+        turn_state.phase = TurnPhase::Select;
+    } else if let Some((action_id, position)) = terrain_action_choice {
+        // Send terrain action event - funds are spent when the order is accepted
+        events.terrain_action.write(TerrainActionEvent {
+            unit: acting_entity,
+            action_id,
+            position,
+        });
+
+        // Ordering terrain work ends the unit's turn
+        if let Ok((mut unit, _, _)) = units.get_mut(acting_entity) {
+            unit.exhausted = true;
+        }
+
         pending_action.unit = None;
         pending_action.targets.clear();
         pending_action.can_capture = false;
@@ -1675,6 +2867,23 @@ fn draw_action_menu(
                 pending_action.join_target = None;
                 turn_state.phase = TurnPhase::Select;
             }
+            MenuAction::TerrainAction(action_id, position) => {
+                events.terrain_action.write(TerrainActionEvent {
+                    unit: acting_entity,
+                    action_id,
+                    position,
+                });
+                if let Ok((mut unit, _, _)) = units.get_mut(acting_entity) {
+                    unit.exhausted = true;
+                }
+                pending_action.unit = None;
+                pending_action.targets.clear();
+                pending_action.can_capture = false;
+                pending_action.capture_tile = None;
+                pending_action.can_join = false;
+                pending_action.join_target = None;
+                turn_state.phase = TurnPhase::Select;
+            }
             MenuAction::Wait => {
                 if let Ok((mut unit, _, _)) = units.get_mut(acting_entity) {
                     unit.exhausted = true;
@@ -1753,31 +2962,14 @@ fn draw_action_menu(
 
         // Switch to next faction
         let old_faction = turn_state.current_faction;
-        turn_state.current_faction = match turn_state.current_faction {
-            Faction::Eastern => Faction::Northern,
-            Faction::Northern => {
-                turn_state.turn_number += 1;
-                Faction::Eastern
-            }
-            _ => Faction::Eastern,
-        };
-        turn_state.phase = TurnPhase::Select;
+        turn_state.next_faction();
 
         // Clear selection
         highlights.selected_unit = None;
         highlights.tiles.clear();
         highlights.attack_targets.clear();
 
-        // Calculate income for the new faction and fire event
-        let income: u32 = tiles.iter()
-            .filter(|t| t.owner == Some(turn_state.current_faction))
-            .map(|t| t.terrain.income_value())
-            .sum();
-
-        turn_start_events.write(TurnStartEvent {
-            faction: turn_state.current_faction,
-            income,
-        });
+        announce_turn_start(&turn_state, tiles.iter(), &mut turn_start_events);
 
         info!("Turn ended via action menu. {} -> {}", game_data.faction_name(old_faction), game_data.faction_name(turn_state.current_faction));
     }
@@ -1813,6 +3005,7 @@ fn draw_production_menu(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     game_data: Res<GameData>,
+    rules: Res<MapRules>,
 ) {
     // Don't show if game is over
     if game_result.game_over {
@@ -1826,24 +3019,12 @@ fn draw_production_menu(
     // Get CO cost modifier
     let co_bonuses = commanders.get_bonuses(turn_state.current_faction);
 
-    // Units available for production at bases
-    let base_unit_types = [
-        UnitType::Scout,
-        UnitType::Shocktrooper,
-        UnitType::Recon,
-        UnitType::Siege,
-        UnitType::Ironclad,
-    ];
-
-    // Apply faction and CO cost modifiers
-    let faction_modifier = game_data.faction_cost_modifier(turn_state.current_faction);
-    let buildable_units: Vec<_> = base_unit_types.iter()
-        .filter_map(|unit_type| {
-            game_data.unit_stats(*unit_type).map(|stats| {
-                let base_cost = stats.cost;
-                let adjusted_cost = (base_cost as f32 * faction_modifier * co_bonuses.cost).round() as u32;
-                (*unit_type, game_data.unit_name(*unit_type), adjusted_cost, game_data.unit_description(*unit_type))
-            })
+    // Units available for production at bases, with faction and CO cost modifiers
+    let buildable_units: Vec<_> = UnitType::base_buildable().iter()
+        .filter(|unit_type| !rules.is_banned(**unit_type) && game_data.unit_stats(**unit_type).is_some())
+        .map(|unit_type| {
+            let adjusted_cost = game_data.build_cost(*unit_type, turn_state.current_faction, &co_bonuses);
+            (*unit_type, game_data.unit_name(*unit_type), adjusted_cost, game_data.unit_description(*unit_type))
         })
         .collect();
 
@@ -1943,23 +3124,28 @@ fn draw_victory_screen(
     units: Query<Entity, With<Unit>>,
     tiles: Query<Entity, With<Tile>>,
     game_data: Res<GameData>,
+    playtest: Res<PlaytestState>,
+    campaign: Res<CampaignSession>,
+    roguelike: Res<RoguelikeSession>,
 ) {
     if !game_result.game_over {
         return;
     }
 
-    let Some(winner) = game_result.winner else {
-        return;
-    };
+    // Determine if player won or lost (Eastern, outside campaign missions); no winner is a draw
+    let winner = game_result.winner;
+    let player = campaign.active.map_or(Faction::Eastern, |m| m.player);
+    let player_won = winner == Some(player);
 
-    // Determine if player won or lost (player is Eastern)
-    let player_won = winner == Faction::Eastern;
-
-    let title = if player_won { "Victory!" } else { "Defeat" };
-    let title_color = if player_won {
-        egui::Color32::from_rgb(100, 255, 100)
-    } else {
-        egui::Color32::from_rgb(255, 100, 100)
+    let title = match winner {
+        None => "Draw",
+        Some(_) if player_won => "Victory!",
+        Some(_) => "Defeat",
+    };
+    let title_color = match winner {
+        None => egui::Color32::from_rgb(220, 220, 120),
+        Some(_) if player_won => egui::Color32::from_rgb(100, 255, 100),
+        Some(_) => egui::Color32::from_rgb(255, 100, 100),
     };
 
     let victory_message = match game_result.victory_type {
@@ -1977,6 +3163,59 @@ fn draw_victory_screen(
                 "The enemy has captured your headquarters..."
             }
         }
+        VictoryType::Properties => {
+            if player_won {
+                "You control enough of the battlefield!"
+            } else {
+                "The enemy controls too much of the battlefield..."
+            }
+        }
+        VictoryType::HoldTile => {
+            if player_won {
+                "You held the objective!"
+            } else {
+                "The enemy held the objective..."
+            }
+        }
+        VictoryType::Survival => {
+            if player_won {
+                "Your forces held out to the end!"
+            } else {
+                "The enemy held out to the end..."
+            }
+        }
+        VictoryType::VipDestroyed => {
+            if player_won {
+                "The enemy VIP has fallen!"
+            } else {
+                "Your VIP has fallen..."
+            }
+        }
+        VictoryType::VipEscorted => {
+            if player_won {
+                "Your VIP reached safety!"
+            } else {
+                "The enemy VIP escaped..."
+            }
+        }
+        VictoryType::TurnLimit => {
+            if winner.is_none() {
+                "Time ran out with the sides evenly matched."
+            } else if player_won {
+                "You held the most ground when time ran out!"
+            } else {
+                "The enemy held the most ground when time ran out..."
+            }
+        }
+        VictoryType::Scripted => {
+            if winner.is_none() {
+                "The battle ended without a victor."
+            } else if player_won {
+                "Mission accomplished!"
+            } else {
+                "Mission failed..."
+            }
+        }
         VictoryType::None => "",
     };
 
@@ -2019,8 +3258,10 @@ fn draw_victory_screen(
                 ui.add_space(10.0);
 
                 // Winner faction
-                ui.label(egui::RichText::new(format!("{} wins!", game_data.faction_name(winner)))
-                    .size(24.0));
+                if let Some(winner) = winner {
+                    ui.label(egui::RichText::new(format!("{} wins!", game_data.faction_name(winner)))
+                        .size(24.0));
+                }
 
                 ui.add_space(10.0);
 
@@ -2031,19 +3272,36 @@ fn draw_victory_screen(
 
                 // Stats
                 ui.label(format!("Turns: {}", turn_state.turn_number));
+                if let Some(rank) = campaign.last_result.as_ref().and_then(|r| r.rank) {
+                    ui.label(egui::RichText::new(format!("Rank: {}", rank.name())).size(20.0).strong());
+                }
+                if let Some(report) = roguelike.last_report.as_ref().filter(|_| roguelike.active.is_some()) {
+                    ui.label(format!("Units lost for good: {}", report.fallen.len()));
+                }
 
                 ui.add_space(30.0);
 
-                // Buttons
-                if ui.add(egui::Button::new(egui::RichText::new("Play Again").size(18.0))
-                    .min_size(egui::vec2(200.0, 40.0))).clicked()
-                {
-                    restart_clicked = true;
-                }
+                // Buttons; campaign missions and run battles can't simply be replayed
+                if !playtest.active && campaign.active.is_none() && roguelike.active.is_none() {
+                    if ui.add(egui::Button::new(egui::RichText::new("Play Again").size(18.0))
+                        .min_size(egui::vec2(200.0, 40.0))).clicked()
+                    {
+                        restart_clicked = true;
+                    }
 
-                ui.add_space(10.0);
+                    ui.add_space(10.0);
+                }
 
-                if ui.add(egui::Button::new(egui::RichText::new("Main Menu").size(18.0))
+                let menu_text = if playtest.active {
+                    "Back to Editor"
+                } else if campaign.active.is_some() {
+                    "Back to Campaign"
+                } else if roguelike.active.is_some() {
+                    "Back to Run"
+                } else {
+                    "Main Menu"
+                };
+                if ui.add(egui::Button::new(egui::RichText::new(menu_text).size(18.0))
                     .min_size(egui::vec2(200.0, 40.0))).clicked()
                 {
                     menu_clicked = true;
@@ -2069,7 +3327,18 @@ fn draw_victory_screen(
         commands.insert_resource(FactionFunds::default());
 
         if menu_clicked {
-            next_state.set(GameState::Menu);
+            commands.insert_resource(MapRules::default());
+            commands.insert_resource(ObjectiveProgress::default());
+            commands.insert_resource(MapTriggers::default());
+            next_state.set(if playtest.active {
+                GameState::Editor
+            } else if campaign.active.is_some() {
+                GameState::Campaign
+            } else if roguelike.active.is_some() {
+                GameState::Roguelike
+            } else {
+                GameState::Menu
+            });
         }
         // If restart_clicked, stay in Battle state - the map will regenerate
     }
@@ -2105,12 +3374,12 @@ fn draw_unit_hp_numbers(
 
     for (entity, unit, unit_transform, faction) in units.iter() {
         // Check fog visibility for enemy units
-        if fog.enabled && faction.faction != Faction::Eastern {
+        if fog.enabled && faction.faction != fog.viewer {
             // Get grid position from world position
             let world_pos = unit_transform.translation();
             let grid_x = (world_pos.x / TILE_SIZE).round() as i32;
             let grid_y = (world_pos.z / TILE_SIZE).round() as i32;
-            if !fog.is_visible(grid_x, grid_y) {
+            if !fog.visible_to(fog.viewer, grid_x, grid_y) {
                 continue;
             }
         }
@@ -2152,6 +3421,117 @@ fn draw_unit_hp_numbers(
     }
 }
 
+/// How long the "Trap!" marker stays on screen (seconds)
+const TRAP_ALERT_DURATION: f32 = 1.5;
+
+/// List the map's objectives during battles that set their own
+fn draw_objectives(
+    mut contexts: EguiContexts,
+    rules: Res<MapRules>,
+    progress: Res<ObjectiveProgress>,
+    setup_state: Res<BattleSetupState>,
+    game_result: Res<GameResult>,
+    game_data: Res<GameData>,
+) {
+    if setup_state.needs_setup || game_result.game_over {
+        return;
+    }
+    if rules.objectives == default_objectives() && rules.turn_limit.is_none() {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    egui::Window::new("Objectives")
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_TOP, [8.0, 70.0])
+        .show(ctx, |ui| {
+            for (index, objective) in rules.objectives.iter().enumerate() {
+                let side = objective.faction.map_or("All", |f| game_data.faction_name(f));
+                let mut text = format!("{}: {}", side, objective.goal.describe());
+                if let (Objective::HoldTile { turns, .. }, Some((holder, held))) = (&objective.goal, progress.held.get(&index)) {
+                    text.push_str(&format!(" - {} {}/{}", game_data.faction_name(*holder), held, turns));
+                }
+                ui.label(egui::RichText::new(text).size(12.0));
+            }
+            if let Some(limit) = rules.turn_limit {
+                ui.label(egui::RichText::new(format!("Turn limit {} (scored on {})", limit, rules.score.name()))
+                    .size(12.0).weak());
+            }
+        });
+}
+
+/// Show dialogue from map triggers, one line at a time
+fn draw_dialogue(
+    mut contexts: EguiContexts,
+    mut triggers: ResMut<MapTriggers>,
+) {
+    let Some(line) = triggers.dialogue.front().cloned() else { return };
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    let mut advance = false;
+    egui::Window::new("Dialogue")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -40.0])
+        .min_width(420.0)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(&line.speaker).strong().size(15.0));
+            ui.label(egui::RichText::new(&line.text).size(14.0));
+            ui.horizontal(|ui| {
+                if triggers.dialogue.len() > 1 {
+                    ui.label(egui::RichText::new(format!("{} more", triggers.dialogue.len() - 1)).weak().size(11.0));
+                }
+                if ui.button("Continue").clicked() {
+                    advance = true;
+                }
+            });
+        });
+
+    if advance {
+        triggers.dialogue.pop_front();
+    }
+}
+
+/// Show a "Trap!" marker over a unit that was stopped by a hidden enemy
+fn draw_trap_alert(
+    mut contexts: EguiContexts,
+    mut events: MessageReader<TrapEvent>,
+    mut alert: ResMut<TrapAlert>,
+    time: Res<Time>,
+    map: Res<GameMap>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+) {
+    for event in events.read() {
+        alert.position = Some(event.position);
+        alert.timer = TRAP_ALERT_DURATION;
+    }
+
+    let Some((x, y)) = alert.position else { return };
+    alert.timer -= time.delta_secs();
+    if alert.timer <= 0.0 {
+        alert.position = None;
+        return;
+    }
+
+    let Ok((camera, camera_transform)) = camera.single() else { return };
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    let world_pos = GridPosition::new(x, y).to_world(&map) + Vec3::Y * TILE_SIZE;
+    let Ok(screen_pos) = camera.world_to_viewport(camera_transform, world_pos) else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("trap_alert"))
+        .fixed_pos(egui::pos2(screen_pos.x - 30.0, screen_pos.y - 20.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new("Trap!")
+                .strong()
+                .size(22.0)
+                .color(egui::Color32::from_rgb(255, 80, 60)));
+        });
+}
+
 // ============================================================================
 // UNIT TOOLTIP
 // ============================================================================
@@ -2186,7 +3566,7 @@ fn track_hovered_unit(
     for (entity, pos, _unit, faction) in units.iter() {
         if pos.x == grid_x && pos.y == grid_y {
             // Check fog of war - only show tooltip for visible units
-            if fog.enabled && faction.faction != Faction::Eastern && !fog.is_visible(pos.x, pos.y) {
+            if faction.faction != fog.viewer && !fog.visible_to(fog.viewer, pos.x, pos.y) {
                 continue;
             }
             hovered.entity = Some(entity);
@@ -2431,6 +3811,11 @@ fn draw_unit_tooltip(
                     .size(10.0)
                     .weak());
             });
+            if unit.veterancy > 0 {
+                ui.label(egui::RichText::new(format!("Veteran {} (+{:.0}% ATK/DEF)", "*".repeat(unit.veterancy as usize), unit.veterancy_bonus()))
+                    .size(11.0)
+                    .color(egui::Color32::from_rgb(255, 200, 50)));
+            }
 
             ui.separator();
 
@@ -2582,7 +3967,7 @@ fn draw_unit_tooltip(
 
                     // Cost
                     ui.label("Cost:");
-                    let adjusted_cost = (stats.cost as f32 * co_bonuses.cost).round() as u32;
+                    let adjusted_cost = game_data.build_cost(unit.unit_type, faction.faction, &co_bonuses);
                     let cost_text = if adjusted_cost != stats.cost {
                         format!("{} ({})", adjusted_cost, stats.cost)
                     } else {
@@ -2604,22 +3989,111 @@ fn draw_unit_tooltip(
                 }
             });
 
-            // Status indicators
-            if unit.moved || unit.attacked {
-                ui.add_space(4.0);
+            // Status indicators
+            if unit.moved || unit.attacked {
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if unit.moved {
+                        ui.label(egui::RichText::new("Moved")
+                            .size(10.0)
+                            .color(egui::Color32::GRAY));
+                    }
+                    if unit.attacked {
+                        ui.label(egui::RichText::new("Attacked")
+                            .size(10.0)
+                            .color(egui::Color32::GRAY));
+                    }
+                });
+            }
+        });
+}
+
+// ============================================================================
+// AI DEBUG
+// ============================================================================
+
+/// Layer picker and the AI's scored plan, shown while the debug overlay is on (F3)
+fn draw_ai_debug_window(
+    mut contexts: EguiContexts,
+    mut overlay: ResMut<AiDebugOverlay>,
+    controllers: Res<AiControllers>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    egui::Window::new("AI Debug")
+        .default_pos([10.0, 80.0])
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            let mut factions: Vec<Faction> = controllers.factions().collect();
+            factions.sort_by_key(|f| f.name());
+            if factions.len() > 1 {
                 ui.horizontal(|ui| {
-                    if unit.moved {
-                        ui.label(egui::RichText::new("Moved")
-                            .size(10.0)
-                            .color(egui::Color32::GRAY));
-                    }
-                    if unit.attacked {
-                        ui.label(egui::RichText::new("Attacked")
-                            .size(10.0)
-                            .color(egui::Color32::GRAY));
+                    for faction in factions {
+                        let selected = overlay.faction == Some(faction);
+                        if ui.selectable_label(selected, faction.name()).clicked() {
+                            overlay.faction = Some(faction);
+                        }
                     }
                 });
             }
+
+            ui.horizontal_wrapped(|ui| {
+                for layer in InfluenceLayer::all() {
+                    if ui.selectable_label(overlay.layer == *layer, layer.name()).clicked() {
+                        overlay.layer = *layer;
+                    }
+                }
+            });
+            if overlay.layer.is_signed() {
+                ui.small("Blue: AI control, red: enemy control");
+            } else {
+                ui.small("Yellow to red: low to high");
+            }
+            ui.separator();
+
+            let Some(view) = overlay.view(&controllers) else {
+                ui.label("No plan recorded yet - it appears on the AI's next turn.");
+                return;
+            };
+
+            ui.label(egui::RichText::new(format!("{} plan", view.faction.name())).strong());
+            for goal in view.goals.iter().take(2) {
+                ui.small(goal);
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (i, action) in view.actions.iter().enumerate() {
+                    let title = format!("{}. {:?} ({}, {}): {}  [{:.1}]",
+                        i + 1, action.unit_type, action.from.0, action.from.1, action.description, action.priority);
+                    egui::CollapsingHeader::new(title)
+                        .id_salt(("ai_debug_action", action.unit))
+                        .show(ui, |ui| {
+                            egui::Grid::new(("ai_debug_terms", action.unit))
+                                .num_columns(2)
+                                .spacing([20.0, 2.0])
+                                .show(ui, |ui| {
+                                    for (label, value) in &action.terms {
+                                        ui.label(*label);
+                                        let color = if *value >= 0.0 {
+                                            egui::Color32::from_rgb(120, 200, 120)
+                                        } else {
+                                            egui::Color32::from_rgb(220, 110, 110)
+                                        };
+                                        ui.label(egui::RichText::new(format!("{:+.1}", value)).color(color));
+                                        ui.end_row();
+                                    }
+                                    let total: f32 = action.terms.iter().map(|(_, v)| v).sum();
+                                    ui.label(egui::RichText::new("Total").strong());
+                                    ui.label(egui::RichText::new(format!("{:.1}", total)).strong());
+                                    ui.end_row();
+                                });
+                        });
+                }
+            });
         });
 }
 
@@ -2640,12 +4114,20 @@ struct EditorUnit;
 
 /// Setup editor when entering editor state
 fn setup_editor(
-    mut commands: Commands,
     mut editor_state: ResMut<EditorState>,
+    mut playtest: ResMut<PlaytestState>,
+    mut angle: ResMut<CameraAngle>,
 ) {
-    // Reset to a fresh map
-    *editor_state = EditorState::default();
-    spawn_editor_tiles(&mut commands, &editor_state.map);
+    // Coming back from a playtest keeps the map being edited;
+    // otherwise reset to a fresh map. sync_editor_tiles spawns the board.
+    if playtest.returning {
+        playtest.returning = false;
+        editor_state.drag = None;
+        editor_state.status = "Playtest finished".to_string();
+    } else {
+        *editor_state = EditorState::default();
+    }
+    angle.look_at = Vec2::ZERO;
 }
 
 /// Cleanup editor entities when leaving
@@ -2662,41 +4144,288 @@ fn cleanup_editor(
     }
 }
 
-/// Spawn editor tile sprites
-fn spawn_editor_tiles(commands: &mut Commands, map: &MapData) {
+/// Spawn editor tiles as flat slabs on the ground plane, laid out like the battle board
+fn spawn_editor_tiles(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    map: &MapData,
+) {
     let offset_x = -(map.width as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
-    let offset_y = -(map.height as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
+    let offset_z = -(map.height as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
+    // One mesh shared by every tile keeps 64x64 boards cheap
+    let tile_mesh = meshes.add(Cuboid::new(TILE_SIZE - 2.0, 2.0, TILE_SIZE - 2.0));
 
     for y in 0..map.height {
         for x in 0..map.width {
             let terrain = map.terrain[y as usize][x as usize];
             let world_x = x as f32 * TILE_SIZE + offset_x;
-            let world_y = y as f32 * TILE_SIZE + offset_y;
+            let world_z = y as f32 * TILE_SIZE + offset_z;  // Grid Y -> World Z
 
             commands.spawn((
-                Sprite {
-                    color: terrain.color(),
-                    custom_size: Some(Vec2::splat(TILE_SIZE - 2.0)),
+                Mesh3d(tile_mesh.clone()),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: terrain.color(),
+                    unlit: true,
                     ..default()
-                },
-                Transform::from_xyz(world_x, world_y, 0.0),
+                })),
+                Transform::from_xyz(world_x, -1.0, world_z),
                 EditorTile { x: x as i32, y: y as i32 },
             ));
         }
     }
 }
 
+/// Keep editor tiles in step with the map, respawning them and refitting the
+/// camera when its size changes
+fn sync_editor_tiles(
+    mut commands: Commands,
+    editor_state: Res<EditorState>,
+    tiles: Query<(Entity, &EditorTile, &MeshMaterial3d<StandardMaterial>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut bounds: ResMut<CameraBounds>,
+    mut angle: ResMut<CameraAngle>,
+    mut spawned_size: Local<Option<(u32, u32)>>,
+) {
+    let map = &editor_state.map;
+    let size = (map.width, map.height);
+    if tiles.is_empty() || *spawned_size != Some(size) {
+        for (entity, _, _) in tiles.iter() {
+            commands.entity(entity).despawn();
+        }
+        spawn_editor_tiles(&mut commands, &mut meshes, &mut materials, map);
+        *spawned_size = Some(size);
+        *bounds = CameraBounds::for_board(map.width, map.height);
+        angle.look_at = bounds.clamp(angle.look_at);
+        return;
+    }
+
+    // Preview of the shape being dragged, or the selected region
+    let mut preview: HashMap<(i32, i32), Color> = HashMap::new();
+    if let (Some(drag), Some(end)) = (editor_state.drag, editor_state.hovered) {
+        let color = editor_state.drag_terrain(drag).color().lighter(0.15);
+        for tile in editor_state.drag_tiles(drag, end) {
+            preview.insert(tile, color);
+        }
+    }
+    let region = match (editor_state.tool, editor_state.drag, editor_state.hovered) {
+        (EditorTool::Select, Some(drag), Some(end)) => Some((drag.start, end)),
+        (EditorTool::Select, None, _) => editor_state.selection,
+        _ => None,
+    };
+    if let Some((a, b)) = region {
+        for (x, y) in rect_tiles(a, b, false) {
+            if let Some(terrain) = map.get_terrain(x, y) {
+                preview.insert((x, y), terrain.color().lighter(0.3));
+            }
+        }
+    }
+
+    let owners: HashMap<(i32, i32), Faction> = map.properties.iter().map(|p| ((p.x, p.y), p.owner)).collect();
+    for (_, tile, material) in tiles.iter() {
+        let Some(terrain) = map.get_terrain(tile.x, tile.y) else { continue };
+        let color = preview.get(&(tile.x, tile.y)).copied()
+            .unwrap_or_else(|| tile_color(terrain, owners.get(&(tile.x, tile.y)).copied()));
+        // Only touch materials that changed, so idle frames don't re-upload the board
+        if materials.get(&material.0).is_some_and(|m| m.base_color != color) {
+            if let Some(m) = materials.get_mut(&material.0) {
+                m.base_color = color;
+            }
+        }
+    }
+}
+
 /// Draw the editor UI
+/// Editor widgets for a map's rules
+fn draw_map_rules(ui: &mut egui::Ui, rules: &mut MapRules, factions: &[Faction], size: (u32, u32), game_data: &GameData) {
+    ui.horizontal(|ui| {
+        ui.label("Players:");
+        ui.add(egui::DragValue::new(&mut rules.players).range(2..=5));
+    });
+
+    ui.label("Starting funds:");
+    for &faction in factions {
+        ui.horizontal(|ui| {
+            ui.label(format!("  {}", faction.name()));
+            let mut amount = rules.starting_funds(faction);
+            if ui.add(egui::DragValue::new(&mut amount).range(0..=10000).speed(10)).changed() {
+                rules.starting_funds.insert(faction, amount);
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Income:");
+        ui.add(egui::DragValue::new(&mut rules.income_multiplier).range(0.0..=5.0).speed(0.05).prefix("x"));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Fog:");
+        ui.selectable_value(&mut rules.fog, None, "Player");
+        ui.selectable_value(&mut rules.fog, Some(true), "On");
+        ui.selectable_value(&mut rules.fog, Some(false), "Off");
+    });
+
+    ui.label("Theme:");
+    ui.horizontal_wrapped(|ui| {
+        for &theme in TilesetTheme::all() {
+            ui.selectable_value(&mut rules.theme, theme, theme.name());
+        }
+    });
+
+    ui.label("Weather:");
+    ui.horizontal(|ui| {
+        for rule in [WeatherRule::Random, WeatherRule::Fixed(WeatherType::Clear), WeatherRule::Schedule(vec![(1, WeatherType::Clear)])] {
+            let selected = std::mem::discriminant(&rules.weather) == std::mem::discriminant(&rule);
+            if ui.selectable_label(selected, rule.name()).clicked() && !selected {
+                rules.weather = rule;
+            }
+        }
+    });
+    match &mut rules.weather {
+        WeatherRule::Random => {}
+        WeatherRule::Fixed(weather) => {
+            ui.horizontal_wrapped(|ui| {
+                for &option in WeatherType::all() {
+                    ui.selectable_value(weather, option, option.icon()).on_hover_text(option.name());
+                }
+            });
+        }
+        WeatherRule::Schedule(schedule) => {
+            let mut remove = None;
+            for (index, (turn, weather)) in schedule.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(turn).range(1..=999).prefix("T"));
+                    for &option in WeatherType::all() {
+                        ui.selectable_value(weather, option, option.icon()).on_hover_text(option.name());
+                    }
+                    if ui.small_button("x").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = remove {
+                schedule.remove(index);
+            }
+            if ui.small_button("+ Change").clicked() {
+                let next = schedule.iter().map(|(turn, _)| *turn).max().unwrap_or(0) + 1;
+                schedule.push((next, WeatherType::Clear));
+            }
+        }
+    }
+
+    ui.horizontal(|ui| {
+        let mut limited = rules.turn_limit.is_some();
+        if ui.checkbox(&mut limited, "Turn limit").changed() {
+            rules.turn_limit = limited.then_some(20);
+        }
+        if let Some(limit) = rules.turn_limit.as_mut() {
+            ui.add(egui::DragValue::new(limit).range(1..=999));
+        }
+    });
+    if rules.turn_limit.is_some() {
+        ui.horizontal(|ui| {
+            ui.label("Score:");
+            for &method in ScoreMethod::all() {
+                ui.selectable_value(&mut rules.score, method, method.name());
+            }
+        });
+    }
+
+    ui.label("Objectives:");
+    let (max_x, max_y) = (size.0 as i32 - 1, size.1 as i32 - 1);
+    let mut remove = None;
+    for (index, objective) in rules.objectives.iter_mut().enumerate() {
+        ui.push_id(index, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(objective.goal.name()).strong());
+                if ui.small_button("x").clicked() {
+                    remove = Some(index);
+                }
+            });
+            ui.horizontal_wrapped(|ui| {
+                ui.selectable_value(&mut objective.faction, None, "All");
+                for &faction in factions {
+                    ui.selectable_value(&mut objective.faction, Some(faction), format!("{:?}", faction));
+                }
+            });
+            match &mut objective.goal {
+                Objective::Eliminate | Objective::CaptureHq | Objective::DestroyVip => {}
+                Objective::OwnProperties(count) => {
+                    ui.add(egui::DragValue::new(count).range(1..=999).prefix("Count: "));
+                }
+                Objective::HoldTile { x, y, turns } => {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(x).range(0..=max_x).prefix("x: "));
+                        ui.add(egui::DragValue::new(y).range(0..=max_y).prefix("y: "));
+                        ui.add(egui::DragValue::new(turns).range(1..=99).prefix("Turns: "));
+                    });
+                }
+                Objective::SurviveUntil(turn) => {
+                    ui.add(egui::DragValue::new(turn).range(1..=999).prefix("Turn: "));
+                }
+                Objective::EscortVip(exits) => {
+                    let mut remove_exit = None;
+                    for (exit_index, (x, y)) in exits.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(x).range(0..=max_x).prefix("Exit x: "));
+                            ui.add(egui::DragValue::new(y).range(0..=max_y).prefix("y: "));
+                            if ui.small_button("x").clicked() {
+                                remove_exit = Some(exit_index);
+                            }
+                        });
+                    }
+                    if let Some(exit_index) = remove_exit {
+                        exits.remove(exit_index);
+                    }
+                    if ui.small_button("+ Exit").clicked() {
+                        exits.push((0, 0));
+                    }
+                }
+            }
+        });
+    }
+    if let Some(index) = remove {
+        rules.objectives.remove(index);
+    }
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Add:");
+        for template in Objective::templates() {
+            if ui.small_button(template.name()).clicked() {
+                // Surviving only means something for one side
+                let faction = match template {
+                    Objective::SurviveUntil(_) => factions.first().copied(),
+                    _ => None,
+                };
+                rules.objectives.push(MapObjective { faction, goal: template });
+            }
+        }
+    });
+
+    ui.label("Banned units:");
+    for &unit_type in UnitType::base_buildable() {
+        let mut banned = rules.is_banned(unit_type);
+        if ui.checkbox(&mut banned, game_data.unit_name(unit_type)).changed() {
+            if banned {
+                rules.banned_units.push(unit_type);
+            } else {
+                rules.banned_units.retain(|t| *t != unit_type);
+            }
+        }
+    }
+}
+
 fn draw_editor(
     mut contexts: EguiContexts,
     mut editor_state: ResMut<EditorState>,
+    mut map_library: ResMut<MapLibrary>,
+    mut playtest: ResMut<PlaytestState>,
     mut next_state: ResMut<NextState<GameState>>,
-    mut commands: Commands,
-    tiles: Query<Entity, With<EditorTile>>,
-    units: Query<Entity, With<EditorUnit>>,
+    game_data: Res<GameData>,
 ) {
-    let mut should_respawn = false;
     let mut should_save = false;
+    let mut open_map: Option<usize> = None;
     let Ok(ctx) = contexts.ctx_mut() else { return };
 
     // Left panel - Tools
@@ -2714,21 +4443,62 @@ fn draw_editor(
         ui.horizontal(|ui| {
             ui.label("W:");
             let mut width = editor_state.map.width;
-            if ui.add(egui::DragValue::new(&mut width).range(8..=20)).changed() {
+            if ui.add(egui::DragValue::new(&mut width).range(MIN_MAP_WIDTH..=MAX_MAP_SIZE)).changed() {
                 if width != editor_state.map.width {
-                    editor_state.map = MapData::new(&editor_state.map.name, width, editor_state.map.height);
-                    should_respawn = true;
+                    let map = resize_map(&editor_state.map, width, editor_state.map.height, editor_state.anchor);
+                    editor_state.replace_map(map);
                 }
             }
             ui.label("H:");
             let mut height = editor_state.map.height;
-            if ui.add(egui::DragValue::new(&mut height).range(6..=16)).changed() {
+            if ui.add(egui::DragValue::new(&mut height).range(MIN_MAP_HEIGHT..=MAX_MAP_SIZE)).changed() {
                 if height != editor_state.map.height {
-                    editor_state.map = MapData::new(&editor_state.map.name, editor_state.map.width, height);
-                    should_respawn = true;
+                    let map = resize_map(&editor_state.map, editor_state.map.width, height, editor_state.anchor);
+                    editor_state.replace_map(map);
                 }
             }
         });
+
+        // Anchor picker and content shifting
+        ui.horizontal(|ui| {
+            ui.vertical(|ui| {
+                ui.label("Anchor:");
+                egui::Grid::new("resize_anchor").spacing([2.0, 2.0]).show(ui, |ui| {
+                    for row in ResizeAnchor::grid() {
+                        for anchor in row {
+                            ui.selectable_value(&mut editor_state.anchor, anchor, anchor.symbol());
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.vertical(|ui| {
+                ui.label("Shift:");
+                egui::Grid::new("shift_content").spacing([2.0, 2.0]).show(ui, |ui| {
+                    let mut shift = None;
+                    ui.label("");
+                    if ui.small_button("↑").clicked() { shift = Some((0, -1)); }
+                    ui.end_row();
+                    if ui.small_button("←").clicked() { shift = Some((-1, 0)); }
+                    ui.label("");
+                    if ui.small_button("→").clicked() { shift = Some((1, 0)); }
+                    ui.end_row();
+                    ui.label("");
+                    if ui.small_button("↓").clicked() { shift = Some((0, 1)); }
+                    ui.end_row();
+                    if let Some((dx, dy)) = shift {
+                        let map = shift_map(&editor_state.map, dx, dy);
+                        editor_state.replace_map(map);
+                    }
+                });
+            });
+        });
+        if let Some((a, b)) = editor_state.selection {
+            if ui.button("Crop to Selection").clicked() {
+                let map = crop_map(&editor_state.map, a, b);
+                editor_state.replace_map(map);
+            }
+        }
         ui.add_space(10.0);
 
         ui.separator();
@@ -2740,6 +4510,54 @@ fn draw_editor(
             ui.selectable_value(&mut editor_state.mode, EditorMode::Units, "Units");
             ui.selectable_value(&mut editor_state.mode, EditorMode::Properties, "Properties");
         });
+        ui.add_space(5.0);
+
+        // Tool selection
+        ui.label("Tool:");
+        ui.horizontal_wrapped(|ui| {
+            for tool in EditorTool::all() {
+                if ui.selectable_label(editor_state.tool == tool, tool.name()).clicked() {
+                    editor_state.tool = tool;
+                    editor_state.drag = None;
+                }
+            }
+        });
+        if editor_state.tool == EditorTool::Rectangle {
+            ui.checkbox(&mut editor_state.outline_rect, "Outline only");
+        }
+        if editor_state.tool == EditorTool::Select {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(editor_state.selection.is_some(), egui::Button::new("Copy")).clicked() {
+                    editor_state.copy_selection();
+                }
+                let paste_target = editor_state.selection.map(|(a, b)| (a.0.min(b.0), a.1.min(b.1)));
+                if ui.add_enabled(editor_state.clipboard.is_some() && paste_target.is_some(), egui::Button::new("Paste")).clicked() {
+                    if let Some(target) = paste_target {
+                        editor_state.paste_at(target);
+                    }
+                }
+            });
+        }
+        ui.add_space(5.0);
+
+        // Symmetry
+        ui.label("Symmetry:");
+        ui.horizontal_wrapped(|ui| {
+            for symmetry in Symmetry::all() {
+                ui.selectable_value(&mut editor_state.symmetry, symmetry, symmetry.name());
+            }
+        });
+        ui.add_space(5.0);
+
+        // History
+        ui.horizontal(|ui| {
+            if ui.add_enabled(editor_state.history.can_undo(), egui::Button::new("Undo")).clicked() {
+                editor_state.undo();
+            }
+            if ui.add_enabled(editor_state.history.can_redo(), egui::Button::new("Redo")).clicked() {
+                editor_state.redo();
+            }
+        });
         ui.add_space(10.0);
 
         ui.separator();
@@ -2753,8 +4571,8 @@ fn draw_editor(
                         Terrain::Grass, Terrain::TallGrass, Terrain::Thicket,
                         Terrain::Brambles, Terrain::Log, Terrain::Boulder,
                         Terrain::Hollow, Terrain::Creek, Terrain::Pond,
-                        Terrain::Shore, Terrain::Base, Terrain::Outpost,
-                        Terrain::Storehouse,
+                        Terrain::Shore, Terrain::Base, Terrain::HQ,
+                        Terrain::Outpost, Terrain::Storehouse, Terrain::Barricade,
                     ];
                     for terrain in terrains {
                         let color = terrain.color().to_srgba();
@@ -2791,8 +4609,17 @@ fn draw_editor(
                         editor_state.selected_unit = unit_type;
                     }
                 }
+                ui.checkbox(&mut editor_state.place_vip, "Place as VIP")
+                    .on_hover_text("VIPs are the targets of escort and assassination objectives");
                 ui.add_space(5.0);
                 ui.label(format!("Units placed: {}", editor_state.map.units.len()));
+                let vips: Vec<String> = editor_state.map.units.iter()
+                    .filter(|u| u.vip)
+                    .map(|u| format!("{} ({}, {})", u.unit_type.name(), u.x, u.y))
+                    .collect();
+                if !vips.is_empty() {
+                    ui.label(format!("VIPs: {}", vips.join(", ")));
+                }
             }
             EditorMode::Properties => {
                 ui.label("Property Owner:");
@@ -2822,12 +4649,53 @@ fn draw_editor(
 
         ui.add_space(5.0);
 
+        // Open a map from the library
+        ui.label(format!("Map Library ({}):", library_location()));
+        if map_library.maps.is_empty() {
+            ui.label(egui::RichText::new("No saved maps").weak());
+        }
+        egui::ScrollArea::vertical().id_salt("editor_library").max_height(120.0).show(ui, |ui| {
+            for (index, entry) in map_library.maps.iter().enumerate() {
+                let label = format!("{} ({}x{})", entry.map.name, entry.map.width, entry.map.height);
+                if ui.button(label).on_hover_text(&entry.file_name).clicked() {
+                    open_map = Some(index);
+                }
+            }
+        });
+
+        ui.add_space(5.0);
+
         if ui.button("Clear Map").clicked() {
             let name = editor_state.map.name.clone();
             let width = editor_state.map.width;
             let height = editor_state.map.height;
-            editor_state.map = MapData::new(&name, width, height);
-            should_respawn = true;
+            editor_state.replace_map(MapData::new(&name, width, height));
+        }
+
+        ui.add_space(10.0);
+        ui.separator();
+
+        // Playtest the current map with a human/AI choice per faction
+        ui.label("Playtest:");
+        let factions = editor_state.map.factions();
+        if factions.is_empty() {
+            ui.label(egui::RichText::new("Place units or properties first").weak());
+        }
+        for &faction in &factions {
+            ui.horizontal(|ui| {
+                ui.label(faction.name());
+                let is_ai = playtest.ai_factions.contains(&faction);
+                if ui.selectable_label(!is_ai, "Human").clicked() {
+                    playtest.ai_factions.remove(&faction);
+                }
+                if ui.selectable_label(is_ai, "AI").clicked() {
+                    playtest.ai_factions.insert(faction);
+                }
+            });
+        }
+        if ui.add_enabled(!factions.is_empty(), egui::Button::new("Playtest")).clicked() {
+            playtest.map = Some(editor_state.map.clone());
+            next_state.set(GameState::Battle);
         }
 
         ui.add_space(20.0);
@@ -2854,6 +4722,14 @@ fn draw_editor(
         ui.label("Controls:");
         ui.label("- Left click: Paint");
         ui.label("- Right click: Erase");
+        ui.label("- Drag: Line / Rect / Select");
+        ui.label("- Select tool, right click: Paste");
+        ui.label("- Ctrl+Z / Ctrl+Y: Undo / Redo");
+        ui.label("- Ctrl+C / Ctrl+V: Copy / Paste");
+        if let Some((x, y)) = editor_state.hovered {
+            ui.add_space(5.0);
+            ui.label(format!("Cursor: ({}, {})", x, y));
+        }
         ui.add_space(10.0);
 
         ui.separator();
@@ -2870,26 +4746,83 @@ fn draw_editor(
         let northern_props = editor_state.map.properties.iter().filter(|p| p.owner == Faction::Northern).count();
         ui.label(format!("  Eastern: {}", eastern_props));
         ui.label(format!("  Northern: {}", northern_props));
-    });
 
-    // Handle respawn
-    if should_respawn {
-        for entity in tiles.iter() {
-            commands.entity(entity).despawn();
+        ui.add_space(10.0);
+        ui.separator();
+
+        // Rules the map is played under
+        let factions = editor_state.map.factions();
+        let size = (editor_state.map.width, editor_state.map.height);
+        egui::CollapsingHeader::new("Rules").show(ui, |ui| {
+            draw_map_rules(ui, &mut editor_state.map.rules, &factions, size, &game_data);
+        });
+        if !editor_state.map.triggers.is_empty() {
+            ui.label(format!("Triggers: {}", editor_state.map.triggers.len()))
+                .on_hover_text("Triggers are written in RON, in a <map>.triggers.ron file next to the map");
         }
-        for entity in units.iter() {
-            commands.entity(entity).despawn();
+
+        ui.add_space(10.0);
+        ui.separator();
+
+        // Validation and balance report
+        if ui.button("Validate").clicked() {
+            let report = validate_map(&editor_state.map, &game_data);
+            editor_state.status = if report.is_playable() {
+                "Map is playable".to_string()
+            } else {
+                format!("{} problem(s) found", report.errors().count())
+            };
+            editor_state.report = Some(report);
+        }
+        if let Some(report) = &editor_state.report {
+            egui::ScrollArea::vertical().id_salt("editor_report").max_height(300.0).show(ui, |ui| {
+                for issue in report.errors() {
+                    let text = match issue.position {
+                        Some((x, y)) => format!("({}, {}) {}", x, y, issue.message),
+                        None => issue.message.clone(),
+                    };
+                    ui.label(egui::RichText::new(text).size(11.0).color(egui::Color32::from_rgb(255, 100, 100)));
+                }
+                for issue in report.warnings() {
+                    ui.label(egui::RichText::new(&issue.message).size(11.0).color(egui::Color32::YELLOW));
+                }
+                for side in &report.factions {
+                    ui.add_space(5.0);
+                    ui.label(egui::RichText::new(game_data.faction_name(side.faction)).strong());
+                    ui.label(format!("  Bases: {}  Properties: {}", side.bases, side.properties));
+                    ui.label(format!("  Income: {}/turn", side.income));
+                    ui.label(format!("  Units: {} (worth {})", side.units, side.unit_value));
+                    let nearest: Vec<String> = side.base_to_neutral.iter()
+                        .map(|d| d.map_or("-".to_string(), |d| d.to_string()))
+                        .collect();
+                    ui.label(format!("  Base to neutral: {}", nearest.join(", ")));
+                    ui.label(format!("  Closer neutrals: {}", side.closer_neutrals));
+                }
+                ui.add_space(5.0);
+                ui.label(format!("Neutral properties: {}", report.neutral_properties));
+                ui.label(format!("Asymmetry: {:.0}%", report.asymmetry * 100.0));
+            });
         }
-        spawn_editor_tiles(&mut commands, &editor_state.map);
+    });
+
+    // Handle opening a library map
+    if let Some(entry) = open_map.and_then(|index| map_library.get(index)) {
+        editor_state.replace_map(entry.map.clone());
+        editor_state.map_name = entry.file_name.clone();
+        editor_state.status = format!("Opened {}", entry.file_name);
     }
 
     // Handle save
     if should_save {
-        let path = std::path::Path::new(&editor_state.map_name).with_extension("json");
-        match editor_state.map.save_to_file(&path) {
-            Ok(()) => {
-                editor_state.status = format!("Saved to {}", path.display());
-                info!("Map saved to {}", path.display());
+        match map_library.save(&editor_state.map_name, &editor_state.map) {
+            Ok(id) => {
+                if let MapId::Custom(index) = id {
+                    if let Some(entry) = map_library.get(index) {
+                        editor_state.map_name = entry.file_name.clone();
+                    }
+                }
+                editor_state.status = format!("Saved {} to {}", editor_state.map_name, library_location());
+                info!("Map saved to {} as {}", library_location(), editor_state.map_name);
             }
             Err(e) => {
                 editor_state.status = format!("Save failed: {}", e);
@@ -2902,12 +4835,28 @@ fn draw_editor(
 /// Handle painting in the editor
 fn editor_paint(
     mut editor_state: ResMut<EditorState>,
-    mut tiles: Query<(&EditorTile, &mut Sprite)>,
     mouse_button: Res<ButtonInput<MouseButton>>,
     windows: Query<&Window>,
-    camera: Query<(&Camera, &GlobalTransform)>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
     egui_wants_input: Res<EguiWantsInput>,
 ) {
+    let editor_state = &mut *editor_state;
+
+    // Finish a shape or selection wherever the mouse is released
+    if let Some(drag) = editor_state.drag {
+        let button = if drag.erase { MouseButton::Right } else { MouseButton::Left };
+        if !mouse_button.pressed(button) {
+            editor_state.drag = None;
+            editor_state.finish_drag(drag);
+            return;
+        }
+    }
+
+    // A stroke ends when the buttons are released, wherever the cursor is
+    if !mouse_button.pressed(MouseButton::Left) && !mouse_button.pressed(MouseButton::Right) {
+        editor_state.stroke_recorded = false;
+    }
+
     // Don't paint if egui wants pointer input (clicking on UI)
     if egui_wants_input.wants_any_pointer_input() {
         return;
@@ -2917,34 +4866,31 @@ fn editor_paint(
     let Ok(window) = windows.single() else { return };
     let Ok((camera, camera_transform)) = camera.single() else { return };
 
-    let Some(cursor_pos) = window.cursor_position() else { return };
-    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_pos) else { return };
-
-    // Intersect with Y=0 ground plane
-    if ray.direction.y.abs() < 0.0001 { return; }
-    let t = -ray.origin.y / ray.direction.y;
-    if t < 0.0 { return; }
-    let hit = ray.origin + ray.direction * t;
-
-    // Convert to tile coordinates
-    let offset_x = -(editor_state.map.width as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
-    let offset_z = -(editor_state.map.height as f32 * TILE_SIZE) / 2.0 + TILE_SIZE / 2.0;
-
-    let tile_x = ((hit.x - offset_x + TILE_SIZE / 2.0) / TILE_SIZE).floor() as i32;
-    let tile_y = ((hit.z - offset_z + TILE_SIZE / 2.0) / TILE_SIZE).floor() as i32;  // World Z -> Grid Y
-
-    // Check bounds
-    if tile_x < 0 || tile_y < 0 || tile_x >= editor_state.map.width as i32 || tile_y >= editor_state.map.height as i32 {
-        return;
-    }
+    let Some(hit) = cursor_ground_hit(window, camera, camera_transform) else { return };
+    let Some(tile) = world_to_grid(hit, editor_state.map.width, editor_state.map.height) else { return };
+    let (tile_x, tile_y) = (tile.x, tile.y);
+    editor_state.hovered = Some((tile_x, tile_y));
 
     let left_pressed = mouse_button.pressed(MouseButton::Left);
     let right_pressed = mouse_button.pressed(MouseButton::Right);
+    let left_clicked = mouse_button.just_pressed(MouseButton::Left);
+    let right_clicked = mouse_button.just_pressed(MouseButton::Right);
 
     if !left_pressed && !right_pressed {
         return;
     }
 
+    // Selecting and pasting work the same in every mode
+    if editor_state.tool == EditorTool::Select {
+        if left_clicked {
+            editor_state.drag = Some(EditorDrag { start: (tile_x, tile_y), erase: false });
+        } else if right_clicked {
+            editor_state.paste_at((tile_x, tile_y));
+        }
+        return;
+    }
+
+    let symmetry = editor_state.symmetry;
     match editor_state.mode {
         EditorMode::Terrain => {
             let terrain = if left_pressed {
@@ -2953,54 +4899,87 @@ fn editor_paint(
                 Terrain::Grass
             };
 
-            // Update map data
-            editor_state.map.set_terrain(tile_x, tile_y, terrain);
-
-            // Update tile sprite color
-            for (tile, mut sprite) in tiles.iter_mut() {
-                if tile.x == tile_x && tile.y == tile_y {
-                    sprite.color = terrain.color();
+            match editor_state.tool {
+                EditorTool::Brush => {
+                    // One undo step per stroke, even one that started off the board
+                    if !editor_state.stroke_recorded {
+                        editor_state.record();
+                        editor_state.stroke_recorded = true;
+                    }
+                    paint_terrain(&mut editor_state.map, &[(tile_x, tile_y)], terrain, symmetry);
+                }
+                EditorTool::Fill => {
+                    if left_clicked || right_clicked {
+                        editor_state.record();
+                        let tiles = flood_fill_tiles(&editor_state.map, tile_x, tile_y);
+                        paint_terrain(&mut editor_state.map, &tiles, terrain, symmetry);
+                    }
+                }
+                EditorTool::Line | EditorTool::Rectangle => {
+                    if left_clicked || right_clicked {
+                        editor_state.drag = Some(EditorDrag { start: (tile_x, tile_y), erase: !left_clicked });
+                    }
                 }
+                EditorTool::Select => {}
             }
         }
         EditorMode::Units => {
-            if left_pressed && mouse_button.just_pressed(MouseButton::Left) {
-                let unit_type = editor_state.selected_unit;
-                let faction = editor_state.selected_faction;
-                // Remove existing unit at this position
-                editor_state.map.units.retain(|u| u.x != tile_x || u.y != tile_y);
-                // Add new unit
-                editor_state.map.units.push(UnitPlacement {
-                    unit_type,
-                    faction,
+            if left_clicked {
+                editor_state.record();
+                let placement = UnitPlacement {
+                    unit_type: editor_state.selected_unit,
+                    faction: editor_state.selected_faction,
                     x: tile_x,
                     y: tile_y,
-                });
-            } else if right_pressed && mouse_button.just_pressed(MouseButton::Right) {
-                // Remove unit at position
-                editor_state.map.units.retain(|u| u.x != tile_x || u.y != tile_y);
+                    vip: editor_state.place_vip,
+                };
+                place_unit(&mut editor_state.map, placement, symmetry);
+            } else if right_clicked {
+                editor_state.record();
+                remove_unit(&mut editor_state.map, tile_x, tile_y, symmetry);
             }
         }
         EditorMode::Properties => {
-            let terrain = editor_state.map.get_terrain(tile_x, tile_y);
-            if let Some(t) = terrain {
-                if t.is_capturable() {
-                    if left_pressed && mouse_button.just_pressed(MouseButton::Left) {
-                        let faction = editor_state.selected_faction;
-                        // Remove existing property at this position
-                        editor_state.map.properties.retain(|p| p.x != tile_x || p.y != tile_y);
-                        // Add new property
-                        editor_state.map.properties.push(PropertyOwnership {
-                            x: tile_x,
-                            y: tile_y,
-                            owner: faction,
-                        });
-                    } else if right_pressed && mouse_button.just_pressed(MouseButton::Right) {
-                        // Remove property at position
-                        editor_state.map.properties.retain(|p| p.x != tile_x || p.y != tile_y);
-                    }
+            let capturable = editor_state.map.get_terrain(tile_x, tile_y).is_some_and(|t| t.is_capturable());
+            if capturable {
+                if left_clicked {
+                    editor_state.record();
+                    let faction = editor_state.selected_faction;
+                    set_owner(&mut editor_state.map, tile_x, tile_y, faction, symmetry);
+                } else if right_clicked {
+                    editor_state.record();
+                    clear_owner(&mut editor_state.map, tile_x, tile_y, symmetry);
                 }
             }
         }
     }
 }
+
+/// Keyboard shortcuts for the editor: undo/redo and copy/paste
+fn editor_shortcuts(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut editor_state: ResMut<EditorState>,
+    egui_wants_input: Res<EguiWantsInput>,
+) {
+    // Leave shortcuts to text fields while typing
+    if egui_wants_input.wants_any_keyboard_input() {
+        return;
+    }
+    let ctrl = keyboard.pressed(KeyCode::ControlLeft) || keyboard.pressed(KeyCode::ControlRight);
+    if !ctrl {
+        return;
+    }
+    let shift = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+
+    if keyboard.just_pressed(KeyCode::KeyZ) {
+        if shift { editor_state.redo() } else { editor_state.undo() }
+    } else if keyboard.just_pressed(KeyCode::KeyY) {
+        editor_state.redo();
+    } else if keyboard.just_pressed(KeyCode::KeyC) {
+        editor_state.copy_selection();
+    } else if keyboard.just_pressed(KeyCode::KeyV) {
+        if let Some(tile) = editor_state.hovered {
+            editor_state.paste_at(tile);
+        }
+    }
+}